@@ -0,0 +1,407 @@
+//! Append-only JSONL record of every action gremlin runs (sys commands, scripts, projects),
+//! so a shared machine can answer "who killed what, and when".
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use std::time::Duration;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::sys_admin::command_output::{CommandOutput, Field, Record, Row, Tone};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Sys,
+    Script,
+    Project,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Sys => "sys",
+            Kind::Script => "script",
+            Kind::Project => "project",
+        }
+    }
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// RFC 3339, local time.
+    pub timestamp: String,
+    pub user: String,
+    pub kind: Kind,
+    pub command: String,
+    /// Arguments as given, with secrets replaced by `***`.
+    pub args: Vec<String>,
+    /// The program line that was run, with secrets redacted; empty if nothing was spawned.
+    #[serde(default)]
+    pub argv: Vec<String>,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Entry {
+    /// A new entry stamped with the current time and user; fill in the outcome before recording.
+    pub fn new(kind: Kind, command: impl Into<String>, args: Vec<String>) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            user: current_user(),
+            kind,
+            command: command.into(),
+            args,
+            argv: Vec::new(),
+            exit_code: 0,
+            duration_ms: 0,
+            dry_run: false,
+            error: None,
+        }
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration_ms = duration.as_millis() as u64;
+        self
+    }
+
+    /// The `gremlin` arguments that run this action again.
+    pub fn replay_args(&self) -> Vec<String> {
+        let separator = if self.args.is_empty() { None } else { Some("--") };
+        let prefix: Vec<&str> = match self.kind {
+            Kind::Sys => ["sys", self.command.as_str()].into_iter().chain(separator).collect(),
+            Kind::Script => ["script", "run", self.command.as_str()].into_iter().chain(separator).collect(),
+            Kind::Project => vec!["new"],
+        };
+        prefix.into_iter().map(String::from).chain(self.args.iter().cloned()).collect()
+    }
+
+    pub fn time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.timestamp).ok().map(|t| t.with_timezone(&Local))
+    }
+}
+
+/// Login name of whoever ran gremlin; the invoking user when run through sudo.
+pub fn current_user() -> String {
+    ["SUDO_USER", "USER", "LOGNAME"]
+        .iter()
+        .find_map(|var| env::var(var).ok().filter(|v| !v.is_empty()))
+        .or_else(user_from_uid)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Name of the real UID in `/etc/passwd`, for environments without `$USER` (cron, systemd).
+fn user_from_uid() -> Option<String> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let uid = status.lines().find_map(|line| line.strip_prefix("Uid:"))?.split_whitespace().next()?.to_string();
+    let passwd = fs::read_to_string("/etc/passwd").ok()?;
+    passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.get(2) == Some(&uid.as_str()))
+        .map(|fields| fields[0].to_string())
+        .or(Some(uid))
+}
+
+/// Append an entry, rotating the file first if it has grown past `audit.max_bytes`. When
+/// the shared log can't be written the entry goes to `audit.fallback_path` instead. A log
+/// that can't be written is reported but never stops the action itself.
+pub fn record(entry: &Entry) {
+    static FALLBACK_WARNING: Once = Once::new();
+
+    let audit = &config::get().audit;
+    if !audit.enabled {
+        return;
+    }
+    let (path, fallback) = (Path::new(&audit.path), Path::new(&audit.fallback_path));
+    let Err(e) = append(path, audit.max_bytes, audit.keep, entry) else {
+        return;
+    };
+    if audit.fallback_path.is_empty() || fallback == path {
+        eprintln!("{} {}: {}", "⚠️ Could not write audit log".bright_yellow(), path.display(), e);
+        return;
+    }
+    FALLBACK_WARNING.call_once(|| {
+        eprintln!("{} {}: {}; logging to {}", "⚠️ Could not write audit log".bright_yellow(), path.display(), e, fallback.display());
+    });
+    if let Err(e) = append(fallback, audit.max_bytes, audit.keep, entry) {
+        eprintln!("{} {}: {}", "⚠️ Could not write audit log".bright_yellow(), fallback.display(), e);
+    }
+}
+
+fn append(path: &Path, max_bytes: u64, keep: usize, entry: &Entry) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if fs::metadata(path).map(|meta| meta.len() >= max_bytes).unwrap_or(false) {
+        rotate(path, keep)?;
+    }
+    let line = serde_json::to_string(entry)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// `audit.jsonl` → `audit.jsonl.1` → ... → `audit.jsonl.<keep>`, dropping the oldest.
+fn rotate(path: &Path, keep: usize) -> std::io::Result<()> {
+    if keep == 0 {
+        return fs::remove_file(path);
+    }
+    let _ = fs::remove_file(rotated(path, keep));
+    for n in (1..keep).rev() {
+        let from = rotated(path, n);
+        if from.exists() {
+            fs::rename(from, rotated(path, n + 1))?;
+        }
+    }
+    fs::rename(path, rotated(path, 1))
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Every readable entry of the shared and the fallback log, oldest first, including rotated
+/// files. Unparsable lines are skipped.
+pub fn read_all() -> Vec<Entry> {
+    let audit = &config::get().audit;
+    let mut entries = read_log(Path::new(&audit.path), audit.keep);
+    if !audit.fallback_path.is_empty() && audit.fallback_path != audit.path {
+        entries.extend(read_log(Path::new(&audit.fallback_path), audit.keep));
+        entries.sort_by_key(Entry::time);
+    }
+    entries
+}
+
+/// Entries of one log and its rotated files, oldest first.
+fn read_log(path: &Path, keep: usize) -> Vec<Entry> {
+    (1..=keep)
+        .rev()
+        .map(|n| rotated(path, n))
+        .chain(std::iter::once(path.to_path_buf()))
+        .filter_map(|file| fs::read_to_string(file).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Which entries `gremlin history` shows.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub user: Option<String>,
+    pub kind: Option<Kind>,
+    /// Substring of the command name.
+    pub command: Option<String>,
+    pub since: Option<DateTime<Local>>,
+    pub failed_only: bool,
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.user.as_ref().is_none_or(|user| &entry.user == user)
+            && self.kind.is_none_or(|kind| entry.kind == kind)
+            && self.command.as_ref().is_none_or(|command| entry.command.contains(command.as_str()))
+            && self.since.is_none_or(|since| entry.time().is_some_and(|time| time >= since))
+            && (!self.failed_only || entry.exit_code != 0)
+    }
+}
+
+/// Parse `--since`: a relative age such as `30m`, `12h` or `7d`, a date (`2024-05-01`)
+/// or an RFC 3339 timestamp.
+pub fn parse_since(value: &str) -> Result<DateTime<Local>, String> {
+    let value = value.trim();
+    let invalid = || format!("invalid --since '{}': expected e.g. 30m, 12h, 7d, 2024-05-01 or an RFC 3339 time", value);
+    if let Some(unit) = value.chars().last().filter(|c| matches!(c, 's' | 'm' | 'h' | 'd')) {
+        if let Ok(amount) = value[..value.len() - 1].parse::<i64>() {
+            let unit_seconds = match unit {
+                's' => 1,
+                'm' => 60,
+                'h' => 3600,
+                _ => 86400,
+            };
+            // Ages far beyond the calendar's range are rejected rather than overflowing.
+            return amount
+                .checked_mul(unit_seconds)
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|age| Local::now().checked_sub_signed(age))
+                .ok_or_else(invalid);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if let Some(midnight) = date.and_hms_opt(0, 0, 0).and_then(|t| Local.from_local_datetime(&t).earliest()) {
+            return Ok(midnight);
+        }
+    }
+    DateTime::parse_from_rfc3339(value).map(|t| t.with_timezone(&Local)).map_err(|_| invalid())
+}
+
+/// Entries as a table/JSON/CSV output, numbered from 1 in the order given.
+pub fn to_output(entries: &[Entry]) -> CommandOutput {
+    let mut out = CommandOutput::default().with_title("Audit history (newest first, replay with --replay N):");
+    out.headers = ["#", "Time", "User", "Kind", "Command", "Exit", "Duration"].map(String::from).to_vec();
+    for (i, entry) in entries.iter().enumerate() {
+        let tone = if entry.dry_run {
+            Tone::Info
+        } else if entry.exit_code == 0 {
+            Tone::Normal
+        } else {
+            Tone::Bad
+        };
+        let time = entry.time().map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_else(|| entry.timestamp.clone());
+        let command_line = std::iter::once(entry.command.as_str()).chain(entry.args.iter().map(String::as_str)).collect::<Vec<_>>().join(" ");
+        let exit = match (&entry.error, entry.dry_run) {
+            (_, true) => "dry run".to_string(),
+            (Some(error), _) => format!("{} ({})", entry.exit_code, error),
+            (None, _) => entry.exit_code.to_string(),
+        };
+
+        out.rows.push(Row::new(
+            vec![
+                (i + 1).to_string(),
+                time,
+                entry.user.clone(),
+                entry.kind.name().to_string(),
+                command_line,
+                exit,
+                format!("{}ms", entry.duration_ms),
+            ],
+            tone,
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("id".to_string(), Field::Int(i as i64 + 1)),
+                ("timestamp".to_string(), Field::Text(entry.timestamp.clone())),
+                ("user".to_string(), Field::Text(entry.user.clone())),
+                ("kind".to_string(), Field::Text(entry.kind.name().to_string())),
+                ("command".to_string(), Field::Text(entry.command.clone())),
+                ("args".to_string(), Field::Text(entry.args.join(" "))),
+                ("argv".to_string(), Field::Text(entry.argv.join(" "))),
+                ("exit_code".to_string(), Field::Int(entry.exit_code as i64)),
+                ("duration_ms".to_string(), Field::Int(entry.duration_ms as i64)),
+                ("dry_run".to_string(), Field::Text(entry.dry_run.to_string())),
+                ("error".to_string(), entry.error.clone().map(Field::Text).unwrap_or(Field::Null)),
+            ],
+        });
+    }
+    if entries.is_empty() {
+        out.note(Tone::Info, "No matching actions in the audit log.");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn entry(command: &str, timestamp: &str) -> Entry {
+        Entry { timestamp: timestamp.to_string(), user: "alice".to_string(), ..Entry::new(Kind::Sys, command, Vec::new()) }
+    }
+
+    fn commands(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.command.as_str()).collect()
+    }
+
+    #[test]
+    fn appends_rotate_through_the_kept_files_and_read_back_oldest_first() {
+        let dir = TempDir::new();
+        let path = dir.root().join("logs/audit.jsonl");
+        for n in 1..=5 {
+            // Every line is bigger than `max_bytes`, so each append rotates the previous one away.
+            append(&path, 10, 2, &entry(&format!("cmd{}", n), "2024-05-01T10:00:00+00:00")).unwrap();
+        }
+        assert!(!rotated(&path, 3).exists());
+        let files: Vec<String> = [rotated(&path, 2), rotated(&path, 1), path.clone()]
+            .iter()
+            .map(|file| serde_json::from_str::<Entry>(fs::read_to_string(file).unwrap().trim()).unwrap().command)
+            .collect();
+        assert_eq!(files, ["cmd3", "cmd4", "cmd5"]);
+        assert_eq!(commands(&read_log(&path, 2)), ["cmd3", "cmd4", "cmd5"]);
+        assert_eq!(commands(&read_log(&path, 1)), ["cmd4", "cmd5"]);
+
+        fs::write(&path, "not json\n{\"half\":\n").unwrap();
+        assert_eq!(commands(&read_log(&path, 2)), ["cmd3", "cmd4"]);
+    }
+
+    #[test]
+    fn keeping_nothing_drops_the_old_log() {
+        let dir = TempDir::new();
+        let path = dir.root().join("audit.jsonl");
+        append(&path, 10, 0, &entry("first", "2024-05-01T10:00:00+00:00")).unwrap();
+        append(&path, 10, 0, &entry("second", "2024-05-01T10:00:00+00:00")).unwrap();
+        assert!(!rotated(&path, 1).exists());
+        assert_eq!(commands(&read_log(&path, 0)), ["second"]);
+
+        append(&path, 1 << 20, 0, &entry("third", "2024-05-01T10:00:00+00:00")).unwrap();
+        assert_eq!(commands(&read_log(&path, 0)), ["second", "third"]);
+    }
+
+    #[test]
+    fn since_takes_ages_dates_and_timestamps() {
+        let age = |value| (Local::now() - parse_since(value).unwrap()).num_seconds();
+        assert!((1799..=1801).contains(&age("30m")));
+        assert!((43199..=43201).contains(&age(" 12h ")));
+        assert!((604799..=604801).contains(&age("7d")));
+        assert!((44..=46).contains(&age("45s")));
+
+        let midnight = parse_since("2024-05-01").unwrap();
+        assert_eq!(midnight.format("%Y-%m-%d %H:%M:%S").to_string(), "2024-05-01 00:00:00");
+        assert_eq!(parse_since("2024-05-01T10:30:00Z").unwrap().timestamp(), 1714559400);
+        for garbage in ["", "soon", "10x", "m", "2024-13-01", "yesterday"] {
+            assert!(parse_since(garbage).is_err(), "{}", garbage);
+        }
+        for huge in ["999999999999999d", "9223372036854775807s", "-9223372036854775808h", "99999999999999m"] {
+            assert!(parse_since(huge).unwrap_err().starts_with("invalid --since"), "{}", huge);
+        }
+        assert!(parse_since("36500d").is_ok());
+    }
+
+    #[test]
+    fn filters_combine() {
+        let mut failed = entry("kill-tree", "2024-05-02T10:00:00+00:00");
+        failed.exit_code = 1;
+        let mut script = entry("backup.sh", "2024-04-30T10:00:00+00:00");
+        script.kind = Kind::Script;
+        script.user = "bob".to_string();
+        let ok = entry("kill", "2024-05-02T10:00:00+00:00");
+        let since = DateTime::parse_from_rfc3339("2024-05-01T00:00:00+00:00").unwrap().with_timezone(&Local);
+
+        let matching = |filter: Filter| -> Vec<&str> {
+            [&failed, &script, &ok].into_iter().filter(|entry| filter.matches(entry)).map(|entry| entry.command.as_str()).collect()
+        };
+        assert_eq!(matching(Filter::default()), ["kill-tree", "backup.sh", "kill"]);
+        assert_eq!(matching(Filter { user: Some("bob".to_string()), ..Default::default() }), ["backup.sh"]);
+        assert_eq!(matching(Filter { kind: Some(Kind::Sys), ..Default::default() }), ["kill-tree", "kill"]);
+        assert_eq!(matching(Filter { command: Some("kill".to_string()), ..Default::default() }), ["kill-tree", "kill"]);
+        assert_eq!(matching(Filter { since: Some(since), ..Default::default() }), ["kill-tree", "kill"]);
+        assert_eq!(matching(Filter { failed_only: true, command: Some("kill".to_string()), ..Default::default() }), ["kill-tree"]);
+        assert!(matching(Filter { user: Some("bob".to_string()), kind: Some(Kind::Sys), ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn replay_args_rebuild_the_command_line() {
+        let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+        let sys = Entry::new(Kind::Sys, "kill", args(&["-9", "by=pid"]));
+        assert_eq!(sys.replay_args(), args(&["sys", "kill", "--", "-9", "by=pid"]));
+        assert_eq!(Entry::new(Kind::Sys, "uptime", Vec::new()).replay_args(), args(&["sys", "uptime"]));
+        let script = Entry::new(Kind::Script, "Bash/backup.sh", args(&["--full"]));
+        assert_eq!(script.replay_args(), args(&["script", "run", "Bash/backup.sh", "--", "--full"]));
+        let project = Entry::new(Kind::Project, "new", args(&["rust", "demo", "--template", "iced", "--github", "none"]));
+        assert_eq!(project.replay_args(), args(&["new", "rust", "demo", "--template", "iced", "--github", "none"]));
+    }
+}
@@ -0,0 +1,329 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use colored::*;
+
+use crate::audit;
+use crate::config;
+use crate::dev::{project_setup, template};
+use crate::packages;
+use crate::script_runner::run_script;
+use crate::sys_admin::args::{ArgKind, Args};
+use crate::sys_admin::command_matcher;
+use crate::sys_admin::command_output::CommandError;
+use crate::sys_admin::doctor;
+use crate::sys_admin::playbook;
+use crate::sys_admin::registry;
+use crate::sys_admin::render::{self, Format};
+use crate::sys_admin::repl;
+
+/// Non-interactive entry point. Running `gremlin` with no subcommand opens the menus.
+#[derive(Parser)]
+#[command(name = "gremlin", version, about = "✨ Gremlin CLI: projects, scripts and sys admin mischief")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Output format for `sys` commands
+    #[arg(long, global = true, value_enum, default_value_t = Format::Table)]
+    pub format: Format,
+    /// Print the resolved command lines instead of running them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    /// Don't ask before running destructive commands
+    #[arg(short, long, global = true)]
+    pub yes: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run a system admin command, e.g. `gremlin sys df`, `gremlin sys kill9 1234` or `gremlin sys ps sort=mem top=10`
+    Sys {
+        /// Command name as listed by `gremlin sys` (omit to list all commands)
+        name: Option<String>,
+        /// Arguments passed to the command, in order or as `name=value` (put arguments starting
+        /// with `-` after `--`)
+        args: Vec<String>,
+    },
+    /// Script runner actions
+    Script {
+        #[command(subcommand)]
+        action: ScriptCommand,
+    },
+    /// Create a new project, e.g. `gremlin new rust myproj --template ratatui --github private`
+    New {
+        language: Language,
+        name: String,
+        /// Project template (standard, or a language-specific one such as ratatui, gin or qt)
+        #[arg(long, default_value = "standard")]
+        template: String,
+        /// Create a GitHub repository for the project
+        #[arg(long, value_enum, default_value_t = GitHub::None)]
+        github: GitHub,
+    },
+    /// Run or list playbooks: named sequences of `sys` commands in `<config dir>/playbooks/`
+    Playbook {
+        #[command(subcommand)]
+        action: PlaybookCommand,
+    },
+    /// Check that the tools behind the `sys` commands are installed, e.g. `gremlin doctor --install`
+    Doctor {
+        /// Only check commands in this category, e.g. `networking`
+        #[arg(long)]
+        category: Option<String>,
+        /// Install the packages providing missing tools through the detected package manager
+        #[arg(long)]
+        install: bool,
+    },
+    /// Open the `gremlin>` command palette for sys admin commands
+    Shell,
+    /// Show past actions from the audit log, newest first, e.g. `gremlin history --command kill --since 1d`
+    History {
+        /// Only actions run by this user
+        #[arg(long)]
+        user: Option<String>,
+        /// Only this kind of action
+        #[arg(long, value_enum)]
+        kind: Option<audit::Kind>,
+        /// Only commands whose name contains this text
+        #[arg(long)]
+        command: Option<String>,
+        /// Only actions newer than this: 30m, 12h, 7d, 2024-05-01 or an RFC 3339 time
+        #[arg(long)]
+        since: Option<String>,
+        /// Only actions that failed or were cancelled
+        #[arg(long)]
+        failed: bool,
+        /// How many entries to list
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Run entry number N (as listed) again
+        #[arg(long, value_name = "N")]
+        replay: Option<usize>,
+    },
+    /// Show the effective configuration and the files it was loaded from
+    Config,
+}
+
+#[derive(Subcommand)]
+pub enum ScriptCommand {
+    /// Run a script, e.g. `gremlin script run Bash/backup.sh -- --verbose`
+    Run {
+        /// Script path relative to the scripts root, as `<Type>/<file>`
+        script: String,
+        /// Arguments passed to the script
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PlaybookCommand {
+    /// List the playbooks in the playbooks dir
+    List,
+    /// Run a playbook step by step, e.g. `gremlin playbook run morning`
+    Run {
+        /// Playbook name (file name without extension) or path to a playbook file
+        name: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Language {
+    Rust,
+    Go,
+    Cpp,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GitHub {
+    None,
+    Public,
+    Private,
+}
+
+/// Run a parsed subcommand and return the process exit code.
+pub fn dispatch(command: Command, format: Format) -> i32 {
+    match command {
+        Command::Sys { name: None, .. } => {
+            command_matcher::show_available_commands();
+            0
+        }
+        Command::Sys { name: Some(name), args } => match command_matcher::execute(&name, &args) {
+            Ok(output) => {
+                render::print_output_as(&output, format);
+                output.exit_code()
+            }
+            Err(e) => {
+                render::print_error_as(&e, format);
+                if let (CommandError::UnknownCommand(name), Format::Table) = (&e, format) {
+                    if let Some(suggestion) = registry::suggest(name) {
+                        eprintln!("{}", format!("Did you mean `{}`?", suggestion).bright_yellow());
+                    }
+                    eprintln!("{}", "Run `gremlin sys` to list available commands.".bright_yellow());
+                }
+                if let (CommandError::NotInstalled(binaries), Format::Table) = (&e, format) {
+                    let providers: Vec<String> = binaries.split(", ").filter_map(packages::provider).collect();
+                    if !providers.is_empty() {
+                        eprintln!("{}", format!("Provided by: {}", providers.join(" ")).bright_yellow());
+                    }
+                    eprintln!("{}", "Run `gremlin doctor --install` to install missing tools.".bright_yellow());
+                }
+                e.exit_code()
+            }
+        },
+        Command::Script { action: ScriptCommand::Run { script, args } } => {
+            match run_script::run_script_file(&script, &args) {
+                Ok(completed) if completed.dry_run => 0,
+                Ok(completed) => completed.status.unwrap_or(1),
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".bright_red(), e);
+                    1
+                }
+            }
+        }
+        Command::Playbook { action: PlaybookCommand::List } => {
+            render::print_output_as(&playbook::list_output(), format);
+            0
+        }
+        Command::Playbook { action: PlaybookCommand::Run { name } } => match playbook::load(&name) {
+            Ok(playbook) => {
+                let report = playbook::run(&playbook, format);
+                if format == Format::Table {
+                    println!();
+                }
+                render::print_output_as(&report.to_output(), format);
+                report.exit_code()
+            }
+            Err(e) => {
+                match format {
+                    Format::Json => println!("{}", serde_json::json!({ "error": e.to_string(), "exit_code": e.exit_code() })),
+                    Format::Table | Format::Csv => eprintln!("{} {}", "Error:".bright_red(), e.to_string().bright_red()),
+                }
+                e.exit_code()
+            }
+        },
+        Command::Doctor { category, install } => doctor::run(category.as_deref(), install, format),
+        Command::Shell => {
+            repl::run();
+            0
+        }
+        Command::History { user, kind, command, since, failed, limit, replay } => {
+            let since = match since.as_deref().map(audit::parse_since).transpose() {
+                Ok(since) => since,
+                Err(e) => {
+                    render::print_error_as(&CommandError::InvalidArgument(e), format);
+                    return 2;
+                }
+            };
+            let filter = audit::Filter { user, kind, command, since, failed_only: failed };
+            let entries: Vec<audit::Entry> = audit::read_all().into_iter().rev().filter(|e| filter.matches(e)).collect();
+
+            match replay {
+                None => {
+                    let output = audit::to_output(&entries[..entries.len().min(limit)]);
+                    render::print_output_as(&output, format);
+                    0
+                }
+                Some(n) => match n.checked_sub(1).and_then(|i| entries.get(i)) {
+                    Some(entry) => replay_entry(entry, format),
+                    None => {
+                        render::print_error_as(&CommandError::InvalidArgument(format!("no history entry #{}", n)), format);
+                        2
+                    }
+                },
+            }
+        }
+        Command::Config => {
+            let config = config::get();
+            println!("{}", "Config files (lowest priority first):".bright_cyan());
+            if config.sources.is_empty() {
+                println!("  {}", "none, using defaults".bright_yellow());
+            }
+            for source in &config.sources {
+                println!("  {}", source.display().to_string().bright_magenta());
+            }
+            println!("\n{}", config.to_toml());
+            0
+        }
+        Command::New { language, name, template, github } => {
+            match create_project(language, &name, &template, github) {
+                Ok(()) => {
+                    println!("{}", "✅ Project created successfully!".bright_green());
+                    0
+                }
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".bright_red(), e);
+                    1
+                }
+            }
+        }
+    }
+}
+
+/// Run an audit log entry again through the same dispatch as the original call.
+/// Secrets were never logged, so they are asked for again.
+fn replay_entry(entry: &audit::Entry, format: Format) -> i32 {
+    eprintln!("{} gremlin {}", "🔁 Replaying:".bright_cyan(), entry.replay_args().join(" "));
+    let mut entry = entry.clone();
+    if let Some(spec) = registry::find(&entry.command).filter(|_| entry.kind == audit::Kind::Sys) {
+        let slots = Args::slots(spec.args, &entry.args).unwrap_or_default();
+        for (slot, value) in slots.into_iter().zip(entry.args.iter_mut()) {
+            let arg_spec = &spec.args[slot];
+            if arg_spec.kind == ArgKind::Secret && Args::value(arg_spec, value) == "***" {
+                match command_matcher::prompt_arg(arg_spec) {
+                    Some(secret) => *value = format!("{}={}", arg_spec.name, secret),
+                    None => {
+                        render::print_error_as(&CommandError::Cancelled, format);
+                        return CommandError::Cancelled.exit_code();
+                    }
+                }
+            }
+        }
+    }
+
+    match Cli::try_parse_from(std::iter::once("gremlin".to_string()).chain(entry.replay_args())) {
+        Ok(Cli { command: Some(command), .. }) => dispatch(command, format),
+        Ok(_) => 2,
+        Err(e) => {
+            eprintln!("{}", e);
+            2
+        }
+    }
+}
+
+/// Map a language/template pair onto the same builders the project menus use.
+fn create_project(language: Language, name: &str, template_name: &str, github: GitHub) -> Result<(), String> {
+    let with_github = github != GitHub::None;
+    let is_private = github == GitHub::Private;
+    let language_name = match language {
+        Language::Rust => "rust",
+        Language::Go => "go",
+        Language::Cpp => "cpp",
+    };
+    project_setup::audited(language_name, name, template_name, with_github, is_private, || {
+        build_project(language, name, template_name, with_github, is_private)
+    })
+}
+
+fn build_project(language: Language, name: &str, template_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    match (language, template_name) {
+        (Language::Rust, "standard") => project_setup::create_rust_project(name, with_github, is_private),
+        (Language::Rust, "iced") => template::create_iced_template(name, with_github, is_private),
+        (Language::Rust, "ratatui") => template::create_ratatui_template(name, with_github, is_private),
+        (Language::Rust, "tauri") => template::create_tauri_template(name, with_github, is_private),
+        (Language::Go, "standard") => project_setup::create_go_project(name, with_github, is_private),
+        (Language::Go, "gin") => template::create_gin_template(name, with_github, is_private),
+        (Language::Go, "echo") => template::create_echo_template(name, with_github, is_private),
+        (Language::Go, "cobra") => template::create_cobra_template(name, with_github, is_private),
+        (Language::Cpp, "standard") => project_setup::create_cpp_project(name, with_github, is_private),
+        (Language::Cpp, "qt") => template::create_qt_template(name, with_github, is_private),
+        (Language::Cpp, "sfml") => template::create_sfml_template(name, with_github, is_private),
+        (Language::Cpp, "cmake") => template::create_cmake_template(name, with_github, is_private),
+        (language, other) => {
+            let available = match language {
+                Language::Rust => "standard, iced, ratatui, tauri",
+                Language::Go => "standard, gin, echo, cobra",
+                Language::Cpp => "standard, qt, sfml, cmake",
+            };
+            Err(format!("Unknown template '{}' (available: {})", other, available))
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use colored::*;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+/// Name of the per-directory override file, looked up from `/` down to the current directory.
+const LOCAL_CONFIG_FILE: &str = ".gremlin.toml";
+const SYSTEM_CONFIG_FILE: &str = "/etc/gremlin/config.toml";
+
+lazy_static! {
+    static ref CONFIG: Config = Config::load();
+}
+
+/// The effective configuration, loaded once on first use.
+pub fn get() -> &'static Config {
+    &CONFIG
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub paths: PathsConfig,
+    pub author: AuthorConfig,
+    pub git: GitConfig,
+    pub packages: PackagesConfig,
+    pub audit: AuditConfig,
+    pub privilege: PrivilegeConfig,
+    pub dashboard: DashboardConfig,
+    /// Files that contributed to this config, lowest priority first.
+    #[serde(skip)]
+    pub sources: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PathsConfig {
+    /// Root holding the `Python/`, `Rust/`, `Bash/`... script folders.
+    pub scripts_root: String,
+    /// Where new projects are created.
+    pub projects_root: String,
+    /// The `/proc` tree process and resource commands read; point it at a copy to replay one.
+    pub proc_root: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthorConfig {
+    pub name: String,
+    pub url: Option<String>,
+    pub license: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    pub default_branch: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackagesConfig {
+    /// Preferred package manager (`apt`, `pacman`, `yay`, `paru`, `dnf`, `zypper`, `apk`).
+    /// Auto-detected when unset, preferring an installed AUR helper on Arch.
+    pub manager: Option<String>,
+    /// Extra package names per manager, keyed by the Debian name, e.g.
+    /// `[packages.names.dnsutils] pacman = "bind"`.
+    pub names: BTreeMap<String, BTreeMap<String, String>>,
+    /// Package providing a binary, keyed by binary, for tools the built-in table doesn't know,
+    /// e.g. `[packages.binaries] kubectl = "kubernetes-client"`.
+    pub binaries: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivilegeConfig {
+    /// How to run commands as root (`sudo`, `doas`, `run0`, `pkexec`). Auto-detected when unset.
+    pub tool: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DashboardConfig {
+    /// Milliseconds between redraws of `gremlin sys dashboard`.
+    pub refresh_ms: u64,
+    /// Initial process sort column (`cpu`, `mem`, `pid`...).
+    pub sort: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    pub enabled: bool,
+    /// JSONL file every action is appended to. The default is shared by every user of the
+    /// machine; make `/var/log/gremlin` group-writable for the users who run gremlin.
+    pub path: String,
+    /// Per-user log used, with a warning, when `path` can't be written.
+    pub fallback_path: String,
+    /// Rotate once the file reaches this size.
+    pub max_bytes: u64,
+    /// Rotated files to keep (`audit.jsonl.1` is the newest).
+    pub keep: usize,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            scripts_root: "~/Documents/Scripts".to_string(),
+            projects_root: "~/Documents/Dev".to_string(),
+            proc_root: "/proc".to_string(),
+        }
+    }
+}
+
+impl Default for AuthorConfig {
+    fn default() -> Self {
+        Self {
+            name: env::var("USER").unwrap_or_else(|_| "gremlin".to_string()),
+            url: None,
+            license: "MIT".to_string(),
+        }
+    }
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self { default_branch: "main".to_string() }
+    }
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self { refresh_ms: 1000, sort: "cpu".to_string() }
+    }
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: "/var/log/gremlin/audit.jsonl".to_string(),
+            fallback_path: "~/.local/state/gremlin/audit.jsonl".to_string(),
+            max_bytes: 1024 * 1024,
+            keep: 5,
+        }
+    }
+}
+
+impl Config {
+    /// Build the config from defaults, the system file, the user file, per-directory
+    /// `.gremlin.toml` overrides and finally `GREMLIN_*` environment variables.
+    pub fn load() -> Self {
+        let cwd = env::current_dir().ok();
+        Self::load_from(&layer_paths(&config_dir(), cwd.as_deref()), |var| env::var(var).ok())
+    }
+
+    /// Merge the config files in `paths` (lowest priority first) over the defaults, then apply
+    /// overrides and `~` expansion from `env`.
+    pub fn load_from(paths: &[PathBuf], env: impl Fn(&str) -> Option<String>) -> Self {
+        let mut merged = toml::Table::new();
+        let mut sources = Vec::new();
+
+        for path in paths {
+            if let Some(table) = read_layer(path) {
+                merge_tables(&mut merged, table);
+                sources.push(path.clone());
+            }
+        }
+
+        let mut config = match Config::deserialize(toml::Value::Table(merged)) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("{} {}", "⚠️ Invalid gremlin config, using defaults:".bright_yellow(), e);
+                Config::default()
+            }
+        };
+        config.sources = sources;
+        config.apply_env(&env);
+
+        let home = env("HOME");
+        for path in [
+            &mut config.paths.scripts_root,
+            &mut config.paths.projects_root,
+            &mut config.paths.proc_root,
+            &mut config.audit.path,
+            &mut config.audit.fallback_path,
+        ] {
+            *path = expand_home_in(path, home.as_deref());
+        }
+        config
+    }
+
+    fn apply_env(&mut self, env: impl Fn(&str) -> Option<String>) {
+        let vars: [(&str, &mut String); 7] = [
+            ("GREMLIN_SCRIPTS_ROOT", &mut self.paths.scripts_root),
+            ("GREMLIN_PROJECTS_ROOT", &mut self.paths.projects_root),
+            ("GREMLIN_PROC_ROOT", &mut self.paths.proc_root),
+            ("GREMLIN_AUTHOR", &mut self.author.name),
+            ("GREMLIN_LICENSE", &mut self.author.license),
+            ("GREMLIN_DEFAULT_BRANCH", &mut self.git.default_branch),
+            ("GREMLIN_AUDIT_LOG", &mut self.audit.path),
+        ];
+        for (var, field) in vars {
+            if let Some(value) = env(var) {
+                *field = value;
+            }
+        }
+        if let Some(url) = env("GREMLIN_AUTHOR_URL") {
+            self.author.url = Some(url).filter(|u| !u.is_empty());
+        }
+        if let Some(manager) = env("GREMLIN_PACKAGE_MANAGER") {
+            self.packages.manager = Some(manager).filter(|m| !m.is_empty());
+        }
+        if let Some(tool) = env("GREMLIN_ELEVATE") {
+            self.privilege.tool = Some(tool).filter(|t| !t.is_empty());
+        }
+    }
+
+    /// Absolute path of a script or script folder, e.g. `Bash/backup.sh`.
+    pub fn script_path(&self, relative: &str) -> String {
+        format!("{}/{}", self.paths.scripts_root.trim_end_matches('/'), relative)
+    }
+
+    /// Absolute path of a project created by the project builder.
+    pub fn project_path(&self, project_name: &str) -> String {
+        format!("{}/{}", self.paths.projects_root.trim_end_matches('/'), project_name)
+    }
+
+    /// Author line used in generated headers, e.g. `Jane — https://github.com/jane`.
+    pub fn author_line(&self) -> String {
+        match &self.author.url {
+            Some(url) => format!("{} — {}", self.author.name, url),
+            None => self.author.name.clone(),
+        }
+    }
+
+    /// The effective configuration rendered as TOML.
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// `$XDG_CONFIG_HOME/gremlin`, falling back to `~/.config/gremlin`.
+pub fn config_dir() -> PathBuf {
+    match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join("gremlin"),
+        _ => PathBuf::from(expand_home("~/.config")).join("gremlin"),
+    }
+}
+
+/// Candidate config files, lowest priority first: system, user, then `.gremlin.toml` from `/`
+/// down to `cwd`.
+fn layer_paths(config_dir: &Path, cwd: Option<&Path>) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(SYSTEM_CONFIG_FILE), config_dir.join("config.toml")];
+
+    if let Some(cwd) = cwd {
+        let mut local: Vec<PathBuf> = cwd.ancestors().map(|dir| dir.join(LOCAL_CONFIG_FILE)).collect();
+        local.reverse();
+        paths.extend(local);
+    }
+
+    paths
+}
+
+fn read_layer(path: &Path) -> Option<toml::Table> {
+    let content = fs::read_to_string(path).ok()?;
+    match content.parse::<toml::Table>() {
+        Ok(table) => Some(table),
+        Err(e) => {
+            eprintln!("{} {}: {}", "⚠️ Ignoring invalid config".bright_yellow(), path.display(), e);
+            None
+        }
+    }
+}
+
+/// Recursively merge `layer` into `base`; values from `layer` win.
+fn merge_tables(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => merge_tables(existing, table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Expand a leading `~` to `$HOME`.
+pub fn expand_home(path: &str) -> String {
+    expand_home_in(path, env::var("HOME").ok().as_deref())
+}
+
+/// Expand a leading `~` to `home`; `~user` forms and a missing home are left alone.
+fn expand_home_in(path: &str, home: Option<&str>) -> String {
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn nested_tables_merge_key_by_key() {
+        let mut base: toml::Table = "[author]\nname = \"base\"\nlicense = \"MIT\"\n[git]\ndefault_branch = \"main\"".parse().unwrap();
+        let layer: toml::Table = "[author]\nname = \"layer\"\n[audit]\nkeep = 2".parse().unwrap();
+        merge_tables(&mut base, layer);
+
+        assert_eq!(base["author"]["name"].as_str(), Some("layer"));
+        assert_eq!(base["author"]["license"].as_str(), Some("MIT"));
+        assert_eq!(base["git"]["default_branch"].as_str(), Some("main"));
+        assert_eq!(base["audit"]["keep"].as_integer(), Some(2));
+    }
+
+    #[test]
+    fn a_scalar_layer_replaces_a_table() {
+        let mut base: toml::Table = "[author]\nname = \"base\"".parse().unwrap();
+        merge_tables(&mut base, "author = \"flat\"".parse().unwrap());
+        assert_eq!(base["author"].as_str(), Some("flat"));
+    }
+
+    #[test]
+    fn local_files_run_from_the_root_down_to_the_cwd() {
+        let paths = layer_paths(Path::new("/home/jane/.config/gremlin"), Some(Path::new("/srv/app/web")));
+        let expected: Vec<PathBuf> = [
+            SYSTEM_CONFIG_FILE,
+            "/home/jane/.config/gremlin/config.toml",
+            "/.gremlin.toml",
+            "/srv/.gremlin.toml",
+            "/srv/app/.gremlin.toml",
+            "/srv/app/web/.gremlin.toml",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        assert_eq!(paths, expected);
+
+        assert_eq!(layer_paths(Path::new("/etc/xdg/gremlin"), None).len(), 2);
+    }
+
+    #[test]
+    fn later_layers_win_and_missing_files_are_skipped() {
+        let dir = TempDir::new();
+        dir
+            .file("system.toml", "[author]\nname = \"system\"\nlicense = \"GPL-3.0\"\n[git]\ndefault_branch = \"trunk\"")
+            .file("user.toml", "[author]\nname = \"user\"\n[audit]\nkeep = 9")
+            .file("project/.gremlin.toml", "[author]\nname = \"project\"")
+            .file("broken.toml", "[author\nname =");
+        let paths: Vec<PathBuf> = ["system.toml", "user.toml", "missing.toml", "broken.toml", "project/.gremlin.toml"]
+            .iter()
+            .map(|name| dir.root().join(name))
+            .collect();
+
+        let config = Config::load_from(&paths, no_env);
+        assert_eq!(config.author.name, "project");
+        assert_eq!(config.author.license, "GPL-3.0");
+        assert_eq!(config.git.default_branch, "trunk");
+        assert_eq!(config.audit.keep, 9);
+        assert_eq!(config.audit.max_bytes, AuditConfig::default().max_bytes);
+        assert_eq!(config.sources, vec![paths[0].clone(), paths[1].clone(), paths[4].clone()]);
+    }
+
+    #[test]
+    fn environment_overrides_every_file() {
+        let dir = TempDir::new();
+        dir.file("user.toml", "[author]\nname = \"user\"\nurl = \"https://example.com\"\n[packages]\nmanager = \"apt\"");
+        let env = |var: &str| match var {
+            "GREMLIN_AUTHOR" => Some("env".to_string()),
+            "GREMLIN_AUTHOR_URL" => Some(String::new()),
+            "GREMLIN_PACKAGE_MANAGER" => Some("dnf".to_string()),
+            "GREMLIN_PROC_ROOT" => Some("/tmp/proc-copy".to_string()),
+            _ => None,
+        };
+
+        let config = Config::load_from(&[dir.root().join("user.toml")], env);
+        assert_eq!(config.author.name, "env");
+        assert_eq!(config.author.url, None);
+        assert_eq!(config.packages.manager.as_deref(), Some("dnf"));
+        assert_eq!(config.paths.proc_root, "/tmp/proc-copy");
+    }
+
+    #[test]
+    fn paths_expand_a_leading_tilde() {
+        let dir = TempDir::new();
+        dir.file("user.toml", "[paths]\nprojects_root = \"~/code\"\nscripts_root = \"~jane/scripts\"");
+        let env = |var: &str| match var {
+            "HOME" => Some("/home/jane".to_string()),
+            "GREMLIN_AUDIT_LOG" => Some("~/audit.jsonl".to_string()),
+            _ => None,
+        };
+
+        let config = Config::load_from(&[dir.root().join("user.toml")], env);
+        assert_eq!(config.paths.projects_root, "/home/jane/code");
+        assert_eq!(config.paths.scripts_root, "~jane/scripts");
+        assert_eq!(config.audit.path, "/home/jane/audit.jsonl");
+        assert_eq!(config.audit.fallback_path, "/home/jane/.local/state/gremlin/audit.jsonl");
+
+        assert_eq!(expand_home_in("~", Some("/root")), "/root");
+        assert_eq!(expand_home_in("a/~/b", Some("/root")), "a/~/b");
+        assert_eq!(expand_home_in("~/x", None), "~/x");
+    }
+}
@@ -1,8 +1,12 @@
-use std::process::Command;
 use std::path::Path;
+use std::sync::Arc;
 use colored::*;
 use thiserror::Error;
 
+use crate::config;
+use crate::exec::{self, Executor, Invocation};
+use crate::packages;
+
 #[derive(Error, Debug)]
 pub enum GitHubError {
     #[error("GitHub CLI not installed")]
@@ -29,13 +33,19 @@ pub struct GitHubCli {
     username: Option<String>,
     use_ssh: bool,
     interactive: bool,
+    exec: Arc<dyn Executor>,
 }
 
 impl GitHubCli {
     pub fn new() -> Self {
-        let is_installed = Self::check_installation();
+        Self::with_executor(exec::current())
+    }
+
+    /// Probe `gh` through `exec` instead of the process-wide executor.
+    pub fn with_executor(exec: Arc<dyn Executor>) -> Self {
+        let is_installed = Self::check_installation(&*exec);
         let (is_authenticated, username) = if is_installed {
-            Self::check_authentication()
+            Self::check_authentication(&*exec)
         } else {
             (false, None)
         };
@@ -44,8 +54,9 @@ impl GitHubCli {
             is_installed,
             is_authenticated,
             username,
-            use_ssh: Self::check_ssh_key(),
+            use_ssh: Self::check_ssh_key(&*exec),
             interactive: true,
+            exec,
         }
     }
 
@@ -61,32 +72,29 @@ impl GitHubCli {
         self
     }
 
-    fn check_installation() -> bool {
-        Command::new("which")
-            .arg("gh")
-            .output()
-            .map(|output| output.status.success())
+    fn check_installation(exec: &dyn Executor) -> bool {
+        exec.run(&Invocation::new("which").arg("gh").read_only())
+            .map(|output| output.success())
             .unwrap_or(false)
     }
 
-    fn check_authentication() -> (bool, Option<String>) {
-        let status = Command::new("gh")
+    fn check_authentication(exec: &dyn Executor) -> (bool, Option<String>) {
+        let status = exec.run(&Invocation::new("gh")
             .arg("auth")
             .arg("status")
-            .output();
+            .read_only());
         
         if let Ok(output) = status {
-            if output.status.success() {
+            if output.success() {
                 // Try to get username
-                let username = Command::new("gh")
+                let username = exec.run(&Invocation::new("gh")
                     .arg("api")
                     .arg("user")
                     .arg("--jq")
                     .arg(".login")
-                    .output()
+                    .read_only())
                     .ok()
-                    .and_then(|out| String::from_utf8(out.stdout).ok())
-                    .map(|s| s.trim().to_string());
+                    .map(|out| out.stdout.trim().to_string());
                 
                 return (true, username);
             }
@@ -94,11 +102,9 @@ impl GitHubCli {
         (false, None)
     }
 
-    fn check_ssh_key() -> bool {
-        Command::new("ssh")
-            .args(&["-T", "git@github.com"])
-            .output()
-            .map(|output| output.status.success())
+    fn check_ssh_key(exec: &dyn Executor) -> bool {
+        exec.run(&Invocation::new("ssh").args(["-T", "git@github.com"]).read_only())
+            .map(|output| output.success())
             .unwrap_or(false)
     }
 
@@ -106,12 +112,12 @@ impl GitHubCli {
         let username = self.username.as_deref()
             .ok_or(GitHubError::UsernameError)?;
 
-        let check_repo = Command::new("gh")
-            .args(&["api", &format!("/repos/{}/{}", username, repo_name)])
-            .output();
+        let check_repo = self.exec.run(&Invocation::new("gh")
+            .args(["api", &format!("/repos/{}/{}", username, repo_name)])
+            .read_only());
 
         match check_repo {
-            Ok(output) => Ok(output.status.success()),
+            Ok(output) => Ok(output.success()),
             Err(_) => Ok(false)
         }
     }
@@ -128,38 +134,11 @@ impl GitHubCli {
 
         println!("{}", "Installing GitHub CLI...".bright_cyan());
         
-        let install_status = if Command::new("which")
-            .arg("apt")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false) {
-            Command::new("sudo")
-                .args(&["apt", "update"])
-                .status()
-                .and_then(|_| {
-                    Command::new("sudo")
-                        .args(&["apt", "install", "-y", "gh"])
-                        .status()
-                })
-        } else if Command::new("which")
-            .arg("pacman")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false) {
-            Command::new("sudo")
-                .args(&["pacman", "-Syu", "--noconfirm"])
-                .status()
-                .and_then(|_| {
-                    Command::new("sudo")
-                        .args(&["pacman", "-S", "--noconfirm", "github-cli"])
-                        .status()
-                })
-        } else {
-            return Err(GitHubError::NotInstalled);
-        };
+        let manager = packages::detect().ok_or(GitHubError::NotInstalled)?;
+        let install_status = packages::install_attached(&*self.exec, manager, &["gh"]);
 
         match install_status {
-            Ok(status) if status.success() => {
+            Ok(true) => {
                 println!("{}", "✅ GitHub CLI installed successfully!".bright_green());
                 Ok(())
             },
@@ -183,9 +162,9 @@ impl GitHubCli {
         println!("{}", "A browser window will open for authentication.".bright_cyan());
         println!("{}", "Please follow the prompts to complete the authentication.".bright_cyan());
         
-        let auth_status = Command::new("gh")
-            .args(&["auth", "login", "--web"])
-            .status();
+        let auth_status = self.exec.run(&Invocation::new("gh")
+            .args(["auth", "login", "--web"])
+            .attached());
             
         match auth_status {
             Ok(status) if status.success() => {
@@ -232,10 +211,10 @@ impl GitHubCli {
             create_args.push("--push");
         }
 
-        let create_repo = Command::new("gh")
-            .args(&create_args)
+        let create_repo = self.exec.run(&Invocation::new("gh")
+            .args(create_args)
             .current_dir(project_path)
-            .status()
+            .attached())
             .map_err(|e| GitHubError::CreateRepoError(e.to_string()))?;
 
         if !create_repo.success() {
@@ -244,28 +223,30 @@ impl GitHubCli {
 
         // Initialize git if not already initialized
         if !Path::new(&format!("{}/.git", project_path)).exists() {
-            Command::new("git")
+            self.exec.run(&Invocation::new("git")
                 .arg("init")
+                .arg("--initial-branch")
+                .arg(&config::get().git.default_branch)
                 .current_dir(project_path)
-                .status()
+                .attached())
                 .map_err(|e| GitHubError::CommandFailed(e.to_string()))?;
 
-            Command::new("git")
-                .args(&["add", "."])
+            self.exec.run(&Invocation::new("git")
+                .args(["add", "."])
                 .current_dir(project_path)
-                .status()
+                .attached())
                 .map_err(|e| GitHubError::CommandFailed(e.to_string()))?;
 
-            Command::new("git")
-                .args(&["commit", "-m", "Initial commit"])
+            self.exec.run(&Invocation::new("git")
+                .args(["commit", "-m", "Initial commit"])
                 .current_dir(project_path)
-                .status()
+                .attached())
                 .map_err(|e| GitHubError::CommandFailed(e.to_string()))?;
         }
 
         // Add remote and push
         self.add_remote(project_path, repo_name)?;
-        self.push_to_remote(project_path, "main")?;
+        self.push_to_remote(project_path, &config::get().git.default_branch)?;
 
         let username = self.username.as_deref()
             .ok_or(GitHubError::UsernameError)?;
@@ -287,22 +268,80 @@ impl GitHubCli {
             format!("https://github.com/{}/{}.git", username, repo_name)
         };
         
-        Command::new("git")
-            .args(&["remote", "add", "origin", &remote_url])
+        self.exec.run(&Invocation::new("git")
+            .args(["remote", "add", "origin", &remote_url])
             .current_dir(project_path)
-            .status()
+            .attached())
             .map_err(|e| GitHubError::CommandFailed(e.to_string()))?;
 
         Ok(())
     }
 
     pub fn push_to_remote(&self, project_path: &str, branch: &str) -> Result<(), GitHubError> {
-        Command::new("git")
-            .args(&["push", "-u", "origin", branch])
+        self.exec.run(&Invocation::new("git")
+            .args(["push", "-u", "origin", branch])
             .current_dir(project_path)
-            .status()
+            .attached())
             .map_err(|e| GitHubError::PushError(e.to_string()))?;
 
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::{Completed, ScriptedExecutor};
+
+    /// `gh` installed and logged in as octocat, no SSH key, and no repository called `demo` yet.
+    fn logged_in() -> ScriptedExecutor {
+        ScriptedExecutor::new()
+            .on("which gh", Completed::ok("/usr/bin/gh\n"))
+            .on("gh auth status", Completed::ok(""))
+            .on("gh api user", Completed::ok("octocat\n"))
+            .on("ssh -T", Completed::failed(255, "Permission denied (publickey)."))
+            .on("gh api /repos/octocat/demo", Completed::failed(1, "Not Found"))
+            .on("gh repo create", Completed::ok(""))
+            .on("git", Completed::ok(""))
+    }
+
+    #[test]
+    fn create_repository_runs_gh_and_git_in_order() {
+        let exec = Arc::new(logged_in());
+        let project = std::env::temp_dir().join(format!("gremlin-test-{}", std::process::id())).join("demo");
+        let project = project.to_str().unwrap();
+        let url = GitHubCli::with_executor(exec.clone()).with_interactive(false).create_repository(project, true).unwrap();
+        assert_eq!(url, "https://github.com/octocat/demo");
+
+        let branch = &config::get().git.default_branch;
+        let calls: Vec<Vec<String>> = exec.calls().iter().skip(4).map(Invocation::argv).collect();
+        let expected: Vec<Vec<String>> = [
+            vec!["gh", "api", "/repos/octocat/demo"],
+            vec!["gh", "repo", "create", "demo", "--private"],
+            vec!["git", "init", "--initial-branch", branch],
+            vec!["git", "add", "."],
+            vec!["git", "commit", "-m", "Initial commit"],
+            vec!["git", "remote", "add", "origin", "https://github.com/octocat/demo.git"],
+            vec!["git", "push", "-u", "origin", branch],
+        ]
+        .into_iter()
+        .map(|argv| argv.into_iter().map(String::from).collect())
+        .collect();
+        assert_eq!(calls, expected);
+        assert!(exec.calls()[5..].iter().all(|call| call.cwd.as_deref() == Some(Path::new(project))));
+    }
+
+    #[test]
+    fn create_repository_refuses_an_existing_repository() {
+        let exec = Arc::new(ScriptedExecutor::new().on("gh api /repos/octocat/demo", Completed::ok("{}")).on("", Completed::ok("octocat\n")));
+        let result = GitHubCli::with_executor(exec.clone()).create_repository("/tmp/demo", false);
+        assert!(matches!(result, Err(GitHubError::RepositoryExists(name)) if name == "demo"));
+        assert!(!exec.calls().iter().any(|call| call.args.first().is_some_and(|arg| arg == "repo")));
+    }
+
+    #[test]
+    fn create_repository_needs_gh() {
+        let exec = Arc::new(ScriptedExecutor::new().on("which gh", Completed::failed(1, "")));
+        let result = GitHubCli::with_executor(exec).create_repository("/tmp/demo", false);
+        assert!(matches!(result, Err(GitHubError::NotInstalled)));
+    }
+}
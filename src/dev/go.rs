@@ -1,6 +1,7 @@
 use std::io::{self, Write};
 use std::fs;
 use colored::*;
+use crate::config;
 use crate::dev::project_setup;
 use crate::dev::template;
 
@@ -65,10 +66,10 @@ pub fn run() {
                 io::stdin().read_line(&mut template_choice).unwrap();
 
                 let result = match template_choice.trim() {
-                    "1" => project_setup::create_go_project(project_name, with_github, is_private),
-                    "2" => template::create_gin_template(project_name, is_private),
-                    "3" => template::create_echo_template(project_name, is_private),
-                    "4" => template::create_cobra_template(project_name, is_private),
+                    "1" => project_setup::audited("go", project_name, "standard", with_github, is_private, || project_setup::create_go_project(project_name, with_github, is_private)),
+                    "2" => project_setup::audited("go", project_name, "gin", with_github, is_private, || template::create_gin_template(project_name, with_github, is_private)),
+                    "3" => project_setup::audited("go", project_name, "echo", with_github, is_private, || template::create_echo_template(project_name, with_github, is_private)),
+                    "4" => project_setup::audited("go", project_name, "cobra", with_github, is_private, || template::create_cobra_template(project_name, with_github, is_private)),
                     _ => {
                         println!("{}", "⚠️ Invalid template choice.".bright_red());
                         continue;
@@ -81,7 +82,7 @@ pub fn run() {
                 }
                 
                 println!("{}", "✅ Project created successfully!".bright_green());
-                println!("{} {}", "Project path:".bright_cyan(), format!("/home/klea/Documents/Dev/{}", project_name).bright_blue());
+                println!("{} {}", "Project path:".bright_cyan(), config::get().project_path(project_name).bright_blue());
             },
             "2" => return,
             "3" => return,
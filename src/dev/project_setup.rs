@@ -1,6 +1,9 @@
 use std::fs;
-use std::process::Command;
+use std::time::Instant;
 use colored::*;
+use crate::audit;
+use crate::config;
+use crate::exec::Invocation;
 use crate::dev::github_utils::GitHubCli;
 
 /// Load ASCII art from a file
@@ -9,32 +12,64 @@ fn load_ascii(path: &str) -> String {
     fs::read_to_string(path).unwrap_or_else(|_| "✨ [ASCII missing] ✨".to_string())
 }
 
+/// Run a project builder action and record it in the audit log as the equivalent
+/// `gremlin new` call, so it can be replayed from `gremlin history`.
+pub fn audited(
+    language: &str,
+    project_name: &str,
+    template: &str,
+    with_github: bool,
+    is_private: bool,
+    build: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+    let github = match (with_github, is_private) {
+        (_, true) => "private",
+        (true, false) => "public",
+        (false, false) => "none",
+    };
+    let args = [language, project_name, "--template", template, "--github", github].map(String::from).to_vec();
+
+    let started = Instant::now();
+    let result = build();
+    let entry = audit::Entry::new(audit::Kind::Project, "new", args).with_duration(started.elapsed());
+    audit::record(&match &result {
+        Ok(()) => entry,
+        Err(e) => audit::Entry { exit_code: 1, error: Some(e.clone()), ..entry },
+    });
+    result
+}
+
 /// Initialize a git repository and create initial commit
-fn initialize_git(project_path: &str, is_private: bool) -> Result<(), String> {
+fn initialize_git(project_path: &str, with_github: bool, is_private: bool) -> Result<(), String> {
     // Initialize git repository
-    Command::new("git")
+    Invocation::new("git")
         .arg("init")
+        .arg("--initial-branch")
+        .arg(&config::get().git.default_branch)
         .current_dir(project_path)
-        .status()
+        .attached()
+        .run()
         .map_err(|e| format!("Failed to initialize git: {}", e))?;
 
     // Create initial commit
-    Command::new("git")
-        .args(&["add", "."])
+    Invocation::new("git")
+        .args(["add", "."])
         .current_dir(project_path)
-        .status()
+        .attached()
+        .run()
         .map_err(|e| format!("Failed to add files to git: {}", e))?;
 
-    Command::new("git")
-        .args(&["commit", "-m", "Initial commit"])
+    Invocation::new("git")
+        .args(["commit", "-m", "Initial commit"])
         .current_dir(project_path)
-        .status()
+        .attached()
+        .run()
         .map_err(|e| format!("Failed to create initial commit: {}", e))?;
 
-    // If private, create GitHub repository
-    if is_private {
+    // Create the GitHub repository if one was asked for
+    if with_github {
         let github = GitHubCli::new();
-        github.create_repository(project_path, true)
+        github.create_repository(project_path, is_private)
             .map_err(|e| format!("Failed to create GitHub repository: {}", e))?;
     }
 
@@ -44,20 +79,18 @@ fn initialize_git(project_path: &str, is_private: bool) -> Result<(), String> {
 /// Display the file structure of a project
 pub fn display_file_structure(path: &str, prefix: &str) {
     if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                let name = path.file_name().unwrap().to_str().unwrap();
-                
-                if name.starts_with('.') {
-                    continue;
-                }
-
-                println!("{}{}", prefix, name.bright_cyan());
-                
-                if path.is_dir() {
-                    display_file_structure(path.to_str().unwrap(), &format!("{}  ", prefix));
-                }
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            
+            if name.starts_with('.') {
+                continue;
+            }
+
+            println!("{}{}", prefix, name.bright_cyan());
+            
+            if path.is_dir() {
+                display_file_structure(path.to_str().unwrap(), &format!("{}  ", prefix));
             }
         }
     }
@@ -130,18 +163,19 @@ cargo run
 
 ## License
 
-This project is licensed under the MIT License - see the [LICENSE](LICENSE) file for details.
+This project is licensed under the {license} License - see the [LICENSE](LICENSE) file for details.
 "#, 
     project_path, 
     template,
     project_path,
     project_path,
-    project_path
+    project_path,
+    license = config::get().author.license
     )
 }
 
 /// Create a new project with the given name and template
-pub fn create_project(project_path: &str, template: &str, is_private: bool) -> Result<(), String> {
+pub fn create_project(project_path: &str, template: &str, with_github: bool, is_private: bool) -> Result<(), String> {
     // Create project directory
     fs::create_dir_all(project_path)
         .map_err(|e| format!("Failed to create project directory: {}", e))?;
@@ -159,8 +193,8 @@ pub fn create_project(project_path: &str, template: &str, is_private: bool) -> R
     fs::write(format!("{}/README.md", project_path), generate_readme(project_path, template))
         .map_err(|e| format!("Failed to create README.md: {}", e))?;
 
-    // Initialize git and create GitHub repository if private
-    initialize_git(project_path, is_private)?;
+    // Initialize git and create the GitHub repository if asked for
+    initialize_git(project_path, with_github, is_private)?;
 
     // Display project structure
     println!("\n{}", "Project structure:".bright_cyan());
@@ -170,8 +204,7 @@ pub fn create_project(project_path: &str, template: &str, is_private: bool) -> R
 }
 
 pub fn create_rust_project(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+    let project_path = config::get().project_path(project_name);
 
     // Create project directory and basic structure
     std::fs::create_dir_all(format!("{}/src", project_path))
@@ -214,11 +247,11 @@ edition = "2021"
 
 /// Create a new Go project
 pub fn create_go_project(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    create_project(&project_path, "Go", is_private)?;
+    // The repository is created below, once the Go files are in place
+    create_project(&project_path, "Go", false, false)?;
 
     // Create go.mod
     let go_mod = format!(r#"module {}
@@ -262,11 +295,11 @@ func main() {
 
 /// Create a new C++ project
 pub fn create_cpp_project(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    create_project(&project_path, "C++", is_private)?;
+    // The repository is created below, once the C++ files are in place
+    create_project(&project_path, "C++", false, false)?;
 
     // Create src directory
     let src_path = format!("{}/src", project_path);
@@ -1,5 +1,7 @@
 use std::fs;
 use colored::*;
+use crate::config;
+use crate::exec::Invocation;
 use crate::dev::project_setup;
 use crate::dev::github_utils::GitHubCli;
 
@@ -11,9 +13,9 @@ fn load_template_ascii(path: &str) -> String {
 
 /// Create a standard Rust project
 #[allow(dead_code)]
-pub fn create_rust_project(project_name: &str, is_private: bool) -> Result<(), String> {
+pub fn create_rust_project(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
     // Create basic project structure
-    project_setup::create_project(project_name, "Standard Rust", is_private)?;
+    project_setup::create_project(project_name, "Standard Rust", with_github, is_private)?;
 
     // Create Cargo.toml
     let cargo_toml = format!(r#"[package]
@@ -39,12 +41,11 @@ edition = "2021"
 
 /// Create an Iced GUI project
 #[allow(dead_code)]
-pub fn create_iced_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_iced_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    project_setup::create_project(&project_path, "Iced GUI", is_private)?;
+    project_setup::create_project(&project_path, "Iced GUI", with_github, is_private)?;
 
     // Create Cargo.toml
     let cargo_toml = format!(r#"[package]
@@ -121,12 +122,11 @@ fn main() -> iced::Result {
 
 /// Create a Ratatui TUI project
 #[allow(dead_code)]
-pub fn create_ratatui_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_ratatui_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    project_setup::create_project(&project_path, "Ratatui TUI", is_private)?;
+    project_setup::create_project(&project_path, "Ratatui TUI", with_github, is_private)?;
 
     // Create Cargo.toml
     let cargo_toml = format!(r#"[package]
@@ -233,9 +233,8 @@ fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result
 
 /// Create a Tauri desktop app project
 #[allow(dead_code)]
-pub fn create_tauri_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_tauri_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
     fs::create_dir_all(format!("{}/src", project_path))
@@ -409,16 +408,16 @@ npm run build
 
 ## License
 
-This project is licensed under the MIT License - see the [LICENSE](LICENSE) file for details.
-"#, project_name, project_name);
+This project is licensed under the {license} License - see the [LICENSE](LICENSE) file for details.
+"#, project_name, project_name, license = config::get().author.license);
 
     fs::write(format!("{}/README.md", project_path), readme)
         .map_err(|e| format!("Failed to create README.md: {}", e))?;
 
-    // Initialize git if needed
-    if is_private {
+    // Create the GitHub repository if asked for
+    if with_github {
         let github = GitHubCli::new();
-        github.create_repository(&project_path, true)
+        github.create_repository(&project_path, is_private)
             .map_err(|e| format!("Failed to create GitHub repository: {}", e))?;
     }
 
@@ -428,10 +427,11 @@ This project is licensed under the MIT License - see the [LICENSE](LICENSE) file
 
     // Run npm install
     println!("\n{}", "Installing npm dependencies...".bright_cyan());
-    std::process::Command::new("npm")
+    Invocation::new("npm")
         .arg("install")
         .current_dir(&project_path)
-        .status()
+        .attached()
+        .run()
         .map_err(|e| format!("Failed to run npm install: {}", e))?;
     println!("{}", "✅ npm dependencies installed successfully!".bright_green());
 
@@ -439,12 +439,11 @@ This project is licensed under the MIT License - see the [LICENSE](LICENSE) file
 }
 
 /// Create a Gin web framework project
-pub fn create_gin_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_gin_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    project_setup::create_project(&project_path, "Gin Web", is_private)?;
+    project_setup::create_project(&project_path, "Gin Web", with_github, is_private)?;
 
     // Create go.mod
     let go_mod = format!(r#"module {}
@@ -490,12 +489,11 @@ func main() {
 }
 
 /// Create an Echo web framework project
-pub fn create_echo_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_echo_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    project_setup::create_project(&project_path, "Echo Web", is_private)?;
+    project_setup::create_project(&project_path, "Echo Web", with_github, is_private)?;
 
     // Create go.mod
     let go_mod = format!(r#"module {}
@@ -541,12 +539,11 @@ func main() {
 }
 
 /// Create a Cobra CLI project
-pub fn create_cobra_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_cobra_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    project_setup::create_project(&project_path, "Cobra CLI", is_private)?;
+    project_setup::create_project(&project_path, "Cobra CLI", with_github, is_private)?;
 
     // Create go.mod
     let go_mod = format!(r#"module {}
@@ -618,12 +615,11 @@ func init() {
 }
 
 /// Create a Qt GUI project
-pub fn create_qt_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_qt_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    project_setup::create_project(&project_path, "Qt GUI", is_private)?;
+    project_setup::create_project(&project_path, "Qt GUI", with_github, is_private)?;
 
     // Create CMakeLists.txt
     let cmake_lists = format!(r#"cmake_minimum_required(VERSION 3.16)
@@ -709,12 +705,11 @@ MainWindow::~MainWindow() {}"#;
 }
 
 /// Create an SFML game project
-pub fn create_sfml_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_sfml_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    project_setup::create_project(&project_path, "SFML Game", is_private)?;
+    project_setup::create_project(&project_path, "SFML Game", with_github, is_private)?;
 
     // Create CMakeLists.txt
     let cmake_lists = format!(r#"cmake_minimum_required(VERSION 3.16)
@@ -767,12 +762,11 @@ int main() {
 }
 
 /// Create a CMake project
-pub fn create_cmake_template(project_name: &str, is_private: bool) -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Dev/";
-    let project_path = format!("{}{}", base_path, project_name);
+pub fn create_cmake_template(project_name: &str, with_github: bool, is_private: bool) -> Result<(), String> {
+    let project_path = config::get().project_path(project_name);
 
     // Create basic project structure
-    project_setup::create_project(&project_path, "CMake", is_private)?;
+    project_setup::create_project(&project_path, "CMake", with_github, is_private)?;
 
     // Create CMakeLists.txt
     let cmake_lists = format!(r#"cmake_minimum_required(VERSION 3.16)
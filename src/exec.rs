@@ -0,0 +1,272 @@
+//! Every external program gremlin runs goes through an [`Executor`], so the code that builds
+//! command lines and parses their output can be exercised against a fake instead of a real
+//! `sudo apt`, `gh` or `systemctl`.
+
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::privilege;
+
+static EXECUTOR: OnceLock<Arc<dyn Executor>> = OnceLock::new();
+
+/// Replace the process-wide executor. Only the first call wins; call it before anything runs.
+pub fn install(executor: Arc<dyn Executor>) {
+    let _ = EXECUTOR.set(executor);
+}
+
+/// The process-wide executor, a [`RealExecutor`] unless something else was installed.
+pub fn current() -> Arc<dyn Executor> {
+    EXECUTOR.get_or_init(|| Arc::new(RealExecutor)).clone()
+}
+
+/// Whether `binary` is an existing file in one of the `PATH` directories.
+pub fn on_path(binary: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| dir.join(binary).is_file()))
+        .unwrap_or(false)
+}
+
+/// One program invocation: what to run, where, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    /// Inherit the terminal instead of capturing output (editors, `htop`, `gh auth login`...).
+    pub attached: bool,
+    /// Only looks at the system; still runs during a dry run.
+    pub read_only: bool,
+    /// Needs root; the executor adds sudo/doas/run0/pkexec unless gremlin is root already.
+    pub elevated: bool,
+}
+
+impl Invocation {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self { program: program.into(), args: Vec::new(), cwd: None, attached: false, read_only: false, elevated: false }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(dir.into());
+        self
+    }
+
+    pub fn attached(mut self) -> Self {
+        self.attached = true;
+        self
+    }
+
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn elevated(mut self) -> Self {
+        self.elevated = true;
+        self
+    }
+
+    /// Program followed by its arguments.
+    pub fn argv(&self) -> Vec<String> {
+        std::iter::once(self.program.clone()).chain(self.args.iter().cloned()).collect()
+    }
+
+    /// The invocation as it would be typed in a shell (without quoting).
+    pub fn command_line(&self) -> String {
+        self.argv().join(" ")
+    }
+
+    /// Run with the process-wide executor.
+    pub fn run(&self) -> io::Result<Completed> {
+        current().run(self)
+    }
+}
+
+/// What a finished invocation produced. Output is empty for attached invocations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completed {
+    /// Exit code, `None` if the process was killed by a signal or never started.
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration: Duration,
+    /// Nothing was run because this is a dry run.
+    pub dry_run: bool,
+}
+
+impl Completed {
+    /// Exited with status 0, or was skipped by a dry run.
+    pub fn success(&self) -> bool {
+        self.dry_run || self.status == Some(0)
+    }
+
+    #[cfg(test)]
+    pub fn ok(stdout: impl Into<String>) -> Self {
+        Self { status: Some(0), stdout: stdout.into(), ..Default::default() }
+    }
+
+    #[cfg(test)]
+    pub fn failed(status: i32, stderr: impl Into<String>) -> Self {
+        Self { status: Some(status), stderr: stderr.into(), ..Default::default() }
+    }
+}
+
+pub trait Executor: Send + Sync {
+    fn run(&self, invocation: &Invocation) -> io::Result<Completed>;
+
+    /// The invocation `run` passes on for `invocation`, e.g. marked as needing root.
+    fn effective(&self, invocation: &Invocation) -> Invocation {
+        invocation.clone()
+    }
+
+    /// True when invocations that change the system are recorded instead of run.
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+}
+
+/// Spawns real processes.
+pub struct RealExecutor;
+
+impl Executor for RealExecutor {
+    fn run(&self, invocation: &Invocation) -> io::Result<Completed> {
+        let invocation = &privilege::resolve(invocation)?;
+        let mut command = Command::new(&invocation.program);
+        command.args(&invocation.args);
+        if let Some(cwd) = &invocation.cwd {
+            command.current_dir(cwd);
+        }
+
+        let started = Instant::now();
+        if invocation.attached {
+            let status = command.status()?;
+            return Ok(Completed { status: status.code(), duration: started.elapsed(), ..Default::default() });
+        }
+
+        let output = command.output()?;
+        Ok(Completed {
+            status: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            duration: started.elapsed(),
+            dry_run: false,
+        })
+    }
+}
+
+/// Dry run: records everything that would change the system instead of running it.
+/// Read-only invocations still run, so previews and lookups show real targets.
+#[derive(Default)]
+pub struct RecordingExecutor {
+    recorded: Mutex<Vec<Invocation>>,
+}
+
+impl RecordingExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Invocations that were skipped, in order.
+    #[cfg(test)]
+    pub fn recorded(&self) -> Vec<Invocation> {
+        self.recorded.lock().unwrap().clone()
+    }
+}
+
+impl Executor for RecordingExecutor {
+    fn run(&self, invocation: &Invocation) -> io::Result<Completed> {
+        if invocation.read_only {
+            return RealExecutor.run(invocation);
+        }
+        self.recorded.lock().unwrap().push(invocation.clone());
+        Ok(Completed { dry_run: true, ..Default::default() })
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+}
+
+/// Fake that answers from canned responses, matched on the start of the command line.
+/// Unmatched invocations fail like a missing program.
+#[cfg(test)]
+#[derive(Default)]
+pub struct ScriptedExecutor {
+    responses: Vec<(String, Completed)>,
+    calls: Mutex<Vec<Invocation>>,
+}
+
+#[cfg(test)]
+impl ScriptedExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer invocations whose command line starts with `prefix`; earlier rules win.
+    pub fn on(mut self, prefix: impl Into<String>, response: Completed) -> Self {
+        self.responses.push((prefix.into(), response));
+        self
+    }
+
+    /// Every invocation seen so far, matched or not.
+    pub fn calls(&self) -> Vec<Invocation> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl Executor for ScriptedExecutor {
+    fn run(&self, invocation: &Invocation) -> io::Result<Completed> {
+        self.calls.lock().unwrap().push(invocation.clone());
+        let command_line = invocation.command_line();
+        self.responses
+            .iter()
+            .find(|(prefix, _)| command_line.starts_with(prefix.as_str()))
+            .map(|(_, response)| response.clone())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no scripted response for `{}`", command_line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_executor_skips_changes() {
+        let exec = RecordingExecutor::new();
+        let completed = exec.run(&Invocation::new("systemctl").args(["restart", "nginx"]).elevated()).unwrap();
+        assert!(completed.dry_run && completed.success());
+        let recorded = exec.recorded();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].argv(), ["systemctl", "restart", "nginx"]);
+        assert!(recorded[0].elevated);
+    }
+
+    #[test]
+    fn scripted_executor_matches_prefixes_in_order() {
+        let exec = ScriptedExecutor::new()
+            .on("gh api /repos", Completed::failed(1, "Not Found"))
+            .on("gh api", Completed::ok("octocat\n"));
+        assert_eq!(exec.run(&Invocation::new("gh").args(["api", "user"])).unwrap().stdout, "octocat\n");
+        assert_eq!(exec.run(&Invocation::new("gh").args(["api", "/repos/a/b"])).unwrap().status, Some(1));
+        let missing = exec.run(&Invocation::new("apt").arg("update")).unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+        assert_eq!(exec.calls().len(), 3);
+    }
+}
@@ -1,10 +1,23 @@
+mod audit;
+mod cli;
+mod config;
+mod exec;
+mod packages;
+mod privilege;
+mod probe;
+mod procfs;
 mod project_builder;
 mod script_runner;
+mod signals;
 pub mod sys_admin;
+#[cfg(test)]
+mod test_support;
 mod dev;
 
 use std::io::{self, Write};
 use std::fs;
+use std::sync::Arc;
+use clap::Parser;
 use colored::*;
 
 /// Load ASCII art from a file
@@ -38,5 +51,13 @@ fn main_menu() {
 }
 
 fn main() {
-    main_menu();
+    let cli = cli::Cli::parse();
+    if cli.dry_run {
+        exec::install(Arc::new(exec::RecordingExecutor::new()));
+    }
+    sys_admin::guard::set_assume_yes(cli.yes);
+    match cli.command {
+        Some(command) => std::process::exit(cli::dispatch(command, cli.format)),
+        None => main_menu(),
+    }
 }
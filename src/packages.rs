@@ -0,0 +1,918 @@
+//! One interface over the distro package managers. Each backend knows how to build the
+//! invocations for an operation and how to parse what comes back; `detect` picks the one
+//! for this machine.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
+
+use crate::config;
+use crate::exec::{self, Executor, Invocation};
+use crate::privilege;
+
+/// Canonical → per-manager package names, see `package_names.toml`.
+const NAMES: &str = include_str!("package_names.toml");
+
+/// Binary → canonical package providing it, see `binary_packages.toml`.
+const PROVIDERS: &str = include_str!("binary_packages.toml");
+
+/// An installed or available package.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+}
+
+/// An installed package with a newer version available.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Upgrade {
+    pub name: String,
+    /// Empty when the manager doesn't report it.
+    pub current: String,
+    pub available: String,
+}
+
+pub trait PackageManager: Send + Sync {
+    /// Short name, e.g. `apt` or `paru`.
+    fn name(&self) -> &'static str;
+
+    /// Key used for package-name mapping; AUR helpers share pacman's names.
+    fn family(&self) -> &'static str {
+        self.name()
+    }
+
+    /// Program whose presence on `PATH` means this manager is usable.
+    fn program(&self) -> &'static str;
+
+    fn install(&self, packages: &[String]) -> Invocation;
+    fn remove(&self, packages: &[String]) -> Invocation;
+    /// Refresh the package index, if the manager keeps one separately from installing.
+    fn refresh(&self) -> Option<Invocation>;
+    /// Upgrade every installed package (after `refresh`).
+    fn upgrade(&self) -> Invocation;
+    fn search(&self, query: &str) -> Invocation;
+    fn info(&self, package: &str) -> Invocation;
+    fn list_installed(&self) -> Invocation;
+    fn list_upgradable(&self) -> Invocation;
+    fn owns_file(&self, path: &str) -> Invocation;
+    /// Read-only simulation of `remove`, listing everything that would go.
+    fn simulate_remove(&self, _packages: &[String]) -> Option<Invocation> {
+        None
+    }
+
+    fn parse_search(&self, stdout: &str) -> Vec<Package>;
+    fn parse_installed(&self, stdout: &str) -> Vec<Package>;
+    fn parse_upgradable(&self, stdout: &str) -> Vec<Upgrade>;
+    fn parse_owner(&self, stdout: &str) -> Option<String>;
+    /// Packages `simulate_remove` says would go, with their versions where it gives them.
+    fn parse_removals(&self, _stdout: &str) -> Vec<Package> {
+        Vec::new()
+    }
+
+    /// Refresh (if needed) and upgrade, in order.
+    fn update(&self) -> Vec<Invocation> {
+        self.refresh().into_iter().chain(std::iter::once(self.upgrade())).collect()
+    }
+}
+
+fn query(program: &str, args: &[&str]) -> Invocation {
+    Invocation::new(program).args(args.iter().copied()).read_only()
+}
+
+fn as_root(program: &str, args: &[&str], packages: &[String]) -> Invocation {
+    Invocation::new(program).args(args.iter().copied()).args(packages.iter().cloned()).elevated()
+}
+
+// apt / dpkg
+
+pub struct Apt;
+
+impl PackageManager for Apt {
+    fn name(&self) -> &'static str {
+        "apt"
+    }
+
+    fn program(&self) -> &'static str {
+        "apt-get"
+    }
+
+    fn install(&self, packages: &[String]) -> Invocation {
+        as_root("apt-get", &["install", "-y"], packages)
+    }
+
+    fn remove(&self, packages: &[String]) -> Invocation {
+        as_root("apt-get", &["remove", "-y"], packages)
+    }
+
+    fn refresh(&self) -> Option<Invocation> {
+        Some(as_root("apt-get", &["update"], &[]))
+    }
+
+    fn upgrade(&self) -> Invocation {
+        as_root("apt-get", &["upgrade", "-y"], &[])
+    }
+
+    fn search(&self, query_text: &str) -> Invocation {
+        query("apt-cache", &["search", query_text])
+    }
+
+    fn info(&self, package: &str) -> Invocation {
+        query("apt-cache", &["show", package])
+    }
+
+    fn list_installed(&self) -> Invocation {
+        query("dpkg-query", &["-W", "-f", "${Package}\t${Version}\t${binary:Summary}\n"])
+    }
+
+    fn list_upgradable(&self) -> Invocation {
+        query("apt", &["list", "--upgradable"])
+    }
+
+    fn owns_file(&self, path: &str) -> Invocation {
+        query("dpkg", &["-S", path])
+    }
+
+    fn simulate_remove(&self, packages: &[String]) -> Option<Invocation> {
+        Some(query("apt-get", &["-s", "remove"]).args(packages.iter().cloned()))
+    }
+
+    /// `name - description`
+    fn parse_search(&self, stdout: &str) -> Vec<Package> {
+        stdout
+            .lines()
+            .filter_map(|line| line.split_once(" - "))
+            .map(|(name, description)| Package { name: name.trim().to_string(), description: description.trim().to_string(), ..Default::default() })
+            .collect()
+    }
+
+    fn parse_installed(&self, stdout: &str) -> Vec<Package> {
+        parse_tab_separated(stdout)
+    }
+
+    /// `curl/jammy-updates 7.81.0-1ubuntu1.16 amd64 [upgradable from: 7.81.0-1ubuntu1.15]`
+    fn parse_upgradable(&self, stdout: &str) -> Vec<Upgrade> {
+        stdout
+            .lines()
+            .filter(|line| line.contains("[upgradable from:"))
+            .filter_map(|line| {
+                let (name, rest) = line.split_once('/')?;
+                let available = rest.split_whitespace().nth(1)?.to_string();
+                let current = line.split("from:").nth(1)?.trim().trim_end_matches(']').to_string();
+                Some(Upgrade { name: name.to_string(), current, available })
+            })
+            .collect()
+    }
+
+    /// `bind9-dnsutils: /usr/bin/dig`
+    fn parse_owner(&self, stdout: &str) -> Option<String> {
+        stdout
+            .lines()
+            .filter(|line| !line.starts_with("diversion"))
+            .find_map(|line| line.split_once(": "))
+            .map(|(package, _)| package.to_string())
+    }
+
+    /// `Remv curl [7.81.0-1]`, or `Remv curl [7.81.0-1] [libcurl4:amd64 ]` when more goes too.
+    fn parse_removals(&self, stdout: &str) -> Vec<Package> {
+        stdout
+            .lines()
+            .filter_map(|line| line.strip_prefix("Remv "))
+            .map(|rest| {
+                let (name, version) = rest.split_once(" [").unwrap_or((rest, ""));
+                let version = version.split(']').next().unwrap_or_default();
+                Package { name: name.trim().to_string(), version: version.to_string(), ..Default::default() }
+            })
+            .collect()
+    }
+}
+
+// pacman and the AUR helpers, which take the same flags
+
+pub struct Pacman {
+    /// `pacman`, `yay` or `paru`.
+    program: &'static str,
+}
+
+impl Pacman {
+    pub fn native() -> Self {
+        Self { program: "pacman" }
+    }
+
+    pub fn aur_helper(program: &'static str) -> Self {
+        Self { program }
+    }
+
+    fn is_helper(&self) -> bool {
+        self.program != "pacman"
+    }
+
+    /// pacman itself needs root; AUR helpers refuse it and call sudo themselves.
+    fn changing(&self, args: &[&str], packages: &[String]) -> Invocation {
+        let invocation = Invocation::new(self.program).args(args.iter().copied()).args(packages.iter().cloned());
+        if self.is_helper() {
+            invocation
+        } else {
+            invocation.elevated()
+        }
+    }
+}
+
+impl PackageManager for Pacman {
+    fn name(&self) -> &'static str {
+        self.program
+    }
+
+    fn family(&self) -> &'static str {
+        "pacman"
+    }
+
+    fn program(&self) -> &'static str {
+        self.program
+    }
+
+    fn install(&self, packages: &[String]) -> Invocation {
+        self.changing(&["-S", "--needed", "--noconfirm"], packages)
+    }
+
+    fn remove(&self, packages: &[String]) -> Invocation {
+        self.changing(&["-R", "--noconfirm"], packages)
+    }
+
+    /// Syncing without upgrading leaves a partially upgraded system, so `upgrade` does both.
+    fn refresh(&self) -> Option<Invocation> {
+        None
+    }
+
+    fn upgrade(&self) -> Invocation {
+        self.changing(&["-Syu", "--noconfirm"], &[])
+    }
+
+    fn search(&self, query_text: &str) -> Invocation {
+        query(self.program, &["-Ss", query_text])
+    }
+
+    fn info(&self, package: &str) -> Invocation {
+        query(self.program, &["-Si", package])
+    }
+
+    fn list_installed(&self) -> Invocation {
+        query("pacman", &["-Q"])
+    }
+
+    fn list_upgradable(&self) -> Invocation {
+        query(self.program, &["-Qu"])
+    }
+
+    fn owns_file(&self, path: &str) -> Invocation {
+        query("pacman", &["-Qo", path])
+    }
+
+    fn simulate_remove(&self, packages: &[String]) -> Option<Invocation> {
+        Some(query("pacman", &["-R", "--print", "--print-format", "%n %v"]).args(packages.iter().cloned()))
+    }
+
+    /// `core/linux 6.1.1-1 (base)` followed by an indented description line.
+    fn parse_search(&self, stdout: &str) -> Vec<Package> {
+        let mut found: Vec<Package> = Vec::new();
+        for line in stdout.lines() {
+            if line.starts_with(char::is_whitespace) {
+                if let Some(last) = found.last_mut() {
+                    last.description = line.trim().to_string();
+                }
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            if let (Some(name), Some(version)) = (fields.next(), fields.next()) {
+                let name = name.split_once('/').map(|(_, name)| name).unwrap_or(name);
+                found.push(Package { name: name.to_string(), version: version.to_string(), ..Default::default() });
+            }
+        }
+        found
+    }
+
+    /// `name version`
+    fn parse_installed(&self, stdout: &str) -> Vec<Package> {
+        stdout
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(name, version)| Package { name: name.to_string(), version: version.trim().to_string(), ..Default::default() })
+            .collect()
+    }
+
+    /// `name 1.0-1 -> 1.1-1`
+    fn parse_upgradable(&self, stdout: &str) -> Vec<Upgrade> {
+        stdout
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                match fields.as_slice() {
+                    [name, current, "->", available, ..] => Some(Upgrade {
+                        name: name.to_string(),
+                        current: current.to_string(),
+                        available: available.to_string(),
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// `/usr/bin/ls is owned by coreutils 9.1-3`
+    fn parse_owner(&self, stdout: &str) -> Option<String> {
+        owned_by(stdout).and_then(|owner| owner.split_whitespace().next()).map(String::from)
+    }
+
+    /// `name version`, as asked for with `--print-format`.
+    fn parse_removals(&self, stdout: &str) -> Vec<Package> {
+        self.parse_installed(stdout)
+    }
+}
+
+// dnf / rpm
+
+pub struct Dnf;
+
+impl PackageManager for Dnf {
+    fn name(&self) -> &'static str {
+        "dnf"
+    }
+
+    fn program(&self) -> &'static str {
+        "dnf"
+    }
+
+    fn install(&self, packages: &[String]) -> Invocation {
+        as_root("dnf", &["install", "-y"], packages)
+    }
+
+    fn remove(&self, packages: &[String]) -> Invocation {
+        as_root("dnf", &["remove", "-y"], packages)
+    }
+
+    /// `upgrade --refresh` fetches metadata itself.
+    fn refresh(&self) -> Option<Invocation> {
+        None
+    }
+
+    fn upgrade(&self) -> Invocation {
+        as_root("dnf", &["upgrade", "-y", "--refresh"], &[])
+    }
+
+    fn search(&self, query_text: &str) -> Invocation {
+        query("dnf", &["search", "-q", query_text])
+    }
+
+    fn info(&self, package: &str) -> Invocation {
+        query("dnf", &["info", "-q", package])
+    }
+
+    fn list_installed(&self) -> Invocation {
+        rpm_list_installed()
+    }
+
+    fn list_upgradable(&self) -> Invocation {
+        query("dnf", &["list", "--upgrades", "-q"])
+    }
+
+    fn owns_file(&self, path: &str) -> Invocation {
+        query("rpm", &["-qf", "--qf", "%{NAME}\n", path])
+    }
+
+    /// dnf won't resolve a removal without root, even one it is told to abort.
+    fn simulate_remove(&self, packages: &[String]) -> Option<Invocation> {
+        Some(query("dnf", &["remove", "--assumeno"]).args(packages.iter().cloned()).elevated())
+    }
+
+    /// `name.arch : summary` (dnf 4) or `name.arch<TAB>summary` (dnf 5), under `=== ... ===` headings.
+    fn parse_search(&self, stdout: &str) -> Vec<Package> {
+        stdout
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('=') && !line.starts_with("Matched fields"))
+            .filter_map(|line| line.split_once(" : ").or_else(|| line.trim_start().split_once('\t')))
+            .map(|(name, description)| Package {
+                name: strip_arch(name.trim()).to_string(),
+                description: description.trim().to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn parse_installed(&self, stdout: &str) -> Vec<Package> {
+        parse_tab_separated(stdout)
+    }
+
+    /// `name.arch  version  repo`; dnf doesn't report the installed version here.
+    fn parse_upgradable(&self, stdout: &str) -> Vec<Upgrade> {
+        stdout
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                match fields.as_slice() {
+                    [name, available, _repo] if name.contains('.') => Some(Upgrade {
+                        name: strip_arch(name).to_string(),
+                        current: String::new(),
+                        available: available.to_string(),
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn parse_owner(&self, stdout: &str) -> Option<String> {
+        rpm_owner(stdout)
+    }
+
+    /// ` name  arch  version  repo  size` rows under the `Removing...:` headings of the
+    /// transaction table; dnf 4 wraps a long name onto a line of its own.
+    fn parse_removals(&self, stdout: &str) -> Vec<Package> {
+        let mut removing = false;
+        let mut wrapped: Option<&str> = None;
+        let mut found = Vec::new();
+        for line in stdout.lines() {
+            if !line.starts_with(char::is_whitespace) {
+                removing = line.starts_with("Removing");
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match (removing, wrapped.take(), fields.as_slice()) {
+                (true, None, [name]) => wrapped = Some(name),
+                (true, Some(name), [_arch, version, ..]) => found.push(Package { name: name.to_string(), version: version.to_string(), ..Default::default() }),
+                (true, None, [name, _arch, version, ..]) if !name.ends_with(':') => {
+                    found.push(Package { name: name.to_string(), version: version.to_string(), ..Default::default() })
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+}
+
+// zypper / rpm
+
+pub struct Zypper;
+
+impl PackageManager for Zypper {
+    fn name(&self) -> &'static str {
+        "zypper"
+    }
+
+    fn program(&self) -> &'static str {
+        "zypper"
+    }
+
+    fn install(&self, packages: &[String]) -> Invocation {
+        as_root("zypper", &["--non-interactive", "install"], packages)
+    }
+
+    fn remove(&self, packages: &[String]) -> Invocation {
+        as_root("zypper", &["--non-interactive", "remove"], packages)
+    }
+
+    fn refresh(&self) -> Option<Invocation> {
+        Some(as_root("zypper", &["--non-interactive", "refresh"], &[]))
+    }
+
+    fn upgrade(&self) -> Invocation {
+        as_root("zypper", &["--non-interactive", "update"], &[])
+    }
+
+    fn search(&self, query_text: &str) -> Invocation {
+        query("zypper", &["--non-interactive", "search", query_text])
+    }
+
+    fn info(&self, package: &str) -> Invocation {
+        query("zypper", &["--non-interactive", "info", package])
+    }
+
+    fn list_installed(&self) -> Invocation {
+        rpm_list_installed()
+    }
+
+    fn list_upgradable(&self) -> Invocation {
+        query("zypper", &["--non-interactive", "list-updates"])
+    }
+
+    fn owns_file(&self, path: &str) -> Invocation {
+        query("rpm", &["-qf", "--qf", "%{NAME}\n", path])
+    }
+
+    fn simulate_remove(&self, packages: &[String]) -> Option<Invocation> {
+        Some(query("zypper", &["--non-interactive", "remove", "--dry-run"]).args(packages.iter().cloned()))
+    }
+
+    /// `S | Name | Summary | Type` table.
+    fn parse_search(&self, stdout: &str) -> Vec<Package> {
+        zypper_table(stdout)
+            .into_iter()
+            .filter_map(|fields| match fields.as_slice() {
+                [_, name, summary, ..] => Some(Package { name: name.clone(), description: summary.clone(), ..Default::default() }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn parse_installed(&self, stdout: &str) -> Vec<Package> {
+        parse_tab_separated(stdout)
+    }
+
+    /// `S | Repository | Name | Current Version | Available Version | Arch` table.
+    fn parse_upgradable(&self, stdout: &str) -> Vec<Upgrade> {
+        zypper_table(stdout)
+            .into_iter()
+            .filter_map(|fields| match fields.as_slice() {
+                [_, _, name, current, available, ..] => Some(Upgrade {
+                    name: name.clone(),
+                    current: current.clone(),
+                    available: available.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn parse_owner(&self, stdout: &str) -> Option<String> {
+        rpm_owner(stdout)
+    }
+
+    /// Names listed after `The following N packages are going to be REMOVED:`.
+    fn parse_removals(&self, stdout: &str) -> Vec<Package> {
+        stdout
+            .lines()
+            .skip_while(|line| !line.contains("going to be REMOVED"))
+            .skip(1)
+            .take_while(|line| !line.trim().is_empty())
+            .flat_map(|line| line.split_whitespace().map(|name| Package { name: name.to_string(), ..Default::default() }).collect::<Vec<_>>())
+            .collect()
+    }
+}
+
+// apk
+
+pub struct Apk;
+
+impl PackageManager for Apk {
+    fn name(&self) -> &'static str {
+        "apk"
+    }
+
+    fn program(&self) -> &'static str {
+        "apk"
+    }
+
+    fn install(&self, packages: &[String]) -> Invocation {
+        as_root("apk", &["add"], packages)
+    }
+
+    fn remove(&self, packages: &[String]) -> Invocation {
+        as_root("apk", &["del"], packages)
+    }
+
+    fn refresh(&self) -> Option<Invocation> {
+        Some(as_root("apk", &["update"], &[]))
+    }
+
+    fn upgrade(&self) -> Invocation {
+        as_root("apk", &["upgrade"], &[])
+    }
+
+    fn search(&self, query_text: &str) -> Invocation {
+        query("apk", &["search", "-v", query_text])
+    }
+
+    fn info(&self, package: &str) -> Invocation {
+        query("apk", &["info", "-a", package])
+    }
+
+    fn list_installed(&self) -> Invocation {
+        query("apk", &["info", "-v"])
+    }
+
+    fn list_upgradable(&self) -> Invocation {
+        query("apk", &["version", "-l", "<"])
+    }
+
+    fn owns_file(&self, path: &str) -> Invocation {
+        query("apk", &["info", "--who-owns", path])
+    }
+
+    fn simulate_remove(&self, packages: &[String]) -> Option<Invocation> {
+        Some(query("apk", &["del", "--simulate"]).args(packages.iter().cloned()))
+    }
+
+    /// `name-1.2.3-r0 - description`
+    fn parse_search(&self, stdout: &str) -> Vec<Package> {
+        stdout
+            .lines()
+            .filter_map(|line| {
+                let (name_version, description) = line.split_once(" - ")?;
+                let (name, version) = split_apk_version(name_version.trim())?;
+                Some(Package { name, version, description: description.trim().to_string() })
+            })
+            .collect()
+    }
+
+    /// `name-1.2.3-r0`
+    fn parse_installed(&self, stdout: &str) -> Vec<Package> {
+        stdout
+            .lines()
+            .filter(|line| !line.starts_with("WARNING"))
+            .filter_map(|line| split_apk_version(line.trim()))
+            .map(|(name, version)| Package { name, version, ..Default::default() })
+            .collect()
+    }
+
+    /// `name-1.2.3-r0 < 1.2.4-r0`
+    fn parse_upgradable(&self, stdout: &str) -> Vec<Upgrade> {
+        stdout
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                match fields.as_slice() {
+                    [installed, "<", available] => {
+                        let (name, current) = split_apk_version(installed)?;
+                        Some(Upgrade { name, current, available: available.to_string() })
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// `/bin/busybox is owned by busybox-1.36.1-r2`
+    fn parse_owner(&self, stdout: &str) -> Option<String> {
+        owned_by(stdout).and_then(|owner| split_apk_version(owner.trim())).map(|(name, _)| name)
+    }
+
+    /// `(1/2) Purging curl (8.5.0-r0)`
+    fn parse_removals(&self, stdout: &str) -> Vec<Package> {
+        stdout
+            .lines()
+            .filter_map(|line| line.split_once("Purging ").map(|(_, rest)| rest.trim()))
+            .map(|rest| {
+                let (name, version) = rest.split_once(" (").unwrap_or((rest, ""));
+                Package { name: name.to_string(), version: version.trim_end_matches(')').to_string(), ..Default::default() }
+            })
+            .collect()
+    }
+}
+
+fn rpm_list_installed() -> Invocation {
+    query("rpm", &["-qa", "--qf", "%{NAME}\t%{VERSION}-%{RELEASE}\t%{SUMMARY}\n"])
+}
+
+fn rpm_owner(stdout: &str) -> Option<String> {
+    stdout
+        .lines()
+        .next()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.contains("not owned"))
+        .map(String::from)
+}
+
+/// `name<TAB>version<TAB>description` lines from dpkg-query and rpm.
+fn parse_tab_separated(stdout: &str) -> Vec<Package> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            Some(Package {
+                name: fields.next().filter(|name| !name.is_empty())?.to_string(),
+                version: fields.next().unwrap_or_default().to_string(),
+                description: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// What follows `is owned by` in pacman and apk output.
+fn owned_by(stdout: &str) -> Option<&str> {
+    stdout.lines().find_map(|line| line.split_once(" is owned by ")).map(|(_, owner)| owner)
+}
+
+fn strip_arch(name: &str) -> &str {
+    name.rsplit_once('.').map(|(name, _)| name).unwrap_or(name)
+}
+
+/// apk names carry their version: the last two `-` separated parts (`1.2.3-r0`).
+fn split_apk_version(name_version: &str) -> Option<(String, String)> {
+    let mut parts = name_version.rsplitn(3, '-');
+    let release = parts.next()?;
+    let version = parts.next()?;
+    let name = parts.next()?;
+    Some((name.to_string(), format!("{}-{}", version, release)))
+}
+
+/// Data rows of a `|`-separated zypper table, skipping its header and rule lines.
+fn zypper_table(stdout: &str) -> Vec<Vec<String>> {
+    stdout
+        .lines()
+        .filter(|line| line.contains('|'))
+        .skip(1)
+        .filter(|line| !line.starts_with('-'))
+        .map(|line| line.split('|').map(|field| field.trim().to_string()).collect())
+        .collect()
+}
+
+/// A backend by the name used in `packages.manager`.
+pub fn by_name(name: &str) -> Option<Box<dyn PackageManager>> {
+    match name.trim() {
+        "apt" | "apt-get" | "dpkg" => Some(Box::new(Apt)),
+        "pacman" => Some(Box::new(Pacman::native())),
+        "yay" => Some(Box::new(Pacman::aur_helper("yay"))),
+        "paru" => Some(Box::new(Pacman::aur_helper("paru"))),
+        "dnf" | "yum" => Some(Box::new(Dnf)),
+        "zypper" => Some(Box::new(Zypper)),
+        "apk" => Some(Box::new(Apk)),
+        _ => None,
+    }
+}
+
+/// The configured backend, else the one matching `/etc/os-release`, else the first on `PATH`.
+pub fn detect() -> Option<&'static dyn PackageManager> {
+    static DETECTED: OnceLock<Option<Box<dyn PackageManager>>> = OnceLock::new();
+    DETECTED
+        .get_or_init(|| {
+            let os_release = fs::read_to_string("/etc/os-release").unwrap_or_default();
+            // AUR helpers refuse to run as root, so `sudo gremlin` falls back to pacman.
+            let root = privilege::is_root();
+            let usable = |program: &str| !(root && AUR_HELPERS.contains(&program)) && exec::on_path(program);
+            detect_from(config::get().packages.manager.as_deref(), &os_release, usable)
+        })
+        .as_deref()
+}
+
+/// Preferred over pacman on Arch when installed, so AUR packages install too.
+const AUR_HELPERS: [&str; 2] = ["yay", "paru"];
+
+/// `detect` with its inputs passed in, so it can be checked against any distro.
+pub fn detect_from(configured: Option<&str>, os_release: &str, on_path: impl Fn(&str) -> bool) -> Option<Box<dyn PackageManager>> {
+    if let Some(name) = configured {
+        return by_name(name);
+    }
+
+    let ids: Vec<String> = os_release
+        .lines()
+        .filter_map(|line| line.strip_prefix("ID=").or_else(|| line.strip_prefix("ID_LIKE=")))
+        .flat_map(|value| value.trim_matches('"').split_whitespace().map(String::from).collect::<Vec<_>>())
+        .collect();
+    let by_distro = ids.iter().find_map(|id| match id.as_str() {
+        "debian" | "ubuntu" => Some("apt"),
+        "arch" => Some("pacman"),
+        "fedora" | "rhel" | "centos" => Some("dnf"),
+        "suse" | "opensuse" => Some("zypper"),
+        "alpine" => Some("apk"),
+        _ => None,
+    });
+
+    let aur_helpers = if by_distro == Some("pacman") { &AUR_HELPERS[..] } else { &[] };
+    aur_helpers
+        .iter()
+        .copied()
+        .chain(by_distro)
+        .chain(["apt", "pacman", "dnf", "zypper", "apk"])
+        .filter_map(by_name)
+        .find(|manager| on_path(manager.program()))
+}
+
+/// Name of a package for this manager: the built-in table, overridden by `[packages.names]`.
+pub fn package_name(manager: &dyn PackageManager, canonical: &str) -> String {
+    static BUILT_IN: OnceLock<BTreeMap<String, BTreeMap<String, String>>> = OnceLock::new();
+    let built_in = BUILT_IN.get_or_init(|| toml::from_str(NAMES).unwrap_or_default());
+    [&config::get().packages.names, built_in]
+        .iter()
+        .find_map(|table| table.get(canonical).and_then(|names| names.get(manager.family())))
+        .cloned()
+        .unwrap_or_else(|| canonical.to_string())
+}
+
+/// Canonical name of the package providing `binary`: `[packages.binaries]`, else the built-in table.
+pub fn provider(binary: &str) -> Option<String> {
+    provider_in(&config::get().packages.binaries, binary)
+}
+
+/// `provider` with the `[packages.binaries]` overrides passed in.
+pub fn provider_in(overrides: &BTreeMap<String, String>, binary: &str) -> Option<String> {
+    static BUILT_IN: OnceLock<BTreeMap<String, String>> = OnceLock::new();
+    let built_in = BUILT_IN.get_or_init(|| toml::from_str(PROVIDERS).unwrap_or_default());
+    [overrides, built_in]
+        .iter()
+        .find_map(|table| table.get(binary))
+        .cloned()
+}
+
+/// Map a list of canonical names for this manager.
+pub fn package_names(manager: &dyn PackageManager, canonical: &[&str]) -> Vec<String> {
+    canonical.iter().map(|name| package_name(manager, name)).collect()
+}
+
+/// Refresh the index, then install `canonical` packages with the terminal attached so password
+/// prompts and progress show. `Ok(false)` when either step fails.
+pub fn install_attached(exec: &dyn Executor, manager: &dyn PackageManager, canonical: &[&str]) -> io::Result<bool> {
+    if let Some(refresh) = manager.refresh() {
+        if !exec.run(&refresh.attached())?.success() {
+            return Ok(false);
+        }
+    }
+    let install = manager.install(&package_names(manager, canonical)).attached();
+    Ok(exec.run(&install)?.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detected(configured: Option<&str>, os_release: &str, installed: &[&str]) -> Option<&'static str> {
+        detect_from(configured, os_release, |program| installed.contains(&program)).map(|manager| manager.name())
+    }
+
+    #[test]
+    fn detection_follows_os_release_then_path() {
+        let cases: &[(&str, &[&str], Option<&str>)] = &[
+            ("ID=debian\n", &["apt-get", "dnf"], Some("apt")),
+            ("ID=ubuntu\nID_LIKE=debian\n", &["apt-get"], Some("apt")),
+            ("ID=linuxmint\nID_LIKE=\"ubuntu debian\"\n", &["apk", "apt-get"], Some("apt")),
+            ("ID=arch\n", &["pacman"], Some("pacman")),
+            ("ID=arch\n", &["pacman", "paru"], Some("paru")),
+            ("ID=arch\n", &["pacman", "paru", "yay"], Some("yay")),
+            ("ID=manjaro\nID_LIKE=arch\n", &["pacman", "yay"], Some("yay")),
+            ("ID=endeavouros\nID_LIKE=arch\n", &["pacman"], Some("pacman")),
+            ("ID=fedora\n", &["dnf"], Some("dnf")),
+            ("ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n", &["dnf", "yay"], Some("dnf")),
+            ("ID=\"opensuse-tumbleweed\"\nID_LIKE=\"opensuse suse\"\n", &["zypper", "rpm"], Some("zypper")),
+            ("ID=alpine\n", &["apk"], Some("apk")),
+            ("ID=debian\n", &["pacman"], Some("pacman")),
+            ("ID=gentoo\n", &["dnf", "zypper"], Some("dnf")),
+            ("ID=gentoo\n", &["yay"], None),
+            ("", &[], None),
+        ];
+        for (os_release, installed, expected) in cases {
+            assert_eq!(detected(None, os_release, installed), *expected, "{:?} with {:?}", os_release, installed);
+        }
+    }
+
+    #[test]
+    fn configured_manager_wins_even_when_missing() {
+        assert_eq!(detected(Some("paru"), "ID=debian\n", &["apt-get"]), Some("paru"));
+        assert_eq!(detected(Some("yum"), "ID=arch\n", &["pacman", "yay"]), Some("dnf"));
+        assert_eq!(detected(Some("portage"), "ID=arch\n", &["pacman"]), None);
+    }
+
+    fn removals(manager: &dyn PackageManager, stdout: &str) -> Vec<(String, String)> {
+        manager.parse_removals(stdout).into_iter().map(|package| (package.name, package.version)).collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected.iter().map(|(name, version)| (name.to_string(), version.to_string())).collect()
+    }
+
+    #[test]
+    fn removal_previews_list_names_and_versions() {
+        let apt = "NOTE: This is only a simulation!\nReading package lists...\nThe following packages will be REMOVED:\n  curl libcurl4\n\
+                   Remv curl [7.81.0-1ubuntu1.16]\nRemv libcurl4:amd64 [7.81.0-1ubuntu1.16] [libcurl4-openssl-dev:amd64 ]\n";
+        let pacman = "curl 8.5.0-1\nlibcurl-gnutls 8.5.0-1\n";
+        let dnf4 = "Dependencies resolved.\n\
+                    ================================================================================\n \
+                    Package                  Arch      Version            Repository      Size\n\
+                    ================================================================================\n\
+                    Removing:\n \
+                    curl                     x86_64    7.85.0-1.fc37      @anaconda      698 k\n\
+                    Removing dependent packages:\n \
+                    python3-pycurl-very-long-package-name\n                              x86_64    7.45.1-2.fc37      @fedora        412 k\n\
+                    Removing unused dependencies:\n \
+                    libpsl                   x86_64    0.21.1-6.fc37      @anaconda      164 k\n\n\
+                    Transaction Summary\n\
+                    ================================================================================\n\
+                    Remove  3 Packages\n\nFreed space: 1.2 M\nOperation aborted.\n";
+        let dnf5 = "Package            Arch   Version          Repository      Size\n\
+                    Removing:\n \
+                    curl               x86_64 8.6.0-7.fc40     <unknown>  451.0 KiB\n\n\
+                    Transaction Summary:\n \
+                    Removing:           1 package\n";
+        let zypper = "Loading repository data...\nReading installed packages...\nResolving package dependencies...\n\n\
+                      The following 2 packages are going to be REMOVED:\n  curl libcurl4\n\n2 packages to remove.\n";
+        let apk = "(1/2) Purging curl (8.5.0-r0)\n(2/2) Purging libcurl (8.5.0-r0)\nOK: 7 MiB in 15 packages\n";
+
+        assert_eq!(removals(&Apt, apt), pairs(&[("curl", "7.81.0-1ubuntu1.16"), ("libcurl4:amd64", "7.81.0-1ubuntu1.16")]));
+        assert_eq!(removals(&Pacman::native(), pacman), pairs(&[("curl", "8.5.0-1"), ("libcurl-gnutls", "8.5.0-1")]));
+        assert_eq!(
+            removals(&Dnf, dnf4),
+            pairs(&[("curl", "7.85.0-1.fc37"), ("python3-pycurl-very-long-package-name", "7.45.1-2.fc37"), ("libpsl", "0.21.1-6.fc37")])
+        );
+        assert_eq!(removals(&Dnf, dnf5), pairs(&[("curl", "8.6.0-7.fc40")]));
+        assert_eq!(removals(&Zypper, zypper), pairs(&[("curl", ""), ("libcurl4", "")]));
+        assert_eq!(removals(&Apk, apk), pairs(&[("curl", "8.5.0-r0"), ("libcurl", "8.5.0-r0")]));
+    }
+
+    #[test]
+    fn every_backend_simulates_removals() {
+        let packages = vec!["curl".to_string()];
+        for name in ["apt", "pacman", "yay", "dnf", "zypper", "apk"] {
+            let simulation = by_name(name).unwrap().simulate_remove(&packages).unwrap();
+            assert!(simulation.read_only, "{}", name);
+            assert_eq!(simulation.args.last().map(String::as_str), Some("curl"), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Running programs as root. Commands mark invocations as `elevated`; the executor wraps them
+//! with whichever of sudo, doas, run0 or pkexec is available, or runs them as-is when gremlin
+//! already is root.
+
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
+
+use crate::config;
+use crate::exec::{self, Completed, Executor, Invocation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Elevator {
+    Sudo,
+    Doas,
+    Run0,
+    Pkexec,
+}
+
+impl Elevator {
+    /// Auto-detection order.
+    pub const ALL: [Elevator; 4] = [Elevator::Sudo, Elevator::Doas, Elevator::Run0, Elevator::Pkexec];
+
+    pub fn program(&self) -> &'static str {
+        match self {
+            Elevator::Sudo => "sudo",
+            Elevator::Doas => "doas",
+            Elevator::Run0 => "run0",
+            Elevator::Pkexec => "pkexec",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Elevator> {
+        Elevator::ALL.into_iter().find(|elevator| elevator.program() == name.trim())
+    }
+}
+
+/// True when the effective UID is 0.
+pub fn is_root() -> bool {
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            let uids = status.lines().find_map(|line| line.strip_prefix("Uid:"))?.to_string();
+            uids.split_whitespace().nth(1).map(|euid| euid == "0")
+        })
+        .unwrap_or(false)
+}
+
+/// The configured tool (`privilege.tool`), else the first one on `PATH`.
+pub fn elevator() -> Option<Elevator> {
+    static DETECTED: OnceLock<Option<Elevator>> = OnceLock::new();
+    *DETECTED.get_or_init(|| match &config::get().privilege.tool {
+        Some(name) => Elevator::from_name(name),
+        None => Elevator::ALL.into_iter().find(|elevator| exec::on_path(elevator.program())),
+    })
+}
+
+/// The invocation that actually gets spawned: elevated ones are prefixed with the elevation
+/// tool unless we are root already. Fails with `PermissionDenied` when no tool is available.
+pub fn resolve(invocation: &Invocation) -> io::Result<Invocation> {
+    if !invocation.elevated || is_root() {
+        return Ok(invocation.clone());
+    }
+    let elevator = elevator().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("`{}` needs root and none of sudo, doas, run0 or pkexec is available", invocation.program),
+        )
+    })?;
+    Ok(Invocation {
+        program: elevator.program().to_string(),
+        args: invocation.argv(),
+        elevated: false,
+        ..invocation.clone()
+    })
+}
+
+/// Runs everything that changes the system as root; read-only lookups stay unprivileged.
+/// Used for registry commands that declare `root: true`.
+pub struct AsRoot<'a>(pub &'a dyn Executor);
+
+impl Executor for AsRoot<'_> {
+    fn run(&self, invocation: &Invocation) -> io::Result<Completed> {
+        self.0.run(&self.effective(invocation))
+    }
+
+    fn effective(&self, invocation: &Invocation) -> Invocation {
+        if invocation.read_only {
+            return self.0.effective(invocation);
+        }
+        self.0.effective(&invocation.clone().elevated())
+    }
+
+    fn is_dry_run(&self) -> bool {
+        self.0.is_dry_run()
+    }
+}
+
+/// Whether a failed command's error output says it lacked privileges.
+pub fn looks_denied(stderr: &str) -> bool {
+    const MARKERS: [&str; 8] = [
+        "permission denied",
+        "operation not permitted",
+        "must be root",
+        "must be run as root",
+        "are not root",
+        "requires root",
+        "access denied",
+        "authentication is required",
+    ];
+    let stderr = stderr.to_lowercase();
+    MARKERS.iter().any(|marker| stderr.contains(marker))
+}
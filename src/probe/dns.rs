@@ -0,0 +1,618 @@
+//! A small DNS client: builds wire-format queries (RFC 1035), sends them over UDP with a TCP
+//! retry when the answer is truncated, and parses the common record types.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PORT: u16 = 53;
+/// Advertised in an EDNS0 OPT record so larger answers fit in one UDP datagram.
+const UDP_PAYLOAD: u16 = 1232;
+const OPT: u16 = 41;
+
+/// Record types gremlin knows how to ask for and print.
+pub const TYPES: &[(&str, u16)] = &[
+    ("A", 1),
+    ("NS", 2),
+    ("CNAME", 5),
+    ("SOA", 6),
+    ("PTR", 12),
+    ("MX", 15),
+    ("TXT", 16),
+    ("AAAA", 28),
+    ("SRV", 33),
+    ("CAA", 257),
+];
+
+/// Type number for a name such as `MX`, or a plain number.
+pub fn type_number(name: &str) -> Option<u16> {
+    let name = name.trim().to_uppercase();
+    TYPES.iter().find(|(known, _)| *known == name).map(|(_, number)| *number).or_else(|| name.strip_prefix("TYPE").unwrap_or(&name).parse().ok())
+}
+
+pub fn type_name(number: u16) -> String {
+    TYPES.iter().find(|(_, known)| *known == number).map(|(name, _)| name.to_string()).unwrap_or_else(|| format!("TYPE{}", number))
+}
+
+/// Response code name, e.g. `NXDOMAIN`.
+pub fn rcode_name(rcode: u8) -> String {
+    match rcode {
+        0 => "NOERROR".to_string(),
+        1 => "FORMERR".to_string(),
+        2 => "SERVFAIL".to_string(),
+        3 => "NXDOMAIN".to_string(),
+        4 => "NOTIMP".to_string(),
+        5 => "REFUSED".to_string(),
+        other => format!("RCODE{}", other),
+    }
+}
+
+/// The name to query for a reverse lookup of `ip`, e.g. `4.3.2.1.in-addr.arpa`.
+pub fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, d] = ip.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+        }
+        IpAddr::V6(ip) => {
+            let nibbles: Vec<String> = ip.octets().iter().rev().flat_map(|byte| [byte & 0xf, byte >> 4]).map(|nibble| format!("{:x}", nibble)).collect();
+            format!("{}.ip6.arpa", nibbles.join("."))
+        }
+    }
+}
+
+/// Nameservers from `/etc/resolv.conf`, in order.
+pub fn system_resolvers() -> Vec<SocketAddr> {
+    parse_resolv_conf(&fs::read_to_string("/etc/resolv.conf").unwrap_or_default())
+}
+
+pub fn parse_resolv_conf(content: &str) -> Vec<SocketAddr> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            if words.next()? != "nameserver" {
+                return None;
+            }
+            // Drop a `%eth0` zone; std can't parse it and link-local resolvers are rare.
+            let ip: IpAddr = words.next()?.split('%').next()?.parse().ok()?;
+            Some(SocketAddr::new(ip, DEFAULT_PORT))
+        })
+        .collect()
+}
+
+/// Parse `1.1.1.1`, `1.1.1.1:5353`, `[::1]:53` or a host name into a server address.
+pub fn parse_server(raw: &str, port: u16) -> io::Result<SocketAddr> {
+    let raw = raw.trim();
+    if let Ok(address) = raw.parse::<SocketAddr>() {
+        return Ok(address);
+    }
+    if let Ok(ip) = raw.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, port));
+    }
+    let (host, port) = match raw.rsplit_once(':') {
+        Some((host, custom)) => (host, custom.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("bad port '{}'", custom)))?),
+        None => (raw, port),
+    };
+    (host, port).to_socket_addrs()?.next().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("'{}' has no addresses", host)))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RData {
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    /// NS, CNAME and PTR.
+    Name(String),
+    Mx { preference: u16, exchange: String },
+    Txt(Vec<String>),
+    Soa { mname: String, rname: String, serial: u32, refresh: u32, retry: u32, expire: u32, minimum: u32 },
+    Srv { priority: u16, weight: u16, port: u16, target: String },
+    Caa { flags: u8, tag: String, value: String },
+    Other(Vec<u8>),
+}
+
+/// Presentation format, as `dig` prints it.
+impl fmt::Display for RData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RData::A(ip) => write!(f, "{}", ip),
+            RData::Aaaa(ip) => write!(f, "{}", ip),
+            RData::Name(name) => write!(f, "{}", name),
+            RData::Mx { preference, exchange } => write!(f, "{} {}", preference, exchange),
+            RData::Txt(strings) => {
+                let quoted: Vec<String> = strings.iter().map(|string| format!("{:?}", string)).collect();
+                write!(f, "{}", quoted.join(" "))
+            }
+            RData::Soa { mname, rname, serial, refresh, retry, expire, minimum } => {
+                write!(f, "{} {} {} {} {} {} {}", mname, rname, serial, refresh, retry, expire, minimum)
+            }
+            RData::Srv { priority, weight, port, target } => write!(f, "{} {} {} {}", priority, weight, port, target),
+            RData::Caa { flags, tag, value } => write!(f, "{} {} {:?}", flags, tag, value),
+            RData::Other(bytes) => {
+                let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+                write!(f, "\\# {} {}", bytes.len(), hex)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceRecord {
+    pub name: String,
+    pub rtype: u16,
+    pub class: u16,
+    pub ttl: u32,
+    pub data: RData,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    pub id: u16,
+    pub rcode: u8,
+    pub authoritative: bool,
+    pub truncated: bool,
+    pub recursion_available: bool,
+    pub answers: Vec<ResourceRecord>,
+    pub authority: Vec<ResourceRecord>,
+    pub additional: Vec<ResourceRecord>,
+}
+
+/// A completed query: the response and how it was obtained.
+#[derive(Debug, Clone)]
+pub struct Exchange {
+    pub server: SocketAddr,
+    pub response: Message,
+    /// `udp`, or `tcp` when forced or after a truncated UDP answer.
+    pub transport: &'static str,
+    pub elapsed: Duration,
+    /// Bytes in the response.
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub timeout: Duration,
+    /// Skip UDP and ask over TCP straight away.
+    pub tcp: bool,
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Wire-format query for `name`/`rtype` with recursion desired and an EDNS0 OPT record.
+pub fn build_query(id: u16, name: &str, rtype: u16) -> io::Result<Vec<u8>> {
+    let mut packet = Vec::with_capacity(64);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&0x0100u16.to_be_bytes());
+    for count in [1u16, 0, 0, 1] {
+        packet.extend_from_slice(&count.to_be_bytes());
+    }
+    encode_name(&mut packet, name)?;
+    packet.extend_from_slice(&rtype.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    // OPT: root name, type 41, the payload size in the class field, no flags or options.
+    packet.push(0);
+    packet.extend_from_slice(&OPT.to_be_bytes());
+    packet.extend_from_slice(&UDP_PAYLOAD.to_be_bytes());
+    packet.extend_from_slice(&[0; 6]);
+    Ok(packet)
+}
+
+fn encode_name(packet: &mut Vec<u8>, name: &str) -> io::Result<()> {
+    let name = name.trim().trim_end_matches('.');
+    if name.len() > 253 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "name is longer than 253 characters"));
+    }
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        if label.len() > 63 || !label.is_ascii() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("'{}' is not a valid DNS label", label)));
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    Ok(())
+}
+
+/// Reads a DNS message, following compression pointers in names.
+struct Reader<'a> {
+    packet: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        let bytes = self.packet.get(self.at..self.at + count).ok_or_else(|| invalid("response is cut short"))?;
+        self.at += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// A possibly compressed name, with a trailing dot.
+    fn name(&mut self) -> io::Result<String> {
+        let mut labels: Vec<String> = Vec::new();
+        let mut at = self.at;
+        let mut resume = None;
+        for _ in 0..128 {
+            let length = *self.packet.get(at).ok_or_else(|| invalid("name runs past the end"))? as usize;
+            match length {
+                0 => {
+                    self.at = resume.unwrap_or(at + 1);
+                    return Ok(format!("{}.", labels.join(".")));
+                }
+                pointer if pointer & 0xc0 == 0xc0 => {
+                    let low = *self.packet.get(at + 1).ok_or_else(|| invalid("name runs past the end"))? as usize;
+                    resume.get_or_insert(at + 2);
+                    at = ((pointer & 0x3f) << 8) | low;
+                }
+                length => {
+                    let label = self.packet.get(at + 1..at + 1 + length).ok_or_else(|| invalid("name runs past the end"))?;
+                    labels.push(String::from_utf8_lossy(label).into_owned());
+                    at += 1 + length;
+                }
+            }
+        }
+        Err(invalid("name has a compression loop"))
+    }
+
+    fn record(&mut self) -> io::Result<ResourceRecord> {
+        let name = self.name()?;
+        let rtype = self.u16()?;
+        let class = self.u16()?;
+        let ttl = self.u32()?;
+        let length = self.u16()? as usize;
+        let end = self.at + length;
+        if end > self.packet.len() {
+            return Err(invalid("record data runs past the end"));
+        }
+        let data = match rtype {
+            1 if length == 4 => {
+                let bytes = self.bytes(4)?;
+                RData::A(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]))
+            }
+            28 if length == 16 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(self.bytes(16)?);
+                RData::Aaaa(Ipv6Addr::from(octets))
+            }
+            2 | 5 | 12 => RData::Name(self.name()?),
+            15 => RData::Mx { preference: self.u16()?, exchange: self.name()? },
+            16 => {
+                let mut strings = Vec::new();
+                while self.at < end {
+                    let length = self.u8()? as usize;
+                    strings.push(String::from_utf8_lossy(self.bytes(length)?).into_owned());
+                }
+                RData::Txt(strings)
+            }
+            6 => RData::Soa {
+                mname: self.name()?,
+                rname: self.name()?,
+                serial: self.u32()?,
+                refresh: self.u32()?,
+                retry: self.u32()?,
+                expire: self.u32()?,
+                minimum: self.u32()?,
+            },
+            33 => RData::Srv { priority: self.u16()?, weight: self.u16()?, port: self.u16()?, target: self.name()? },
+            257 => {
+                let flags = self.u8()?;
+                let tag_length = self.u8()? as usize;
+                let tag = String::from_utf8_lossy(self.bytes(tag_length)?).into_owned();
+                let value = String::from_utf8_lossy(self.bytes(end.saturating_sub(self.at))?).into_owned();
+                RData::Caa { flags, tag, value }
+            }
+            _ => RData::Other(self.bytes(length)?.to_vec()),
+        };
+        // Whatever the parser didn't use (or a malformed length) is skipped.
+        self.at = end;
+        Ok(ResourceRecord { name, rtype, class, ttl, data })
+    }
+}
+
+pub fn parse_message(packet: &[u8]) -> io::Result<Message> {
+    let mut reader = Reader { packet, at: 0 };
+    let id = reader.u16()?;
+    let flags = reader.u16()?;
+    let counts = [reader.u16()?, reader.u16()?, reader.u16()?, reader.u16()?];
+    for _ in 0..counts[0] {
+        reader.name()?;
+        reader.bytes(4)?;
+    }
+    let mut sections: [Vec<ResourceRecord>; 3] = Default::default();
+    for (section, &count) in sections.iter_mut().zip(&counts[1..]) {
+        for _ in 0..count {
+            section.push(reader.record()?);
+        }
+    }
+    let [answers, authority, mut additional] = sections;
+    additional.retain(|record| record.rtype != OPT);
+    Ok(Message {
+        id,
+        rcode: (flags & 0x000f) as u8,
+        authoritative: flags & 0x0400 != 0,
+        truncated: flags & 0x0200 != 0,
+        recursion_available: flags & 0x0080 != 0,
+        answers,
+        authority,
+        additional,
+    })
+}
+
+/// Ask `server` for `name`/`rtype`.
+pub fn query(server: SocketAddr, name: &str, rtype: u16, options: Options) -> io::Result<Exchange> {
+    let id = query_id();
+    let packet = build_query(id, name, rtype)?;
+    let started = Instant::now();
+    if !options.tcp {
+        let (response, size) = over_udp(server, &packet, id, options.timeout)?;
+        if !response.truncated {
+            return Ok(Exchange { server, response, transport: "udp", elapsed: started.elapsed(), size });
+        }
+    }
+    let (response, size) = over_tcp(server, &packet, id, options.timeout)?;
+    Ok(Exchange { server, response, transport: "tcp", elapsed: started.elapsed(), size })
+}
+
+/// A query ID that differs between runs, without pulling in a random number crate.
+fn query_id() -> u16 {
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|time| time.subsec_nanos()).unwrap_or_default();
+    (nanos ^ std::process::id() ^ (nanos >> 16)) as u16
+}
+
+fn over_udp(server: SocketAddr, packet: &[u8], id: u16, timeout: Duration) -> io::Result<(Message, usize)> {
+    let local: SocketAddr = if server.is_ipv4() { (Ipv4Addr::UNSPECIFIED, 0).into() } else { (Ipv6Addr::UNSPECIFIED, 0).into() };
+    let socket = UdpSocket::bind(local)?;
+    socket.connect(server)?;
+    socket.send(packet)?;
+    let deadline = Instant::now() + timeout;
+    let mut buffer = [0u8; 65_535];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, format!("no answer from {} within {}ms", server, timeout.as_millis())));
+        }
+        socket.set_read_timeout(Some(left))?;
+        let size = match socket.recv(&mut buffer) {
+            Ok(size) => size,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e),
+        };
+        // Stray or spoofed datagrams with the wrong ID are ignored.
+        match parse_message(&buffer[..size]) {
+            Ok(message) if message.id == id => return Ok((message, size)),
+            _ => continue,
+        }
+    }
+}
+
+fn over_tcp(server: SocketAddr, packet: &[u8], id: u16, timeout: Duration) -> io::Result<(Message, usize)> {
+    let mut stream = TcpStream::connect_timeout(&server, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    let mut framed = (packet.len() as u16).to_be_bytes().to_vec();
+    framed.extend_from_slice(packet);
+    stream.write_all(&framed)?;
+    let mut length = [0u8; 2];
+    stream.read_exact(&mut length)?;
+    let mut response = vec![0u8; u16::from_be_bytes(length) as usize];
+    stream.read_exact(&mut response)?;
+    let message = parse_message(&response)?;
+    if message.id != id {
+        return Err(invalid("answer has the wrong query ID"));
+    }
+    Ok((message, response.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// One resource record in wire format, with the data given raw.
+    fn record(name: &str, rtype: u16, data: &[u8]) -> Vec<u8> {
+        let mut record = Vec::new();
+        encode_name(&mut record, name).unwrap();
+        record.extend_from_slice(&rtype.to_be_bytes());
+        record.extend_from_slice(&1u16.to_be_bytes());
+        record.extend_from_slice(&300u32.to_be_bytes());
+        record.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        record.extend_from_slice(data);
+        record
+    }
+
+    fn name(name: &str) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encode_name(&mut encoded, name).unwrap();
+        encoded
+    }
+
+    /// A response echoing `question` (name, type and class), with `answers` and `additional`
+    /// records in their sections.
+    fn response(id: u16, flags: u16, question: &[u8], answers: &[Vec<u8>], additional: &[Vec<u8>]) -> Vec<u8> {
+        let mut packet = Vec::new();
+        for field in [id, flags, 1, answers.len() as u16, 0, additional.len() as u16] {
+            packet.extend_from_slice(&field.to_be_bytes());
+        }
+        packet.extend_from_slice(question);
+        answers.iter().chain(additional).for_each(|record| packet.extend_from_slice(record));
+        packet
+    }
+
+    /// The question of a query from `build_query`: everything between the header and the OPT record.
+    fn question(query: &[u8]) -> &[u8] {
+        &query[12..query.len() - 11]
+    }
+
+    #[test]
+    fn builds_queries_with_an_opt_record() {
+        let query = build_query(0xbeef, "Example.test.", 15).unwrap();
+        let mut expected = vec![0xbe, 0xef, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 1];
+        expected.extend_from_slice(b"\x07Example\x04test\x00\x00\x0f\x00\x01");
+        expected.extend_from_slice(&[0, 0, 41, 0x04, 0xd0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(query, expected);
+        assert_eq!(question(&query), b"\x07Example\x04test\x00\x00\x0f\x00\x01");
+        assert_eq!(build_query(1, ".", 2).unwrap()[12], 0);
+
+        let error = |name: &str| build_query(1, name, 1).unwrap_err().to_string();
+        assert_eq!(error(&format!("{}.test", "a".repeat(64))), format!("'{}' is not a valid DNS label", "a".repeat(64)));
+        assert_eq!(error("bücher.test"), "'bücher' is not a valid DNS label");
+        assert_eq!(error(&["abcdefghi"; 26].join(".")), "name is longer than 253 characters");
+    }
+
+    #[test]
+    fn decodes_each_record_type() {
+        let mut mx = 10u16.to_be_bytes().to_vec();
+        mx.extend(name("mail.example.test"));
+        let mut soa = name("ns1.example.test");
+        soa.extend(name("hostmaster.example.test"));
+        [2024010101u32, 7200, 900, 1209600, 300].iter().for_each(|value| soa.extend_from_slice(&value.to_be_bytes()));
+        let mut srv = [0u8, 10, 0, 60, 0x14, 0x95].to_vec();
+        srv.extend(name("sip.example.test"));
+        let cases: Vec<(u16, Vec<u8>, RData, &str)> = vec![
+            (1, vec![192, 0, 2, 1], RData::A(Ipv4Addr::new(192, 0, 2, 1)), "192.0.2.1"),
+            (28, "2001:db8::1".parse::<Ipv6Addr>().unwrap().octets().to_vec(), RData::Aaaa("2001:db8::1".parse().unwrap()), "2001:db8::1"),
+            (2, name("ns1.example.test"), RData::Name("ns1.example.test.".to_string()), "ns1.example.test."),
+            (5, name("www.example.test"), RData::Name("www.example.test.".to_string()), "www.example.test."),
+            (12, name("host.example.test"), RData::Name("host.example.test.".to_string()), "host.example.test."),
+            (15, mx, RData::Mx { preference: 10, exchange: "mail.example.test.".to_string() }, "10 mail.example.test."),
+            (
+                16,
+                b"\x05hello\x00\x0bv=spf1 -all".to_vec(),
+                RData::Txt(vec!["hello".to_string(), String::new(), "v=spf1 -all".to_string()]),
+                "\"hello\" \"\" \"v=spf1 -all\"",
+            ),
+            (
+                6,
+                soa,
+                RData::Soa {
+                    mname: "ns1.example.test.".to_string(),
+                    rname: "hostmaster.example.test.".to_string(),
+                    serial: 2024010101,
+                    refresh: 7200,
+                    retry: 900,
+                    expire: 1209600,
+                    minimum: 300,
+                },
+                "ns1.example.test. hostmaster.example.test. 2024010101 7200 900 1209600 300",
+            ),
+            (33, srv, RData::Srv { priority: 10, weight: 60, port: 5269, target: "sip.example.test.".to_string() }, "10 60 5269 sip.example.test."),
+            (
+                257,
+                b"\x00\x05issueletsencrypt.org".to_vec(),
+                RData::Caa { flags: 0, tag: "issue".to_string(), value: "letsencrypt.org".to_string() },
+                "0 issue \"letsencrypt.org\"",
+            ),
+            (99, vec![0xde, 0xad], RData::Other(vec![0xde, 0xad]), "\\# 2 dead"),
+            // A wrong length for an address isn't read as one.
+            (1, vec![1, 2, 3], RData::Other(vec![1, 2, 3]), "\\# 3 010203"),
+        ];
+        for (rtype, data, expected, shown) in cases {
+            let query = build_query(7, "example.test", rtype).unwrap();
+            let packet = response(7, 0x8580, question(&query), &[record("example.test", rtype, &data)], &[record("", OPT, &[])]);
+            let message = parse_message(&packet).unwrap();
+            assert!(message.authoritative && message.recursion_available && !message.truncated);
+            assert_eq!(message.answers.len(), 1);
+            assert!(message.additional.is_empty(), "OPT records are left out");
+            let answer = &message.answers[0];
+            assert_eq!((answer.name.as_str(), answer.rtype, answer.class, answer.ttl), ("example.test.", rtype, 1, 300));
+            assert_eq!(answer.data, expected, "{}", type_name(rtype));
+            assert_eq!(answer.data.to_string(), shown);
+        }
+    }
+
+    #[test]
+    fn follows_compression_pointers_and_refuses_loops() {
+        let query = build_query(9, "example.test", 15).unwrap();
+        // The owner is a pointer to the question name at offset 12; the exchange is `mail` + that pointer.
+        let mut answer = vec![0xc0, 12, 0, 15, 0, 1, 0, 0, 0, 60, 0, 9, 0, 5];
+        answer.extend_from_slice(b"\x04mail\xc0\x0c");
+        let message = parse_message(&response(9, 0x8180, question(&query), &[answer], &[])).unwrap();
+        assert_eq!(message.answers[0].name, "example.test.");
+        assert_eq!(message.answers[0].data, RData::Mx { preference: 5, exchange: "mail.example.test.".to_string() });
+
+        let looped = |pointer: [u8; 2]| {
+            let mut packet = response(9, 0x8180, &[], &[], &[]);
+            packet[5] = 0;
+            packet[7] = 1;
+            packet.extend_from_slice(&pointer);
+            parse_message(&packet).unwrap_err().to_string()
+        };
+        // A pointer to itself, and one past the end of the message.
+        assert_eq!(looped([0xc0, 12]), "name has a compression loop");
+        assert_eq!(looped([0xc0, 0xff]), "name runs past the end");
+        assert_eq!(parse_message(&[0, 1, 0x81]).unwrap_err().to_string(), "response is cut short");
+    }
+
+    #[test]
+    fn reads_rcodes_and_names() {
+        let query = build_query(3, "missing.test", 1).unwrap();
+        let message = parse_message(&response(3, 0x8183, question(&query), &[], &[])).unwrap();
+        assert_eq!(rcode_name(message.rcode), "NXDOMAIN");
+        assert_eq!((type_number("mx"), type_number("TYPE65"), type_number("bogus")), (Some(15), Some(65), None));
+        assert_eq!(reverse_name("192.0.2.10".parse().unwrap()), "10.2.0.192.in-addr.arpa");
+        assert!(reverse_name("2001:db8::1".parse().unwrap()).starts_with("1.0.0.0.0.0.0.0"));
+        let resolvers = parse_resolv_conf("# local\nnameserver 127.0.0.53\nsearch lan\nnameserver fe80::1%eth0\n");
+        assert_eq!(resolvers, ["127.0.0.53:53".parse().unwrap(), "[fe80::1]:53".parse().unwrap()]);
+    }
+
+    /// A server on 127.0.0.1 that answers every UDP query truncated and the same query in full
+    /// over TCP on the same port.
+    fn truncating_stub() -> SocketAddr {
+        let (udp, tcp) = loop {
+            let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+            if let Ok(tcp) = TcpListener::bind(udp.local_addr().unwrap()) {
+                break (udp, tcp);
+            }
+        };
+        let address = udp.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            let (size, client) = udp.recv_from(&mut buffer).unwrap();
+            let query = &buffer[..size];
+            let id = u16::from_be_bytes([query[0], query[1]]);
+            udp.send_to(&response(id, 0x8380, question(query), &[], &[]), client).unwrap();
+        });
+        thread::spawn(move || {
+            let (mut stream, _) = tcp.accept().unwrap();
+            let mut length = [0u8; 2];
+            stream.read_exact(&mut length).unwrap();
+            let mut query = vec![0u8; u16::from_be_bytes(length) as usize];
+            stream.read_exact(&mut query).unwrap();
+            let id = u16::from_be_bytes([query[0], query[1]]);
+            let answers: Vec<Vec<u8>> = (1..=40).map(|host| record("big.test", 1, &[192, 0, 2, host])).collect();
+            let packet = response(id, 0x8180, question(&query), &answers, &[]);
+            let mut framed = (packet.len() as u16).to_be_bytes().to_vec();
+            framed.extend_from_slice(&packet);
+            stream.write_all(&framed).unwrap();
+        });
+        address
+    }
+
+    #[test]
+    fn retries_truncated_answers_over_tcp() {
+        let server = truncating_stub();
+        let exchange = query(server, "big.test", 1, Options { timeout: Duration::from_secs(2), tcp: false }).unwrap();
+        assert_eq!(exchange.transport, "tcp");
+        assert!(!exchange.response.truncated);
+        assert_eq!(exchange.response.answers.len(), 40);
+        assert_eq!(exchange.response.answers[39].data, RData::A(Ipv4Addr::new(192, 0, 2, 40)));
+    }
+}
@@ -0,0 +1,779 @@
+//! A minimal HTTP/1.1 client that times each phase of a request: name resolution, TCP connect,
+//! TLS handshake (rustls with the Mozilla roots), time to first byte and the whole exchange.
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{self, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, SignatureScheme, StreamOwned};
+
+/// Bodies are cut off here; enough for any assertion, small enough to keep in memory.
+pub const MAX_BODY: usize = 8 * 1024 * 1024;
+const MAX_HEADER: usize = 64 * 1024;
+const MAX_CHUNK_LINE: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Url {
+    pub https: bool,
+    pub host: String,
+    pub port: u16,
+    /// Path and query, always starting with `/`.
+    pub target: String,
+}
+
+impl Url {
+    pub fn parse(raw: &str) -> Result<Url, String> {
+        let raw = raw.trim();
+        let (https, rest) = if let Some(rest) = raw.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = raw.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(format!("'{}' must start with http:// or https://", raw));
+        };
+        let (authority, target) = match rest.find(['/', '?', '#']) {
+            Some(at) => (&rest[..at], &rest[at..]),
+            None => (rest, "/"),
+        };
+        let target = target.split('#').next().unwrap_or_default();
+        let target = if target.starts_with('/') { target.to_string() } else { format!("/{}", target) };
+        if authority.contains('@') {
+            return Err("credentials in URLs aren't supported; pass an Authorization header".to_string());
+        }
+        let default_port = if https { 443 } else { 80 };
+        let (host, port) = match authority.rsplit_once(':') {
+            // A bare IPv6 address has colons but no port.
+            Some((host, port)) if !port.contains(']') && (!host.contains(':') || host.ends_with(']')) => {
+                (host, port.parse().map_err(|_| format!("'{}' is not a port", port))?)
+            }
+            _ => (authority, default_port),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() {
+            return Err(format!("'{}' has no host", raw));
+        }
+        Ok(Url { https, host: host.to_string(), port, target })
+    }
+
+    /// Resolve a `Location` header against this URL.
+    pub fn join(&self, location: &str) -> Result<Url, String> {
+        let location = location.trim();
+        if location.starts_with("http://") || location.starts_with("https://") {
+            return Url::parse(location);
+        }
+        if let Some(rest) = location.strip_prefix("//") {
+            return Url::parse(&format!("{}://{}", if self.https { "https" } else { "http" }, rest));
+        }
+        let target = if location.starts_with('/') {
+            location.to_string()
+        } else {
+            let path = self.target.split('?').next().unwrap_or("/");
+            format!("{}{}", &path[..path.rfind('/').map(|at| at + 1).unwrap_or(0)], location)
+        };
+        Ok(Url { target, ..self.clone() })
+    }
+
+    fn default_port(&self) -> bool {
+        self.port == if self.https { 443 } else { 80 }
+    }
+
+    /// Value for the `Host` header.
+    /// Same scheme, host and port: the only redirects that may carry credentials along, so
+    /// they never go out in plaintext or to another service on the host.
+    pub fn same_origin(&self, other: &Url) -> bool {
+        self.https == other.https && self.host.eq_ignore_ascii_case(&other.host) && self.port == other.port
+    }
+
+    fn authority(&self) -> String {
+        let host = if self.host.contains(':') { format!("[{}]", self.host) } else { self.host.clone() };
+        if self.default_port() { host } else { format!("{}:{}", host, self.port) }
+    }
+}
+
+impl std::fmt::Display for Url {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}{}", if self.https { "https" } else { "http" }, self.authority(), self.target)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    /// Sent after `Host`, `User-Agent`, `Accept` and `Connection`; a header given here with the
+    /// same name replaces the default.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// For connecting and for each read and write.
+    pub timeout: Duration,
+    /// Accept any TLS certificate, for self-signed or internal-CA services.
+    pub insecure: bool,
+}
+
+/// How long each phase took.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Timing {
+    pub dns: Duration,
+    pub connect: Duration,
+    /// `None` for plain HTTP.
+    pub tls: Option<Duration>,
+    /// From sending the request to the first byte of the response: the server's think time.
+    pub ttfb: Duration,
+    /// Everything, from resolving the name to the last byte of the body.
+    pub total: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub url: Url,
+    pub remote: SocketAddr,
+    pub version: String,
+    pub status: u16,
+    pub reason: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// The body was longer than `MAX_BODY`.
+    pub truncated: bool,
+    pub timing: Timing,
+    /// Negotiated TLS version and cipher suite.
+    pub tls: Option<String>,
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    pub fn is_redirect(&self) -> bool {
+        matches!(self.status, 301 | 302 | 303 | 307 | 308) && self.header("location").is_some()
+    }
+}
+
+enum Transport {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+/// A connection with an overall deadline: every read and write may only wait for what is left
+/// of it, so a server trickling bytes can't stretch a request past the timeout.
+struct Stream {
+    transport: Transport,
+    deadline: Instant,
+}
+
+impl Stream {
+    fn limit(&self) -> io::Result<()> {
+        let socket = match &self.transport {
+            Transport::Plain(stream) => stream,
+            Transport::Tls(stream) => &stream.sock,
+        };
+        let left = remaining(self.deadline)?;
+        socket.set_read_timeout(Some(left))?;
+        socket.set_write_timeout(Some(left))
+    }
+}
+
+/// Time left before `deadline`; an error once it has passed.
+fn remaining(deadline: Instant) -> io::Result<Duration> {
+    deadline.checked_duration_since(Instant::now()).filter(|left| !left.is_zero()).ok_or_else(timed_out)
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "the request took longer than the timeout")
+}
+
+/// A socket timeout reads as `WouldBlock` on Linux; report it as the request timing out.
+fn deadline_error(error: io::Error) -> io::Error {
+    match error.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => timed_out(),
+        _ => error,
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.limit()?;
+        match &mut self.transport {
+            Transport::Plain(stream) => stream.read(buffer),
+            Transport::Tls(stream) => stream.read(buffer),
+        }
+        .map_err(deadline_error)
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.limit()?;
+        match &mut self.transport {
+            Transport::Plain(stream) => stream.write(buffer),
+            Transport::Tls(stream) => stream.write(buffer),
+        }
+        .map_err(deadline_error)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.limit()?;
+        match &mut self.transport {
+            Transport::Plain(stream) => stream.flush(),
+            Transport::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Trusts any certificate but still checks the handshake signatures, so the connection is
+/// encrypted, just not authenticated.
+#[derive(Debug)]
+struct AnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn tls_config(insecure: bool) -> Arc<ClientConfig> {
+    static VERIFIED: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    static INSECURE: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    let build = || {
+        let provider = Arc::new(crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .expect("the ring provider supports the default TLS versions");
+        let config = if insecure {
+            builder.dangerous().with_custom_certificate_verifier(Arc::new(AnyCertificate(provider))).with_no_client_auth()
+        } else {
+            builder.with_root_certificates(RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() }).with_no_client_auth()
+        };
+        Arc::new(config)
+    };
+    if insecure { INSECURE.get_or_init(build).clone() } else { VERIFIED.get_or_init(build).clone() }
+}
+
+/// The server's certificate was rejected: expired, self-signed, for another name...
+pub fn is_certificate_error(error: &io::Error) -> bool {
+    let inner = error.get_ref().and_then(|inner| inner.downcast_ref::<rustls::Error>());
+    matches!(inner, Some(rustls::Error::InvalidCertificate(_)))
+}
+
+/// Send one request (no redirects followed) and read the whole response, all within
+/// `options.timeout` once the name has resolved.
+pub fn send(url: &Url, request: &Request, options: Options) -> io::Result<Response> {
+    let started = Instant::now();
+    let addresses: Vec<SocketAddr> = (url.host.as_str(), url.port).to_socket_addrs()?.collect();
+    let dns = started.elapsed();
+
+    let connecting = Instant::now();
+    let deadline = connecting + options.timeout;
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, format!("{} has no addresses", url.host));
+    let mut connected = None;
+    for address in addresses {
+        match remaining(deadline).and_then(|left| TcpStream::connect_timeout(&address, left)) {
+            Ok(stream) => {
+                connected = Some((stream, address));
+                break;
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    let (tcp, remote) = connected.ok_or(last_error)?;
+    let connect = connecting.elapsed();
+    tcp.set_nodelay(true)?;
+
+    let (stream, tls, tls_info) = if url.https {
+        let handshaking = Instant::now();
+        let name = ServerName::try_from(url.host.clone()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        let connection = ClientConnection::new(tls_config(options.insecure), name).map_err(io::Error::other)?;
+        let mut stream = StreamOwned::new(connection, tcp);
+        while stream.conn.is_handshaking() {
+            let left = remaining(deadline)?;
+            stream.sock.set_read_timeout(Some(left))?;
+            stream.sock.set_write_timeout(Some(left))?;
+            stream.conn.complete_io(&mut stream.sock).map_err(deadline_error)?;
+        }
+        let version = stream.conn.protocol_version().map(|version| format!("{:?}", version)).unwrap_or_default();
+        let suite = stream.conn.negotiated_cipher_suite().map(|suite| format!("{:?}", suite.suite())).unwrap_or_default();
+        let info = format!("{} {}", version.replace('_', "."), suite);
+        (Transport::Tls(Box::new(stream)), Some(handshaking.elapsed()), Some(info.trim().to_string()))
+    } else {
+        (Transport::Plain(tcp), None, None)
+    };
+    let mut stream = Stream { transport: stream, deadline };
+
+    let method = request.method.to_uppercase();
+    let sending = Instant::now();
+    stream.write_all(&encode_request(url, &method, request))?;
+    stream.flush()?;
+
+    let (head, rest, ttfb) = read_head(&mut stream, sending)?;
+    let (version, status, reason, headers) = parse_head(&head)?;
+    let bodiless = method == "HEAD" || (100..200).contains(&status) || status == 204 || status == 304;
+    let chunked = headers.iter().any(|(key, value)| key.eq_ignore_ascii_case("transfer-encoding") && value.to_lowercase().contains("chunked"));
+    let length = headers.iter().find(|(key, _)| key.eq_ignore_ascii_case("content-length")).and_then(|(_, value)| value.trim().parse::<usize>().ok());
+    let (body, truncated) = match (bodiless, chunked, length) {
+        (true, _, _) => (Vec::new(), false),
+        (_, true, _) => read_chunked(&mut stream, rest)?,
+        (_, _, Some(length)) => read_length(&mut stream, rest, length)?,
+        _ => read_to_close(&mut stream, rest)?,
+    };
+
+    Ok(Response {
+        url: url.clone(),
+        remote,
+        version,
+        status,
+        reason,
+        headers,
+        body,
+        truncated,
+        timing: Timing { dns, connect, tls, ttfb, total: started.elapsed() },
+        tls: tls_info,
+    })
+}
+
+fn encode_request(url: &Url, method: &str, request: &Request) -> Vec<u8> {
+    let mut headers: Vec<(String, String)> = vec![
+        ("Host".to_string(), url.authority()),
+        ("User-Agent".to_string(), format!("gremlin/{}", env!("CARGO_PKG_VERSION"))),
+        ("Accept".to_string(), "*/*".to_string()),
+        ("Connection".to_string(), "close".to_string()),
+    ];
+    for (name, value) in &request.headers {
+        headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        headers.push((name.clone(), value.clone()));
+    }
+    let has_length = headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("content-length"));
+    if !has_length && (!request.body.is_empty() || matches!(method, "POST" | "PUT" | "PATCH")) {
+        headers.push(("Content-Length".to_string(), request.body.len().to_string()));
+    }
+    let mut encoded = format!("{} {} HTTP/1.1\r\n", method, url.target);
+    for (name, value) in headers {
+        encoded += &format!("{}: {}\r\n", name, value);
+    }
+    encoded += "\r\n";
+    let mut encoded = encoded.into_bytes();
+    encoded.extend_from_slice(&request.body);
+    encoded
+}
+
+/// Read up to the blank line after the headers. Returns the head, any body bytes read with it,
+/// and the time from starting to send the request to the first byte.
+fn read_head(stream: &mut Stream, sending: Instant) -> io::Result<(Vec<u8>, Vec<u8>, Duration)> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let mut ttfb = None;
+    loop {
+        let read = stream.read(&mut chunk)?;
+        if read == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the server closed the connection without a full response"));
+        }
+        ttfb.get_or_insert_with(|| sending.elapsed());
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            let rest = buffer.split_off(end + 4);
+            return Ok((buffer, rest, ttfb.unwrap_or_default()));
+        }
+        if buffer.len() > MAX_HEADER {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "response headers are larger than 64K"));
+        }
+    }
+}
+
+type Head = (String, u16, String, Vec<(String, String)>);
+
+fn parse_head(head: &[u8]) -> io::Result<Head> {
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap_or_default();
+    let mut parts = status_line.splitn(3, ' ');
+    let version = parts.next().unwrap_or_default().to_string();
+    let status = parts.next().and_then(|status| status.parse().ok()).filter(|_| version.starts_with("HTTP/"));
+    let status = status.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("not an HTTP response: {:?}", status_line)))?;
+    let reason = parts.next().unwrap_or_default().to_string();
+    let headers = lines
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
+    Ok((version, status, reason, headers))
+}
+
+/// Read until the server closes. A TLS peer that closes without `close_notify` still counts
+/// as the end of the body.
+fn read_to_close(stream: &mut Stream, mut body: Vec<u8>) -> io::Result<(Vec<u8>, bool)> {
+    let mut chunk = [0u8; 16384];
+    loop {
+        match stream.read(&mut chunk) {
+            Ok(0) => return Ok((body, false)),
+            Ok(read) => body.extend_from_slice(&chunk[..read]),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok((body, false)),
+            Err(e) => return Err(e),
+        }
+        if body.len() > MAX_BODY {
+            body.truncate(MAX_BODY);
+            return Ok((body, true));
+        }
+    }
+}
+
+fn read_length(stream: &mut Stream, mut body: Vec<u8>, length: usize) -> io::Result<(Vec<u8>, bool)> {
+    let wanted = length.min(MAX_BODY);
+    let mut chunk = [0u8; 16384];
+    while body.len() < wanted {
+        let read = stream.read(&mut chunk)?;
+        if read == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("body ended after {} of {} bytes", body.len(), length)));
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(wanted);
+    Ok((body, length > MAX_BODY))
+}
+
+/// Decode `Transfer-Encoding: chunked`. Trailers are ignored. Only what fits under `MAX_BODY`
+/// is ever buffered, whatever size the server declares.
+fn read_chunked(stream: &mut Stream, mut raw: Vec<u8>) -> io::Result<(Vec<u8>, bool)> {
+    let mut body = Vec::new();
+    let mut chunk = [0u8; 16384];
+    let mut more = |raw: &mut Vec<u8>| -> io::Result<()> {
+        let read = stream.read(&mut chunk)?;
+        if read == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "chunked body ended early"));
+        }
+        raw.extend_from_slice(&chunk[..read]);
+        Ok(())
+    };
+    loop {
+        let line_end = loop {
+            match raw.windows(2).position(|window| window == b"\r\n") {
+                Some(end) => break end,
+                None if raw.len() > MAX_CHUNK_LINE => return Err(io::Error::new(io::ErrorKind::InvalidData, "chunk size line is too long")),
+                None => more(&mut raw)?,
+            }
+        };
+        let size_line = String::from_utf8_lossy(&raw[..line_end]).to_string();
+        let size = u64::from_str_radix(size_line.split(';').next().unwrap_or_default().trim(), 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("bad chunk size {:?}", size_line)))?;
+        if size == 0 {
+            return Ok((body, false));
+        }
+        raw.drain(..line_end + 2);
+        let room = MAX_BODY - body.len();
+        let wanted = usize::try_from(size).map_or(room, |size| size.min(room));
+        while raw.len() < wanted {
+            more(&mut raw)?;
+        }
+        body.extend_from_slice(&raw[..wanted]);
+        if (wanted as u64) < size {
+            return Ok((body, true));
+        }
+        raw.drain(..wanted);
+        while raw.len() < 2 {
+            more(&mut raw)?;
+        }
+        raw.drain(..2);
+    }
+}
+
+/// Send a request and follow up to `redirects` redirects, returning every response in order.
+/// 303, and 301/302 after anything but GET or HEAD, turn into a GET without a body as browsers
+/// do; 307 and 308 resend the same request. Credentials aren't sent on to a different origin.
+/// An error carries the URL it happened at.
+pub fn follow(url: &Url, request: &Request, redirects: usize, options: Options) -> Result<Vec<Response>, (Url, io::Error)> {
+    let mut chain: Vec<Response> = Vec::new();
+    let mut url = url.clone();
+    let mut request = request.clone();
+    loop {
+        let response = send(&url, &request, options).map_err(|e| (url.clone(), e))?;
+        let next = response.header("location").filter(|_| response.is_redirect() && chain.len() < redirects).map(|location| url.join(location));
+        let status = response.status;
+        chain.push(response);
+        let next = match next {
+            Some(Ok(next)) => next,
+            // An unusable Location ends the chain at the redirect itself.
+            Some(Err(_)) | None => return Ok(chain),
+        };
+        let method = request.method.to_uppercase();
+        if status == 303 || (matches!(status, 301 | 302) && method != "GET" && method != "HEAD") {
+            request.method = if method == "HEAD" { method } else { "GET".to_string() };
+            request.body.clear();
+            request.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("content-length") && !name.eq_ignore_ascii_case("content-type"));
+        }
+        if !url.same_origin(&next) {
+            request.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("authorization") && !name.eq_ignore_ascii_case("cookie"));
+        }
+        url = next;
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+    use std::thread;
+
+    /// A request as the test server received it.
+    #[derive(Debug, Clone)]
+    pub(crate) struct Seen {
+        pub method: String,
+        pub target: String,
+        pub headers: Vec<(String, String)>,
+        pub body: Vec<u8>,
+    }
+
+    impl Seen {
+        pub fn header(&self, name: &str) -> Option<&str> {
+            self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+        }
+    }
+
+    /// Serve on an ephemeral 127.0.0.1 port, answering each connection with `respond(request, port)`
+    /// and closing it. Returns the port and every request seen.
+    pub(crate) fn serve(respond: impl Fn(&Seen, u16) -> Vec<u8> + Send + 'static) -> (u16, Arc<Mutex<Vec<Seen>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = io::BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let (method, target) = (parts.next().unwrap_or_default().to_string(), parts.next().unwrap_or_default().to_string());
+                let mut headers = Vec::new();
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).unwrap();
+                    match line.trim_end().split_once(':') {
+                        Some((name, value)) => headers.push((name.trim().to_string(), value.trim().to_string())),
+                        None => break,
+                    }
+                }
+                let request = Seen { method, target, headers, body: Vec::new() };
+                let length = request.header("content-length").and_then(|length| length.parse().ok()).unwrap_or(0);
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let request = Seen { body, ..request };
+                let response = respond(&request, port);
+                log.lock().unwrap().push(request);
+                let _ = stream.write_all(&response);
+            }
+        });
+        (port, seen)
+    }
+
+    pub(crate) fn ok(body: &str) -> Vec<u8> {
+        format!("HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).into_bytes()
+    }
+
+    fn redirect(status: &str, location: &str) -> Vec<u8> {
+        format!("HTTP/1.1 {}\r\nLocation: {}\r\nContent-Length: 0\r\n\r\n", status, location).into_bytes()
+    }
+
+    fn options() -> Options {
+        Options { timeout: Duration::from_secs(5), insecure: false }
+    }
+
+    fn get(port: u16, target: &str) -> io::Result<Response> {
+        let url = Url::parse(&format!("http://127.0.0.1:{}{}", port, target)).unwrap();
+        send(&url, &Request { method: "GET".to_string(), ..Default::default() }, options())
+    }
+
+    #[test]
+    fn parses_and_joins_urls() {
+        let url = Url::parse("https://example.com:8443/a/b?q=1#frag").unwrap();
+        assert_eq!(url, Url { https: true, host: "example.com".to_string(), port: 8443, target: "/a/b?q=1".to_string() });
+        assert_eq!(Url::parse("http://[::1]/").unwrap().host, "::1");
+        assert_eq!(Url::parse("http://[::1]:8080").unwrap().to_string(), "http://[::1]:8080/");
+        assert!(Url::parse("ftp://example.com").is_err());
+        assert!(Url::parse("http://user:pw@example.com").is_err());
+        assert_eq!(url.join("c").unwrap().target, "/a/c");
+        assert_eq!(url.join("/root").unwrap().to_string(), "https://example.com:8443/root");
+        assert_eq!(url.join("//other.org/x").unwrap().to_string(), "https://other.org/x");
+        assert_eq!(url.join("http://other.org").unwrap().to_string(), "http://other.org/");
+    }
+
+    #[test]
+    fn reads_a_chunked_body() {
+        let (port, _) = serve(|_, _| b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n5;ext=1\r\ndefgh\r\n0\r\nX-Trailer: 1\r\n\r\n".to_vec());
+        let response = get(port, "/").unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"abcdefgh");
+        assert!(!response.truncated);
+    }
+
+    #[test]
+    fn huge_chunk_sizes_neither_overflow_nor_buffer() {
+        let (port, _) = serve(|request, _| match request.target.as_str() {
+            "/max" => b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffffffff\r\nabc".to_vec(),
+            "/wide" => b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1ffffffffffffffff\r\nabc".to_vec(),
+            _ => {
+                let size = MAX_BODY + 10;
+                let mut response = format!("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n", size).into_bytes();
+                response.resize(response.len() + size, b'x');
+                response.extend_from_slice(b"\r\n0\r\n\r\n");
+                response
+            }
+        });
+        assert_eq!(get(port, "/max").unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(get(port, "/wide").unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let response = get(port, "/big").unwrap();
+        assert_eq!(response.body.len(), MAX_BODY);
+        assert!(response.truncated);
+    }
+
+    #[test]
+    fn reads_content_length_and_times_phases() {
+        let (port, seen) = serve(|_, _| ok("hello"));
+        let response = get(port, "/path?q=1").unwrap();
+        assert_eq!((response.status, response.reason.as_str(), response.body.as_slice()), (200, "OK", &b"hello"[..]));
+        assert_eq!(response.header("content-type"), Some("text/plain"));
+        assert!(response.timing.tls.is_none());
+        assert!(response.timing.total >= response.timing.ttfb);
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].target, "/path?q=1");
+        assert_eq!(seen[0].header("host"), Some(format!("127.0.0.1:{}", port).as_str()));
+        assert_eq!(seen[0].header("connection"), Some("close"));
+    }
+
+    #[test]
+    fn see_other_turns_into_a_get_and_credentials_stay_on_the_host() {
+        let (port, seen) = serve(|request, port| match request.target.as_str() {
+            "/form" => redirect("303 See Other", &format!("http://localhost:{}/done", port)),
+            _ => ok("done"),
+        });
+        let url = Url::parse(&format!("http://127.0.0.1:{}/form", port)).unwrap();
+        let request = Request {
+            method: "POST".to_string(),
+            headers: [("Authorization", "Bearer secret"), ("Cookie", "session=1"), ("Content-Type", "text/plain"), ("X-Trace", "7")]
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .to_vec(),
+            body: b"payload".to_vec(),
+        };
+        let chain = follow(&url, &request, 5, options()).unwrap();
+        assert_eq!(chain.iter().map(|response| response.status).collect::<Vec<_>>(), [303, 200]);
+        assert_eq!(chain[1].url.host, "localhost");
+
+        let seen = seen.lock().unwrap();
+        assert_eq!((seen[0].method.as_str(), seen[0].body.as_slice()), ("POST", &b"payload"[..]));
+        assert_eq!(seen[0].header("authorization"), Some("Bearer secret"));
+        assert_eq!((seen[1].method.as_str(), seen[1].target.as_str()), ("GET", "/done"));
+        assert!(seen[1].body.is_empty());
+        for dropped in ["authorization", "cookie", "content-type", "content-length"] {
+            assert_eq!(seen[1].header(dropped), None, "{} was resent", dropped);
+        }
+        assert_eq!(seen[1].header("x-trace"), Some("7"));
+    }
+
+    #[test]
+    fn credentials_only_follow_redirects_to_the_same_origin() {
+        let url = Url::parse("https://example.com/login").unwrap();
+        assert!(url.same_origin(&Url::parse("https://EXAMPLE.com:443/next").unwrap()));
+        assert!(!url.same_origin(&Url::parse("http://example.com/next").unwrap()));
+        assert!(!url.same_origin(&Url::parse("https://example.com:8443/next").unwrap()));
+        assert!(!url.same_origin(&Url::parse("https://api.example.com/next").unwrap()));
+
+        let (other, other_seen) = serve(|_, _| ok("other"));
+        let (port, seen) = serve(move |_, _| redirect("307 Temporary Redirect", &format!("http://127.0.0.1:{}/", other)));
+        let url = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+        let request = Request {
+            method: "GET".to_string(),
+            headers: vec![("Authorization".to_string(), "Bearer secret".to_string()), ("Cookie".to_string(), "session=1".to_string())],
+            body: Vec::new(),
+        };
+        assert_eq!(follow(&url, &request, 5, options()).unwrap().len(), 2);
+        assert_eq!(seen.lock().unwrap()[0].header("authorization"), Some("Bearer secret"));
+        let other_seen = other_seen.lock().unwrap();
+        assert_eq!((other_seen[0].header("authorization"), other_seen[0].header("cookie")), (None, None));
+    }
+
+    #[test]
+    fn a_trickling_server_cannot_outlast_the_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n");
+            for _ in 0..100 {
+                thread::sleep(Duration::from_millis(50));
+                if stream.write_all(b"x").is_err() {
+                    break;
+                }
+            }
+        });
+        let url = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+        let started = Instant::now();
+        let error = send(&url, &Request { method: "GET".to_string(), ..Default::default() }, Options { timeout: Duration::from_millis(400), insecure: false }).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut, "{:?}", error);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn temporary_redirects_resend_the_request_and_stop_at_the_limit() {
+        let (port, seen) = serve(|request, _| match request.target.as_str() {
+            "/a" => redirect("307 Temporary Redirect", "/b"),
+            "/b" => redirect("308 Permanent Redirect", "c"),
+            _ => ok("end"),
+        });
+        let url = Url::parse(&format!("http://127.0.0.1:{}/a", port)).unwrap();
+        let request = Request { method: "PUT".to_string(), headers: vec![("Authorization".to_string(), "x".to_string())], body: b"v".to_vec() };
+        let chain = follow(&url, &request, 5, options()).unwrap();
+        assert_eq!(chain.iter().map(|response| response.url.target.as_str()).collect::<Vec<_>>(), ["/a", "/b", "/c"]);
+        assert!(seen.lock().unwrap().iter().all(|seen| seen.method == "PUT" && seen.body == b"v" && seen.header("authorization") == Some("x")));
+
+        let chain = follow(&url, &request, 1, options()).unwrap();
+        assert_eq!(chain.len(), 2);
+        assert!(chain[1].is_redirect());
+        assert_eq!(follow(&url, &request, 0, options()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn head_and_no_content_have_no_body() {
+        let (port, _) = serve(|request, _| match request.method.as_str() {
+            "HEAD" => b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n".to_vec(),
+            _ => b"HTTP/1.1 204 No Content\r\n\r\n".to_vec(),
+        });
+        let url = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+        let head = send(&url, &Request { method: "head".to_string(), ..Default::default() }, options()).unwrap();
+        assert!(head.body.is_empty());
+        assert_eq!(get(port, "/").unwrap().status, 204);
+    }
+
+    #[test]
+    fn refused_connections_are_errors() {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        assert_eq!(get(port, "/").unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+        let (port, _) = serve(|_, _| b"SSH-2.0-OpenSSH_9.6\r\n\r\n".to_vec());
+        assert_eq!(get(port, "/").unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,5 @@
+//! Network probes built directly on std sockets, so they work without nmap, dig or curl.
+
+pub mod dns;
+pub mod http;
+pub mod scan;
@@ -0,0 +1,415 @@
+//! TCP connect scanning: target and port parsing, a thread pool that tries each port, and
+//! banner grabbing for protocols that announce themselves or answer a simple request.
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Largest number of hosts a CIDR target may expand to.
+pub const MAX_HOSTS: usize = 65_536;
+/// Largest number of host/port pairs one scan may try.
+pub const MAX_PROBES: usize = 1 << 20;
+
+/// What to send once connected to get the service to identify itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Greeting {
+    /// The server talks first (SSH, SMTP, FTP...).
+    Listen,
+    Send(&'static [u8]),
+    /// Nothing readable without TLS or a binary handshake.
+    None,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Service {
+    pub port: u16,
+    pub name: &'static str,
+    pub greeting: Greeting,
+}
+
+const HTTP: Greeting = Greeting::Send(b"HEAD / HTTP/1.0\r\n\r\n");
+
+/// Well-known services, by port.
+pub const SERVICES: &[Service] = &[
+    Service { port: 21, name: "ftp", greeting: Greeting::Listen },
+    Service { port: 22, name: "ssh", greeting: Greeting::Listen },
+    Service { port: 23, name: "telnet", greeting: Greeting::Listen },
+    Service { port: 25, name: "smtp", greeting: Greeting::Listen },
+    Service { port: 53, name: "dns", greeting: Greeting::None },
+    Service { port: 80, name: "http", greeting: HTTP },
+    Service { port: 110, name: "pop3", greeting: Greeting::Listen },
+    Service { port: 111, name: "rpcbind", greeting: Greeting::None },
+    Service { port: 135, name: "msrpc", greeting: Greeting::None },
+    Service { port: 139, name: "netbios", greeting: Greeting::None },
+    Service { port: 143, name: "imap", greeting: Greeting::Listen },
+    Service { port: 389, name: "ldap", greeting: Greeting::None },
+    Service { port: 443, name: "https", greeting: Greeting::None },
+    Service { port: 445, name: "smb", greeting: Greeting::None },
+    Service { port: 465, name: "smtps", greeting: Greeting::None },
+    Service { port: 587, name: "submission", greeting: Greeting::Listen },
+    Service { port: 631, name: "ipp", greeting: HTTP },
+    Service { port: 993, name: "imaps", greeting: Greeting::None },
+    Service { port: 995, name: "pop3s", greeting: Greeting::None },
+    Service { port: 1433, name: "mssql", greeting: Greeting::None },
+    Service { port: 1521, name: "oracle", greeting: Greeting::None },
+    Service { port: 2049, name: "nfs", greeting: Greeting::None },
+    Service { port: 2375, name: "docker", greeting: HTTP },
+    Service { port: 3000, name: "http-alt", greeting: HTTP },
+    Service { port: 3306, name: "mysql", greeting: Greeting::Listen },
+    Service { port: 3389, name: "rdp", greeting: Greeting::None },
+    Service { port: 5000, name: "http-alt", greeting: HTTP },
+    Service { port: 5432, name: "postgresql", greeting: Greeting::None },
+    Service { port: 5672, name: "amqp", greeting: Greeting::None },
+    Service { port: 5900, name: "vnc", greeting: Greeting::Listen },
+    Service { port: 6379, name: "redis", greeting: Greeting::Send(b"PING\r\n") },
+    Service { port: 8000, name: "http-alt", greeting: HTTP },
+    Service { port: 8080, name: "http-proxy", greeting: HTTP },
+    Service { port: 8443, name: "https-alt", greeting: Greeting::None },
+    Service { port: 8888, name: "http-alt", greeting: HTTP },
+    Service { port: 9090, name: "http-alt", greeting: HTTP },
+    Service { port: 9200, name: "elasticsearch", greeting: HTTP },
+    Service { port: 11211, name: "memcached", greeting: Greeting::Send(b"version\r\n") },
+    Service { port: 27017, name: "mongodb", greeting: Greeting::None },
+];
+
+/// Named port lists for the `ports` argument.
+pub const PRESETS: &[(&str, &[u16])] = &[
+    (
+        "top",
+        &[
+            21, 22, 23, 25, 53, 80, 110, 111, 135, 139, 143, 389, 443, 445, 587, 631, 993, 995, 1433, 2049, 3000, 3306, 3389, 5000, 5432,
+            5672, 5900, 6379, 8000, 8080, 8443, 8888, 9090, 9200, 11211, 27017,
+        ],
+    ),
+    ("web", &[80, 443, 3000, 5000, 8000, 8080, 8443, 8888, 9090]),
+    ("db", &[1433, 1521, 3306, 5432, 6379, 9200, 11211, 27017]),
+    ("mail", &[25, 110, 143, 465, 587, 993, 995]),
+    ("remote", &[22, 23, 3389, 5900]),
+    ("files", &[21, 139, 445, 2049]),
+];
+
+pub fn service(port: u16) -> Option<&'static Service> {
+    SERVICES.iter().find(|service| service.port == port)
+}
+
+/// Parse `22,80,8000-8100,web` into sorted, unique ports. `all` is 1-65535.
+pub fn parse_ports(spec: &str) -> Result<Vec<u16>, String> {
+    let mut ports = Vec::new();
+    for item in spec.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+        let item = item.to_lowercase();
+        if item == "all" {
+            ports.extend(1..=u16::MAX);
+        } else if let Some((_, preset)) = PRESETS.iter().find(|(name, _)| *name == item) {
+            ports.extend_from_slice(preset);
+        } else if let Some((from, to)) = item.split_once('-') {
+            let (from, to) = (port_number(from)?, port_number(to)?);
+            if from > to {
+                return Err(format!("range '{}' runs backwards", item));
+            }
+            ports.extend(from..=to);
+        } else {
+            ports.push(port_number(&item)?);
+        }
+    }
+    ports.sort_unstable();
+    ports.dedup();
+    if ports.is_empty() {
+        return Err("no ports given".to_string());
+    }
+    Ok(ports)
+}
+
+fn port_number(raw: &str) -> Result<u16, String> {
+    match raw.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => {
+            let presets: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+            Err(format!("'{}' is not a port (1-65535), range or preset ({}, all)", raw.trim(), presets.join(", ")))
+        }
+    }
+}
+
+/// Parse comma-separated IPs, hostnames and CIDR blocks into addresses. A hostname becomes its
+/// first address; CIDR blocks leave out the network and broadcast addresses.
+pub fn parse_targets(spec: &str) -> Result<Vec<IpAddr>, String> {
+    let mut hosts = Vec::new();
+    for item in spec.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+        if let Some((network, prefix)) = item.split_once('/') {
+            let network: IpAddr = network.parse().map_err(|_| format!("'{}' is not a network address", network))?;
+            let prefix: u32 = prefix.parse().map_err(|_| format!("'{}' is not a prefix length", prefix))?;
+            hosts.extend(expand_cidr(network, prefix)?);
+        } else if let Ok(ip) = item.parse::<IpAddr>() {
+            hosts.push(ip);
+        } else {
+            let address = (item, 0)
+                .to_socket_addrs()
+                .map_err(|e| format!("can't resolve '{}': {}", item, e))?
+                .next()
+                .ok_or_else(|| format!("'{}' has no addresses", item))?;
+            hosts.push(address.ip());
+        }
+        if hosts.len() > MAX_HOSTS {
+            return Err(format!("more than {} hosts", MAX_HOSTS));
+        }
+    }
+    hosts.dedup();
+    if hosts.is_empty() {
+        return Err("no targets given".to_string());
+    }
+    Ok(hosts)
+}
+
+fn expand_cidr(network: IpAddr, prefix: u32) -> Result<Vec<IpAddr>, String> {
+    let bits = if network.is_ipv4() { 32 } else { 128 };
+    if prefix > bits {
+        return Err(format!("prefix /{} is longer than {} bits", prefix, bits));
+    }
+    if bits - prefix > MAX_HOSTS.trailing_zeros() {
+        return Err(format!("/{} is too large; the most is {} hosts", prefix, MAX_HOSTS));
+    }
+    let size = 1u128 << (bits - prefix);
+    let base = match network {
+        IpAddr::V4(ip) => u128::from(u32::from(ip)),
+        IpAddr::V6(ip) => u128::from(ip),
+    } & !(size - 1);
+    // With room for them, the network and (IPv4) broadcast addresses aren't hosts.
+    let hosts = match (size > 2, network.is_ipv4()) {
+        (false, _) => base..base + size,
+        (true, true) => base + 1..base + size - 1,
+        (true, false) => base + 1..base + size,
+    };
+    Ok(hosts
+        .map(|value| match network {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(value as u32)),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(value)),
+        })
+        .collect())
+}
+
+/// Loopback, RFC 1918, carrier-grade NAT, link-local and IPv6 unique-local addresses: the ones
+/// that can't belong to somebody else on the internet.
+pub fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback() || ip.is_private() || ip.is_link_local() || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_private(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback() || first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PortState {
+    Open,
+    /// Refused: the host is up but nothing listens.
+    Closed,
+    /// No answer in time, or unreachable; usually a firewall dropping packets.
+    Filtered,
+}
+
+impl PortState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PortState::Open => "open",
+            PortState::Closed => "closed",
+            PortState::Filtered => "filtered",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PortResult {
+    pub host: IpAddr,
+    pub port: u16,
+    pub state: PortState,
+    /// Time to connect, or to give up.
+    pub latency: Duration,
+    /// First line the service sent, cleaned up for display.
+    pub banner: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Connections in flight at once.
+    pub concurrency: usize,
+    pub timeout: Duration,
+    /// How long to wait for a banner once connected.
+    pub banner_timeout: Duration,
+}
+
+/// Try every port on every host, `concurrency` at a time. Results are sorted by host and port.
+pub fn scan(hosts: &[IpAddr], ports: &[u16], options: Options) -> Vec<PortResult> {
+    let jobs: Vec<SocketAddr> = hosts.iter().flat_map(|&host| ports.iter().map(move |&port| SocketAddr::new(host, port))).collect();
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(jobs.len()));
+    thread::scope(|scope| {
+        for _ in 0..options.concurrency.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| {
+                while let Some(&address) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = probe(address, &options);
+                    results.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(result);
+                }
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+    results.sort_by_key(|result| (result.host, result.port));
+    results
+}
+
+fn probe(address: SocketAddr, options: &Options) -> PortResult {
+    let started = Instant::now();
+    let connected = TcpStream::connect_timeout(&address, options.timeout);
+    let latency = started.elapsed();
+    let state = match &connected {
+        Ok(_) => PortState::Open,
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => PortState::Closed,
+        Err(_) => PortState::Filtered,
+    };
+    let banner = connected.ok().and_then(|stream| {
+        let greeting = service(address.port()).map(|service| service.greeting).unwrap_or(Greeting::Listen);
+        grab_banner(stream, greeting, options.banner_timeout)
+    });
+    PortResult { host: address.ip(), port: address.port(), state, latency, banner }
+}
+
+fn grab_banner(mut stream: TcpStream, greeting: Greeting, timeout: Duration) -> Option<String> {
+    if !timeout.is_zero() {
+        stream.set_read_timeout(Some(timeout)).ok()?;
+    }
+    match greeting {
+        Greeting::None => return None,
+        Greeting::Send(request) => stream.write_all(request).ok()?,
+        Greeting::Listen => {}
+    }
+    let mut buffer = [0u8; 1024];
+    let read = stream.read(&mut buffer).ok()?;
+    clean_banner(&buffer[..read])
+}
+
+/// The first line of a banner, printable ASCII only; for HTTP, the status line and `Server`.
+pub fn clean_banner(raw: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(raw);
+    let mut lines = text.lines().map(|line| line.chars().filter(|c| c.is_ascii_graphic() || *c == ' ').collect::<String>());
+    let first = lines.next()?.trim().to_string();
+    let banner = if first.starts_with("HTTP/") {
+        let server = lines.find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case("server").then(|| value.trim().to_string())
+        });
+        match server {
+            Some(server) => format!("{} ({})", first, server),
+            None => first,
+        }
+    } else {
+        first
+    };
+    let banner: String = banner.chars().take(120).collect();
+    (!banner.is_empty()).then_some(banner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn parses_ports_ranges_and_presets() {
+        assert_eq!(parse_ports(" 8001-8003, 22,8002 ,,").unwrap(), [22, 8001, 8002, 8003]);
+        assert_eq!(parse_ports("WEB,80").unwrap(), PRESETS[1].1);
+        assert_eq!(parse_ports("all").unwrap().len(), 65_535);
+        assert_eq!(parse_ports("65535-65535").unwrap(), [65_535]);
+        assert_eq!(parse_ports("90-80").unwrap_err(), "range '90-80' runs backwards");
+        assert!(parse_ports("0").unwrap_err().starts_with("'0' is not a port (1-65535)"));
+        assert!(parse_ports("0-10").is_err());
+        assert!(parse_ports("65536").is_err());
+        assert!(parse_ports("ssh").unwrap_err().contains("web, db"));
+        assert_eq!(parse_ports(" , ").unwrap_err(), "no ports given");
+    }
+
+    fn addresses(hosts: &[IpAddr]) -> Vec<String> {
+        hosts.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn expands_cidr_blocks() {
+        let ip = |raw: &str| raw.parse::<IpAddr>().unwrap();
+        assert_eq!(addresses(&expand_cidr(ip("192.168.1.77"), 30).unwrap()), ["192.168.1.77", "192.168.1.78"]);
+        // Point-to-point and single-host blocks have no network or broadcast address to skip.
+        assert_eq!(addresses(&expand_cidr(ip("10.0.0.1"), 31).unwrap()), ["10.0.0.0", "10.0.0.1"]);
+        assert_eq!(addresses(&expand_cidr(ip("10.0.0.1"), 32).unwrap()), ["10.0.0.1"]);
+        // IPv6 has no broadcast address, so only the subnet-router address is left out.
+        assert_eq!(addresses(&expand_cidr(ip("fd00::5"), 126).unwrap()), ["fd00::5", "fd00::6", "fd00::7"]);
+        assert_eq!(addresses(&expand_cidr(ip("fd00::5"), 128).unwrap()), ["fd00::5"]);
+
+        assert_eq!(expand_cidr(ip("10.0.0.0"), 16).unwrap().len(), 65_534);
+        assert_eq!(expand_cidr(ip("10.0.0.0"), 15).unwrap_err(), "/15 is too large; the most is 65536 hosts");
+        assert_eq!(expand_cidr(ip("fd00::"), 112).unwrap().len(), 65_535);
+        assert!(expand_cidr(ip("fd00::"), 64).is_err());
+        assert_eq!(expand_cidr(ip("10.0.0.0"), 33).unwrap_err(), "prefix /33 is longer than 32 bits");
+    }
+
+    #[test]
+    fn parses_targets() {
+        assert_eq!(addresses(&parse_targets("127.0.0.1, 10.1.2.0/30,,").unwrap()), ["127.0.0.1", "10.1.2.1", "10.1.2.2"]);
+        assert_eq!(parse_targets("10.0.0.0/x").unwrap_err(), "'x' is not a prefix length");
+        assert_eq!(parse_targets("10.0.0.300/24").unwrap_err(), "'10.0.0.300' is not a network address");
+        assert_eq!(parse_targets("10.0.0.0/16,10.1.0.0/16").unwrap_err(), format!("more than {} hosts", MAX_HOSTS));
+        assert_eq!(parse_targets("").unwrap_err(), "no targets given");
+    }
+
+    #[test]
+    fn tells_private_addresses_from_public_ones() {
+        let private = [
+            "127.0.0.1", "10.9.8.7", "172.16.0.1", "192.168.0.1", "100.64.0.1", "100.127.255.255", "169.254.1.1", "::1", "fd12::1", "fe80::1",
+            "::ffff:10.0.0.1",
+        ];
+        let public = ["8.8.8.8", "100.128.0.1", "172.32.0.1", "2001:4860::8888", "::ffff:1.1.1.1"];
+        for ip in private {
+            assert!(is_private(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in public {
+            assert!(!is_private(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn cleans_banners() {
+        assert_eq!(clean_banner(b"SSH-2.0-OpenSSH_9.6\r\n").as_deref(), Some("SSH-2.0-OpenSSH_9.6"));
+        assert_eq!(clean_banner(b"HTTP/1.1 200 OK\r\nserver: nginx\r\n\r\n").as_deref(), Some("HTTP/1.1 200 OK (nginx)"));
+        assert_eq!(clean_banner(b"+PONG\x00\x07\r\n").as_deref(), Some("+PONG"));
+        assert_eq!(clean_banner(b"\r\n"), None);
+        assert_eq!(clean_banner(&[b'a'; 500]).map(|banner| banner.len()), Some(120));
+    }
+
+    #[test]
+    fn scans_open_and_closed_local_ports() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap().port();
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"SSH-2.0-gremlin-test\r\n").unwrap();
+        });
+
+        let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let options = Options { concurrency: 4, timeout: Duration::from_secs(2), banner_timeout: Duration::from_secs(2) };
+        let results = scan(&[localhost], &[closed, open], options);
+        server.join().unwrap();
+
+        let mut expected = [(open, PortState::Open, Some("SSH-2.0-gremlin-test")), (closed, PortState::Closed, None)];
+        expected.sort_by_key(|(port, ..)| *port);
+        let seen: Vec<_> = results.iter().map(|result| (result.port, result.state, result.banner.as_deref())).collect();
+        assert_eq!(seen, expected);
+        assert!(results.iter().all(|result| result.host == localhost));
+    }
+}
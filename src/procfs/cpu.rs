@@ -0,0 +1,196 @@
+//! CPU time from `/proc/stat`. The counters only grow, so usage is always the difference
+//! between two readings.
+
+use crate::procfs::ProcFs;
+
+/// Cumulative clock ticks spent in each mode, for one core or all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    /// Guest time is already counted in `user` and `nice`, so it is left out.
+    pub fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle + self.iowait + self.irq + self.softirq + self.steal
+    }
+}
+
+/// One reading of `/proc/stat`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CpuStat {
+    /// The `cpu` line: every core together.
+    pub all: CpuTimes,
+    /// `cpu0`, `cpu1`... in order.
+    pub cores: Vec<CpuTimes>,
+    pub interrupts: u64,
+    pub context_switches: u64,
+    pub procs_running: u64,
+    pub procs_blocked: u64,
+}
+
+pub fn parse_stat(content: &str) -> CpuStat {
+    let mut stat = CpuStat::default();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(key) = fields.next() else { continue };
+        let values: Vec<u64> = fields.map(|field| field.parse().unwrap_or_default()).collect();
+        let first = values.first().copied().unwrap_or_default();
+        match key {
+            "cpu" => stat.all = times(&values),
+            key if key.starts_with("cpu") => stat.cores.push(times(&values)),
+            "intr" => stat.interrupts = first,
+            "ctxt" => stat.context_switches = first,
+            "procs_running" => stat.procs_running = first,
+            "procs_blocked" => stat.procs_blocked = first,
+            _ => {}
+        }
+    }
+    stat
+}
+
+fn times(values: &[u64]) -> CpuTimes {
+    let get = |i: usize| values.get(i).copied().unwrap_or_default();
+    CpuTimes {
+        user: get(0),
+        nice: get(1),
+        system: get(2),
+        idle: get(3),
+        iowait: get(4),
+        irq: get(5),
+        softirq: get(6),
+        steal: get(7),
+    }
+}
+
+pub fn read(proc: &ProcFs) -> Option<CpuStat> {
+    proc.read("stat").ok().map(|content| parse_stat(&content))
+}
+
+/// Share of time in each mode between two readings, in percent.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CpuUsage {
+    pub user: f64,
+    pub nice: f64,
+    pub system: f64,
+    pub idle: f64,
+    pub iowait: f64,
+    pub irq: f64,
+    pub softirq: f64,
+    pub steal: f64,
+}
+
+impl CpuUsage {
+    pub fn between(before: &CpuTimes, after: &CpuTimes) -> Self {
+        let elapsed = after.total().saturating_sub(before.total());
+        let share = |pick: fn(&CpuTimes) -> u64| {
+            if elapsed == 0 {
+                0.0
+            } else {
+                pick(after).saturating_sub(pick(before)) as f64 / elapsed as f64 * 100.0
+            }
+        };
+        Self {
+            user: share(|t| t.user),
+            nice: share(|t| t.nice),
+            system: share(|t| t.system),
+            idle: if elapsed == 0 { 100.0 } else { share(|t| t.idle) },
+            iowait: share(|t| t.iowait),
+            irq: share(|t| t.irq),
+            softirq: share(|t| t.softirq),
+            steal: share(|t| t.steal),
+        }
+    }
+
+    /// Everything but idle and iowait.
+    pub fn busy(&self) -> f64 {
+        (100.0 - self.idle - self.iowait).max(0.0)
+    }
+}
+
+/// Run-queue averages from `/proc/loadavg`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+    pub running: u32,
+    pub total: u32,
+}
+
+pub fn parse_loadavg(content: &str) -> Option<LoadAverage> {
+    let fields: Vec<&str> = content.split_whitespace().collect();
+    let (running, total) = fields.get(3)?.split_once('/')?;
+    Some(LoadAverage {
+        one: fields.first()?.parse().ok()?,
+        five: fields.get(1)?.parse().ok()?,
+        fifteen: fields.get(2)?.parse().ok()?,
+        running: running.parse().ok()?,
+        total: total.parse().ok()?,
+    })
+}
+
+pub fn load_average(proc: &ProcFs) -> Option<LoadAverage> {
+    parse_loadavg(&proc.read("loadavg").ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::fixture::Fixture;
+
+    const STAT: &str = "cpu  100 10 50 800 20 5 5 10 40 0
+cpu0 60 5 25 400 10 3 2 5 20 0
+cpu1 40 5 25 400 10 2 3 5 20 0
+intr 123456 30 0 0
+ctxt 987654
+btime 1700000000
+processes 4321
+procs_running 3
+procs_blocked 1
+softirq 5555 0 1
+";
+
+    #[test]
+    fn parses_totals_cores_and_counters() {
+        let stat = parse_stat(STAT);
+        assert_eq!(stat.all, CpuTimes { user: 100, nice: 10, system: 50, idle: 800, iowait: 20, irq: 5, softirq: 5, steal: 10 });
+        // Guest time (the ninth and tenth columns) is already in user and nice.
+        assert_eq!(stat.all.total(), 1000);
+        assert_eq!(stat.cores.len(), 2);
+        assert_eq!(stat.cores[1].softirq, 3);
+        assert_eq!((stat.interrupts, stat.context_switches, stat.procs_running, stat.procs_blocked), (123456, 987654, 3, 1));
+    }
+
+    #[test]
+    fn usage_is_the_share_of_the_delta() {
+        let before = parse_stat(STAT).all;
+        let after = CpuTimes { user: 150, nice: 10, system: 75, idle: 900, iowait: 40, irq: 5, softirq: 10, steal: 10 };
+        let usage = CpuUsage::between(&before, &after);
+        assert_eq!((usage.user, usage.system, usage.idle, usage.softirq, usage.iowait), (25.0, 12.5, 50.0, 2.5, 10.0));
+        assert_eq!(usage.busy(), 40.0);
+
+        let idle = CpuUsage::between(&before, &before);
+        assert_eq!((idle.idle, idle.busy()), (100.0, 0.0));
+        // A counter that went backwards (a CPU taken offline) doesn't underflow.
+        let shrunk = CpuUsage::between(&after, &CpuTimes { user: 0, ..after });
+        assert_eq!(shrunk.busy(), 0.0);
+    }
+
+    #[test]
+    fn reads_load_average_from_a_fixture() {
+        let fixture = Fixture::new();
+        assert_eq!(read(&fixture.proc()), None);
+        fixture.file("stat", STAT).file("loadavg", "0.52 0.58 0.59 2/1021 42424\n");
+        assert_eq!(read(&fixture.proc()).map(|stat| stat.cores.len()), Some(2));
+        assert_eq!(load_average(&fixture.proc()), Some(LoadAverage { one: 0.52, five: 0.58, fifteen: 0.59, running: 2, total: 1021 }));
+        assert_eq!(parse_loadavg("0.52 0.58 0.59"), None);
+    }
+}
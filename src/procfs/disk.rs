@@ -0,0 +1,159 @@
+//! Block device counters from `/proc/diskstats`, turned into rates between two readings.
+
+use crate::procfs::ProcFs;
+
+/// `/proc/diskstats` counts in 512-byte sectors regardless of the device.
+const SECTOR_BYTES: u64 = 512;
+
+/// Cumulative counters for one device.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiskCounters {
+    pub name: String,
+    pub reads: u64,
+    pub sectors_read: u64,
+    pub read_ms: u64,
+    pub writes: u64,
+    pub sectors_written: u64,
+    pub write_ms: u64,
+    /// Time the device had I/O in flight.
+    pub busy_ms: u64,
+}
+
+pub fn parse_diskstats(content: &str) -> Vec<DiskCounters> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let get = |i: usize| fields.get(i).and_then(|field| field.parse().ok());
+            Some(DiskCounters {
+                name: fields.get(2)?.to_string(),
+                reads: get(3)?,
+                sectors_read: get(5)?,
+                read_ms: get(6)?,
+                writes: get(7)?,
+                sectors_written: get(9)?,
+                write_ms: get(10)?,
+                busy_ms: get(12)?,
+            })
+        })
+        .collect()
+}
+
+pub fn read(proc: &ProcFs) -> Option<Vec<DiskCounters>> {
+    proc.read("diskstats").ok().map(|content| parse_diskstats(&content))
+}
+
+/// Whole disks only: partitions (`sda1`, `nvme0n1p2`) and devices that never did any I/O are
+/// left out, as are RAM disks.
+pub fn is_reported(disk: &DiskCounters, all: &[DiskCounters]) -> bool {
+    let numbered = |suffix: &str| !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit());
+    let partition = all.iter().any(|other| {
+        let Some(suffix) = disk.name.strip_prefix(other.name.as_str()) else { return false };
+        // `sda` + `1`, but `nvme0n1` + `p1`: names ending in a digit separate the partition number.
+        if other.name.ends_with(|c: char| c.is_ascii_digit()) {
+            suffix.strip_prefix('p').is_some_and(numbered)
+        } else {
+            numbered(suffix)
+        }
+    });
+    !partition && !disk.name.starts_with("ram") && disk.reads + disk.writes > 0
+}
+
+/// I/O rates for one device over a sampling interval.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiskRates {
+    pub name: String,
+    pub reads_per_sec: f64,
+    pub writes_per_sec: f64,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    /// Average time per read, queueing included.
+    pub read_await_ms: f64,
+    pub write_await_ms: f64,
+    /// Share of the interval the device was busy.
+    pub util_percent: f64,
+}
+
+/// Rates for every reported device present in both readings.
+pub fn rates(before: &[DiskCounters], after: &[DiskCounters], seconds: f64) -> Vec<DiskRates> {
+    after
+        .iter()
+        .filter(|disk| is_reported(disk, after))
+        .filter_map(|now| {
+            let then = before.iter().find(|disk| disk.name == now.name)?;
+            let delta = |pick: fn(&DiskCounters) -> u64| pick(now).saturating_sub(pick(then)) as f64;
+            let per_sec = |value: f64| if seconds > 0.0 { value / seconds } else { 0.0 };
+            let average = |ms: f64, ops: f64| if ops > 0.0 { ms / ops } else { 0.0 };
+            Some(DiskRates {
+                name: now.name.clone(),
+                reads_per_sec: per_sec(delta(|d| d.reads)),
+                writes_per_sec: per_sec(delta(|d| d.writes)),
+                read_bytes_per_sec: per_sec(delta(|d| d.sectors_read) * SECTOR_BYTES as f64),
+                write_bytes_per_sec: per_sec(delta(|d| d.sectors_written) * SECTOR_BYTES as f64),
+                read_await_ms: average(delta(|d| d.read_ms), delta(|d| d.reads)),
+                write_await_ms: average(delta(|d| d.write_ms), delta(|d| d.writes)),
+                util_percent: (per_sec(delta(|d| d.busy_ms)) / 10.0).min(100.0),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::fixture::Fixture;
+
+    const BEFORE: &str = "   7       0 loop0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+   1       0 ram0 50 0 400 1 0 0 0 0 0 1 1 0 0 0 0 0 0
+   8       0 sda 1000 5 80000 4000 2000 10 160000 6000 0 3000 10000 0 0 0 0
+   8       1 sda1 900 5 70000 3500 1900 10 150000 5500 0 2800 9000 0 0 0 0
+ 259       0 nvme0n1 10 0 80 2 0 0 0 0 0 2 2 0 0 0 0
+ 259       1 nvme0n1p2 10 0 80 2 0 0 0 0 0 2 2 0 0 0 0
+ 259       2 nvme0n10 5 0 40 1 0 0 0 0 0 1 1 0 0 0 0
+";
+
+    fn names(disks: &[DiskCounters]) -> Vec<&str> {
+        disks.iter().filter(|disk| is_reported(disk, disks)).map(|disk| disk.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_diskstats_and_keeps_whole_disks() {
+        let disks = parse_diskstats(BEFORE);
+        assert_eq!(disks.len(), 7);
+        assert_eq!(
+            disks[2],
+            DiskCounters { name: "sda".to_string(), reads: 1000, sectors_read: 80000, read_ms: 4000, writes: 2000, sectors_written: 160000, write_ms: 6000, busy_ms: 3000 }
+        );
+        assert_eq!(names(&disks), ["sda", "nvme0n1", "nvme0n10"]);
+        assert!(parse_diskstats("8 0 sda 1 2\n").is_empty());
+    }
+
+    #[test]
+    fn rates_come_from_the_delta() {
+        let fixture = Fixture::new();
+        assert_eq!(read(&fixture.proc()), None);
+        fixture.file("diskstats", BEFORE);
+        let before = read(&fixture.proc()).unwrap();
+        let mut after = before.clone();
+        let sda = &mut after[2];
+        sda.reads += 200;
+        sda.sectors_read += 4096;
+        sda.read_ms += 400;
+        sda.writes += 100;
+        sda.sectors_written += 2048;
+        sda.write_ms += 500;
+        sda.busy_ms += 1500;
+
+        let rates = rates(&before, &after, 2.0);
+        assert_eq!(rates.iter().map(|rate| rate.name.as_str()).collect::<Vec<_>>(), ["sda", "nvme0n1", "nvme0n10"]);
+        let sda = &rates[0];
+        assert_eq!((sda.reads_per_sec, sda.writes_per_sec), (100.0, 50.0));
+        assert_eq!((sda.read_bytes_per_sec, sda.write_bytes_per_sec), (1_048_576.0, 524_288.0));
+        assert_eq!((sda.read_await_ms, sda.write_await_ms, sda.util_percent), (2.0, 5.0, 75.0));
+        assert_eq!(rates[1], DiskRates { name: "nvme0n1".to_string(), ..Default::default() });
+
+        // Busy time can outrun the wall clock by a little; utilisation stays at 100.
+        after[2].busy_ms += 10_000;
+        assert_eq!(super::rates(&before, &after, 2.0)[0].util_percent, 100.0);
+    }
+}
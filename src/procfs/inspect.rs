@@ -0,0 +1,345 @@
+//! Everything `/proc/<pid>` says about one process, section by section. Most sections of
+//! another user's process are only readable as root, so each reader returns its own error.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use crate::procfs::process;
+use crate::procfs::{ProcFs, TICKS_PER_SECOND};
+
+/// Environment variable names containing any of these have their values masked.
+const SECRET_MARKERS: [&str; 10] =
+    ["PASS", "SECRET", "TOKEN", "KEY", "CREDENTIAL", "AUTH", "COOKIE", "SESSION", "PRIVATE", "DSN"];
+
+fn read(proc: &ProcFs, pid: u32, file: &str) -> io::Result<String> {
+    proc.read(format!("{}/{}", pid, file))
+}
+
+/// Program and arguments; empty for kernel threads.
+pub fn cmdline(proc: &ProcFs, pid: u32) -> io::Result<Vec<String>> {
+    let raw = fs::read(proc.path(format!("{}/cmdline", pid)))?;
+    Ok(raw.split(|&byte| byte == 0).filter(|arg| !arg.is_empty()).map(|arg| String::from_utf8_lossy(arg).into_owned()).collect())
+}
+
+/// Target of a symlink under `/proc/<pid>`, such as `cwd`, `exe` or `root`.
+pub fn link(proc: &ProcFs, pid: u32, name: &str) -> io::Result<String> {
+    fs::read_link(proc.path(format!("{}/{}", pid, name))).map(|target| target.display().to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvVar {
+    pub name: String,
+    /// `***` when `masked`.
+    pub value: String,
+    pub masked: bool,
+}
+
+/// Whether a variable looks like it holds a password, token or key.
+pub fn is_secret(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// The environment the process started with, secrets masked.
+pub fn environ(proc: &ProcFs, pid: u32) -> io::Result<Vec<EnvVar>> {
+    let raw = fs::read(proc.path(format!("{}/environ", pid)))?;
+    Ok(raw
+        .split(|&byte| byte == 0)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (name, value) = entry.split_once('=').unwrap_or((&entry, ""));
+            let masked = is_secret(name) && !value.is_empty();
+            EnvVar { name: name.to_string(), value: if masked { "***".to_string() } else { value.to_string() }, masked }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FdKind {
+    File,
+    Socket,
+    Pipe,
+    /// eventfd, epoll, inotify, timerfd...
+    AnonInode,
+    Other,
+}
+
+impl FdKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FdKind::File => "file",
+            FdKind::Socket => "socket",
+            FdKind::Pipe => "pipe",
+            FdKind::AnonInode => "anon_inode",
+            FdKind::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fd {
+    pub fd: u32,
+    pub kind: FdKind,
+    /// Where the descriptor points, e.g. `/var/log/syslog` or `socket:[12345]`.
+    pub target: String,
+    /// Inode of a socket or pipe.
+    pub inode: Option<u64>,
+}
+
+/// Classify an `fd/<n>` link target.
+pub fn parse_fd_target(fd: u32, target: &str) -> Fd {
+    let inode = |prefix: &str| target.strip_prefix(prefix)?.strip_suffix(']')?.parse().ok();
+    let (kind, inode) = if let Some(inode) = inode("socket:[") {
+        (FdKind::Socket, Some(inode))
+    } else if let Some(inode) = inode("pipe:[") {
+        (FdKind::Pipe, Some(inode))
+    } else if target.starts_with("anon_inode:") {
+        (FdKind::AnonInode, None)
+    } else if target.starts_with('/') {
+        (FdKind::File, None)
+    } else {
+        (FdKind::Other, None)
+    };
+    Fd { fd, kind, target: target.to_string(), inode }
+}
+
+/// Open file descriptors by number.
+pub fn fds(proc: &ProcFs, pid: u32) -> io::Result<Vec<Fd>> {
+    let mut fds = Vec::new();
+    for entry in fs::read_dir(proc.path(format!("{}/fd", pid)))?.flatten() {
+        let Some(fd) = entry.file_name().to_str().and_then(|name| name.parse().ok()) else { continue };
+        match fs::read_link(entry.path()) {
+            Ok(target) => fds.push(parse_fd_target(fd, &target.display().to_string())),
+            // Closed since the directory was listed.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    fds.sort_by_key(|fd| fd.fd);
+    Ok(fds)
+}
+
+/// One line of `/proc/<pid>/maps`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    pub start: u64,
+    pub end: u64,
+    /// `r-xp` and the like.
+    pub perms: String,
+    /// File path, `[heap]`, `[stack]`, `[vdso]`..., or empty for anonymous memory.
+    pub path: String,
+}
+
+impl Mapping {
+    pub fn size(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+pub fn parse_maps(content: &str) -> Vec<Mapping> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(6, char::is_whitespace);
+            let (start, end) = fields.next()?.split_once('-')?;
+            let perms = fields.next()?.to_string();
+            let path = fields.nth(3).unwrap_or_default().trim().to_string();
+            Some(Mapping { start: u64::from_str_radix(start, 16).ok()?, end: u64::from_str_radix(end, 16).ok()?, perms, path })
+        })
+        .collect()
+}
+
+pub fn maps(proc: &ProcFs, pid: u32) -> io::Result<Vec<Mapping>> {
+    read(proc, pid, "maps").map(|content| parse_maps(&content))
+}
+
+/// Mappings of one file or region added up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapGroup {
+    /// The path, `[heap]`-style region, or `[anon]`.
+    pub name: String,
+    pub mappings: usize,
+    pub size: u64,
+    pub executable: bool,
+}
+
+/// Mappings grouped by what backs them, largest first.
+pub fn summarize_maps(mappings: &[Mapping]) -> Vec<MapGroup> {
+    let mut groups: BTreeMap<&str, MapGroup> = BTreeMap::new();
+    for mapping in mappings {
+        let name = if mapping.path.is_empty() { "[anon]" } else { mapping.path.as_str() };
+        let group = groups.entry(name).or_insert_with(|| MapGroup { name: name.to_string(), mappings: 0, size: 0, executable: false });
+        group.mappings += 1;
+        group.size += mapping.size();
+        group.executable |= mapping.perms.contains('x');
+    }
+    let mut groups: Vec<MapGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    groups
+}
+
+/// `Rss`, `Pss`, `Swap`... from `smaps_rollup`, in bytes.
+pub fn parse_rollup(content: &str) -> Vec<(String, u64)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let kib: u64 = value.trim().strip_suffix("kB")?.trim().parse().ok()?;
+            Some((key.to_string(), kib * 1024))
+        })
+        .collect()
+}
+
+pub fn rollup(proc: &ProcFs, pid: u32) -> io::Result<Vec<(String, u64)>> {
+    read(proc, pid, "smaps_rollup").map(|content| parse_rollup(&content))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Limit {
+    pub name: String,
+    /// `None` is unlimited.
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+    pub unit: String,
+}
+
+/// Parse `/proc/<pid>/limits`. Names contain spaces, so columns are cut at the header's
+/// `Soft Limit`, `Hard Limit` and `Units` positions.
+pub fn parse_limits(content: &str) -> Vec<Limit> {
+    let mut lines = content.lines();
+    let Some(header) = lines.next() else { return Vec::new() };
+    let (Some(soft_at), Some(hard_at), Some(unit_at)) = (header.find("Soft Limit"), header.find("Hard Limit"), header.find("Units")) else {
+        return Vec::new();
+    };
+    let column = |line: &str, from: usize, to: usize| line.get(from..to.min(line.len())).unwrap_or_default().trim().to_string();
+    let value = |raw: String| raw.parse().ok();
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Limit {
+            name: column(line, 0, soft_at),
+            soft: value(column(line, soft_at, hard_at)),
+            hard: value(column(line, hard_at, unit_at)),
+            unit: column(line, unit_at, line.len()),
+        })
+        .collect()
+}
+
+pub fn limits(proc: &ProcFs, pid: u32) -> io::Result<Vec<Limit>> {
+    read(proc, pid, "limits").map(|content| parse_limits(&content))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cgroup {
+    /// 0 for the unified (v2) hierarchy.
+    pub hierarchy: u32,
+    /// Empty for the unified hierarchy.
+    pub controllers: String,
+    pub path: String,
+}
+
+pub fn parse_cgroup(content: &str) -> Vec<Cgroup> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ':');
+            Some(Cgroup { hierarchy: fields.next()?.parse().ok()?, controllers: fields.next()?.to_string(), path: fields.next()?.to_string() })
+        })
+        .collect()
+}
+
+pub fn cgroups(proc: &ProcFs, pid: u32) -> io::Result<Vec<Cgroup>> {
+    read(proc, pid, "cgroup").map(|content| parse_cgroup(&content))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Namespace {
+    /// `net`, `pid`, `mnt`...
+    pub kind: String,
+    /// Inode number; processes with the same one share the namespace.
+    pub inode: u64,
+}
+
+pub fn namespaces(proc: &ProcFs, pid: u32) -> io::Result<Vec<Namespace>> {
+    let mut namespaces = Vec::new();
+    for entry in fs::read_dir(proc.path(format!("{}/ns", pid)))?.flatten() {
+        // Listing the directory is allowed even where reading the links isn't.
+        let target = fs::read_link(entry.path())?.display().to_string();
+        // Links read as `net:[4026531840]`; `pid_for_children` and `time_for_children` read as
+        // `pid:` and `time:`, so the kind comes from the file name.
+        let inode = target.split_once(":[").and_then(|(_, inode)| inode.strip_suffix(']')?.parse().ok());
+        if let Some(inode) = inode {
+            namespaces.push(Namespace { kind: entry.file_name().to_string_lossy().into_owned(), inode });
+        }
+    }
+    namespaces.sort_by(|a, b| a.kind.cmp(&b.kind));
+    Ok(namespaces)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thread {
+    pub tid: u32,
+    pub name: String,
+    pub state: char,
+    pub cpu_seconds: f64,
+}
+
+pub fn threads(proc: &ProcFs, pid: u32) -> io::Result<Vec<Thread>> {
+    let mut threads: Vec<Thread> = fs::read_dir(proc.path(format!("{}/task", pid)))?
+        .flatten()
+        .filter_map(|entry| {
+            let tid = entry.file_name().to_str()?.parse().ok()?;
+            let stat = process::parse_stat(&fs::read_to_string(entry.path().join("stat")).ok()?)?;
+            Some(Thread { tid, name: stat.name, state: stat.state, cpu_seconds: (stat.utime + stat.stime) as f64 / TICKS_PER_SECOND })
+        })
+        .collect();
+    threads.sort_by_key(|thread| thread.tid);
+    Ok(threads)
+}
+
+/// `oom_score` (how likely the OOM killer picks it, 0–1000) and `oom_score_adj` (-1000–1000).
+pub fn oom(proc: &ProcFs, pid: u32) -> io::Result<(i64, i64)> {
+    let number = |file: &str| -> io::Result<i64> {
+        read(proc, pid, file)?.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("bad {}", file)))
+    };
+    Ok((number("oom_score")?, number("oom_score_adj")?))
+}
+
+/// `/proc/<pid>/io`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoCounters {
+    /// Bytes passed to read-like calls, page cache hits included.
+    pub rchar: u64,
+    pub wchar: u64,
+    pub syscr: u64,
+    pub syscw: u64,
+    /// Bytes actually fetched from storage.
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub cancelled_write_bytes: u64,
+}
+
+pub fn parse_io(content: &str) -> IoCounters {
+    let values: BTreeMap<&str, u64> = content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim(), value.trim().parse().ok()?))
+        })
+        .collect();
+    let get = |key: &str| values.get(key).copied().unwrap_or_default();
+    IoCounters {
+        rchar: get("rchar"),
+        wchar: get("wchar"),
+        syscr: get("syscr"),
+        syscw: get("syscw"),
+        read_bytes: get("read_bytes"),
+        write_bytes: get("write_bytes"),
+        cancelled_write_bytes: get("cancelled_write_bytes"),
+    }
+}
+
+pub fn io_counters(proc: &ProcFs, pid: u32) -> io::Result<IoCounters> {
+    read(proc, pid, "io").map(|content| parse_io(&content))
+}
@@ -0,0 +1,154 @@
+//! Memory and swap from `/proc/meminfo`, paging counters from `/proc/vmstat`.
+
+use std::collections::HashMap;
+
+use crate::procfs::ProcFs;
+
+/// Size of a page, for the swap counters in `/proc/vmstat` (4 KiB on x86 and most arm64 kernels).
+pub const PAGE_SIZE: u64 = 4096;
+
+/// Values from `/proc/meminfo`, in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Memory {
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+    pub buffers: u64,
+    /// Page cache plus reclaimable slab, as `free` counts it.
+    pub cached: u64,
+    pub shared: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+impl Memory {
+    /// Memory that can't be reclaimed, as `free` computes it: total minus available.
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    pub fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+
+    pub fn used_percent(&self) -> f64 {
+        percent(self.used(), self.total)
+    }
+
+    pub fn swap_percent(&self) -> f64 {
+        percent(self.swap_used(), self.swap_total)
+    }
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64 * 100.0
+    }
+}
+
+/// Parse `/proc/meminfo`; missing keys are zero.
+pub fn parse_meminfo(content: &str) -> Memory {
+    let values: HashMap<&str, u64> = content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let kib: u64 = value.split_whitespace().next()?.parse().ok()?;
+            Some((key.trim(), kib * 1024))
+        })
+        .collect();
+    let get = |key: &str| values.get(key).copied().unwrap_or_default();
+    Memory {
+        total: get("MemTotal"),
+        free: get("MemFree"),
+        available: values.get("MemAvailable").copied().unwrap_or_else(|| get("MemFree")),
+        buffers: get("Buffers"),
+        cached: get("Cached") + get("SReclaimable"),
+        shared: get("Shmem"),
+        swap_total: get("SwapTotal"),
+        swap_free: get("SwapFree"),
+    }
+}
+
+pub fn read(proc: &ProcFs) -> Option<Memory> {
+    proc.read("meminfo").ok().map(|content| parse_meminfo(&content))
+}
+
+/// Cumulative paging counters from `/proc/vmstat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Paging {
+    /// KiB read from block devices.
+    pub paged_in: u64,
+    /// KiB written to block devices.
+    pub paged_out: u64,
+    /// Pages swapped in.
+    pub swapped_in: u64,
+    /// Pages swapped out.
+    pub swapped_out: u64,
+}
+
+pub fn parse_vmstat(content: &str) -> Paging {
+    let mut paging = Paging::default();
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(' ') else { continue };
+        let value = value.trim().parse().unwrap_or_default();
+        match key {
+            "pgpgin" => paging.paged_in = value,
+            "pgpgout" => paging.paged_out = value,
+            "pswpin" => paging.swapped_in = value,
+            "pswpout" => paging.swapped_out = value,
+            _ => {}
+        }
+    }
+    paging
+}
+
+pub fn read_paging(proc: &ProcFs) -> Option<Paging> {
+    proc.read("vmstat").ok().map(|content| parse_vmstat(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_meminfo_the_way_free_counts() {
+        let memory = parse_meminfo(
+            "MemTotal:       16000000 kB
+MemFree:         2000000 kB
+MemAvailable:    8000000 kB
+Buffers:          500000 kB
+Cached:          4000000 kB
+SwapCached:        10000 kB
+Shmem:            300000 kB
+SReclaimable:     250000 kB
+SwapTotal:       2000000 kB
+SwapFree:        1500000 kB
+HugePages_Total:       0
+",
+        );
+        assert_eq!(memory.total, 16_000_000 * 1024);
+        assert_eq!(memory.cached, 4_250_000 * 1024);
+        assert_eq!(memory.shared, 300_000 * 1024);
+        assert_eq!(memory.used(), 8_000_000 * 1024);
+        assert_eq!(memory.used_percent(), 50.0);
+        assert_eq!(memory.swap_used(), 500_000 * 1024);
+        assert_eq!(memory.swap_percent(), 25.0);
+    }
+
+    #[test]
+    fn available_falls_back_to_free_on_old_kernels() {
+        let memory = parse_meminfo("MemTotal: 1000 kB\nMemFree: 250 kB\n");
+        assert_eq!(memory.available, 250 * 1024);
+        assert_eq!(memory.used_percent(), 75.0);
+        assert_eq!((memory.swap_total, memory.swap_percent()), (0, 0.0));
+        assert_eq!(parse_meminfo(""), Memory::default());
+    }
+
+    #[test]
+    fn parses_paging_counters() {
+        let paging = parse_vmstat("nr_free_pages 12345\npgpgin 1000\npgpgout 2000\npswpin 3\npswpout 4\npgfault 99\n");
+        assert_eq!(paging, Paging { paged_in: 1000, paged_out: 2000, swapped_in: 3, swapped_out: 4 });
+    }
+}
@@ -0,0 +1,144 @@
+//! Readers for the Linux `/proc` filesystem. Everything goes through a `ProcFs`, so it can be
+//! pointed at a fixture directory instead of the live `/proc`.
+
+pub mod cpu;
+pub mod disk;
+pub mod inspect;
+pub mod memory;
+pub mod net;
+pub mod pressure;
+pub mod process;
+pub mod tree;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+
+/// Clock ticks per second for the times in `/proc/<pid>/stat` (`USER_HZ`, fixed at 100 on Linux).
+pub const TICKS_PER_SECOND: f64 = 100.0;
+
+/// A `/proc` tree: the live one, or a copy laid out the same way.
+#[derive(Debug, Clone)]
+pub struct ProcFs {
+    root: PathBuf,
+}
+
+impl ProcFs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The configured root (`paths.proc_root`, `$GREMLIN_PROC_ROOT`), normally the live `/proc`.
+    pub fn system() -> Self {
+        Self::new(&config::get().paths.proc_root)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.root.join(relative)
+    }
+
+    pub fn read(&self, relative: impl AsRef<Path>) -> io::Result<String> {
+        fs::read_to_string(self.path(relative))
+    }
+
+    /// PIDs of every process, ascending.
+    pub fn pids(&self) -> Vec<u32> {
+        let mut pids: Vec<u32> = fs::read_dir(&self.root)
+            .map(|entries| entries.flatten().filter_map(|entry| entry.file_name().to_str()?.parse().ok()).collect())
+            .unwrap_or_default();
+        pids.sort_unstable();
+        pids
+    }
+
+    /// Seconds since boot, from `uptime`.
+    pub fn uptime(&self) -> Option<f64> {
+        self.read("uptime").ok()?.split_whitespace().next()?.parse().ok()
+    }
+
+    /// Boot time as a Unix timestamp, from the `btime` line of `stat`.
+    pub fn boot_time(&self) -> Option<i64> {
+        self.read("stat").ok()?.lines().find_map(|line| line.strip_prefix("btime")?.trim().parse().ok())
+    }
+}
+
+/// UID → user name from `/etc/passwd`.
+pub fn users() -> HashMap<u32, String> {
+    fs::read_to_string("/etc/passwd")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            Some((fields.get(2)?.parse().ok()?, fields[0].to_string()))
+        })
+        .collect()
+}
+
+/// Throwaway `/proc` trees for tests.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use std::path::Path;
+
+    use super::ProcFs;
+    use crate::test_support::TempDir;
+
+    /// A directory laid out like `/proc`, removed when dropped.
+    pub(crate) struct Fixture {
+        dir: TempDir,
+    }
+
+    /// One `/proc/<pid>` entry. The defaults are a sleeping root-owned process.
+    #[derive(Debug, Clone)]
+    pub(crate) struct FakeProcess<'a> {
+        pub pid: u32,
+        pub ppid: u32,
+        pub name: &'a str,
+        pub state: char,
+        pub uid: u32,
+        /// Empty for kernel threads and zombies.
+        pub cmdline: &'a [&'a str],
+        pub flags: u64,
+        pub utime: u64,
+        pub rss_kib: u64,
+        pub threads: u32,
+        pub start_ticks: u64,
+    }
+
+    impl Default for FakeProcess<'_> {
+        fn default() -> Self {
+            Self { pid: 1, ppid: 0, name: "init", state: 'S', uid: 0, cmdline: &[], flags: 0x0040_0100, utime: 0, rss_kib: 0, threads: 1, start_ticks: 0 }
+        }
+    }
+
+    /// `PF_KTHREAD` and the other flags a kernel worker has.
+    pub(crate) const KTHREAD_FLAGS: u64 = 0x0020_8040;
+
+    impl Fixture {
+        pub fn new() -> Self {
+            Self { dir: TempDir::new() }
+        }
+
+        pub fn proc(&self) -> ProcFs {
+            ProcFs::new(self.dir.root())
+        }
+
+        pub fn file(&self, relative: impl AsRef<Path>, content: &str) -> &Self {
+            self.dir.file(relative, content);
+            self
+        }
+
+        pub fn process(&self, process: FakeProcess<'_>) -> &Self {
+            let FakeProcess { pid, ppid, name, state, uid, cmdline, flags, utime, rss_kib, threads, start_ticks } = process;
+            let stat = format!("{} ({}) {} {} {} {} 0 -1 {} 0 0 0 0 {} 0 0 0 20 0 {} 0 {} 0 0", pid, name, state, ppid, pid, pid, flags, utime, threads, start_ticks);
+            let status = format!("Name:\t{}\nState:\t{}\nUid:\t{uid}\t{uid}\t{uid}\t{uid}\nVmRSS:\t{} kB\n", name, state, rss_kib, uid = uid);
+            let cmdline: String = cmdline.iter().map(|arg| format!("{}\0", arg)).collect();
+            self.file(format!("{}/stat", pid), &stat).file(format!("{}/status", pid), &status).file(format!("{}/cmdline", pid), &cmdline)
+        }
+    }
+}
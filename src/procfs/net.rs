@@ -0,0 +1,427 @@
+//! Network interface counters from `/proc/net/dev`, and the socket tables in `/proc/net/tcp`,
+//! `udp` and `unix` with the processes that own each socket.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::procfs::inspect::{self, FdKind};
+use crate::procfs::ProcFs;
+
+/// Cumulative counters for one interface.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterfaceCounters {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+}
+
+impl InterfaceCounters {
+    pub fn is_loopback(&self) -> bool {
+        self.name == "lo"
+    }
+}
+
+/// Parse `/proc/net/dev`: two header lines, then `name: <8 receive> <8 transmit>` counters.
+pub fn parse_dev(content: &str) -> Vec<InterfaceCounters> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (name, counters) = line.split_once(':')?;
+            let values: Vec<u64> = counters.split_whitespace().filter_map(|value| value.parse().ok()).collect();
+            Some(InterfaceCounters {
+                name: name.trim().to_string(),
+                rx_bytes: *values.first()?,
+                rx_packets: *values.get(1)?,
+                rx_errors: *values.get(2)?,
+                tx_bytes: *values.get(8)?,
+                tx_packets: *values.get(9)?,
+                tx_errors: *values.get(10)?,
+            })
+        })
+        .collect()
+}
+
+pub fn read(proc: &ProcFs) -> Vec<InterfaceCounters> {
+    proc.read("net/dev").map(|content| parse_dev(&content)).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Unix,
+}
+
+impl Protocol {
+    pub const ALL: [Protocol; 5] = [Protocol::Tcp, Protocol::Tcp6, Protocol::Udp, Protocol::Udp6, Protocol::Unix];
+
+    /// Also the file name under `/proc/net`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+            Protocol::Unix => "unix",
+        }
+    }
+
+    /// `tcp` and `udp` include their IPv6 tables; `inet` is both.
+    pub fn parse_list(names: &str) -> Option<Vec<Protocol>> {
+        let mut protocols = Vec::new();
+        for name in names.split(',').map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty()) {
+            protocols.extend_from_slice(match name.as_str() {
+                "all" => &Protocol::ALL,
+                "inet" => &Protocol::ALL[..4],
+                "tcp" => &[Protocol::Tcp, Protocol::Tcp6],
+                "udp" => &[Protocol::Udp, Protocol::Udp6],
+                "tcp4" => &[Protocol::Tcp],
+                "udp4" => &[Protocol::Udp],
+                "tcp6" => &[Protocol::Tcp6],
+                "udp6" => &[Protocol::Udp6],
+                "unix" => &[Protocol::Unix],
+                _ => return None,
+            });
+        }
+        protocols.sort_unstable();
+        protocols.dedup();
+        Some(protocols)
+    }
+
+    fn is_udp(&self) -> bool {
+        matches!(self, Protocol::Udp | Protocol::Udp6)
+    }
+}
+
+/// One row of a `/proc/net` socket table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Socket {
+    pub protocol: Protocol,
+    /// `None` for unix sockets.
+    pub local: Option<SocketAddr>,
+    pub remote: Option<SocketAddr>,
+    /// Bound path of a unix socket, `@name` for abstract ones; empty otherwise.
+    pub path: String,
+    /// `LISTEN`, `ESTABLISHED`... as `netstat` names them; UDP sockets are `UNCONN` or `ESTAB`.
+    pub state: &'static str,
+    /// Owner, for TCP and UDP sockets.
+    pub uid: Option<u32>,
+    pub inode: u64,
+    pub tx_queue: u64,
+    pub rx_queue: u64,
+}
+
+impl Socket {
+    /// Accepting connections, or for UDP bound and not connected to a peer.
+    pub fn listening(&self) -> bool {
+        self.state == "LISTEN" || (self.protocol.is_udp() && self.state == "UNCONN")
+    }
+
+    pub fn local_port(&self) -> Option<u16> {
+        self.local.map(|local| local.port())
+    }
+
+    pub fn remote_port(&self) -> Option<u16> {
+        self.remote.map(|remote| remote.port()).filter(|&port| port != 0)
+    }
+
+    /// Short description such as `tcp 127.0.0.1:8080 → 10.0.0.2:51234 ESTABLISHED`.
+    pub fn describe(&self) -> String {
+        match (self.local, self.remote) {
+            (Some(local), Some(remote)) if self.remote_port().is_some() => {
+                format!("{} {} → {} {}", self.protocol.name(), local, remote, self.state)
+            }
+            (Some(local), _) => format!("{} {} {}", self.protocol.name(), local, self.state),
+            _ if self.path.is_empty() => format!("unix {}", self.state),
+            _ => format!("unix {} {}", self.path, self.state),
+        }
+    }
+}
+
+/// TCP states in the order of the kernel's `st` codes, 01 to 0C.
+pub const TCP_STATES: [&str; 12] = [
+    "ESTABLISHED",
+    "SYN_SENT",
+    "SYN_RECV",
+    "FIN_WAIT1",
+    "FIN_WAIT2",
+    "TIME_WAIT",
+    "CLOSE",
+    "CLOSE_WAIT",
+    "LAST_ACK",
+    "LISTEN",
+    "CLOSING",
+    "NEW_SYN_RECV",
+];
+
+/// An address as the kernel prints it: the raw network-order words in host-order hex, then the
+/// port, e.g. `0100007F:1F90` for 127.0.0.1:8080.
+fn parse_address(raw: &str) -> Option<SocketAddr> {
+    let (ip, port) = raw.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    if ip.len() % 8 != 0 {
+        return None;
+    }
+    let words: Vec<[u8; 4]> = (0..ip.len() / 8)
+        .map(|i| u32::from_str_radix(ip.get(i * 8..i * 8 + 8)?, 16).ok().map(u32::to_ne_bytes))
+        .collect::<Option<_>>()?;
+    let ip = match words.as_slice() {
+        [word] => IpAddr::V4(Ipv4Addr::from(*word)),
+        [a, b, c, d] => {
+            let mut octets = [0u8; 16];
+            for (chunk, word) in octets.chunks_mut(4).zip([a, b, c, d]) {
+                chunk.copy_from_slice(word);
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Parse `/proc/net/tcp`, `tcp6`, `udp` or `udp6`.
+pub fn parse_inet(content: &str, protocol: Protocol) -> Vec<Socket> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let state = usize::from_str_radix(fields.get(3)?, 16).ok()?;
+            let state = if protocol.is_udp() {
+                // UDP reuses TCP_ESTABLISHED for connected sockets and TCP_CLOSE for the rest.
+                if state == 1 { "ESTAB" } else { "UNCONN" }
+            } else {
+                TCP_STATES.get(state.checked_sub(1)?).copied()?
+            };
+            let (tx_queue, rx_queue) = fields.get(4)?.split_once(':')?;
+            Some(Socket {
+                protocol,
+                local: Some(parse_address(fields.get(1)?)?),
+                remote: Some(parse_address(fields.get(2)?)?),
+                path: String::new(),
+                state,
+                uid: fields.get(7)?.parse().ok(),
+                inode: fields.get(9)?.parse().ok()?,
+                tx_queue: u64::from_str_radix(tx_queue, 16).unwrap_or_default(),
+                rx_queue: u64::from_str_radix(rx_queue, 16).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Parse `/proc/net/unix`: `Num RefCount Protocol Flags Type St Inode [Path]`.
+pub fn parse_unix(content: &str) -> Vec<Socket> {
+    const ACCEPTING: u32 = 0x10000;
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let flags = u32::from_str_radix(fields.get(3)?, 16).ok()?;
+            let state = match *fields.get(5)? {
+                _ if flags & ACCEPTING != 0 => "LISTEN",
+                "01" => "UNCONN",
+                "02" => "CONNECTING",
+                "03" => "ESTAB",
+                "04" => "DISCONNECTING",
+                _ => "UNKNOWN",
+            };
+            Some(Socket {
+                protocol: Protocol::Unix,
+                local: None,
+                remote: None,
+                path: fields.get(7).map(|_| after_fields(line, 7).to_string()).unwrap_or_default(),
+                state,
+                uid: None,
+                inode: fields.get(6)?.parse().ok()?,
+                tx_queue: 0,
+                rx_queue: 0,
+            })
+        })
+        .collect()
+}
+
+/// The rest of `line` after its first `count` whitespace-separated fields, inner spacing kept.
+fn after_fields(line: &str, count: usize) -> &str {
+    (0..count).fold(line.trim_start(), |rest, _| rest.trim_start_matches(|c: char| !c.is_whitespace()).trim_start())
+}
+
+/// Every socket in the given tables. Missing tables (no IPv6, say) are skipped.
+pub fn sockets(proc: &ProcFs, protocols: &[Protocol]) -> Vec<Socket> {
+    protocols
+        .iter()
+        .flat_map(|&protocol| {
+            let content = proc.read(format!("net/{}", protocol.name())).unwrap_or_default();
+            match protocol {
+                Protocol::Unix => parse_unix(&content),
+                _ => parse_inet(&content, protocol),
+            }
+        })
+        .collect()
+}
+
+/// Socket inode → PIDs holding it open, from every readable `/proc/<pid>/fd`. Without root,
+/// other users' processes are missing.
+pub fn socket_owners(proc: &ProcFs) -> HashMap<u64, Vec<u32>> {
+    let mut owners: HashMap<u64, Vec<u32>> = HashMap::new();
+    for pid in proc.pids() {
+        for fd in inspect::fds(proc, pid).unwrap_or_default() {
+            if let (FdKind::Socket, Some(inode)) = (fd.kind, fd.inode) {
+                let pids = owners.entry(inode).or_default();
+                if !pids.contains(&pid) {
+                    pids.push(pid);
+                }
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::fixture::{FakeProcess, Fixture};
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 23456 1 0000000000000000 100 0 0 10 0
+   1: 0F02000A:0016 6402000A:D4C3 01 00000024:00000000 01:00000020 00000000     0        0 34567 4 0000000000000000 20 4 29 10 -1
+   2: 0F02000A:9C40 22D8B85D:01BB 06 00000000:00000000 03:000016A8 00000000     0        0 0 3 0000000000000000
+";
+
+    const TCP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0277 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 18812 1 0000000000000000 100 0 0 10 0
+   1: B80D0120000000000000000001000000:0050 B80D01200000000000000000FE000000:C350 01 00000000:00000010 00:00000000 00000000    33        0 41230 1 0000000000000000 20 4 30 10 -1
+   2: 0000000000000000FFFF00000100007F:1F90 0000000000000000FFFF00000100007F:A0B2 01 00000000:00000000 00:00000000 00000000  1000        0 41288 1 0000000000000000 20 4 30 10 -1
+";
+
+    const UDP: &str = "   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  123: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 19283 2 0000000000000000 0
+  456: 0F02000A:E1A2 08080808:0035 01 00000000:00000300 00:00000000 00000000  1000        0 45678 2 0000000000000000 0
+";
+
+    const UNIX: &str = "Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 20341 /run/systemd/private
+0000000000000000: 00000003 00000000 00000000 0001 03 20399
+0000000000000000: 00000002 00000000 00010000 0001 01 30111 @/tmp/.X11-unix/X0
+0000000000000000: 00000002 00000000 00000000 0002 01 30222 /run/user/1000/my  app.sock
+0000000000000000: 00000002 00000000 00000000 0001 02 30333
+";
+
+    fn addr(text: &str) -> Option<SocketAddr> {
+        Some(text.parse().unwrap())
+    }
+
+    #[test]
+    fn addresses_are_host_order_words_and_hex_ports() {
+        assert_eq!(parse_address("0100007F:1F90"), addr("127.0.0.1:8080"));
+        assert_eq!(parse_address("0F02000A:0016"), addr("10.0.2.15:22"));
+        assert_eq!(parse_address("00000000000000000000000001000000:0277"), addr("[::1]:631"));
+        assert_eq!(parse_address("B80D01200000000000000000FE000000:C350"), addr("[2001:db8::fe]:50000"));
+        assert_eq!(parse_address("0000000000000000FFFF00000100007F:1F90"), addr("[::ffff:127.0.0.1]:8080"));
+        assert_eq!(parse_address("0100007F"), None);
+        assert_eq!(parse_address("0100007F:XYZ"), None);
+        assert_eq!(parse_address("0100007F0100:0016"), None);
+    }
+
+    #[test]
+    fn tcp_tables_keep_state_queues_owner_and_inode() {
+        let sockets = parse_inet(TCP, Protocol::Tcp);
+        assert_eq!(sockets.len(), 3);
+
+        let listener = &sockets[0];
+        assert_eq!((listener.local, listener.state, listener.uid, listener.inode), (addr("127.0.0.1:8080"), "LISTEN", Some(1000), 23456));
+        assert!(listener.listening());
+        assert_eq!(listener.remote_port(), None);
+        assert_eq!(listener.describe(), "tcp 127.0.0.1:8080 LISTEN");
+
+        let ssh = &sockets[1];
+        assert_eq!((ssh.remote, ssh.state, ssh.tx_queue, ssh.rx_queue), (addr("10.0.2.100:54467"), "ESTABLISHED", 36, 0));
+        assert!(!ssh.listening());
+        assert_eq!(ssh.describe(), "tcp 10.0.2.15:22 → 10.0.2.100:54467 ESTABLISHED");
+
+        assert_eq!((sockets[2].state, sockets[2].inode), ("TIME_WAIT", 0));
+    }
+
+    #[test]
+    fn tcp6_tables_parse_all_four_words() {
+        let sockets = parse_inet(TCP6, Protocol::Tcp6);
+        let summary: Vec<(Option<SocketAddr>, Option<SocketAddr>, &str)> = sockets.iter().map(|socket| (socket.local, socket.remote, socket.state)).collect();
+        assert_eq!(
+            summary,
+            [
+                (addr("[::1]:631"), addr("[::]:0"), "LISTEN"),
+                (addr("[2001:db8::1]:80"), addr("[2001:db8::fe]:50000"), "ESTABLISHED"),
+                (addr("[::ffff:127.0.0.1]:8080"), addr("[::ffff:127.0.0.1]:41138"), "ESTABLISHED"),
+            ]
+        );
+        assert_eq!(sockets[1].rx_queue, 16);
+        assert_eq!(sockets[1].uid, Some(33));
+    }
+
+    #[test]
+    fn udp_sockets_are_unconn_or_estab() {
+        let sockets = parse_inet(UDP, Protocol::Udp);
+        let states: Vec<(&str, bool)> = sockets.iter().map(|socket| (socket.state, socket.listening())).collect();
+        assert_eq!(states, [("UNCONN", true), ("ESTAB", false)]);
+        assert_eq!(sockets[0].local, addr("127.0.0.53:53"));
+        assert_eq!(sockets[1].remote, addr("8.8.8.8:53"));
+        assert_eq!(sockets[1].rx_queue, 0x300);
+        assert_eq!(sockets[0].describe(), "udp 127.0.0.53:53 UNCONN");
+    }
+
+    #[test]
+    fn unix_sockets_listen_by_flag_and_keep_paths_verbatim() {
+        let sockets = parse_unix(UNIX);
+        let summary: Vec<(&str, &str, u64)> = sockets.iter().map(|socket| (socket.state, socket.path.as_str(), socket.inode)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("LISTEN", "/run/systemd/private", 20341),
+                ("ESTAB", "", 20399),
+                ("LISTEN", "@/tmp/.X11-unix/X0", 30111),
+                ("UNCONN", "/run/user/1000/my  app.sock", 30222),
+                ("CONNECTING", "", 30333),
+            ]
+        );
+        assert!(sockets[0].listening() && !sockets[3].listening());
+        assert_eq!(sockets[1].describe(), "unix ESTAB");
+        assert_eq!(sockets[2].describe(), "unix @/tmp/.X11-unix/X0 LISTEN");
+    }
+
+    #[test]
+    fn sockets_read_the_requested_tables_and_skip_missing_ones() {
+        let fixture = Fixture::new();
+        fixture.file("net/tcp", TCP).file("net/udp", UDP).file("net/unix", UNIX);
+        let proc = fixture.proc();
+        assert_eq!(sockets(&proc, &Protocol::ALL).len(), 3 + 2 + 5);
+        assert_eq!(sockets(&proc, &[Protocol::Tcp6, Protocol::Udp6]), []);
+        assert!(sockets(&proc, &Protocol::parse_list("udp").unwrap()).iter().all(|socket| socket.protocol == Protocol::Udp));
+    }
+
+    #[test]
+    fn socket_owners_map_inodes_to_each_pid_once() {
+        use std::os::unix::fs::symlink;
+
+        let fixture = Fixture::new();
+        fixture
+            .process(FakeProcess { pid: 10, name: "sshd", ..Default::default() })
+            .process(FakeProcess { pid: 11, name: "sshd", ..Default::default() })
+            .process(FakeProcess { pid: 12, name: "cat", ..Default::default() });
+        let proc = fixture.proc();
+        for (fd, target) in [("10/fd/3", "socket:[34567]"), ("10/fd/4", "socket:[34567]"), ("11/fd/5", "socket:[34567]"), ("11/fd/6", "socket:[23456]"), ("12/fd/0", "/dev/null")] {
+            std::fs::create_dir_all(proc.path(fd).parent().unwrap()).unwrap();
+            symlink(target, proc.path(fd)).unwrap();
+        }
+
+        let owners = socket_owners(&proc);
+        assert_eq!(owners.len(), 2);
+        assert_eq!(owners[&34567], [10, 11]);
+        assert_eq!(owners[&23456], [11]);
+    }
+}
@@ -0,0 +1,85 @@
+//! Pressure stall information from `/proc/pressure/{cpu,memory,io}`: the share of time tasks
+//! were stalled waiting for a resource.
+
+use crate::procfs::ProcFs;
+
+pub const RESOURCES: [&str; 3] = ["cpu", "memory", "io"];
+
+/// One line of a pressure file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pressure {
+    pub resource: String,
+    /// `some`: at least one task stalled; `full`: every non-idle task stalled.
+    pub scope: String,
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    /// Total stall time in microseconds.
+    pub total_us: u64,
+}
+
+pub fn parse(resource: &str, content: &str) -> Vec<Pressure> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let scope = fields.next()?.to_string();
+            let mut pressure = Pressure { resource: resource.to_string(), scope, avg10: 0.0, avg60: 0.0, avg300: 0.0, total_us: 0 };
+            for field in fields {
+                let (key, value) = field.split_once('=')?;
+                match key {
+                    "avg10" => pressure.avg10 = value.parse().ok()?,
+                    "avg60" => pressure.avg60 = value.parse().ok()?,
+                    "avg300" => pressure.avg300 = value.parse().ok()?,
+                    "total" => pressure.total_us = value.parse().ok()?,
+                    _ => {}
+                }
+            }
+            Some(pressure)
+        })
+        .collect()
+}
+
+/// Every resource the kernel reports; empty when PSI is disabled.
+pub fn read(proc: &ProcFs) -> Vec<Pressure> {
+    RESOURCES
+        .iter()
+        .filter_map(|resource| Some(parse(resource, &proc.read(format!("pressure/{}", resource)).ok()?)))
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::fixture::Fixture;
+
+    #[test]
+    fn parses_some_and_full_lines() {
+        let lines = parse(
+            "memory",
+            "some avg10=1.50 avg60=0.75 avg300=0.10 total=123456\nfull avg10=0.00 avg60=0.00 avg300=0.00 total=42\n",
+        );
+        assert_eq!(
+            lines,
+            [
+                Pressure { resource: "memory".to_string(), scope: "some".to_string(), avg10: 1.5, avg60: 0.75, avg300: 0.1, total_us: 123456 },
+                Pressure { resource: "memory".to_string(), scope: "full".to_string(), avg10: 0.0, avg60: 0.0, avg300: 0.0, total_us: 42 },
+            ]
+        );
+        // Unparsable lines are dropped rather than reported as zero.
+        assert!(parse("io", "some avg10=high\nsome garbage\n").is_empty());
+    }
+
+    #[test]
+    fn reads_whichever_resources_exist() {
+        let fixture = Fixture::new();
+        assert!(read(&fixture.proc()).is_empty());
+        fixture
+            .file("pressure/cpu", "some avg10=5.00 avg60=4.00 avg300=3.00 total=1\n")
+            .file("pressure/io", "some avg10=0.00 avg60=0.00 avg300=0.00 total=2\nfull avg10=0.00 avg60=0.00 avg300=0.00 total=3\n");
+        let read = read(&fixture.proc());
+        let lines: Vec<(&str, &str, u64)> = read.iter().map(|p| (p.resource.as_str(), p.scope.as_str(), p.total_us)).collect();
+        assert_eq!(lines, [("cpu", "some", 1), ("io", "some", 2), ("io", "full", 3)]);
+    }
+}
@@ -0,0 +1,416 @@
+//! The process table: one typed `Process` per `/proc/<pid>`, plus sorting and filtering.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use chrono::{DateTime, Local, TimeZone};
+use regex::Regex;
+
+use crate::procfs::{self, ProcFs, TICKS_PER_SECOND};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Process {
+    pub pid: u32,
+    pub ppid: u32,
+    pub uid: u32,
+    /// User name, or the UID when it isn't in `/etc/passwd`.
+    pub user: String,
+    /// One-letter state as in `ps`: R, S, D, Z, T, t, X, I.
+    pub state: char,
+    /// Short name from `stat` (`comm`), at most 15 characters.
+    pub name: String,
+    /// Full command line, or `[name]` for kernel threads.
+    pub command: String,
+    /// User + system CPU time so far.
+    pub cpu_seconds: f64,
+    /// CPU time over the process's lifetime, as `ps` reports it.
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub threads: u32,
+    pub start_time: Option<DateTime<Local>>,
+    /// Run by the kernel itself (`kthreadd` and its workers), not a program.
+    pub kernel_thread: bool,
+}
+
+/// The fields of `/proc/<pid>/stat` gremlin uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stat {
+    pub name: String,
+    pub state: char,
+    pub ppid: u32,
+    /// `PF_*` flags of the task.
+    pub flags: u64,
+    pub utime: u64,
+    pub stime: u64,
+    pub threads: u32,
+    /// Clock ticks after boot.
+    pub start_ticks: u64,
+}
+
+/// Parse `/proc/<pid>/stat`. The name is in parentheses and may itself contain spaces and
+/// parentheses, so the fields are counted from the last `)`.
+pub fn parse_stat(content: &str) -> Option<Stat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let name = content.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = content.get(close + 1..)?.split_whitespace().collect();
+    Some(Stat {
+        name,
+        state: fields.first()?.chars().next()?,
+        ppid: fields.get(1)?.parse().ok()?,
+        flags: fields.get(6)?.parse().ok()?,
+        utime: fields.get(11)?.parse().ok()?,
+        stime: fields.get(12)?.parse().ok()?,
+        threads: fields.get(17)?.parse().ok()?,
+        start_ticks: fields.get(19)?.parse().ok()?,
+    })
+}
+
+/// `PF_KTHREAD` in the `stat` flags.
+const KERNEL_THREAD: u64 = 0x0020_0000;
+
+/// A `Key:  value` line of `/proc/<pid>/status`.
+pub fn status_field<'a>(status: &'a str, key: &str) -> Option<&'a str> {
+    status.lines().find_map(|line| line.strip_prefix(key)?.strip_prefix(':')).map(str::trim)
+}
+
+/// Times from `uptime` and `stat`, read once per listing.
+struct Clock {
+    uptime: f64,
+    boot_time: Option<i64>,
+}
+
+/// Read one process; `None` if it has exited or its files can't be read.
+fn read_with(proc: &ProcFs, pid: u32, clock: &Clock, users: &HashMap<u32, String>) -> Option<Process> {
+    let stat = parse_stat(&proc.read(format!("{}/stat", pid)).ok()?)?;
+    let status = proc.read(format!("{}/status", pid)).unwrap_or_default();
+    let cmdline = proc.read(format!("{}/cmdline", pid)).unwrap_or_default();
+
+    let uid: u32 = status_field(&status, "Uid")
+        .and_then(|uids| uids.split_whitespace().next()?.parse().ok())
+        .unwrap_or_default();
+    let rss_bytes = status_field(&status, "VmRSS")
+        .and_then(|rss| rss.trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map(|kib| kib * 1024)
+        .unwrap_or_default();
+    let command = cmdline.split('\0').filter(|arg| !arg.is_empty()).collect::<Vec<_>>().join(" ");
+    let command = if command.is_empty() { format!("[{}]", stat.name) } else { command };
+
+    let cpu_seconds = (stat.utime + stat.stime) as f64 / TICKS_PER_SECOND;
+    let started_after_boot = stat.start_ticks as f64 / TICKS_PER_SECOND;
+    let elapsed = clock.uptime - started_after_boot;
+    let cpu_percent = if elapsed > 0.0 { (cpu_seconds / elapsed * 1000.0).round() / 10.0 } else { 0.0 };
+    let start_time = clock
+        .boot_time
+        .and_then(|boot| Local.timestamp_opt(boot + started_after_boot as i64, 0).single());
+
+    Some(Process {
+        pid,
+        ppid: stat.ppid,
+        uid,
+        user: users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()),
+        state: stat.state,
+        name: stat.name,
+        command,
+        cpu_seconds,
+        cpu_percent,
+        rss_bytes,
+        threads: stat.threads,
+        start_time,
+        kernel_thread: stat.flags & KERNEL_THREAD != 0,
+    })
+}
+
+/// Every process, by PID. Processes that exit while being read are left out.
+pub fn list(proc: &ProcFs) -> Vec<Process> {
+    let clock = Clock { uptime: proc.uptime().unwrap_or_default(), boot_time: proc.boot_time() };
+    let users = procfs::users();
+    proc.pids().into_iter().filter_map(|pid| read_with(proc, pid, &clock, &users)).collect()
+}
+
+/// One process by PID; `None` if there is no such process.
+pub fn read(proc: &ProcFs, pid: u32) -> Option<Process> {
+    let clock = Clock { uptime: proc.uptime().unwrap_or_default(), boot_time: proc.boot_time() };
+    read_with(proc, pid, &clock, &procfs::users())
+}
+
+/// Column to sort the table by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Pid,
+    Ppid,
+    User,
+    State,
+    Cpu,
+    Mem,
+    Threads,
+    Start,
+    Command,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 9] = [
+        SortKey::Pid,
+        SortKey::Ppid,
+        SortKey::User,
+        SortKey::State,
+        SortKey::Cpu,
+        SortKey::Mem,
+        SortKey::Threads,
+        SortKey::Start,
+        SortKey::Command,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortKey::Pid => "pid",
+            SortKey::Ppid => "ppid",
+            SortKey::User => "user",
+            SortKey::State => "state",
+            SortKey::Cpu => "cpu",
+            SortKey::Mem => "mem",
+            SortKey::Threads => "threads",
+            SortKey::Start => "start",
+            SortKey::Command => "command",
+        }
+    }
+
+    /// Key by name; `rss` and `name` are accepted for `mem` and `command`.
+    pub fn from_name(name: &str) -> Option<SortKey> {
+        match name.trim().to_lowercase().as_str() {
+            "rss" | "memory" => Some(SortKey::Mem),
+            "name" | "cmd" => Some(SortKey::Command),
+            "time" => Some(SortKey::Start),
+            other => SortKey::ALL.into_iter().find(|key| key.name() == other),
+        }
+    }
+
+    /// Busiest first for the resource columns, ascending for the rest.
+    pub fn descending(&self) -> bool {
+        matches!(self, SortKey::Cpu | SortKey::Mem | SortKey::Threads)
+    }
+
+    fn compare(&self, a: &Process, b: &Process) -> Ordering {
+        match self {
+            SortKey::Pid => a.pid.cmp(&b.pid),
+            SortKey::Ppid => a.ppid.cmp(&b.ppid),
+            SortKey::User => a.user.cmp(&b.user),
+            SortKey::State => a.state.cmp(&b.state),
+            SortKey::Cpu => a.cpu_percent.total_cmp(&b.cpu_percent),
+            SortKey::Mem => a.rss_bytes.cmp(&b.rss_bytes),
+            SortKey::Threads => a.threads.cmp(&b.threads),
+            SortKey::Start => a.start_time.cmp(&b.start_time),
+            SortKey::Command => a.command.cmp(&b.command),
+        }
+    }
+}
+
+/// Sort in the key's natural direction (see `SortKey::descending`), or the opposite one.
+/// Ties keep PID order.
+pub fn sort(processes: &mut [Process], key: SortKey, reverse: bool) {
+    processes.sort_by(|a, b| {
+        let ordering = key.compare(a, b);
+        let ordering = if key.descending() != reverse { ordering.reverse() } else { ordering };
+        ordering.then(a.pid.cmp(&b.pid))
+    });
+}
+
+/// Which processes to keep; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// User name or UID.
+    pub user: Option<String>,
+    /// Matched against the name and the full command line.
+    pub name: Option<Regex>,
+    /// Any of these state letters, e.g. `DZ`.
+    pub states: Option<String>,
+    pub min_rss_bytes: u64,
+}
+
+impl Filter {
+    pub fn matches(&self, process: &Process) -> bool {
+        self.user.as_ref().is_none_or(|user| *user == process.user || *user == process.uid.to_string())
+            && self.name.as_ref().is_none_or(|name| name.is_match(&process.name) || name.is_match(&process.command))
+            && self.states.as_ref().is_none_or(|states| states.contains(process.state))
+            && process.rss_bytes >= self.min_rss_bytes
+    }
+}
+
+/// Filter, sort and cut a listing down to `top` entries.
+pub fn select(processes: Vec<Process>, filter: &Filter, key: SortKey, reverse: bool, top: Option<usize>) -> Vec<Process> {
+    let mut selected: Vec<Process> = processes.into_iter().filter(|process| filter.matches(process)).collect();
+    sort(&mut selected, key, reverse);
+    if let Some(top) = top {
+        selected.truncate(top);
+    }
+    selected
+}
+
+/// Which processes a signal goes to.
+#[derive(Debug, Clone)]
+pub enum Selector {
+    Pid(u32),
+    /// Exact short name (`comm`) or program name, like `pidof`.
+    Name(String),
+    /// User name or UID.
+    User(String),
+    /// Regex over the name and the full command line, like `pkill -f`.
+    Pattern(Regex),
+}
+
+impl Selector {
+    /// `by` is `pid`, `name`, `user` or `pattern`; empty or `auto` means a PID when `target` is
+    /// numeric and a name otherwise.
+    pub fn parse(target: &str, by: &str) -> Result<Selector, String> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Err("nothing to match".to_string());
+        }
+        match by.trim().to_lowercase().as_str() {
+            "" | "auto" if target.chars().all(|c| c.is_ascii_digit()) => Selector::parse(target, "pid"),
+            "" | "auto" | "name" if target.contains('/') => Err(format!("'{}' is a path; match a process name, or by=pattern", target)),
+            "" | "auto" | "name" => Ok(Selector::Name(target.to_string())),
+            "pid" => match target.parse() {
+                Ok(0) | Err(_) => Err(format!("'{}' is not a PID", target)),
+                Ok(pid) => Ok(Selector::Pid(pid)),
+            },
+            "user" if target.chars().any(|c| c.is_whitespace() || c == ':') => Err(format!("'{}' is not a user name or UID", target)),
+            "user" => Ok(Selector::User(target.to_string())),
+            "pattern" => Regex::new(target).map(Selector::Pattern).map_err(|e| format!("pattern '{}': {}", target, e)),
+            other => Err(format!("match by '{}': expected pid, name, user or pattern", other)),
+        }
+    }
+
+    pub fn matches(&self, process: &Process) -> bool {
+        match self {
+            Selector::Pid(pid) => process.pid == *pid,
+            Selector::Name(name) => {
+                let program = process.command.split_whitespace().next().unwrap_or_default();
+                process.name == *name || program.rsplit('/').next() == Some(name.as_str())
+            }
+            Selector::User(user) => process.user == *user || process.uid.to_string() == *user,
+            Selector::Pattern(pattern) => pattern.is_match(&process.name) || pattern.is_match(&process.command),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::fixture::{FakeProcess, Fixture, KTHREAD_FLAGS};
+
+    #[test]
+    fn selectors_are_checked_for_the_chosen_kind() {
+        assert!(matches!(Selector::parse("4242", ""), Ok(Selector::Pid(4242))));
+        assert!(matches!(Selector::parse("4242", "user"), Ok(Selector::User(user)) if user == "4242"));
+        assert!(matches!(Selector::parse("4242", "pattern"), Ok(Selector::Pattern(_))));
+        assert!(matches!(Selector::parse("nginx", "auto"), Ok(Selector::Name(name)) if name == "nginx"));
+        assert!(Selector::parse("0", "pid").is_err());
+        assert!(Selector::parse("nginx", "pid").is_err());
+        assert!(Selector::parse("/usr/sbin/nginx", "name").is_err());
+        assert!(Selector::parse("www data", "user").is_err());
+        assert!(Selector::parse("(", "pattern").is_err());
+        assert!(Selector::parse("nginx", "group").is_err());
+        assert!(Selector::parse(" ", "").is_err());
+    }
+
+    #[test]
+    fn kernel_threads_come_from_the_stat_flags() {
+        let fixture = Fixture::new();
+        fixture
+            .process(FakeProcess { pid: 2, name: "kthreadd", flags: KTHREAD_FLAGS, ..Default::default() })
+            .process(FakeProcess { pid: 7, ppid: 1, name: "sshd", cmdline: &["sshd"], ..Default::default() });
+        let kernel: Vec<(u32, bool, String)> = list(&fixture.proc()).into_iter().map(|process| (process.pid, process.kernel_thread, process.command)).collect();
+        assert_eq!(kernel, [(2, true, "[kthreadd]".to_string()), (7, false, "sshd".to_string())]);
+    }
+
+    #[test]
+    fn stat_names_may_contain_spaces_and_parentheses() {
+        let stat = parse_stat("4242 (tmux: server) (1)) R 1 4242 4242 0 -1 4194560 0 0 0 0 150 50 0 0 20 0 3 0 9000 0 0").unwrap();
+        assert_eq!(stat.name, "tmux: server) (1)");
+        assert_eq!((stat.state, stat.ppid, stat.utime, stat.stime, stat.threads, stat.start_ticks), ('R', 1, 150, 50, 3, 9000));
+        assert_eq!(parse_stat("12 (cut) S 1 2 3"), None);
+        assert_eq!(parse_stat("no parentheses"), None);
+    }
+
+    #[test]
+    fn status_fields_are_trimmed() {
+        let status = "Name:\tbash\nUid:\t1000\t1000\t1000\t1000\nVmRSS:\t   2048 kB\n";
+        assert_eq!(status_field(status, "VmRSS"), Some("2048 kB"));
+        assert_eq!(status_field(status, "Uid"), Some("1000\t1000\t1000\t1000"));
+        assert_eq!(status_field(status, "VmSwap"), None);
+    }
+
+    /// Five processes with distinct CPU, memory and states, two of them owned by UID 4242.
+    fn listing() -> (Fixture, Vec<Process>) {
+        let fixture = Fixture::new();
+        fixture
+            .file("uptime", "1000.00 4000.00\n")
+            .file("stat", "cpu 0 0 0 0\nbtime 1700000000\n")
+            .process(FakeProcess { pid: 1, name: "init", cmdline: &["/sbin/init"], utime: 1000, rss_kib: 8000, ..Default::default() })
+            .process(FakeProcess { pid: 20, ppid: 1, name: "my daemon (v2)", uid: 4242, cmdline: &["/opt/daemon", "--serve"], utime: 50000, rss_kib: 1000, start_ticks: 50000, threads: 8, ..Default::default() })
+            .process(FakeProcess { pid: 30, ppid: 1, name: "postgres", uid: 4242, state: 'D', cmdline: &["postgres", "-D", "/var/lib/pg"], utime: 100, rss_kib: 900_000, ..Default::default() })
+            .process(FakeProcess { pid: 40, ppid: 1, name: "zombie", state: 'Z', ..Default::default() })
+            .process(FakeProcess { pid: 50, ppid: 1, name: "bash", state: 'R', cmdline: &["-bash"], utime: 10, rss_kib: 4000, start_ticks: 90000, ..Default::default() });
+        let processes = list(&fixture.proc());
+        (fixture, processes)
+    }
+
+    fn pids(processes: &[Process]) -> Vec<u32> {
+        processes.iter().map(|process| process.pid).collect()
+    }
+
+    #[test]
+    fn lists_processes_from_a_fixture() {
+        let (fixture, processes) = listing();
+        assert_eq!(pids(&processes), [1, 20, 30, 40, 50]);
+        let daemon = &processes[1];
+        assert_eq!(daemon.name, "my daemon (v2)");
+        assert_eq!(daemon.command, "/opt/daemon --serve");
+        assert_eq!(daemon.user, "4242");
+        assert_eq!(daemon.rss_bytes, 1000 * 1024);
+        assert_eq!(daemon.cpu_seconds, 500.0);
+        // 500 CPU seconds over the 500 seconds since it started at 500s after boot.
+        assert_eq!(daemon.cpu_percent, 100.0);
+        assert_eq!(daemon.start_time.map(|time| time.timestamp()), Some(1_700_000_500));
+        assert_eq!(processes[3].command, "[zombie]");
+        assert_eq!(read(&fixture.proc(), 99), None);
+    }
+
+    #[test]
+    fn sorts_in_each_keys_natural_direction() {
+        let (_fixture, processes) = listing();
+        let sorted = |key: SortKey, reverse: bool| {
+            let mut processes = processes.clone();
+            sort(&mut processes, key, reverse);
+            pids(&processes)
+        };
+        assert_eq!(sorted(SortKey::Cpu, false), [20, 1, 30, 50, 40]);
+        assert_eq!(sorted(SortKey::Mem, false), [30, 1, 50, 20, 40]);
+        assert_eq!(sorted(SortKey::Mem, true), [40, 20, 50, 1, 30]);
+        assert_eq!(sorted(SortKey::Pid, true), [50, 40, 30, 20, 1]);
+        assert_eq!(sorted(SortKey::State, false), [30, 50, 1, 20, 40]);
+        // "4242" has no passwd entry and sorts before "root"; ties keep PID order.
+        assert_eq!(sorted(SortKey::User, false), [20, 30, 1, 40, 50]);
+    }
+
+    #[test]
+    fn filters_and_cuts_to_top_n() {
+        let (_fixture, processes) = listing();
+        let select = |filter: Filter, top: Option<usize>| pids(&super::select(processes.clone(), &filter, SortKey::Cpu, false, top));
+        assert_eq!(select(Filter::default(), Some(2)), [20, 1]);
+        assert_eq!(select(Filter { user: Some("4242".to_string()), ..Default::default() }, None), [20, 30]);
+        assert_eq!(select(Filter { name: Some(Regex::new("^/opt|^post").unwrap()), ..Default::default() }, None), [20, 30]);
+        assert_eq!(select(Filter { name: Some(Regex::new(r"\(v2\)").unwrap()), ..Default::default() }, None), [20]);
+        assert_eq!(select(Filter { states: Some("DZ".to_string()), ..Default::default() }, None), [30, 40]);
+        assert_eq!(select(Filter { min_rss_bytes: 4000 * 1024, ..Default::default() }, Some(10)), [1, 30, 50]);
+        assert!(select(Filter { user: Some("nobody-here".to_string()), ..Default::default() }, None).is_empty());
+    }
+
+    #[test]
+    fn sort_keys_by_name() {
+        assert_eq!(SortKey::from_name("RSS"), Some(SortKey::Mem));
+        assert_eq!(SortKey::from_name(" cmd "), Some(SortKey::Command));
+        assert_eq!(SortKey::from_name("threads"), Some(SortKey::Threads));
+        assert_eq!(SortKey::from_name("size"), None);
+    }
+}
@@ -0,0 +1,114 @@
+//! The process tree, built from the parent PID of every process.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::procfs::process::Process;
+
+/// Parent/child links over a process listing. Processes whose parent isn't in the listing
+/// (PID 1, `kthreadd`, or orphans of a partial listing) are roots.
+pub struct Tree<'a> {
+    processes: HashMap<u32, &'a Process>,
+    children: HashMap<u32, Vec<u32>>,
+    roots: Vec<u32>,
+}
+
+/// CPU and memory of a process and everything below it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Totals {
+    pub processes: usize,
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+}
+
+/// One line of a drawn tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub pid: u32,
+    pub depth: usize,
+    /// Box-drawing lines leading up to the process, e.g. `│  ├─ `.
+    pub prefix: String,
+    /// Descendants left out because this node is folded.
+    pub hidden: usize,
+}
+
+impl<'a> Tree<'a> {
+    pub fn new(processes: &'a [Process]) -> Self {
+        let by_pid: HashMap<u32, &Process> = processes.iter().map(|process| (process.pid, process)).collect();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        let mut roots = Vec::new();
+        for process in processes {
+            if process.ppid != process.pid && by_pid.contains_key(&process.ppid) {
+                children.entry(process.ppid).or_default().push(process.pid);
+            } else {
+                roots.push(process.pid);
+            }
+        }
+        for pids in children.values_mut() {
+            pids.sort_unstable();
+        }
+        roots.sort_unstable();
+        Self { processes: by_pid, children, roots }
+    }
+
+    pub fn get(&self, pid: u32) -> Option<&'a Process> {
+        self.processes.get(&pid).copied()
+    }
+
+    pub fn roots(&self) -> &[u32] {
+        &self.roots
+    }
+
+    pub fn children(&self, pid: u32) -> &[u32] {
+        self.children.get(&pid).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// `pid` and everything below it, each parent before its children.
+    pub fn subtree(&self, pid: u32) -> Vec<u32> {
+        let mut pids = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack = vec![pid];
+        while let Some(pid) = stack.pop() {
+            if self.processes.contains_key(&pid) && seen.insert(pid) {
+                pids.push(pid);
+                stack.extend(self.children(pid).iter().rev());
+            }
+        }
+        pids
+    }
+
+    pub fn totals(&self, pid: u32) -> Totals {
+        self.subtree(pid).into_iter().filter_map(|pid| self.get(pid)).fold(Totals::default(), |totals, process| Totals {
+            processes: totals.processes + 1,
+            cpu_percent: totals.cpu_percent + process.cpu_percent,
+            rss_bytes: totals.rss_bytes + process.rss_bytes,
+        })
+    }
+
+    /// The trees under `roots`, one node per line. Processes in `folded` and those at
+    /// `max_depth` are shown without their descendants.
+    pub fn draw(&self, roots: &[u32], folded: &HashSet<u32>, max_depth: Option<usize>) -> Vec<Node> {
+        let fold = |pid: u32, depth: usize| folded.contains(&pid) || max_depth.is_some_and(|max| depth >= max);
+        let mut nodes = Vec::new();
+        for &root in roots {
+            self.draw_from(root, 0, (String::new(), String::new()), &fold, &mut nodes);
+        }
+        nodes
+    }
+
+    /// `prefix` goes in front of this node, `indent` in front of its children's branches.
+    fn draw_from(&self, pid: u32, depth: usize, (prefix, indent): (String, String), fold: &dyn Fn(u32, usize) -> bool, nodes: &mut Vec<Node>) {
+        let children = self.children(pid);
+        let folded = fold(pid, depth);
+        let hidden = if folded { self.subtree(pid).len() - 1 } else { 0 };
+        nodes.push(Node { pid, depth, prefix, hidden });
+        if folded {
+            return;
+        }
+        for (i, &child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let branch = format!("{}{}", indent, if last { "└─ " } else { "├─ " });
+            let below = format!("{}{}", indent, if last { "   " } else { "│  " });
+            self.draw_from(child, depth + 1, (branch, below), fold, nodes);
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use std::io::{self, Write};
 use std::fs;
-use std::process::Command;
 use colored::*;
+use crate::config;
+use crate::exec::Invocation;
 use crate::script_runner::header;
 
 #[allow(dead_code)]
@@ -11,11 +12,10 @@ fn load_ascii(path: &str) -> String {
 
 #[allow(dead_code)]
 fn ensure_script_dirs() -> Result<(), String> {
-    let base_path = "/home/klea/Documents/Scripts/";
     let dirs = ["Python", "Rust", "Bash", "Go", "Lua", "PS1"];
 
     for dir in dirs.iter() {
-        let path = format!("{}{}", base_path, dir);
+        let path = config::get().script_path(dir);
         fs::create_dir_all(&path)
             .map_err(|e| format!("Failed to create directory {}: {}", dir, e))?;
     }
@@ -25,20 +25,22 @@ fn ensure_script_dirs() -> Result<(), String> {
 
 fn setup_go_project(script_path: &str, script_name: &str) -> Result<(), String> {
     // Initialize Go module
-    Command::new("go")
+    Invocation::new("go")
         .arg("mod")
         .arg("init")
         .arg(script_name)
         .current_dir(script_path)
-        .status()
+        .attached()
+        .run()
         .map_err(|e| format!("Failed to initialize Go module: {}", e))?;
 
     // Get dependencies
-    Command::new("go")
+    Invocation::new("go")
         .arg("mod")
         .arg("tidy")
         .current_dir(script_path)
-        .status()
+        .attached()
+        .run()
         .map_err(|e| format!("Failed to get Go dependencies: {}", e))?;
 
     Ok(())
@@ -46,12 +48,13 @@ fn setup_go_project(script_path: &str, script_name: &str) -> Result<(), String>
 
 fn setup_rust_project(script_path: &str, script_name: &str) -> Result<(), String> {
     // Initialize Cargo project
-    Command::new("cargo")
+    Invocation::new("cargo")
         .arg("init")
         .arg("--bin")
         .arg(script_name)
         .current_dir(script_path)
-        .status()
+        .attached()
+        .run()
         .map_err(|e| format!("Failed to initialize Rust project: {}", e))?;
 
     Ok(())
@@ -108,16 +111,16 @@ pub fn run() {
                 } else {
                     format!("{}.{}", script_name, extension)
                 };
-                let full_path = format!("/home/klea/Documents/Scripts/{}/{}", dir, script_file);
+                let full_path = config::get().script_path(&format!("{}/{}", dir, script_file));
 
                 // Setup project for Go and Rust
                 if language == "Go" {
-                    if let Err(e) = setup_go_project(&format!("/home/klea/Documents/Scripts/{}", dir), script_name) {
+                    if let Err(e) = setup_go_project(&config::get().script_path(dir), script_name) {
                         println!("{} {}", "Error:".bright_red(), e);
                         continue;
                     }
                 } else if language == "Rust" {
-                    if let Err(e) = setup_rust_project(&format!("/home/klea/Documents/Scripts/{}", dir), script_name) {
+                    if let Err(e) = setup_rust_project(&config::get().script_path(dir), script_name) {
                         println!("{} {}", "Error:".bright_red(), e);
                         continue;
                     }
@@ -130,10 +133,11 @@ pub fn run() {
                     .map_err(|e| format!("Failed to create script: {}", e))
                     .and_then(|_| {
                         if dir == "Bash" {
-                            Command::new("chmod")
+                            Invocation::new("chmod")
                                 .arg("+x")
                                 .arg(&full_path)
-                                .status()
+                                .attached()
+                                .run()
                                 .map_err(|e| format!("Failed to make script executable: {}", e))?;
                         }
                         Ok(())
@@ -1,8 +1,12 @@
 use chrono::Local;
 
+use crate::config;
+
 #[allow(dead_code)]
 pub fn generate_header(filename: &str, script_type: &str) -> String {
     let date = Local::now().format("%Y-%m-%d").to_string();
+    let author = config::get().author_line();
+    let license = &config::get().author.license;
     
     let header_template = match script_type {
         "Python" => format!(r#"#!/usr/bin/env python3
@@ -12,74 +16,74 @@ pub fn generate_header(filename: &str, script_type: &str) -> String {
 # 🗓️   Created     : {}
 # 🕒  Last Update : {} 
 # 🧬  Version     : 0.1.0
-# ✍️   Author      : KleaSCM — https://github.com/KleaSCM
+# ✍️   Author      : {author}
 # 📝  Description : <Short summary of what this script does>
-# 🔒  License     : MIT
+# 🔒  License     : {license}
 # 💡  Usage       : python3 {}
 # 📦  Dependencies: <list of Python packages>
 
-"#, filename, date, date, filename),
+"#, filename, date, date, filename, author = author, license = license),
         "Rust" => format!(r#"// 📄  File        : {}
 // 🗓️   Created     : {}
 // 🕒  Last Update : {} 
 // 🧬  Version     : 0.1.0
-// ✍️   Author      : KleaSCM — https://github.com/KleaSCM
+// ✍️   Author      : {author}
 // 📝  Description : <Short summary of what this script does>
-// 🔒  License     : MIT
+// 🔒  License     : {license}
 // 💡  Usage       : cargo run
 // 📦  Dependencies: <list of Rust crates>
 
-"#, filename, date, date),
+"#, filename, date, date, author = author, license = license),
         "Bash" => format!(r#"#!/bin/bash
 
 # 📄  File        : {}
 # 🗓️   Created     : {}
 # 🕒  Last Update : {} 
 # 🧬  Version     : 0.1.0
-# ✍️   Author      : KleaSCM — https://github.com/KleaSCM
+# ✍️   Author      : {author}
 # 📝  Description : <Short summary of what this script does>
-# 🔒  License     : MIT
+# 🔒  License     : {license}
 # 💡  Usage       : bash {}
 # 📦  Dependencies: <list of CLI tools>
 
-"#, filename, date, date, filename),
+"#, filename, date, date, filename, author = author, license = license),
         "Go" => format!(r#"// 📄  File        : {}
 // 🗓️   Created     : {}
 // 🕒  Last Update : {} 
 // 🧬  Version     : 0.1.0
-// ✍️   Author      : KleaSCM — https://github.com/KleaSCM
+// ✍️   Author      : {author}
 // 📝  Description : <Short summary of what this script does>
-// 🔒  License     : MIT
+// 🔒  License     : {license}
 // 💡  Usage       : go run {}
 // 📦  Dependencies: <list of Go packages>
 
-"#, filename, date, date, filename),
+"#, filename, date, date, filename, author = author, license = license),
         "Lua" => format!(r#"#!/usr/bin/env lua
 
 -- 📄  File        : {}
 -- 🗓️   Created     : {}
 -- 🕒  Last Update : {} 
 -- 🧬  Version     : 0.1.0
--- ✍️   Author      : KleaSCM — https://github.com/KleaSCM
+-- ✍️   Author      : {author}
 -- 📝  Description : <Short summary of what this script does>
--- 🔒  License     : MIT
+-- 🔒  License     : {license}
 -- 💡  Usage       : lua {}
 -- 📦  Dependencies: <list of Lua modules>
 
-"#, filename, date, date, filename),
+"#, filename, date, date, filename, author = author, license = license),
         "PS1" => format!(r#"<#
     📄  File        : {}
     🗓️   Created     : {}
     🕒  Last Update : {} 
     🧬  Version     : 0.1.0
-    ✍️   Author      : KleaSCM — https://github.com/KleaSCM
+    ✍️   Author      : {author}
     📝  Description : <Short summary of what this script does>
-    🔒  License     : MIT
+    🔒  License     : {license}
     💡  Usage       : pwsh {}
     📦  Dependencies: <list of PowerShell modules>
 #>
 
-"#, filename, date, date, filename),
+"#, filename, date, date, filename, author = author, license = license),
         _ => unreachable!(),
     };
 
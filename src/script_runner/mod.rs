@@ -2,7 +2,7 @@ use std::io::{self, Write};
 use std::fs;
 use colored::*;
 
-mod run_script;
+pub mod run_script;
 mod create_script;
 mod search;
 mod header;
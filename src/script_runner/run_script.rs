@@ -1,15 +1,21 @@
 use std::io::{self, Write};
 use std::fs;
-use std::process::Command;
-use std::path::Path;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 use colored::*;
 
+use crate::audit;
+use crate::config;
+use crate::exec::{self, Completed, Executor, Invocation};
+
 fn load_ascii(path: &str) -> String {
     fs::read_to_string(path).unwrap_or_else(|_| "✨ [ASCII missing] ✨".to_string())
 }
 
 fn list_scripts(dir: &str) -> Vec<String> {
-    let path = format!("/home/klea/Documents/Scripts/{}", dir);
+    let path = config::get().script_path(dir);
     let mut scripts = Vec::new();
     
     fn walk_dir(path: &Path, scripts: &mut Vec<String>) {
@@ -31,6 +37,106 @@ fn list_scripts(dir: &str) -> Vec<String> {
     scripts
 }
 
+/// Run a script given as `<Type>/<file>` relative to the scripts root, e.g. `Bash/backup.sh`.
+/// The interpreter is picked from the type directory, the same way the menu does it.
+/// Every run is recorded in the audit log.
+pub fn run_script_file(script: &str, args: &[String]) -> Result<Completed, String> {
+    let result = run_script_with(&*exec::current(), script, args);
+
+    let entry = audit::Entry::new(audit::Kind::Script, script, args.to_vec());
+    let entry = match &result {
+        Ok((completed, argv)) => audit::Entry {
+            argv: argv.clone(),
+            exit_code: if completed.dry_run { 0 } else { completed.status.unwrap_or(1) },
+            dry_run: completed.dry_run,
+            ..entry
+        }
+        .with_duration(completed.duration),
+        Err(e) => audit::Entry { exit_code: 1, error: Some(e.clone()), ..entry },
+    };
+    audit::record(&entry);
+    result.map(|(completed, _)| completed)
+}
+
+/// `run_script_file` with an explicit executor. Also returns the command line that ran.
+pub fn run_script_with(exec: &dyn Executor, script: &str, args: &[String]) -> Result<(Completed, Vec<String>), String> {
+    let script_path = config::get().script_path(script);
+    // Lives until the compiled script has finished, then takes the binary with it.
+    let build_dir = if script.starts_with("Rust/") {
+        Some(BuildDir::create().map_err(|e| format!("Failed to create a build directory: {}", e))?)
+    } else {
+        None
+    };
+    let invocation = script_invocation(script, args, build_dir.as_ref().map(|dir| dir.0.as_path()))?;
+    if !Path::new(&script_path).is_file() {
+        return Err(format!("Script not found: {}", script_path));
+    }
+
+    if build_dir.is_some() {
+        let compiled = exec.run(&Invocation::new("rustc").arg(&script_path).arg("-o").arg(&invocation.program).attached())
+            .map_err(|e| format!("Failed to run rustc: {}", e))?;
+        if !compiled.success() {
+            return Ok((compiled, invocation.argv()));
+        }
+    }
+    let completed = exec.run(&invocation.clone().attached()).map_err(|e| e.to_string())?;
+    Ok((completed, invocation.argv()))
+}
+
+/// What runs a script: its interpreter, or for Rust the binary `rustc` compiles it to in
+/// `build_dir`.
+fn script_invocation(script: &str, args: &[String], build_dir: Option<&Path>) -> Result<Invocation, String> {
+    let dir = script.split('/').next().unwrap_or_default();
+    let script_path = config::get().script_path(script);
+
+    let invocation = match dir {
+        "Python" => Invocation::new("python3").arg(&script_path),
+        "Rust" => {
+            let stem = Path::new(&script_path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "main".to_string());
+            let build_dir = build_dir.ok_or_else(|| "Rust scripts need a build directory".to_string())?;
+            Invocation::new(build_dir.join(stem).to_string_lossy())
+        },
+        "Bash" => Invocation::new("bash").arg(&script_path),
+        "Go" => Invocation::new("go").arg("run").arg(&script_path),
+        "Lua" => Invocation::new("lua").arg(&script_path),
+        "PS1" => Invocation::new("pwsh").arg(&script_path),
+        _ => return Err(format!("Unknown script type '{}' (expected Python, Rust, Bash, Go, Lua or PS1)", dir)),
+    };
+    Ok(invocation.args(args.iter().cloned()))
+}
+
+/// A private directory for one compiled Rust script, removed when dropped. It gets a fresh
+/// name and mode 0700, so other users can't plant or swap the binary between compile and run,
+/// and two runs of same-named scripts don't overwrite each other.
+struct BuildDir(PathBuf);
+
+impl BuildDir {
+    fn create() -> io::Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let mut attempts = 0;
+        loop {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+            let name = format!("gremlin-script-{}-{}-{}", std::process::id(), nanos, NEXT.fetch_add(1, Ordering::Relaxed));
+            let path = std::env::temp_dir().join(name);
+            // `create` refuses an existing path, so a directory someone else made is never used.
+            match fs::DirBuilder::new().mode(0o700).create(&path) {
+                Ok(()) => return Ok(Self(path)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempts < 100 => attempts += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for BuildDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
 pub fn run() {
     let splash_art = load_ascii("ascii/networkingGirl.txt");
     println!("{}", splash_art.bright_magenta());
@@ -85,26 +191,8 @@ pub fn run() {
                         continue;
                     }
                     if num > 0 && num <= scripts.len() {
-                        let script_path = format!("/home/klea/Documents/Scripts/{}/{}", dir, scripts[num - 1]);
-                        
-                        let status = match dir {
-                            "Python" => Command::new("python3").arg(&script_path).status(),
-                            "Rust" => {
-                                let compile_status = Command::new("rustc").arg(&script_path).status();
-                                if compile_status.is_ok() {
-                                    Command::new("./main").status()
-                                } else {
-                                    compile_status
-                                }
-                            },
-                            "Bash" => Command::new("bash").arg(&script_path).status(),
-                            "Go" => Command::new("go").arg("run").arg(&script_path).status(),
-                            "Lua" => Command::new("lua").arg(&script_path).status(),
-                            "PS1" => Command::new("pwsh").arg(&script_path).status(),
-                            _ => unreachable!(),
-                        };
-
-                        if let Err(e) = status {
+                        let script = format!("{}/{}", dir, scripts[num - 1]);
+                        if let Err(e) = run_script_file(&script, &[]) {
                             println!("{} {}", "Error running script:".bright_red(), e);
                         }
                     } else {
@@ -118,4 +206,26 @@ pub fn run() {
             _ => println!("{}", "⚠️ Invalid choice. Try again.".bright_red()),
         }
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn rust_scripts_build_in_a_private_directory_that_is_removed() {
+        let first = BuildDir::create().unwrap();
+        let second = BuildDir::create().unwrap();
+        assert_ne!(first.0, second.0);
+        assert_eq!(fs::metadata(&first.0).unwrap().permissions().mode() & 0o777, 0o700);
+
+        let invocation = script_invocation("Rust/hello.rs", &["x".to_string()], Some(&first.0)).unwrap();
+        assert_eq!(invocation.argv(), [first.0.join("hello").display().to_string(), "x".to_string()]);
+        assert!(script_invocation("Rust/hello.rs", &[], None).is_err());
+
+        let path = first.0.clone();
+        fs::write(path.join("hello"), "binary").unwrap();
+        drop(first);
+        assert!(!path.exists());
+    }
+}
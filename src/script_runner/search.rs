@@ -1,15 +1,17 @@
 use std::io::{self, Write};
 use std::fs;
-use std::process::Command;
 use std::path::Path;
 use colored::*;
 
+use crate::config;
+use crate::exec::Invocation;
+
 fn load_ascii(path: &str) -> String {
     fs::read_to_string(path).unwrap_or_else(|_| "✨ [ASCII missing] ✨".to_string())
 }
 
 fn list_scripts(dir: &str) -> Vec<String> {
-    let path = format!("/home/klea/Documents/Scripts/{}", dir);
+    let path = config::get().script_path(dir);
     let mut scripts = Vec::new();
     
     fn walk_dir(path: &Path, scripts: &mut Vec<String>) {
@@ -97,10 +99,11 @@ pub fn run() {
                     }
                     if num > 0 && num <= all_scripts.len() {
                         let (dir, script) = &all_scripts[num - 1];
-                        let script_path = format!("/home/klea/Documents/Scripts/{}/{}", dir, script);
-                        Command::new("xdg-open")
+                        let script_path = config::get().script_path(&format!("{}/{}", dir, script));
+                        Invocation::new("xdg-open")
                             .arg(&script_path)
-                            .status()
+                            .attached()
+                            .run()
                             .unwrap_or_else(|_| {
                                 println!("{}", "Failed to open script. Try using your default editor.".bright_red());
                                 std::process::exit(1);
@@ -0,0 +1,281 @@
+//! POSIX signals by name, sending them to processes directly rather than through `kill`, and
+//! watching `/proc` to see which processes exited.
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::procfs::process;
+use crate::procfs::ProcFs;
+
+/// How long to watch for exits after a signal when not escalating.
+const SETTLE: Duration = Duration::from_millis(300);
+/// How long to watch for exits after the final KILL.
+const KILL_WAIT: Duration = Duration::from_secs(1);
+const POLL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signal {
+    /// Name without the `SIG` prefix, e.g. `TERM`.
+    pub name: &'static str,
+    pub number: i32,
+}
+
+/// Every signal gremlin can send, in number order.
+pub const SIGNALS: &[Signal] = &[
+    Signal { name: "HUP", number: libc::SIGHUP },
+    Signal { name: "INT", number: libc::SIGINT },
+    Signal { name: "QUIT", number: libc::SIGQUIT },
+    Signal { name: "ILL", number: libc::SIGILL },
+    Signal { name: "TRAP", number: libc::SIGTRAP },
+    Signal { name: "ABRT", number: libc::SIGABRT },
+    Signal { name: "BUS", number: libc::SIGBUS },
+    Signal { name: "FPE", number: libc::SIGFPE },
+    Signal { name: "KILL", number: libc::SIGKILL },
+    Signal { name: "USR1", number: libc::SIGUSR1 },
+    Signal { name: "SEGV", number: libc::SIGSEGV },
+    Signal { name: "USR2", number: libc::SIGUSR2 },
+    Signal { name: "PIPE", number: libc::SIGPIPE },
+    Signal { name: "ALRM", number: libc::SIGALRM },
+    Signal { name: "TERM", number: libc::SIGTERM },
+    Signal { name: "CHLD", number: libc::SIGCHLD },
+    Signal { name: "CONT", number: libc::SIGCONT },
+    Signal { name: "STOP", number: libc::SIGSTOP },
+    Signal { name: "TSTP", number: libc::SIGTSTP },
+    Signal { name: "TTIN", number: libc::SIGTTIN },
+    Signal { name: "TTOU", number: libc::SIGTTOU },
+    Signal { name: "URG", number: libc::SIGURG },
+    Signal { name: "XCPU", number: libc::SIGXCPU },
+    Signal { name: "XFSZ", number: libc::SIGXFSZ },
+    Signal { name: "VTALRM", number: libc::SIGVTALRM },
+    Signal { name: "PROF", number: libc::SIGPROF },
+    Signal { name: "WINCH", number: libc::SIGWINCH },
+    Signal { name: "IO", number: libc::SIGIO },
+    Signal { name: "PWR", number: libc::SIGPWR },
+    Signal { name: "SYS", number: libc::SIGSYS },
+];
+
+pub const TERM: Signal = Signal { name: "TERM", number: libc::SIGTERM };
+pub const KILL: Signal = Signal { name: "KILL", number: libc::SIGKILL };
+
+impl Signal {
+    /// Look a signal up as `TERM`, `SIGTERM`, `term` or `15`.
+    pub fn from_name(name: &str) -> Option<Signal> {
+        let name = name.trim().to_uppercase();
+        if let Ok(number) = name.parse::<i32>() {
+            return SIGNALS.iter().copied().find(|signal| signal.number == number);
+        }
+        let name = name.strip_prefix("SIG").unwrap_or(&name);
+        SIGNALS.iter().copied().find(|signal| signal.name == name)
+    }
+
+    /// Send the signal to `pid`. Fails with `PermissionDenied` for other users' processes and
+    /// `NotFound` when the process is gone.
+    pub fn send(&self, pid: u32) -> io::Result<()> {
+        let pid = i32::try_from(pid).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PID out of range"))?;
+        // SAFETY: kill(2) only reads its arguments.
+        if unsafe { libc::kill(pid, self.number) } == 0 {
+            return Ok(());
+        }
+        let error = io::Error::last_os_error();
+        match error.raw_os_error() {
+            Some(libc::ESRCH) => Err(io::Error::new(io::ErrorKind::NotFound, format!("no process {}", pid))),
+            _ => Err(error),
+        }
+    }
+}
+
+/// What became of a signalled process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fate {
+    Exited,
+    Running,
+    /// Stopped (`T`), e.g. after STOP.
+    Stopped,
+    /// The signal couldn't be sent.
+    Failed(String),
+}
+
+impl Fate {
+    pub fn describe(&self) -> String {
+        match self {
+            Fate::Exited => "exited".to_string(),
+            Fate::Running => "still running".to_string(),
+            Fate::Stopped => "stopped".to_string(),
+            Fate::Failed(error) => error.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub pid: u32,
+    /// Signals delivered, in order.
+    pub sent: Vec<Signal>,
+    pub fate: Fate,
+}
+
+/// A process we signalled, told apart from a later one that reuses its PID by its start time.
+struct Watched {
+    outcome: Outcome,
+    start_ticks: Option<u64>,
+}
+
+impl Watched {
+    /// State letter while the same process is still around; `None` once it exited or was reaped.
+    fn state(&self, proc: &ProcFs) -> Option<char> {
+        let stat = process::parse_stat(&proc.read(format!("{}/stat", self.outcome.pid)).ok()?)?;
+        (Some(stat.start_ticks) == self.start_ticks && !matches!(stat.state, 'Z' | 'X')).then_some(stat.state)
+    }
+
+    /// Signalled successfully and neither exited nor stopped yet.
+    fn running(&self, proc: &ProcFs) -> bool {
+        self.outcome.fate == Fate::Running && self.state(proc).is_some_and(|state| !matches!(state, 'T' | 't'))
+    }
+
+    fn send(&mut self, signal: Signal) {
+        match signal.send(self.outcome.pid) {
+            Ok(()) => self.outcome.sent.push(signal),
+            Err(e) => self.outcome.fate = Fate::Failed(e.to_string()),
+        }
+    }
+}
+
+/// Send `signal` to each PID, then watch `/proc` until they exit. With `escalate`, whatever is
+/// still running after that long gets KILL.
+pub fn deliver(proc: &ProcFs, pids: &[u32], signal: Signal, escalate: Option<Duration>) -> Vec<Outcome> {
+    let mut watched: Vec<Watched> = pids
+        .iter()
+        .map(|&pid| Watched {
+            outcome: Outcome { pid, sent: Vec::new(), fate: Fate::Running },
+            start_ticks: proc
+                .read(format!("{}/stat", pid))
+                .ok()
+                .and_then(|stat| process::parse_stat(&stat))
+                .map(|stat| stat.start_ticks),
+        })
+        .collect();
+
+    for watched in &mut watched {
+        watched.send(signal);
+    }
+    wait_for_exit(proc, &watched, escalate.unwrap_or(SETTLE));
+    if escalate.is_some() && signal != KILL {
+        // Stopped processes count as survivors here: TERM stays pending until they continue.
+        let mut escalated = false;
+        for watched in watched.iter_mut().filter(|watched| watched.outcome.fate == Fate::Running && watched.state(proc).is_some()) {
+            watched.send(KILL);
+            escalated = true;
+        }
+        if escalated {
+            wait_for_exit(proc, &watched, KILL_WAIT);
+        }
+    }
+
+    watched
+        .into_iter()
+        .map(|watched| {
+            let fate = match (&watched.outcome.fate, watched.state(proc)) {
+                (Fate::Failed(_), _) => watched.outcome.fate.clone(),
+                (_, None) => Fate::Exited,
+                (_, Some('T' | 't')) => Fate::Stopped,
+                (_, Some(_)) => Fate::Running,
+            };
+            Outcome { fate, ..watched.outcome }
+        })
+        .collect()
+}
+
+/// Poll until every signalled process is gone or stopped, or `timeout` runs out.
+fn wait_for_exit(proc: &ProcFs, watched: &[Watched], timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if !watched.iter().any(|watched| watched.running(proc)) {
+            return;
+        }
+        thread::sleep(POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::fixture::{FakeProcess, Fixture};
+    use std::process::{Child, Command};
+
+    /// Signal 0 only checks the target exists, so real children can stand in for the processes
+    /// the fixture describes.
+    const PROBE: Signal = Signal { name: "0", number: 0 };
+    /// Never a live PID: `pid_max` tops out at 2^22 and PIDs stay below it.
+    const NO_SUCH_PID: u32 = 1 << 22;
+
+    fn sleeper() -> Child {
+        Command::new("sleep").arg("60").spawn().unwrap()
+    }
+
+    #[test]
+    fn signals_are_found_by_name_prefix_or_number() {
+        assert_eq!(Signal::from_name("TERM"), Some(TERM));
+        assert_eq!(Signal::from_name("SIGTERM"), Some(TERM));
+        assert_eq!(Signal::from_name(" sigkill "), Some(KILL));
+        assert_eq!(Signal::from_name("15"), Some(TERM));
+        assert_eq!(Signal::from_name("hup").map(|signal| signal.number), Some(libc::SIGHUP));
+        assert_eq!(Signal::from_name("BOGUS"), None);
+        assert_eq!(Signal::from_name("SIG"), None);
+        assert_eq!(Signal::from_name("99"), None);
+    }
+
+    #[test]
+    fn fates_follow_the_proc_state_after_signalling() {
+        let mut children = [sleeper(), sleeper(), sleeper(), sleeper()];
+        let [running, stopped, zombie, gone] = [0, 1, 2, 3].map(|i| children[i].id());
+        let fixture = Fixture::new();
+        fixture
+            .process(FakeProcess { pid: running, name: "sleep", ..Default::default() })
+            .process(FakeProcess { pid: stopped, name: "sleep", state: 'T', ..Default::default() })
+            .process(FakeProcess { pid: zombie, name: "sleep", state: 'Z', ..Default::default() });
+
+        let outcomes = deliver(&fixture.proc(), &[running, stopped, zombie, gone, NO_SUCH_PID], PROBE, None);
+        let fates: Vec<(u32, Fate)> = outcomes.iter().map(|outcome| (outcome.pid, outcome.fate.clone())).collect();
+        assert_eq!(fates[..4], [(running, Fate::Running), (stopped, Fate::Stopped), (zombie, Fate::Exited), (gone, Fate::Exited)]);
+        assert_eq!(fates[4], (NO_SUCH_PID, Fate::Failed(format!("no process {}", NO_SUCH_PID))));
+        assert!(outcomes[..4].iter().all(|outcome| outcome.sent == [PROBE]));
+        assert!(outcomes[4].sent.is_empty());
+
+        for child in &mut children {
+            child.kill().unwrap();
+            child.wait().unwrap();
+        }
+    }
+
+    #[test]
+    fn survivors_get_kill_after_escalating() {
+        let mut running = sleeper();
+        let mut stopped = sleeper();
+        let mut zombie = sleeper();
+        let fixture = Fixture::new();
+        fixture
+            .process(FakeProcess { pid: running.id(), name: "sleep", ..Default::default() })
+            .process(FakeProcess { pid: stopped.id(), name: "sleep", state: 'T', ..Default::default() })
+            .process(FakeProcess { pid: zombie.id(), name: "sleep", state: 'Z', ..Default::default() });
+
+        let outcomes = deliver(&fixture.proc(), &[running.id(), stopped.id(), zombie.id()], PROBE, Some(Duration::from_millis(100)));
+        let sent: Vec<Vec<&str>> = outcomes.iter().map(|outcome| outcome.sent.iter().map(|signal| signal.name).collect()).collect();
+        assert_eq!(sent, [vec!["0", "KILL"], vec!["0", "KILL"], vec!["0"]]);
+        // The real children really got KILL; only the one that was already a zombie survived.
+        assert!(running.wait().unwrap().code().is_none());
+        assert!(stopped.wait().unwrap().code().is_none());
+        zombie.kill().unwrap();
+        zombie.wait().unwrap();
+    }
+
+    #[test]
+    fn a_reused_pid_is_not_the_process_that_was_signalled() {
+        let fixture = Fixture::new();
+        let watched = Watched { outcome: Outcome { pid: 77, sent: Vec::new(), fate: Fate::Running }, start_ticks: Some(100) };
+        fixture.process(FakeProcess { pid: 77, name: "sleep", start_ticks: 100, ..Default::default() });
+        assert_eq!(watched.state(&fixture.proc()), Some('S'));
+        fixture.process(FakeProcess { pid: 77, name: "bash", start_ticks: 900, ..Default::default() });
+        assert_eq!(watched.state(&fixture.proc()), None);
+    }
+}
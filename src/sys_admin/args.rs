@@ -0,0 +1,391 @@
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use serde::Deserialize;
+
+use crate::procfs::ProcFs;
+use crate::signals::Signal;
+use crate::sys_admin::command_output::{parse_size, CommandError};
+
+/// What kind of value an argument takes; each kind has its own validator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArgKind {
+    Text,
+    /// Integer greater than zero.
+    PositiveInt,
+    /// PID of a running process.
+    Pid,
+    /// Name of a process to match.
+    ProcessName,
+    /// systemd unit with a unit file; `.service` is implied when there is no suffix.
+    Service,
+    /// Mounted mount point or block device.
+    MountPoint,
+    /// One package name.
+    Package,
+    /// Package names separated by spaces.
+    Packages,
+    /// IP address or DNS name.
+    Hostname,
+    /// `http://` or `https://` URL.
+    Url,
+    /// Path to a regular file that exists.
+    ExistingFile,
+    /// Path to a file or directory that exists.
+    ExistingPath,
+    /// Byte count with an optional unit, e.g. `512K`, `100M` or `2G`.
+    Size,
+    /// Signal name or number, e.g. `TERM`, `SIGHUP` or `9`.
+    Signal,
+    /// `yes`/`no`, `true`/`false` or `on`/`off`.
+    YesNo,
+    /// Free text that must not be echoed or logged (passwords).
+    Secret,
+}
+
+/// One positional argument a command takes.
+#[derive(Debug, Clone, Copy)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    /// Shown when prompting for the argument in the menus.
+    pub prompt: &'static str,
+    pub required: bool,
+    /// Used when an optional argument is left empty.
+    pub default: Option<&'static str>,
+}
+
+impl ArgSpec {
+    pub const fn required(name: &'static str, kind: ArgKind, prompt: &'static str) -> Self {
+        Self { name, kind, prompt, required: true, default: None }
+    }
+
+    pub const fn optional(name: &'static str, kind: ArgKind, prompt: &'static str, default: Option<&'static str>) -> Self {
+        Self { name, kind, prompt, required: false, default }
+    }
+
+    /// Validate one raw value, filling in the default when it is empty.
+    pub fn check(&self, raw: &str) -> Result<String, CommandError> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return match (self.required, self.default) {
+                (true, _) => Err(CommandError::MissingArgument(self.name)),
+                (false, default) => Ok(default.unwrap_or_default().to_string()),
+            };
+        }
+        validate(&ProcFs::system(), self.kind, raw).map_err(|reason| CommandError::InvalidArgument(format!("{} '{}': {}", self.name, raw, reason)))?;
+        Ok(raw.to_string())
+    }
+}
+
+/// Where systemd looks for unit files, highest priority first.
+const UNIT_DIRS: &[&str] = &[
+    "/etc/systemd/system",
+    "/run/systemd/system",
+    "/run/systemd/transient",
+    "/run/systemd/generator",
+    "/usr/local/lib/systemd/system",
+    "/usr/lib/systemd/system",
+    "/lib/systemd/system",
+];
+
+fn validate(proc: &ProcFs, kind: ArgKind, value: &str) -> Result<(), &'static str> {
+    // These end up as positional arguments of systemctl, umount, pgrep and the package
+    // managers, some of them as root, so a leading `-` would be read as an option.
+    if matches!(kind, ArgKind::ProcessName | ArgKind::Service | ArgKind::MountPoint | ArgKind::Package | ArgKind::Packages)
+        && value.split_whitespace().any(|word| word.starts_with('-'))
+    {
+        return Err("must not start with -");
+    }
+    match kind {
+        ArgKind::Text | ArgKind::Secret | ArgKind::ProcessName | ArgKind::Packages => Ok(()),
+        ArgKind::Package if value.split_whitespace().count() == 1 => Ok(()),
+        ArgKind::Package => Err("expected a single package name"),
+        ArgKind::Service if unit_exists(UNIT_DIRS.iter().map(Path::new), value) => Ok(()),
+        ArgKind::Service => Err("no such systemd unit"),
+        ArgKind::MountPoint if is_mounted(proc, value) => Ok(()),
+        ArgKind::MountPoint => Err("not a mounted mount point or device"),
+        ArgKind::PositiveInt => match value.parse::<u64>() {
+            Ok(n) if n > 0 => Ok(()),
+            _ => Err("expected a positive integer"),
+        },
+        ArgKind::Pid => match value.parse::<u32>() {
+            Ok(pid) if pid > 0 && proc.path(pid.to_string()).exists() => Ok(()),
+            Ok(_) => Err("no running process with that PID"),
+            Err(_) => Err("expected a PID"),
+        },
+        ArgKind::Hostname if value.parse::<IpAddr>().is_ok() || is_hostname(value) => Ok(()),
+        ArgKind::Hostname => Err("expected an IP address or hostname"),
+        ArgKind::Url if value.starts_with("http://") || value.starts_with("https://") => Ok(()),
+        ArgKind::Url => Err("URL must start with http:// or https://"),
+        ArgKind::ExistingFile if Path::new(value).is_file() => Ok(()),
+        ArgKind::ExistingFile => Err("no such file"),
+        ArgKind::ExistingPath if Path::new(value).exists() => Ok(()),
+        ArgKind::ExistingPath => Err("no such file or directory"),
+        ArgKind::Size if parse_size(value).is_some() => Ok(()),
+        ArgKind::Size => Err("expected a size such as 512K, 100M or 2G"),
+        ArgKind::Signal if Signal::from_name(value).is_some() => Ok(()),
+        ArgKind::Signal => Err("expected a signal such as TERM, HUP, KILL or 9"),
+        ArgKind::YesNo if yes_no(value).is_some() => Ok(()),
+        ArgKind::YesNo => Err("expected yes or no"),
+    }
+}
+
+/// Whether one of `dirs` has a unit file for `unit`; an instance such as `getty@tty1`
+/// counts when its template `getty@.service` is there.
+fn unit_exists<'a>(dirs: impl IntoIterator<Item = &'a Path>, unit: &str) -> bool {
+    if unit.contains('/') {
+        return false;
+    }
+    let unit = if unit.contains('.') { unit.to_string() } else { format!("{}.service", unit) };
+    let template = unit.split_once('@').and_then(|(prefix, rest)| Some(format!("{}@.{}", prefix, rest.rsplit_once('.')?.1)));
+    dirs.into_iter().any(|dir| std::iter::once(&unit).chain(&template).any(|name| dir.join(name).exists()))
+}
+
+/// Whether `value` is a mount point or mounted device in `mounts`, following device symlinks
+/// such as `/dev/disk/by-uuid/...`.
+fn is_mounted(proc: &ProcFs, value: &str) -> bool {
+    let trimmed = match value.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    };
+    let canonical = fs::canonicalize(value).ok().map(|path| path.display().to_string());
+    let mounts = proc.read("mounts").unwrap_or_default();
+    mounts
+        .lines()
+        .flat_map(|line| line.split_whitespace().take(2).map(unescape_mount))
+        .any(|field| field == trimmed || canonical.as_deref() == Some(field.as_str()))
+}
+
+/// Undo the octal escapes (`\040` for a space...) `/proc/mounts` uses in paths.
+fn unescape_mount(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        match rest.get(i + 1..i + 4).and_then(|digits| u8::from_str_radix(digits, 8).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[i + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `yes`, `true`, `on` or `1` as true; `no`, `false`, `off` or `0` as false.
+pub fn yes_no(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "yes" | "y" | "true" | "on" | "1" => Some(true),
+        "no" | "n" | "false" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// RFC 1123 host name: dot-separated labels of letters, digits and inner hyphens.
+fn is_hostname(value: &str) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Validated arguments for one command invocation, keyed by `ArgSpec::name`.
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    command: &'static str,
+    values: Vec<(&'static str, String)>,
+}
+
+impl Args {
+    /// Check raw values against `specs`. Values fill the arguments in order; one written as
+    /// `name=value`, with `name` one of the spec names, sets that argument instead, so optional
+    /// arguments can be skipped. Nothing is spawned until this succeeds.
+    pub fn parse(command: &'static str, specs: &[ArgSpec], raw: &[String]) -> Result<Self, CommandError> {
+        let mut given: Vec<&str> = vec![""; specs.len()];
+        for (slot, value) in Self::slots(specs, raw)?.into_iter().zip(raw) {
+            given[slot] = Self::value(&specs[slot], value);
+        }
+        let values = specs
+            .iter()
+            .zip(given)
+            .map(|(spec, value)| Ok((spec.name, spec.check(value)?)))
+            .collect::<Result<_, CommandError>>()?;
+        Ok(Self { command, values })
+    }
+
+    /// Index of the spec each raw value is for.
+    pub fn slots(specs: &[ArgSpec], raw: &[String]) -> Result<Vec<usize>, CommandError> {
+        if raw.len() > specs.len() {
+            return Err(CommandError::InvalidArgument(format!(
+                "expected at most {} argument(s), got {}",
+                specs.len(),
+                raw.len()
+            )));
+        }
+
+        let named: Vec<Option<usize>> = raw
+            .iter()
+            .map(|value| {
+                let (name, _) = value.split_once('=')?;
+                specs.iter().position(|spec| spec.name == name)
+            })
+            .collect();
+        let mut taken = vec![false; specs.len()];
+        for &slot in named.iter().flatten() {
+            if taken[slot] {
+                return Err(CommandError::InvalidArgument(format!("{} given more than once", specs[slot].name)));
+            }
+            taken[slot] = true;
+        }
+        let mut free = (0..specs.len()).filter(|&slot| !taken[slot]);
+        Ok(named.into_iter().map(|slot| slot.or_else(|| free.next()).unwrap_or_default()).collect())
+    }
+
+    /// A raw value without its `name=` prefix, if it has one for `spec`.
+    pub fn value<'a>(spec: &ArgSpec, raw: &'a str) -> &'a str {
+        raw.strip_prefix(spec.name).and_then(|rest| rest.strip_prefix('=')).unwrap_or(raw)
+    }
+
+    /// Name of the command these arguments were parsed for.
+    pub fn command(&self) -> &'static str {
+        self.command
+    }
+
+    /// Value of an argument; empty for optional arguments without a default.
+    pub fn get(&self, name: &str) -> &str {
+        self.values.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str()).unwrap_or("")
+    }
+
+    /// A `YesNo` argument; false when it is empty.
+    pub fn flag(&self, name: &str) -> bool {
+        yes_no(self.get(name)).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::fixture::{FakeProcess, Fixture};
+    use crate::test_support::TempDir;
+
+    const SPECS: &[ArgSpec] = &[
+        ArgSpec::required("host", ArgKind::Hostname, ""),
+        ArgSpec::optional("count", ArgKind::PositiveInt, "", Some("4")),
+        ArgSpec::optional("note", ArgKind::Text, "", None),
+    ];
+
+    fn raw(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn parsed(values: &[&str]) -> Result<Vec<String>, CommandError> {
+        let args = Args::parse("ping", SPECS, &raw(values))?;
+        Ok(SPECS.iter().map(|spec| args.get(spec.name).to_string()).collect())
+    }
+
+    #[test]
+    fn values_fill_arguments_in_order_and_defaults_fill_the_rest() {
+        assert_eq!(parsed(&["example.com"]).unwrap(), ["example.com", "4", ""]);
+        assert_eq!(parsed(&["example.com", "2", "hi"]).unwrap(), ["example.com", "2", "hi"]);
+        assert_eq!(parsed(&[" example.com ", " "]).unwrap(), ["example.com", "4", ""]);
+        assert_eq!(Args::parse("ping", SPECS, &raw(&["example.com"])).unwrap().command(), "ping");
+    }
+
+    #[test]
+    fn named_values_skip_optional_arguments() {
+        assert_eq!(parsed(&["example.com", "note=a=b"]).unwrap(), ["example.com", "4", "a=b"]);
+        assert_eq!(parsed(&["note=x", "count=3", "example.com"]).unwrap(), ["example.com", "3", "x"]);
+        assert_eq!(parsed(&["example.com", "2", "other=1"]).unwrap(), ["example.com", "2", "other=1"]);
+        assert_eq!(Args::slots(SPECS, &raw(&["note=x", "example.com", "7"])).unwrap(), [2, 0, 1]);
+        assert_eq!(Args::value(&SPECS[2], "note=x"), "x");
+        assert_eq!(Args::value(&SPECS[2], "count=x"), "count=x");
+        assert_eq!(Args::value(&SPECS[2], "notes=x"), "notes=x");
+    }
+
+    #[test]
+    fn missing_repeated_extra_and_invalid_values_are_rejected() {
+        assert!(matches!(parsed(&[]), Err(CommandError::MissingArgument("host"))));
+        assert!(matches!(parsed(&["count=2"]), Err(CommandError::MissingArgument("host"))));
+        assert!(matches!(parsed(&["a.com", "1", "x", "y"]), Err(CommandError::InvalidArgument(e)) if e.contains("at most 3")));
+        assert!(matches!(parsed(&["count=1", "count=2"]), Err(CommandError::InvalidArgument(e)) if e.contains("more than once")));
+        assert!(matches!(parsed(&["a.com", "0"]), Err(CommandError::InvalidArgument(e)) if e == "count '0': expected a positive integer"));
+        assert!(matches!(parsed(&["-bad-.com"]), Err(CommandError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn simple_kinds() {
+        let proc = Fixture::new().proc();
+        let ok = |kind: ArgKind, value: &str| validate(&proc, kind, value).is_ok();
+        assert!(ok(ArgKind::PositiveInt, "12") && !ok(ArgKind::PositiveInt, "0") && !ok(ArgKind::PositiveInt, "-1"));
+        assert!(ok(ArgKind::Hostname, "example.com.") && ok(ArgKind::Hostname, "::1") && ok(ArgKind::Hostname, "10.0.0.1"));
+        assert!(!ok(ArgKind::Hostname, "exa mple.com") && !ok(ArgKind::Hostname, "a..b") && !ok(ArgKind::Hostname, &"a".repeat(64)));
+        assert!(ok(ArgKind::Url, "https://example.com") && !ok(ArgKind::Url, "ftp://example.com"));
+        assert!(ok(ArgKind::ExistingFile, "Cargo.toml") && !ok(ArgKind::ExistingFile, "src") && !ok(ArgKind::ExistingFile, "missing"));
+        assert!(ok(ArgKind::ExistingPath, "src") && !ok(ArgKind::ExistingPath, "missing"));
+        assert!(ok(ArgKind::Size, "512K") && !ok(ArgKind::Size, "lots"));
+        assert!(ok(ArgKind::Signal, "SIGHUP") && ok(ArgKind::Signal, "9") && !ok(ArgKind::Signal, "NAP"));
+        assert!(ok(ArgKind::YesNo, "On") && !ok(ArgKind::YesNo, "maybe"));
+        assert_eq!((yes_no(" Y "), yes_no("off"), yes_no("2")), (Some(true), Some(false), None));
+        assert!(ok(ArgKind::Text, "-anything") && ok(ArgKind::Secret, "-p@ss word"));
+    }
+
+    #[test]
+    fn values_passed_on_as_arguments_cannot_be_options() {
+        let proc = Fixture::new().proc();
+        let ok = |kind: ArgKind, value: &str| validate(&proc, kind, value).is_ok();
+        assert!(ok(ArgKind::ProcessName, "nginx") && !ok(ArgKind::ProcessName, "-u root"));
+        assert!(ok(ArgKind::Packages, "curl vim") && !ok(ArgKind::Packages, "curl -o APT::Get::AllowUnauthenticated=1"));
+        assert!(ok(ArgKind::Package, "curl") && !ok(ArgKind::Package, "curl vim") && !ok(ArgKind::Package, "--help"));
+        assert!(validate(&proc, ArgKind::Service, "--root=/tmp") == Err("must not start with -"));
+        assert!(validate(&proc, ArgKind::MountPoint, "-a") == Err("must not start with -"));
+    }
+
+    #[test]
+    fn pids_are_looked_up_in_the_proc_root() {
+        let fixture = Fixture::new();
+        fixture.process(FakeProcess { pid: 4242, ..Default::default() });
+        let proc = fixture.proc();
+        assert!(validate(&proc, ArgKind::Pid, "4242").is_ok());
+        assert_eq!(validate(&proc, ArgKind::Pid, "4243"), Err("no running process with that PID"));
+        assert_eq!(validate(&proc, ArgKind::Pid, "0"), Err("no running process with that PID"));
+        assert_eq!(validate(&proc, ArgKind::Pid, "nginx"), Err("expected a PID"));
+    }
+
+    #[test]
+    fn mount_points_and_devices_must_be_mounted() {
+        let fixture = Fixture::new();
+        fixture.file("mounts", "/dev/sda2 / ext4 rw 0 0\n/dev/sdb1 /mnt/my\\040disk ext4 rw 0 0\ntmpfs /tmp tmpfs rw 0 0\n");
+        let proc = fixture.proc();
+        for value in ["/", "/mnt/my disk", "/mnt/my disk/", "/dev/sdb1", "tmpfs", "/tmp"] {
+            assert!(validate(&proc, ArgKind::MountPoint, value).is_ok(), "{}", value);
+        }
+        for value in ["/mnt", "/mnt/my", "/dev/sdc1"] {
+            assert!(validate(&proc, ArgKind::MountPoint, value).is_err(), "{}", value);
+        }
+        assert_eq!(unescape_mount(r"a\040b\011c\134d\9"), "a b\tc\\d\\9");
+    }
+
+    #[test]
+    fn services_need_a_unit_file_or_template() {
+        let units = TempDir::new();
+        units.file("lib/nginx.service", "").file("lib/getty@.service", "").file("etc/backup.timer", "");
+        let dirs = [units.root().join("etc"), units.root().join("lib")];
+        let exists = |unit| unit_exists(dirs.iter().map(|dir| dir.as_path()), unit);
+        assert!(exists("nginx") && exists("nginx.service") && exists("backup.timer"));
+        assert!(exists("getty@tty1") && exists("getty@tty1.service"));
+        assert!(!exists("apache2") && !exists("backup") && !exists("getty@tty1.socket") && !exists("../lib/nginx"));
+    }
+}
@@ -1,1277 +1,2633 @@
-use std::process::Command;
-use colored::*;
-
-pub fn ip_a() {
-    println!("{}", "Checking network interfaces and IPs...".bright_cyan());
-    let output = Command::new("ip")
-        .arg("a")
-        .output();
-
-    match output {
-        Ok(out) => {
-            let interfaces = String::from_utf8_lossy(&out.stdout);
-            if interfaces.is_empty() {
-                println!("{}", "No network interfaces found.".bright_red());
-            } else {
-                println!("{}", "Network Interfaces:".bright_cyan());
-                println!("{}", interfaces.bright_green());
-            }
-        },
-        Err(e) => println!("{} {}", "IP command failed:".bright_red(), e),
-    }
-}
-
-pub fn ping(target: &str) {
-    // Try to determine if it's an IP or hostname
-    let is_ip = target.split('.').count() == 4 && 
-                target.split('.').all(|x| x.parse::<u8>().is_ok());
-    
-    println!("{}", format!("Pinging {} ({})...", 
-        target, 
-        if is_ip { "IP address" } else { "hostname" }
-    ).bright_cyan());
-    
-    let output = Command::new("ping")
-        .arg("-c")
-        .arg("4")
-        .arg(target)
-        .output();
-
-    match output {
-        Ok(out) => {
-            let result = String::from_utf8_lossy(&out.stdout);
-            if result.contains("100% packet loss") {
-                println!("{}", "❌ Target is unreachable".bright_red());
-            } else if result.contains("0% packet loss") {
-                println!("{}", "✅ Target is reachable".bright_green());
-            } else {
-                println!("{}", result.bright_green());
-            }
-        },
-        Err(e) => println!("{} {}", "Ping failed:".bright_red(), e),
-    }
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use regex::Regex;
+
+use crate::exec::{Executor, Invocation};
+use crate::packages::{self, Package, PackageManager};
+use crate::privilege;
+use crate::probe::{dns, http};
+use crate::probe::scan::{self, PortState};
+use crate::procfs::cpu::{self, CpuUsage};
+use crate::procfs::inspect::{self, FdKind};
+use crate::procfs::process::{self, Filter, Process, Selector, SortKey};
+use crate::procfs::tree::Tree;
+use crate::procfs::net::{self, Protocol, Socket};
+use crate::procfs::{self, disk, memory, pressure, ProcFs};
+use crate::signals::{self, Fate, Signal};
+use crate::sys_admin::command_output::{human_bytes, parse_size, CommandError, CommandOutput, CommandResult, Field, Kind, Record, Row, Tone};
+
+/// Run a program to completion and capture its output. During a dry run nothing is
+/// spawned and the output only records the command line.
+fn capture(exec: &dyn Executor, program: &str, args: &[&str]) -> CommandResult {
+    run(exec, Invocation::new(program).args(args.iter().copied()))
 }
 
-pub fn traceroute(target: &str) {
-    println!("{}", format!("Tracing route to {}...", target).bright_cyan());
-    let output = Command::new("traceroute")
-        .arg(target)
-        .output();
+/// Run a read-only program even during a dry run; used to resolve what a command will affect.
+fn probe(exec: &dyn Executor, program: &str, args: &[&str]) -> CommandResult {
+    run(exec, Invocation::new(program).args(args.iter().copied()).read_only())
+}
 
-    match output {
-        Ok(out) => {
-            let trace = String::from_utf8_lossy(&out.stdout);
-            if trace.is_empty() {
-                println!("{}", "No route found to target.".bright_red());
-            } else {
-                println!("{}", "Route:".bright_cyan());
-                println!("{}", trace.bright_green());
-            }
-        },
-        Err(e) => println!("{} {}", "Traceroute failed:".bright_red(), e),
-    }
+/// Run a program attached to the terminal (htop, ncdu, `tail -f`...).
+fn attach(exec: &dyn Executor, program: &str, args: &[&str]) -> CommandResult {
+    run(exec, Invocation::new(program).args(args.iter().copied()).attached())
 }
 
-pub fn netstat() {
-    println!("{}", "Checking network connections...".bright_cyan());
-    let output = Command::new("netstat")
-        .arg("-tuln")
-        .output();
+/// Run any invocation and wrap what it produced.
+pub fn run(exec: &dyn Executor, invocation: Invocation) -> CommandResult {
+    run_shown(exec, &invocation, &invocation)
+}
 
-    match output {
-        Ok(out) => {
-            let connections = String::from_utf8_lossy(&out.stdout);
-            if connections.is_empty() {
-                println!("{}", "No active network connections found.".bright_red());
-            } else {
-                println!("{}", "Active Connections:".bright_cyan());
-                println!("{}", connections.bright_green());
-            }
-        },
-        Err(e) => println!("{} {}", "Netstat failed:".bright_red(), e),
-    }
-}
-
-pub fn nmap(target: &str) {
-    // Try to determine if it's an IP or hostname
-    let is_ip = target.split('.').count() == 4 && 
-                target.split('.').all(|x| x.parse::<u8>().is_ok());
-    
-    println!("{}", format!("Scanning {} ({}) for open ports...", 
-        target, 
-        if is_ip { "IP address" } else { "hostname" }
-    ).bright_cyan());
-    
-    let output = Command::new("nmap")
-        .arg("-sV")
-        .arg(target)
-        .output();
-
-    match output {
-        Ok(out) => {
-            let scan = String::from_utf8_lossy(&out.stdout);
-            if scan.is_empty() {
-                println!("{}", "No open ports found.".bright_red());
-            } else {
-                println!("{}", "Scan Results:".bright_cyan());
-                println!("{}", scan.bright_green());
-            }
-        },
-        Err(e) => println!("{} {}", "Nmap failed:".bright_red(), e),
+/// Run `invocation` but report `shown` as the command line, in the output, the dry-run note
+/// and errors; plugins pass a copy with their secret arguments masked.
+pub fn run_shown(exec: &dyn Executor, invocation: &Invocation, shown: &Invocation) -> CommandResult {
+    let completed = exec.run(invocation).map_err(|e| CommandError::spawn(&invocation.program, e))?;
+    // A dry run shows the command as it would be typed, `sudo` and all; without an elevation
+    // tool it stays bare and `execute_with` says root is missing.
+    let argv = if completed.dry_run {
+        privilege::resolve(&exec.effective(shown)).map_or_else(|_| shown.argv(), |resolved| resolved.argv())
+    } else {
+        shown.argv()
+    };
+    let mut out = CommandOutput {
+        argv,
+        stdout: completed.stdout,
+        stderr: completed.stderr,
+        status: completed.status,
+        duration: completed.duration,
+        dry_run: completed.dry_run,
+        ..Default::default()
+    };
+    if out.dry_run {
+        out.note(Tone::Info, format!("🧪 dry run: {}", out.command_line()));
+    } else if !out.success() && out.stdout.trim().is_empty() && privilege::looks_denied(&out.stderr) {
+        let reason = out.stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or_default().trim();
+        return Err(CommandError::PermissionDenied(format!("{} ({})", out.command_line(), reason)));
     }
+    Ok(out)
 }
 
-pub fn curl(url: &str) {
-    // Validate URL format
-    if !url.starts_with("http://") && !url.starts_with("https://") {
-        println!("{}", "Invalid URL format. Please include http:// or https://".bright_red());
-        return;
+fn require<'a>(value: &'a str, name: &'static str) -> Result<&'a str, CommandError> {
+    if value.trim().is_empty() {
+        Err(CommandError::MissingArgument(name))
+    } else {
+        Ok(value.trim())
     }
+}
 
-    println!("{}", format!("Testing connection to {}...", url).bright_cyan());
-    let output = Command::new("curl")
-        .arg("-I")
-        .arg(url)
-        .output();
+fn is_ip(target: &str) -> bool {
+    target.split('.').count() == 4 && target.split('.').all(|x| x.parse::<u8>().is_ok())
+}
 
-    match output {
-        Ok(out) => {
-            let headers = String::from_utf8_lossy(&out.stdout);
-            if headers.is_empty() {
-                println!("{}", "No response received from server.".bright_red());
-            } else {
-                println!("{}", "Server Response:".bright_cyan());
-                println!("{}", headers.bright_green());
-            }
-        },
-        Err(e) => println!("{} {}", "Curl failed:".bright_red(), e),
+/// Tone for a percentage: bad above `bad`, warning above `warn`.
+fn usage_tone(usage: f32, warn: f32, bad: f32) -> Tone {
+    if usage > bad {
+        Tone::Bad
+    } else if usage > warn {
+        Tone::Warn
+    } else {
+        Tone::Good
     }
 }
 
-pub fn dig(domain: &str) {
-    println!("{}", format!("Checking DNS records for {}...", domain).bright_cyan());
-    let output = Command::new("dig")
-        .arg("+short")
-        .arg(domain)
-        .output();
+/// Tone for a log line based on the words it contains.
+fn log_tone(line: &str) -> Tone {
+    let lower = line.to_lowercase();
+    if lower.contains("error") || lower.contains("failed") {
+        Tone::Bad
+    } else if lower.contains("warning") {
+        Tone::Warn
+    } else if lower.contains("info") || lower.contains("accepted") {
+        Tone::Good
+    } else {
+        Tone::Plain
+    }
+}
 
-    match output {
-        Ok(out) => {
-            let records = String::from_utf8_lossy(&out.stdout);
-            if records.is_empty() {
-                println!("{}", "No DNS records found.".bright_red());
-            } else {
-                println!("{}", "DNS Records:".bright_cyan());
-                println!("{}", records.bright_green());
-            }
-        },
-        Err(e) => println!("{} {}", "Dig failed:".bright_red(), e),
+/// Split a line on whitespace into at most `max` fields; the last field keeps the remainder.
+fn split_columns(line: &str, max: usize) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        if fields.len() + 1 == max {
+            fields.push(rest.to_string());
+            break;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        fields.push(rest[..end].to_string());
+        rest = rest[end..].trim_start();
     }
+    fields
 }
 
-pub fn kill(target: &str) {
-    println!("{}", format!("Attempting to kill {}...", target).bright_cyan());
-    let output = if target.chars().all(char::is_numeric) {
-        Command::new("kill").arg(target).output()
-    } else {
-        Command::new("pkill").arg(target).output()
-    };
+fn log_rows(text: &str) -> Vec<Row> {
+    text.lines().map(|line| Row::new(vec![line.to_string()], log_tone(line))).collect()
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Kill failed:".bright_red(), e),
+// Networking
+
+pub fn ip_a(exec: &dyn Executor) -> CommandResult {
+    let mut out = capture(exec, "ip", &["a"])?.with_title("Network Interfaces:");
+    if out.stdout.trim().is_empty() {
+        out.note(Tone::Bad, "No network interfaces found.");
     }
+    Ok(out)
 }
 
-pub fn ps() {
-    println!("{}", "Showing running processes...".bright_cyan());
-    let output = Command::new("ps")
-        .arg("aux")
-        .output();
+pub fn ping(exec: &dyn Executor, target: &str) -> CommandResult {
+    let target = require(target, "target")?;
+    let kind = if is_ip(target) { "IP address" } else { "hostname" };
+    let mut out = capture(exec, "ping", &["-c", "4", target])?.with_title(format!("Pinging {} ({})...", target, kind));
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "PS failed:".bright_red(), e),
+    if out.stdout.contains("100% packet loss") {
+        out.note(Tone::Bad, "❌ Target is unreachable");
+    } else if out.stdout.contains(" 0% packet loss") {
+        out.note(Tone::Good, "✅ Target is reachable");
     }
+    Ok(out)
 }
 
-pub fn htop() {
-    println!("{}", "Launching htop...".bright_cyan());
-    let status = Command::new("htop")
-        .status();
+pub fn traceroute(exec: &dyn Executor, target: &str) -> CommandResult {
+    let target = require(target, "target")?;
+    let mut out = capture(exec, "traceroute", &[target])?.with_title(format!("Route to {}:", target));
+    if out.stdout.trim().is_empty() {
+        out.note(Tone::Bad, "No route found to target.");
+    }
+    Ok(out)
+}
 
-    match status {
-        Ok(_) => println!("{}", "Htop closed.".bright_green()),
-        Err(e) => println!("{} {}", "Htop failed:".bright_red(), e),
+/// `ip:port` as `netstat` prints it, `*` for port 0.
+fn endpoint(address: Option<SocketAddr>) -> String {
+    match address {
+        None => "-".to_string(),
+        Some(address) if address.port() == 0 => match address.ip() {
+            IpAddr::V4(ip) => format!("{}:*", ip),
+            IpAddr::V6(ip) => format!("[{}]:*", ip),
+        },
+        Some(address) => address.to_string(),
     }
 }
 
-pub fn kill_graceful(pid_or_name: &str) {
-    // Try to parse as PID first
-    if let Ok(pid) = pid_or_name.parse::<i32>() {
-        println!("{}", format!("Attempting to kill PID {} gracefully...", pid).bright_cyan());
-        let output = Command::new("kill")
-            .arg(pid.to_string())
-            .output();
+/// One row per socket with its owner and the processes holding it open.
+fn socket_table(sockets: &[Socket], owners: &HashMap<u64, Vec<u32>>, processes: &HashMap<u32, &Process>) -> CommandOutput {
+    let users = procfs::users();
+    let mut out = CommandOutput { headers: ["PROTO", "STATE", "LOCAL", "REMOTE", "USER", "PID/PROGRAM"].map(String::from).to_vec(), ..Default::default() };
+    for socket in sockets {
+        let pids = owners.get(&socket.inode).map(Vec::as_slice).unwrap_or_default();
+        let programs: Vec<String> = pids
+            .iter()
+            .map(|pid| format!("{}/{}", pid, processes.get(pid).map(|process| process.name.as_str()).unwrap_or("?")))
+            .collect();
+        // Unix sockets don't record an owner; the first process holding one stands in.
+        let user = match socket.uid {
+            Some(uid) => users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()),
+            None => pids.iter().find_map(|pid| processes.get(pid)).map(|process| process.user.clone()).unwrap_or_else(|| "-".to_string()),
+        };
+        let local = if socket.protocol == Protocol::Unix { Some(socket.path.clone()).filter(|path| !path.is_empty()).unwrap_or_else(|| "-".to_string()) } else { endpoint(socket.local) };
+        let tone = match socket.state {
+            "LISTEN" => Tone::Good,
+            // Piling up CLOSE_WAIT means the program isn't closing connections its peer closed.
+            "CLOSE_WAIT" => Tone::Warn,
+            _ => Tone::Normal,
+        };
+        out.rows.push(Row::new(
+            vec![
+                socket.protocol.name().to_string(),
+                socket.state.to_string(),
+                local.clone(),
+                endpoint(socket.remote),
+                user.clone(),
+                if programs.is_empty() { "-".to_string() } else { programs.join(", ") },
+            ],
+            tone,
+        ));
+        let address = |address: Option<SocketAddr>| address.map(|address| Field::Text(address.ip().to_string())).unwrap_or(Field::Null);
+        let port = |port: Option<u16>| port.map(|port| Field::Int(port.into())).unwrap_or(Field::Null);
+        out.records.push(Record {
+            fields: vec![
+                ("protocol".to_string(), Field::Text(socket.protocol.name().to_string())),
+                ("state".to_string(), Field::Text(socket.state.to_string())),
+                ("local_address".to_string(), address(socket.local)),
+                ("local_port".to_string(), port(socket.local_port())),
+                ("remote_address".to_string(), address(socket.remote)),
+                ("remote_port".to_string(), port(socket.remote_port())),
+                ("path".to_string(), if socket.path.is_empty() { Field::Null } else { Field::Text(socket.path.clone()) }),
+                ("user".to_string(), Field::Text(user)),
+                ("inode".to_string(), Field::Int(socket.inode as i64)),
+                ("pids".to_string(), Field::Text(pids.iter().map(u32::to_string).collect::<Vec<_>>().join(","))),
+                ("programs".to_string(), Field::Text(programs.join(","))),
+                ("rx_queue".to_string(), Field::Int(socket.rx_queue as i64)),
+                ("tx_queue".to_string(), Field::Int(socket.tx_queue as i64)),
+            ],
+        });
+    }
+    if !privilege::is_root() && sockets.iter().any(|socket| !owners.contains_key(&socket.inode)) {
+        out.note(Tone::Info, "Run as root to see which process owns other users' sockets.");
+    }
+    out
+}
+
+/// Which sockets `state` selects: `LISTENING` (the default), `CONNECTED`, `ALL`, `ESTABLISHED`,
+/// or one state by name, as checked by `state_matches`.
+fn state_filter(state: &str) -> Result<&'static str, CommandError> {
+    let filter = match state.trim().to_uppercase().as_str() {
+        "" | "LISTEN" | "LISTENING" => Some("LISTENING"),
+        "CONNECTED" => Some("CONNECTED"),
+        "ALL" => Some("ALL"),
+        "ESTABLISHED" | "ESTAB" => Some("ESTABLISHED"),
+        name => net::TCP_STATES.iter().chain(&["UNCONN", "CONNECTING", "DISCONNECTING"]).find(|known| **known == name).copied(),
+    };
+    filter.ok_or_else(|| CommandError::InvalidArgument(format!("state '{}': expected listening, connected, all or a state such as TIME_WAIT", state.trim())))
+}
+
+fn state_matches(filter: &str, socket: &Socket) -> bool {
+    match filter {
+        "LISTENING" => socket.listening(),
+        "CONNECTED" => !socket.listening(),
+        "ALL" => true,
+        // UDP and unix sockets call it ESTAB, as `ss` does.
+        "ESTABLISHED" => matches!(socket.state, "ESTABLISHED" | "ESTAB"),
+        state => socket.state == state,
+    }
+}
 
-        match output {
-            Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-            Err(e) => println!("{} {}", "Kill failed:".bright_red(), e),
+/// Native replacement for `netstat -tulnp`: sockets from `/proc/net` with the processes owning
+/// them, filtered by protocol, state, port (local or remote) and process PID or name regex.
+pub fn netstat(proc: &ProcFs, protocols: &str, state: &str, port: &str, process: &str) -> CommandResult {
+    let protocols = net::Protocol::parse_list(if protocols.trim().is_empty() { "tcp,udp" } else { protocols }).ok_or_else(|| {
+        CommandError::InvalidArgument(format!("protocol '{}': expected tcp, udp, tcp4, tcp6, udp4, udp6, unix, inet or all", protocols.trim()))
+    })?;
+    let state = state_filter(state)?;
+    let port: Option<u16> = match port.trim() {
+        "" => None,
+        raw => Some(raw.parse().map_err(|_| CommandError::InvalidArgument(format!("port '{}': expected 1-65535", raw)))?),
+    };
+    let owner = match process.trim() {
+        "" => None,
+        pattern => {
+            let by = if pattern.chars().all(|c| c.is_ascii_digit()) { "pid" } else { "pattern" };
+            Some(Selector::parse(pattern, by).map_err(|e| CommandError::InvalidArgument(format!("process {}", e)))?)
         }
+    };
+
+    let all = process::list(proc);
+    let processes: HashMap<u32, &Process> = all.iter().map(|process| (process.pid, process)).collect();
+    let owners = net::socket_owners(proc);
+    let mut sockets: Vec<Socket> = net::sockets(proc, &protocols)
+        .into_iter()
+        .filter(|socket| state_matches(state, socket))
+        .filter(|socket| port.is_none_or(|port| socket.local_port() == Some(port) || socket.remote_port() == Some(port)))
+        .filter(|socket| {
+            owner.as_ref().is_none_or(|owner| {
+                owners.get(&socket.inode).is_some_and(|pids| pids.iter().filter_map(|pid| processes.get(pid)).any(|process| owner.matches(process)))
+            })
+        })
+        .collect();
+    sockets.sort_by_key(|socket| (socket.protocol, socket.local_port(), socket.local.map(|local| local.ip()), socket.path.clone()));
+
+    let listening = sockets.iter().filter(|socket| socket.listening()).count();
+    let mut out = socket_table(&sockets, &owners, &processes).with_title(format!("Sockets ({}, {} listening):", sockets.len(), listening));
+    if sockets.is_empty() {
+        out.note(Tone::Warn, "No sockets match.");
+    }
+    Ok(out)
+}
+
+/// Who is using a port: every TCP and UDP socket bound to or connected to it, with the
+/// processes behind them.
+pub fn port_users(proc: &ProcFs, port: &str) -> CommandResult {
+    let port: u16 = port.trim().parse().map_err(|_| CommandError::InvalidArgument(format!("port '{}': expected 1-65535", port.trim())))?;
+    let all = process::list(proc);
+    let processes: HashMap<u32, &Process> = all.iter().map(|process| (process.pid, process)).collect();
+    let owners = net::socket_owners(proc);
+    let mut sockets: Vec<Socket> = net::sockets(proc, &Protocol::ALL[..4])
+        .into_iter()
+        .filter(|socket| socket.local_port() == Some(port) || socket.remote_port() == Some(port))
+        .collect();
+    // Whoever is bound to the port first, then connections to it.
+    sockets.sort_by_key(|socket| (!socket.listening(), socket.local_port() != Some(port), socket.protocol));
+
+    let mut out = socket_table(&sockets, &owners, &processes).with_title(format!("Port {}:", port));
+    let bound: Vec<String> = sockets
+        .iter()
+        .filter(|socket| socket.listening() && socket.local_port() == Some(port))
+        .flat_map(|socket| owners.get(&socket.inode).cloned().unwrap_or_default())
+        .filter_map(|pid| processes.get(&pid).map(|process| format!("{} (PID {}, {})", process.name, pid, process.user)))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    if sockets.is_empty() {
+        out.note(Tone::Good, format!("Nothing is using port {}.", port));
+    } else if bound.is_empty() {
+        out.note(Tone::Info, format!("Nothing is listening on port {}; {} connection(s) use it.", port, sockets.len()));
     } else {
-        // If not a PID, try to find the process by name
-        println!("{}", format!("Searching for processes named {}...", pid_or_name).bright_cyan());
-        let pgrep_output = Command::new("pgrep")
-            .arg("-l")
-            .arg(pid_or_name)
-            .output();
-
-        match pgrep_output {
-            Ok(out) => {
-                let processes = String::from_utf8_lossy(&out.stdout);
-                if processes.is_empty() {
-                    println!("{}", format!("No processes found matching '{}'", pid_or_name).bright_red());
-                    return;
-                }
-                
-                println!("{}", "Found processes:".bright_cyan());
-                println!("{}", processes.bright_green());
-                
-                // Ask if user wants to kill all matching processes
-                println!("{}", "Kill all matching processes? (y/n): ".bright_yellow());
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input).unwrap();
-                
-                if input.trim().to_lowercase() == "y" {
-                    let pkill_output = Command::new("pkill")
-                        .arg(pid_or_name)
-                        .output();
-                    
-                    match pkill_output {
-                        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-                        Err(e) => println!("{} {}", "Pkill failed:".bright_red(), e),
-                    }
-                }
-            },
-            Err(e) => println!("{} {}", "Pgrep failed:".bright_red(), e),
-        }
+        let mut bound = bound;
+        bound.sort();
+        out.note(Tone::Info, format!("Listening on port {}: {}", port, bound.join(", ")));
     }
+    Ok(out)
 }
 
-pub fn kill_force(pid_or_name: &str) {
-    // Try to parse as PID first
-    if let Ok(pid) = pid_or_name.parse::<i32>() {
-        println!("{}", format!("Brutally terminating PID {}...", pid).bright_cyan());
-        let output = Command::new("kill")
-            .arg("-9")
-            .arg(pid.to_string())
-            .output();
+pub fn nmap(exec: &dyn Executor, target: &str) -> CommandResult {
+    let target = require(target, "target")?;
+    let kind = if is_ip(target) { "IP address" } else { "hostname" };
+    let mut out = capture(exec, "nmap", &["-sV", target])?.with_title(format!("Scan results for {} ({}):", target, kind));
+    if out.stdout.trim().is_empty() {
+        out.note(Tone::Bad, "No open ports found.");
+    }
+    Ok(out)
+}
 
-        match output {
-            Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-            Err(e) => println!("{} {}", "Kill failed:".bright_red(), e),
-        }
-    } else {
-        // If not a PID, try to find the process by name
-        println!("{}", format!("Searching for processes named {}...", pid_or_name).bright_cyan());
-        let pgrep_output = Command::new("pgrep")
-            .arg("-l")
-            .arg(pid_or_name)
-            .output();
-
-        match pgrep_output {
-            Ok(out) => {
-                let processes = String::from_utf8_lossy(&out.stdout);
-                if processes.is_empty() {
-                    println!("{}", format!("No processes found matching '{}'", pid_or_name).bright_red());
-                    return;
-                }
-                
-                println!("{}", "Found processes:".bright_cyan());
-                println!("{}", processes.bright_green());
-                
-                // Ask if user wants to kill all matching processes
-                println!("{}", "Kill all matching processes? (y/n): ".bright_yellow());
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input).unwrap();
-                
-                if input.trim().to_lowercase() == "y" {
-                    let pkill_output = Command::new("pkill")
-                        .arg("-9")
-                        .arg(pid_or_name)
-                        .output();
-                    
-                    match pkill_output {
-                        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-                        Err(e) => println!("{} {}", "Pkill failed:".bright_red(), e),
-                    }
-                }
-            },
-            Err(e) => println!("{} {}", "Pgrep failed:".bright_red(), e),
+/// Built-in TCP connect scan of `targets` (IPs, hostnames or CIDR blocks) on `ports` (numbers,
+/// ranges and presets). Public addresses are refused unless `allow_public`; only scan hosts you
+/// are allowed to.
+pub fn port_scan(exec: &dyn Executor, targets: &str, ports: &str, concurrency: &str, timeout: &str, allow_public: bool) -> CommandResult {
+    let hosts = scan::parse_targets(require(targets, "target")?).map_err(|e| CommandError::InvalidArgument(format!("target: {}", e)))?;
+    let ports = scan::parse_ports(if ports.trim().is_empty() { "top" } else { ports }).map_err(|e| CommandError::InvalidArgument(format!("ports: {}", e)))?;
+    let concurrency: usize = concurrency.trim().parse().unwrap_or(200);
+    if concurrency > 1000 {
+        return Err(CommandError::InvalidArgument(format!("concurrency {}: the most is 1000", concurrency)));
+    }
+    let timeout = Duration::from_millis(timeout.trim().parse().unwrap_or(1000));
+    let probes = hosts.len() * ports.len();
+    if probes > scan::MAX_PROBES {
+        return Err(CommandError::InvalidArgument(format!("{} hosts × {} ports is too many; the most is {} probes", hosts.len(), ports.len(), scan::MAX_PROBES)));
+    }
+    if !allow_public {
+        if let Some(public) = hosts.iter().find(|&&host| !scan::is_private(host)) {
+            return Err(CommandError::InvalidArgument(format!(
+                "refusing to scan public address {}; set allow-public=yes if you are authorised to scan it",
+                public
+            )));
         }
     }
-}
 
-pub fn pkill(name: &str) {
-    println!("{}", format!("Searching for processes named {}...", name).bright_cyan());
-    let pgrep_output = Command::new("pgrep")
-        .arg("-l")
-        .arg(name)
-        .output();
+    let hosts_label = if hosts.len() == 1 { hosts[0].to_string() } else { format!("{} hosts", hosts.len()) };
+    let mut out = CommandOutput {
+        title: format!("Port scan of {} ({} port(s)):", hosts_label, ports.len()),
+        headers: ["HOST", "PORT", "SERVICE", "STATE", "LATENCY", "BANNER"].map(String::from).to_vec(),
+        dry_run: exec.is_dry_run(),
+        ..Default::default()
+    };
+    if exec.is_dry_run() {
+        out.note(Tone::Info, format!("Would try {} connection(s), {} at a time, {}ms timeout.", probes, concurrency, timeout.as_millis()));
+        return Ok(out);
+    }
+
+    let started = Instant::now();
+    let options = scan::Options { concurrency, timeout, banner_timeout: timeout.min(Duration::from_millis(1500)) };
+    let results = scan::scan(&hosts, &ports, options);
+    out.duration = started.elapsed();
+    let count = |state: PortState| results.iter().filter(|result| result.state == state).count();
+    for result in results.iter().filter(|result| result.state == PortState::Open) {
+        let service = scan::service(result.port).map(|service| service.name).unwrap_or("");
+        let latency = result.latency.as_secs_f64() * 1000.0;
+        out.rows.push(Row::new(
+            vec![
+                result.host.to_string(),
+                result.port.to_string(),
+                service.to_string(),
+                result.state.name().to_string(),
+                format!("{:.1}ms", latency),
+                result.banner.clone().unwrap_or_default(),
+            ],
+            Tone::Good,
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("host".to_string(), Field::Text(result.host.to_string())),
+                ("port".to_string(), Field::Int(result.port.into())),
+                ("service".to_string(), if service.is_empty() { Field::Null } else { Field::Text(service.to_string()) }),
+                ("state".to_string(), Field::Text(result.state.name().to_string())),
+                ("latency_ms".to_string(), Field::Float((latency * 100.0).round() / 100.0)),
+                ("banner".to_string(), result.banner.clone().map(Field::Text).unwrap_or(Field::Null)),
+            ],
+        });
+    }
 
-    match pgrep_output {
-        Ok(out) => {
-            let processes = String::from_utf8_lossy(&out.stdout);
-            if processes.is_empty() {
-                println!("{}", format!("No processes found matching '{}'", name).bright_red());
-                return;
-            }
-            
-            println!("{}", "Found processes:".bright_cyan());
-            println!("{}", processes.bright_green());
-            
-            // Ask if user wants to kill all matching processes
-            println!("{}", "Kill all matching processes? (y/n): ".bright_yellow());
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input).unwrap();
-            
-            if input.trim().to_lowercase() == "y" {
-                let pkill_output = Command::new("pkill")
-                    .arg(name)
-                    .output();
-                
-                match pkill_output {
-                    Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-                    Err(e) => println!("{} {}", "Pkill failed:".bright_red(), e),
-                }
-            }
-        },
-        Err(e) => println!("{} {}", "Pgrep failed:".bright_red(), e),
+    let (open, closed, filtered) = (count(PortState::Open), count(PortState::Closed), count(PortState::Filtered));
+    let mut summary = format!("{} open, {} closed, {} filtered", open, closed, filtered);
+    if hosts.len() > 1 {
+        let up: HashSet<IpAddr> = results.iter().filter(|result| result.state != PortState::Filtered).map(|result| result.host).collect();
+        summary += &format!("; {} of {} hosts answered", up.len(), hosts.len());
     }
+    summary += &format!(" in {:.1}s", out.duration.as_secs_f64());
+    out.note(if open > 0 { Tone::Good } else { Tone::Warn }, summary);
+    if filtered > 0 {
+        out.note(Tone::Info, format!("Filtered ports gave no answer within {}ms; a firewall may be dropping them.", timeout.as_millis()));
+    }
+    Ok(out)
 }
 
-pub fn pgrep(name: &str) {
-    println!("{}", format!("Searching for processes named {}...", name).bright_cyan());
-    let output = Command::new("pgrep")
-        .arg("-l")
-        .arg(name)
-        .output();
+/// Native HTTP request inspector: sends `method` with `headers` (`Name: value`, separated by
+/// `|`) and `body` (`@path` reads a file), follows redirects and times each phase. `status`
+/// (e.g. `200`, `2xx` or `200,204`) and `expect` (a body regex) turn it into a check that
+/// fails the command; `repeat` sends it N times and reports latency percentiles. `insecure`
+/// skips TLS certificate checks.
+#[allow(clippy::too_many_arguments)]
+pub fn curl(
+    exec: &dyn Executor,
+    url: &str,
+    method: &str,
+    headers: &str,
+    body: &str,
+    redirects: &str,
+    status: &str,
+    expect: &str,
+    repeat: &str,
+    timeout: &str,
+    insecure: bool,
+) -> CommandResult {
+    let url = http::Url::parse(require(url, "URL")?).map_err(|e| CommandError::InvalidArgument(format!("url: {}", e)))?;
+    let method = match method.trim() {
+        "" => "GET".to_string(),
+        method if method.chars().all(|c| c.is_ascii_alphabetic()) => method.to_uppercase(),
+        method => return Err(CommandError::InvalidArgument(format!("method '{}': expected a name such as GET or POST", method))),
+    };
+    let headers = headers
+        .split('|')
+        .map(str::trim)
+        .filter(|header| !header.is_empty())
+        .map(|header| match header.split_once(':') {
+            Some((name, value)) if !name.trim().is_empty() && !name.trim().contains(' ') => Ok((name.trim().to_string(), value.trim().to_string())),
+            _ => Err(CommandError::InvalidArgument(format!("header '{}': expected Name: value", header))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let body = match body.strip_prefix('@') {
+        Some(path) => fs::read(path.trim()).map_err(|e| CommandError::InvalidArgument(format!("body file '{}': {}", path.trim(), e)))?,
+        None => body.as_bytes().to_vec(),
+    };
+    let redirects: usize = match redirects.trim() {
+        "" => 5,
+        redirects => redirects.parse().map_err(|_| CommandError::InvalidArgument(format!("redirects '{}': expected a number, 0 to not follow", redirects)))?,
+    };
+    let expected_status: Vec<&str> = status.split(',').map(str::trim).filter(|code| !code.is_empty()).collect();
+    if let Some(code) = expected_status.iter().find(|code| !status_pattern(code)) {
+        return Err(CommandError::InvalidArgument(format!("status '{}': expected a code such as 200 or a class such as 2xx", code)));
+    }
+    let expect = match expect.trim() {
+        "" => None,
+        pattern => Some(Regex::new(pattern).map_err(|e| CommandError::InvalidArgument(format!("expect '{}': {}", pattern, e)))?),
+    };
+    let repeat = repeat.trim().parse::<usize>().unwrap_or(1).clamp(1, 1000);
+    let options = http::Options { timeout: Duration::from_millis(timeout.trim().parse().unwrap_or(5000)), insecure };
+    let request = http::Request { method: method.clone(), headers, body };
+
+    if exec.is_dry_run() {
+        let mut out = CommandOutput { title: format!("{} {}:", method, url), dry_run: true, ..Default::default() };
+        let times = if repeat > 1 { format!(" {} times", repeat) } else { String::new() };
+        out.note(Tone::Info, format!("Would send {} header(s) and {} body bytes{}, following up to {} redirect(s).", request.headers.len(), request.body.len(), times, redirects));
+        return Ok(out);
+    }
 
-    match output {
-        Ok(out) => {
-            let processes = String::from_utf8_lossy(&out.stdout);
-            if processes.is_empty() {
-                println!("{}", format!("No processes found matching '{}'", name).bright_red());
-            } else {
-                println!("{}", "Found processes:".bright_cyan());
-                println!("{}", processes.bright_green());
+    // Why a response fails the checks; empty when it passes.
+    let check = |response: &http::Response| -> Vec<String> {
+        let mut failures = Vec::new();
+        if !expected_status.is_empty() && !expected_status.iter().any(|code| status_matches(code, response.status)) {
+            failures.push(format!("status {} is not {}", response.status, expected_status.join(" or ")));
+        }
+        if let Some(expect) = &expect {
+            if !expect.is_match(&String::from_utf8_lossy(&response.body)) {
+                failures.push(format!("body does not match /{}/", expect));
             }
-        },
-        Err(e) => println!("{} {}", "Pgrep failed:".bright_red(), e),
+        }
+        failures
+    };
+    let asserting = !expected_status.is_empty() || expect.is_some();
+
+    if repeat > 1 {
+        return Ok(http_repeat(&url, &request, redirects, options, repeat, asserting, check));
+    }
+
+    let chain = http::follow(&url, &request, redirects, options).map_err(|(url, e)| {
+        let hint = if http::is_certificate_error(&e) { "; set insecure=yes to skip the check" } else { "" };
+        CommandError::Unreachable(format!("{}: {}{}", url, e, hint))
+    })?;
+    let Some(response) = chain.last() else {
+        return Err(CommandError::Unreachable(url.to_string()));
+    };
+    let mut out = CommandOutput {
+        title: format!("{} {}:", method, url),
+        headers: ["HEADER", "VALUE"].map(String::from).to_vec(),
+        duration: chain.iter().map(|response| response.timing.total).sum(),
+        ..Default::default()
+    };
+    for (name, value) in &response.headers {
+        out.push_record(
+            Record { fields: vec![("header".to_string(), Field::Text(name.clone())), ("value".to_string(), Field::Text(value.clone()))] },
+            Tone::Normal,
+        );
+    }
+    for (hop, redirect) in chain.iter().enumerate().take(chain.len() - 1) {
+        out.note(
+            Tone::Info,
+            format!("↪ {}. {} {} {} ({})", hop + 1, redirect.status, redirect.url, redirect.header("location").unwrap_or_default(), milliseconds(redirect.timing.total)),
+        );
+    }
+    if response.is_redirect() && chain.len() > redirects {
+        out.note(Tone::Warn, format!("Stopped after {} redirect(s); raise redirects= to follow more.", redirects));
+    }
+    let tone = match response.status {
+        200..=299 => Tone::Good,
+        300..=399 => Tone::Info,
+        _ => Tone::Bad,
+    };
+    let content_type = response.header("content-type").map(|kind| format!(", {}", kind)).unwrap_or_default();
+    out.note(
+        tone,
+        format!("{} {} {} from {}: {}{}", response.version, response.status, response.reason, response.remote, human_bytes(response.body.len() as u64), content_type),
+    );
+    if response.truncated {
+        out.note(Tone::Warn, format!("The body was cut off at {}.", human_bytes(http::MAX_BODY as u64)));
+    }
+    let timing = response.timing;
+    let mut phases = vec![format!("DNS {}", milliseconds(timing.dns)), format!("connect {}", milliseconds(timing.connect))];
+    if let Some(tls) = timing.tls {
+        phases.push(format!("TLS {}", milliseconds(tls)));
+    }
+    phases.push(format!("TTFB {}", milliseconds(timing.ttfb)));
+    phases.push(format!("total {}", milliseconds(timing.total)));
+    if chain.len() > 1 {
+        phases.push(format!("{} with redirects", milliseconds(out.duration)));
     }
+    out.note(Tone::Info, phases.join(", "));
+    if let Some(tls) = &response.tls {
+        let unverified = if options.insecure { ", certificate not verified" } else { "" };
+        out.note(if options.insecure { Tone::Warn } else { Tone::Info }, format!("TLS: {}{}", tls, unverified));
+    }
+    if asserting {
+        let failures = check(response);
+        for failure in &failures {
+            out.note(Tone::Bad, format!("❌ {}", failure));
+        }
+        if failures.is_empty() {
+            out.note(Tone::Good, "✅ All checks passed");
+        }
+        out.status = Some(if failures.is_empty() { 0 } else { 1 });
+    }
+    Ok(out)
 }
 
-pub fn free() {
-    println!("{}", "Checking memory usage...".bright_cyan());
-    let output = Command::new("free")
-        .arg("-h")
-        .output();
+/// A status check: a code such as `404` or a class such as `2xx`.
+fn status_pattern(code: &str) -> bool {
+    let code = code.to_lowercase();
+    code.len() == 3 && matches!(code.as_bytes()[0], b'1'..=b'5') && (code[1..].chars().all(|c| c.is_ascii_digit()) || &code[1..] == "xx")
+}
 
-    match output {
-        Ok(out) => {
-            let memory = String::from_utf8_lossy(&out.stdout);
-            if memory.is_empty() {
-                println!("{}", "No memory information available.".bright_red());
-            } else {
-                println!("{}", "Memory Usage:".bright_cyan());
-                // Parse and format the output
-                let lines: Vec<&str> = memory.lines().collect();
-                if lines.len() >= 2 {
-                    let headers = lines[0];
-                    let mem_info = lines[1];
-                    let swap_info = if lines.len() >= 3 { lines[2] } else { "" };
-                    
-                    println!("{}", format!("{}\n{}", headers, mem_info).bright_green());
-                    if !swap_info.is_empty() {
-                        println!("{}", swap_info.bright_green());
-                    }
-                } else {
-                    println!("{}", memory.bright_green());
-                }
-            }
-        },
-        Err(e) => println!("{} {}", "Free failed:".bright_red(), e),
+fn status_matches(code: &str, status: u16) -> bool {
+    match code.to_lowercase().strip_suffix("xx") {
+        Some(class) => class == (status / 100).to_string(),
+        None => code == status.to_string(),
     }
 }
 
-pub fn vmstat(interval: &str, count: &str) {
-    // Set defaults if not provided
-    let interval = if interval.is_empty() { "1" } else { interval };
-    let count = if count.is_empty() { "5" } else { count };
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
 
-    println!("{}", format!("Monitoring system stats (interval: {}s, count: {})...", interval, count).bright_cyan());
-    let output = Command::new("vmstat")
-        .arg(interval)
-        .arg(count)
-        .output();
+/// The value at percentile `p` of sorted samples, by nearest rank.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+}
 
-    match output {
-        Ok(out) => {
-            let stats = String::from_utf8_lossy(&out.stdout);
-            if stats.is_empty() {
-                println!("{}", "No system statistics available.".bright_red());
-            } else {
-                println!("{}", "System Statistics:".bright_cyan());
-                let lines: Vec<&str> = stats.lines().collect();
-                if lines.len() >= 2 {
-                    let headers = lines[0];
-                    println!("{}", headers.bright_green());
-                    
-                    // Format each data line with color indicators
-                    for line in lines.iter().skip(1) {
-                        let fields: Vec<&str> = line.split_whitespace().collect();
-                        if fields.len() >= 17 {
-                            // CPU usage indicators
-                            let cpu_idle = fields[15].parse::<f32>().unwrap_or(0.0);
-                            let cpu_usage = 100.0 - cpu_idle;
-                            let cpu_color = if cpu_usage > 80.0 { "red" } 
-                                         else if cpu_usage > 50.0 { "yellow" } 
-                                         else { "green" };
-                            
-                            let mem_free = fields[3].parse::<i32>().unwrap_or(0);
-                            
-                            println!("{}", format!("{} (CPU: {:.1}%, Free Mem: {}K)", 
-                                line, cpu_usage, mem_free).color(cpu_color));
-                        } else {
-                            println!("{}", line.bright_green());
-                        }
-                    }
-                } else {
-                    println!("{}", stats.bright_green());
-                }
+/// One row per attempt with the phase timings of its last response, then percentiles of the
+/// totals. Attempts that error or fail a check make the command fail.
+fn http_repeat(
+    url: &http::Url,
+    request: &http::Request,
+    redirects: usize,
+    options: http::Options,
+    repeat: usize,
+    asserting: bool,
+    check: impl Fn(&http::Response) -> Vec<String>,
+) -> CommandOutput {
+    let mut out = CommandOutput {
+        title: format!("{} {} × {}:", request.method, url, repeat),
+        headers: ["#", "STATUS", "DNS", "CONNECT", "TLS", "TTFB", "TOTAL", "SIZE", "RESULT"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    let ms = |duration: Duration| Field::Float((duration.as_secs_f64() * 100_000.0).round() / 100.0);
+    let (mut totals, mut ttfbs, mut failed) = (Vec::new(), Vec::new(), 0);
+    for attempt in 1..=repeat {
+        let started = Instant::now();
+        let result = http::follow(url, request, redirects, options);
+        out.duration += started.elapsed();
+        let chain = match result {
+            Ok(chain) if !chain.is_empty() => chain,
+            failure => {
+                failed += 1;
+                let error = match failure {
+                    Err((url, e)) => format!("{}: {}", url, e),
+                    Ok(_) => format!("{}: no response", url),
+                };
+                let mut row = vec!["-".to_string(); 9];
+                row[0] = attempt.to_string();
+                row[8] = error.clone();
+                out.rows.push(Row::new(row, Tone::Bad));
+                out.records.push(Record {
+                    fields: vec![
+                        ("attempt".to_string(), Field::Int(attempt as i64)),
+                        ("status".to_string(), Field::Null),
+                        ("dns_ms".to_string(), Field::Null),
+                        ("connect_ms".to_string(), Field::Null),
+                        ("tls_ms".to_string(), Field::Null),
+                        ("ttfb_ms".to_string(), Field::Null),
+                        ("total_ms".to_string(), Field::Null),
+                        ("size".to_string(), Field::Null),
+                        ("result".to_string(), Field::Text(error)),
+                    ],
+                });
+                continue;
             }
-        },
-        Err(e) => println!("{} {}", "Vmstat failed:".bright_red(), e),
-    }
-}
-
-pub fn iostat(interval: &str, count: &str) {
-    println!("{}", format!("Monitoring I/O stats (interval: {}s, count: {})...", interval, count).bright_cyan());
-    println!("{}", "I/O Statistics:".bright_cyan());
-    
-    let output = Command::new("iostat")
-        .arg("-x")
-        .arg(interval)
-        .arg(count)
-        .output()
-        .expect("Failed to execute iostat");
-    
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    if output_str.is_empty() {
-        println!("{}", "No I/O statistics available".bright_red());
-        return;
-    }
-    
-    // Print the header
-    println!("{}", output_str.lines().take(3).collect::<Vec<&str>>().join("\n").bright_white());
-    
-    // Parse and format the device statistics
-    for line in output_str.lines().skip(3) {
-        if line.trim().is_empty() {
-            continue;
-        }
-        
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 7 {
-            continue;
-        }
-        
-        let device = parts[0];
-        let util = parts[parts.len() - 1].parse::<f64>().unwrap_or(0.0);
-        
-        // Color code based on utilization
-        let color = if util > 80.0 {
-            "red"
-        } else if util > 50.0 {
-            "yellow"
+        };
+        let response = &chain[chain.len() - 1];
+        let total: Duration = chain.iter().map(|response| response.timing.total).sum();
+        let timing = response.timing;
+        totals.push(total);
+        ttfbs.push(timing.ttfb);
+        let failures = check(response);
+        failed += usize::from(!failures.is_empty());
+        let result = if !failures.is_empty() {
+            failures.join("; ")
+        } else if asserting {
+            "ok".to_string()
         } else {
-            "green"
+            response.reason.clone()
         };
-        
-        println!("{}", format!("{}: {:.1}%", device, util).color(color));
+        let tone = if !failures.is_empty() { Tone::Bad } else { Tone::Normal };
+        let tls = timing.tls.map(milliseconds).unwrap_or_else(|| "-".to_string());
+        out.rows.push(Row::new(
+            vec![
+                attempt.to_string(),
+                response.status.to_string(),
+                milliseconds(timing.dns),
+                milliseconds(timing.connect),
+                tls,
+                milliseconds(timing.ttfb),
+                milliseconds(total),
+                human_bytes(response.body.len() as u64),
+                result.clone(),
+            ],
+            tone,
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("attempt".to_string(), Field::Int(attempt as i64)),
+                ("status".to_string(), Field::Int(response.status.into())),
+                ("dns_ms".to_string(), ms(timing.dns)),
+                ("connect_ms".to_string(), ms(timing.connect)),
+                ("tls_ms".to_string(), timing.tls.map(ms).unwrap_or(Field::Null)),
+                ("ttfb_ms".to_string(), ms(timing.ttfb)),
+                ("total_ms".to_string(), ms(total)),
+                ("size".to_string(), Field::Bytes(response.body.len() as u64)),
+                ("result".to_string(), Field::Text(result)),
+            ],
+        });
+    }
+
+    totals.sort();
+    ttfbs.sort();
+    if !totals.is_empty() {
+        for (name, samples) in [("Total", &totals), ("TTFB", &ttfbs)] {
+            let points: Vec<String> = [("min", 0.0), ("p50", 50.0), ("p90", 90.0), ("p99", 99.0), ("max", 100.0)]
+                .iter()
+                .map(|(label, p)| format!("{} {}", label, milliseconds(percentile(samples, *p))))
+                .collect();
+            out.note(Tone::Info, format!("{}: {}", name, points.join(", ")));
+        }
     }
+    let passed = repeat - failed;
+    out.note(if failed == 0 { Tone::Good } else { Tone::Bad }, format!("{} of {} request(s) succeeded", passed, repeat));
+    out.status = Some(if failed == 0 { 0 } else { 1 });
+    out
 }
 
-pub fn mpstat(interval: &str, count: &str) {
-    // Set defaults if not provided
-    let interval = if interval.is_empty() { "1" } else { interval };
-    let count = if count.is_empty() { "5" } else { count };
+/// Native DNS lookup. An IP address with no type (or PTR) is looked up in reverse. `server` is
+/// an address, optionally with `:port`; empty asks the first resolver in `/etc/resolv.conf` and
+/// `all` asks every one of them and compares the answers.
+pub fn dig(exec: &dyn Executor, name: &str, rtype: &str, server: &str, port: &str, tcp: bool, timeout: &str) -> CommandResult {
+    let name = require(name, "name")?;
+    let reverse = name.parse::<IpAddr>().ok().filter(|_| rtype.trim().is_empty() || rtype.trim().eq_ignore_ascii_case("PTR"));
+    let rtype = match (rtype.trim(), reverse) {
+        ("", Some(_)) => 12,
+        ("", None) => 1,
+        (rtype, _) => dns::type_number(rtype).ok_or_else(|| {
+            let types: Vec<&str> = dns::TYPES.iter().map(|(name, _)| *name).collect();
+            CommandError::InvalidArgument(format!("type '{}': expected one of {}", rtype, types.join(", ")))
+        })?,
+    };
+    let qname = reverse.map(dns::reverse_name).unwrap_or_else(|| name.to_string());
+    let port: u16 = port.trim().parse().map_err(|_| CommandError::InvalidArgument(format!("port '{}': expected 1-65535", port.trim())))?;
+    let options = dns::Options { timeout: Duration::from_millis(timeout.trim().parse().unwrap_or(2000)), tcp };
+
+    let compare = server.trim().eq_ignore_ascii_case("all");
+    let servers = match server.trim() {
+        "" | "all" | "ALL" => {
+            let resolvers = dns::system_resolvers();
+            if resolvers.is_empty() {
+                return Err(CommandError::Unreadable("the nameservers in /etc/resolv.conf".to_string()));
+            }
+            if compare { resolvers } else { resolvers[..1].to_vec() }
+        }
+        server => vec![dns::parse_server(server, port).map_err(|e| CommandError::InvalidArgument(format!("server '{}': {}", server, e)))?],
+    };
+    let type_name = dns::type_name(rtype);
+    if exec.is_dry_run() {
+        let servers: Vec<String> = servers.iter().map(SocketAddr::to_string).collect();
+        let mut out = CommandOutput { title: format!("DNS {} lookup of {}:", type_name, qname), dry_run: true, ..Default::default() };
+        out.note(Tone::Info, format!("Would ask {} over {}.", servers.join(", "), if tcp { "TCP" } else { "UDP" }));
+        return Ok(out);
+    }
+    if compare {
+        return Ok(dns_comparison(&servers, &qname, rtype, options));
+    }
 
-    println!("{}", format!("Monitoring CPU stats (interval: {}s, count: {})...", interval, count).bright_cyan());
-    let output = Command::new("mpstat")
-        .arg("-P")
-        .arg("ALL")
-        .arg(interval)
-        .arg(count)
-        .output();
+    let exchange = dns::query(servers[0], &qname, rtype, options).map_err(|e| CommandError::Unreachable(format!("{}: {}", servers[0], e)))?;
+    let response = &exchange.response;
+    let mut out = CommandOutput {
+        title: format!("DNS {} records for {} from {}:", type_name, qname, exchange.server),
+        headers: ["NAME", "TTL", "TYPE", "DATA"].map(String::from).to_vec(),
+        duration: exchange.elapsed,
+        ..Default::default()
+    };
+    // Without answers, the authority section says why (the zone's SOA for a negative answer).
+    let (section, records) = if response.answers.is_empty() { ("authority", &response.authority) } else { ("answer", &response.answers) };
+    for record in records {
+        let rtype = dns::type_name(record.rtype);
+        let data = record.data.to_string();
+        let tone = if section == "answer" { Tone::Normal } else { Tone::Info };
+        out.rows.push(Row::new(vec![record.name.clone(), record.ttl.to_string(), rtype.clone(), data.clone()], tone));
+        out.records.push(Record {
+            fields: vec![
+                ("name".to_string(), Field::Text(record.name.clone())),
+                ("ttl".to_string(), Field::Int(record.ttl.into())),
+                ("type".to_string(), Field::Text(rtype)),
+                ("data".to_string(), Field::Text(data)),
+                ("section".to_string(), Field::Text(section.to_string())),
+            ],
+        });
+    }
+    let status = dns::rcode_name(response.rcode);
+    match (response.rcode, response.answers.is_empty()) {
+        (0, false) => out.note(Tone::Good, format!("{} {} record(s)", response.answers.len(), type_name)),
+        (0, true) => out.note(Tone::Warn, format!("{} exists but has no {} records", qname, type_name)),
+        (3, _) => out.note(Tone::Bad, format!("{} does not exist (NXDOMAIN)", qname)),
+        _ => out.note(Tone::Bad, format!("The server answered {}", status)),
+    }
+    let mut flags = vec![status];
+    if response.authoritative {
+        flags.push("authoritative".to_string());
+    }
+    if !response.recursion_available {
+        flags.push("no recursion".to_string());
+    }
+    out.note(
+        Tone::Info,
+        format!("{} in {:.1}ms over {}, {} bytes ({})", exchange.server, exchange.elapsed.as_secs_f64() * 1000.0, exchange.transport, exchange.size, flags.join(", ")),
+    );
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => {
-            let stats = String::from_utf8_lossy(&out.stdout);
-            if stats.is_empty() {
-                println!("{}", "No CPU statistics available.".bright_red());
-            } else {
-                println!("{}", "CPU Statistics:".bright_cyan());
-                let lines: Vec<&str> = stats.lines().collect();
-                if lines.len() >= 2 {
-                    let headers = lines[0];
-                    println!("{}", headers.bright_green());
-                    
-                    // Format each data line with color indicators
-                    for line in lines.iter().skip(1) {
-                        let fields: Vec<&str> = line.split_whitespace().collect();
-                        if fields.len() >= 4 {
-                            // CPU usage indicators
-                            let idle = fields[3].parse::<f32>().unwrap_or(0.0);
-                            let usage = 100.0 - idle;
-                            let cpu_color = if usage > 80.0 { "red" } 
-                                         else if usage > 50.0 { "yellow" } 
-                                         else { "green" };
-                            
-                            println!("{}", format!("{} (Usage: {:.1}%)", 
-                                line, usage).color(cpu_color));
-                        } else {
-                            println!("{}", line.bright_green());
-                        }
-                    }
-                } else {
-                    println!("{}", stats.bright_green());
-                }
+/// One row per resolver with its answers, flagging resolvers that disagree with the first.
+fn dns_comparison(servers: &[SocketAddr], qname: &str, rtype: u16, options: dns::Options) -> CommandOutput {
+    let mut out = CommandOutput {
+        title: format!("DNS {} records for {} from {} resolver(s):", dns::type_name(rtype), qname, servers.len()),
+        headers: ["RESOLVER", "STATUS", "TIME", "VIA", "ANSWERS"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    let mut expected: Option<(String, Vec<String>)> = None;
+    let mut disagree = 0;
+    for &server in servers {
+        let (status, elapsed, transport, answers) = match dns::query(server, qname, rtype, options) {
+            Ok(exchange) => {
+                let mut answers: Vec<String> = exchange.response.answers.iter().filter(|record| record.rtype == rtype).map(|record| record.data.to_string()).collect();
+                answers.sort();
+                (dns::rcode_name(exchange.response.rcode), Some(exchange.elapsed), exchange.transport, answers)
             }
-        },
-        Err(e) => println!("{} {}", "Mpstat failed:".bright_red(), e),
+            Err(e) => (format!("error: {}", e), None, "-", Vec::new()),
+        };
+        let answered = elapsed.is_some();
+        let differs = answered && expected.as_ref().is_some_and(|expected| *expected != (status.clone(), answers.clone()));
+        if answered && expected.is_none() {
+            expected = Some((status.clone(), answers.clone()));
+        }
+        disagree += usize::from(differs);
+        let tone = if !answered { Tone::Bad } else if differs { Tone::Warn } else { Tone::Normal };
+        let milliseconds = elapsed.map(|elapsed| elapsed.as_secs_f64() * 1000.0);
+        out.rows.push(Row::new(
+            vec![
+                server.to_string(),
+                status.clone(),
+                milliseconds.map(|ms| format!("{:.1}ms", ms)).unwrap_or_else(|| "-".to_string()),
+                transport.to_string(),
+                if answers.is_empty() { "-".to_string() } else { answers.join(", ") },
+            ],
+            tone,
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("resolver".to_string(), Field::Text(server.to_string())),
+                ("status".to_string(), Field::Text(status)),
+                ("time_ms".to_string(), milliseconds.map(|ms| Field::Float((ms * 100.0).round() / 100.0)).unwrap_or(Field::Null)),
+                ("transport".to_string(), Field::Text(transport.to_string())),
+                ("answers".to_string(), Field::Text(answers.join(","))),
+            ],
+        });
+    }
+    match (expected, disagree) {
+        (None, _) => out.note(Tone::Bad, "No resolver answered."),
+        (Some(_), 0) => out.note(Tone::Good, "All answering resolvers agree."),
+        (Some(_), count) => {
+            out.note(Tone::Warn, format!("{} resolver(s) disagree with the first; CDN-hosted names often resolve differently per resolver.", count))
+        }
     }
+    out
 }
 
-pub fn sar(interval: &str, count: &str) {
-    // Set defaults if not provided
-    let interval = if interval.is_empty() { "1" } else { interval };
-    let count = if count.is_empty() { "5" } else { count };
+// Process Management
 
-    println!("{}", format!("Monitoring system activity (interval: {}s, count: {})...", interval, count).bright_cyan());
-    let output = Command::new("sar")
-        .arg("-u")
-        .arg("-r")
-        .arg("-b")
-        .arg(interval)
-        .arg(count)
-        .output();
+/// A sort column as typed, `-` in front to flip it; empty means `cpu`.
+pub fn sort_key(sort: &str) -> Result<(SortKey, bool), CommandError> {
+    let (reverse, key) = match sort.trim().strip_prefix('-') {
+        Some(key) => (true, key),
+        None => (false, sort.trim()),
+    };
+    let key = SortKey::from_name(if key.is_empty() { "cpu" } else { key }).ok_or_else(|| {
+        let keys: Vec<&str> = SortKey::ALL.iter().map(SortKey::name).collect();
+        CommandError::InvalidArgument(format!("sort '{}': expected one of {}", sort, keys.join(", ")))
+    })?;
+    Ok((key, reverse))
+}
 
-    match output {
-        Ok(out) => {
-            let stats = String::from_utf8_lossy(&out.stdout);
-            if stats.is_empty() {
-                println!("{}", "No system activity data available.".bright_red());
-            } else {
-                println!("{}", "System Activity Report:".bright_cyan());
-                let lines: Vec<&str> = stats.lines().collect();
-                if lines.len() >= 2 {
-                    let headers = lines[0];
-                    println!("{}", headers.bright_green());
-                    
-                    // Format each data line with color indicators
-                    for line in lines.iter().skip(1) {
-                        let fields: Vec<&str> = line.split_whitespace().collect();
-                        if fields.len() >= 4 {
-                            // CPU usage indicators
-                            let idle = fields[3].parse::<f32>().unwrap_or(0.0);
-                            let usage = 100.0 - idle;
-                            let cpu_color = if usage > 80.0 { "red" } 
-                                         else if usage > 50.0 { "yellow" } 
-                                         else { "green" };
-                            
-                            println!("{}", format!("{} (CPU: {:.1}%)", 
-                                line, usage).color(cpu_color));
-                        } else {
-                            println!("{}", line.bright_green());
-                        }
-                    }
-                } else {
-                    println!("{}", stats.bright_green());
-                }
-            }
+/// Native process table from `/proc`: filtered by user, name regex, states and minimum RSS,
+/// sorted by `sort` (prefix `-` to flip its direction) and cut to the top N.
+pub fn ps(proc: &ProcFs, sort: &str, top: &str, user: &str, name: &str, states: &str, min_mem: &str) -> CommandResult {
+    let (key, reverse) = sort_key(sort)?;
+    let filter = Filter {
+        user: Some(user.trim()).filter(|user| !user.is_empty()).map(String::from),
+        name: match name.trim() {
+            "" => None,
+            pattern => Some(Regex::new(pattern).map_err(|e| CommandError::InvalidArgument(format!("name '{}': {}", pattern, e)))?),
         },
-        Err(e) => println!("{} {}", "Sar failed:".bright_red(), e),
+        states: Some(states.trim()).filter(|states| !states.is_empty()).map(String::from),
+        min_rss_bytes: parse_size(min_mem.trim()).unwrap_or_default(),
+    };
+    let top = top.trim().parse().ok();
+
+    let all = process::list(proc);
+    let total = all.len();
+    let selected = process::select(all, &filter, key, reverse, top);
+    let title = if selected.len() == total {
+        format!("Running Processes ({}):", total)
+    } else {
+        format!("Running Processes ({} of {}):", selected.len(), total)
+    };
+    let mut out = process_table(&selected).with_title(title);
+    if selected.is_empty() {
+        out.note(Tone::Warn, "No processes match.");
     }
+    Ok(out)
 }
 
-pub fn df() {
-    println!("{}", "Checking disk space usage...".bright_cyan());
-    let output = Command::new("df")
-        .arg("-h")
-        .output();
-
-    match output {
-        Ok(out) => {
-            let disk = String::from_utf8_lossy(&out.stdout);
-            if disk.is_empty() {
-                println!("{}", "No disk information available.".bright_red());
+/// One row per process, with typed records for JSON and CSV.
+pub fn process_table(processes: &[Process]) -> CommandOutput {
+    let mut out = CommandOutput {
+        headers: ["PID", "PPID", "USER", "S", "%CPU", "RSS", "THR", "START", "COMMAND"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    for process in processes {
+        let tone = state_tone(process.state);
+        let started = process.start_time.map(|time| {
+            if time.date_naive() == Local::now().date_naive() {
+                time.format("%H:%M").to_string()
             } else {
-                println!("{}", "Disk Usage:".bright_cyan());
-                let lines: Vec<&str> = disk.lines().collect();
-                if lines.len() >= 2 {
-                    let headers = lines[0];
-                    println!("{}", headers.bright_green());
-                    
-                    // Format each data line with color indicators
-                    for line in lines.iter().skip(1) {
-                        let fields: Vec<&str> = line.split_whitespace().collect();
-                        if fields.len() >= 5 {
-                            // Disk usage indicators
-                            let usage = fields[4].trim_end_matches('%').parse::<f32>().unwrap_or(0.0);
-                            let disk_color = if usage > 90.0 { "red" } 
-                                          else if usage > 70.0 { "yellow" } 
-                                          else { "green" };
-                            
-                            println!("{}", format!("{} (Usage: {:.1}%)", 
-                                line, usage).color(disk_color));
-                        } else {
-                            println!("{}", line.bright_green());
-                        }
-                    }
-                } else {
-                    println!("{}", disk.bright_green());
-                }
+                time.format("%b %d").to_string()
             }
-        },
-        Err(e) => println!("{} {}", "Df failed:".bright_red(), e),
+        });
+        out.rows.push(Row::new(
+            vec![
+                process.pid.to_string(),
+                process.ppid.to_string(),
+                process.user.clone(),
+                process.state.to_string(),
+                format!("{:.1}", process.cpu_percent),
+                human_bytes(process.rss_bytes),
+                process.threads.to_string(),
+                started.unwrap_or_else(|| "-".to_string()),
+                process.command.replace(['\n', '\t'], " "),
+            ],
+            tone,
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("pid".to_string(), Field::Int(process.pid.into())),
+                ("ppid".to_string(), Field::Int(process.ppid.into())),
+                ("user".to_string(), Field::Text(process.user.clone())),
+                ("uid".to_string(), Field::Int(process.uid.into())),
+                ("state".to_string(), Field::Text(process.state.to_string())),
+                ("cpu_percent".to_string(), Field::Percent(process.cpu_percent)),
+                ("rss_bytes".to_string(), Field::Bytes(process.rss_bytes)),
+                ("threads".to_string(), Field::Int(process.threads.into())),
+                ("start_time".to_string(), process.start_time.map(|time| Field::Text(time.to_rfc3339())).unwrap_or(Field::Null)),
+                ("name".to_string(), Field::Text(process.name.clone())),
+                ("command".to_string(), Field::Text(process.command.clone())),
+            ],
+        });
     }
+    out
 }
 
-pub fn du(path: &str) {
-    if path.is_empty() {
-        println!("{}", "Please provide a path to analyze".bright_red());
-        return;
+/// Colour for a process state letter: running green, uninterruptible yellow, zombies red.
+fn state_tone(state: char) -> Tone {
+    match state {
+        'R' => Tone::Good,
+        'D' => Tone::Warn,
+        'Z' | 'X' => Tone::Bad,
+        _ => Tone::Normal,
     }
+}
 
-    println!("{}", format!("Analyzing disk usage in {}...", path).bright_cyan());
-    let output = Command::new("du")
-        .arg("-h")
-        .arg("--max-depth=1")
-        .arg(path)
-        .output();
-
-    match output {
-        Ok(out) => {
-            let usage = String::from_utf8_lossy(&out.stdout);
-            if usage.is_empty() {
-                println!("{}", "No disk usage information available.".bright_red());
-            } else {
-                println!("{}", "Directory Sizes:".bright_cyan());
-                let lines: Vec<&str> = usage.lines().collect();
-                for line in lines {
-                    let fields: Vec<&str> = line.split_whitespace().collect();
-                    if fields.len() >= 2 {
-                        let size = fields[0];
-                        let path = fields[1];
-                        println!("{} {}", size.bright_green(), path.bright_blue());
-                    }
-                }
+/// Processes a tree command starts from: a PID, or every process whose name or command matches
+/// the pattern and that has no matching ancestor, so each family appears once.
+fn tree_roots(tree: &Tree, processes: &[Process], root: &str) -> Result<Vec<u32>, CommandError> {
+    let root = root.trim();
+    if root.is_empty() {
+        return Ok(tree.roots().to_vec());
+    }
+    if let Ok(pid) = root.parse::<u32>() {
+        return tree.get(pid).map(|_| vec![pid]).ok_or_else(|| CommandError::NoMatch(format!("PID {}", pid)));
+    }
+    let pattern = Regex::new(root).map_err(|e| CommandError::InvalidArgument(format!("root '{}': {}", root, e)))?;
+    let matching: HashSet<u32> = processes
+        .iter()
+        .filter(|process| pattern.is_match(&process.name) || pattern.is_match(&process.command))
+        .map(|process| process.pid)
+        .collect();
+    let has_matching_ancestor = |pid: u32| {
+        let mut seen = HashSet::new();
+        let mut parent = tree.get(pid).map(|process| process.ppid);
+        while let Some(ppid) = parent.filter(|ppid| seen.insert(*ppid)) {
+            if matching.contains(&ppid) {
+                return true;
             }
-        },
-        Err(e) => println!("{} {}", "Du failed:".bright_red(), e),
+            parent = tree.get(ppid).map(|process| process.ppid);
+        }
+        false
+    };
+    let mut roots: Vec<u32> = matching.iter().copied().filter(|&pid| !has_matching_ancestor(pid)).collect();
+    roots.sort_unstable();
+    if roots.is_empty() {
+        return Err(CommandError::NoMatch(root.to_string()));
     }
+    Ok(roots)
 }
 
-pub fn ncdu(path: &str) {
-    if path.is_empty() {
-        println!("{}", "Please provide a path to analyze".bright_red());
-        return;
+/// `pstree`: the process tree from `root` (everything when empty), with each process's own and
+/// subtree CPU and memory. Subtrees below `depth`, and under the PIDs or names listed in `fold`,
+/// are folded into their top process.
+pub fn pstree(proc: &ProcFs, root: &str, depth: &str, fold: &str) -> CommandResult {
+    let processes = process::list(proc);
+    let tree = Tree::new(&processes);
+    let roots = tree_roots(&tree, &processes, root)?;
+    let folded: HashSet<u32> = fold
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .flat_map(|item| processes.iter().filter(move |process| process.pid.to_string() == item || process.name == item))
+        .map(|process| process.pid)
+        .collect();
+    let nodes = tree.draw(&roots, &folded, depth.trim().parse().ok());
+
+    let mut out = CommandOutput {
+        title: format!("Process Tree ({} processes):", roots.iter().map(|&pid| tree.subtree(pid).len()).sum::<usize>()),
+        headers: ["PID", "USER", "%CPU", "RSS", "Σ%CPU", "ΣRSS", "PROCS", "TREE"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    for node in &nodes {
+        let Some(process) = tree.get(node.pid) else { continue };
+        let totals = tree.totals(node.pid);
+        let label = match node.hidden {
+            0 => format!("{}{}", node.prefix, process.name),
+            hidden => format!("{}{} [+{}]", node.prefix, process.name, hidden),
+        };
+        out.rows.push(Row::new(
+            vec![
+                process.pid.to_string(),
+                process.user.clone(),
+                format!("{:.1}", process.cpu_percent),
+                human_bytes(process.rss_bytes),
+                format!("{:.1}", totals.cpu_percent),
+                human_bytes(totals.rss_bytes),
+                totals.processes.to_string(),
+                label,
+            ],
+            state_tone(process.state),
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("pid".to_string(), Field::Int(process.pid.into())),
+                ("ppid".to_string(), Field::Int(process.ppid.into())),
+                ("depth".to_string(), Field::Int(node.depth as i64)),
+                ("user".to_string(), Field::Text(process.user.clone())),
+                ("state".to_string(), Field::Text(process.state.to_string())),
+                ("name".to_string(), Field::Text(process.name.clone())),
+                ("command".to_string(), Field::Text(process.command.clone())),
+                ("cpu_percent".to_string(), Field::Percent(process.cpu_percent)),
+                ("rss_bytes".to_string(), Field::Bytes(process.rss_bytes)),
+                ("subtree_processes".to_string(), Field::Int(totals.processes as i64)),
+                ("subtree_cpu_percent".to_string(), Field::Percent(totals.cpu_percent)),
+                ("subtree_rss_bytes".to_string(), Field::Bytes(totals.rss_bytes)),
+                ("folded".to_string(), Field::Int(node.hidden as i64)),
+            ],
+        });
     }
-
-    println!("{}", format!("Launching interactive disk usage explorer in {}...", path).bright_cyan());
-    let status = Command::new("ncdu")
-        .arg(path)
-        .status();
-
-    match status {
-        Ok(_) => println!("{}", "Ncdu closed.".bright_green()),
-        Err(e) => println!("{} {}", "Ncdu failed:".bright_red(), e),
+    if nodes.iter().any(|node| node.hidden > 0) {
+        out.note(Tone::Info, "[+N] marks a folded subtree of N processes; set fold=none and no depth to expand everything.");
     }
+    Ok(out)
 }
 
-pub fn lsblk() {
-    println!("{}", "Listing block devices...".bright_cyan());
-    let output = Command::new("lsblk")
-        .arg("-o")
-        .arg("NAME,SIZE,TYPE,MOUNTPOINT,FSTYPE")
-        .output();
+/// The PID at the top of a tree to signal. PID 1's tree is every process, so it is refused.
+fn tree_target(target: &str) -> Result<u32, CommandError> {
+    match target.trim().parse::<u32>() {
+        Ok(pid) if pid > 1 => Ok(pid),
+        Ok(pid) => Err(CommandError::InvalidArgument(format!("refusing to signal the tree of PID {}: that's every process", pid))),
+        Err(_) => Err(CommandError::InvalidArgument(format!("target '{}': expected a PID", target))),
+    }
+}
 
-    match output {
-        Ok(out) => {
-            let devices = String::from_utf8_lossy(&out.stdout);
-            if devices.is_empty() {
-                println!("{}", "No block devices found.".bright_red());
-            } else {
-                println!("{}", "Block Devices:".bright_cyan());
-                let lines: Vec<&str> = devices.lines().collect();
-                if lines.len() >= 2 {
-                    let headers = lines[0];
-                    println!("{}", headers.bright_green());
-                    
-                    // Format each device line
-                    for line in lines.iter().skip(1) {
-                        let fields: Vec<&str> = line.split_whitespace().collect();
-                        if fields.len() >= 4 {
-                            let mountpoint = fields[3];
-                            let color = if mountpoint == "/" { "yellow" } 
-                                     else if !mountpoint.is_empty() { "green" } 
-                                     else { "blue" };
-                            println!("{}", line.color(color));
-                        } else {
-                            println!("{}", line.bright_green());
-                        }
-                    }
-                } else {
-                    println!("{}", devices.bright_green());
+/// `kill-tree`: send `signal` to a process and every descendant. The whole family is listed
+/// (and confirmed by the guard) before anything is sent; parents are signalled before their
+/// children so a supervisor can't respawn workers that were already stopped.
+pub fn kill_tree(exec: &dyn Executor, proc: &ProcFs, target: &str, signal: &str) -> CommandResult {
+    let pid = tree_target(target)?;
+    let signal = Signal::from_name(signal).ok_or_else(|| CommandError::InvalidArgument(format!("signal '{}': unknown signal", signal)))?;
+    let processes = process::list(proc);
+    let tree = Tree::new(&processes);
+    if tree.get(pid).is_none() {
+        return Err(CommandError::NoMatch(format!("PID {}", pid)));
+    }
+    let nodes = tree.draw(&[pid], &HashSet::new(), None);
+    // gremlin may be in the subtree (under the shell it was started from); it must survive to
+    // finish the job and report.
+    let own_pid = std::process::id();
+    let pids: Vec<u32> = nodes.iter().map(|node| node.pid).filter(|&pid| pid != own_pid).collect();
+
+    let mut out = CommandOutput {
+        title: format!("Sending {} to {} and {} descendant(s):", signal.name, pid, pids.iter().filter(|&&other| other != pid).count()),
+        argv: ["kill", "-s", signal.name].iter().map(|arg| arg.to_string()).chain(pids.iter().map(u32::to_string)).collect(),
+        dry_run: exec.is_dry_run(),
+        headers: ["PID", "TREE", "RESULT"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    let mut failed = 0;
+    for node in &nodes {
+        let name = tree.get(node.pid).map(|process| process.name.as_str()).unwrap_or_default();
+        let (result, tone) = if node.pid == own_pid {
+            ("skipped: gremlin itself".to_string(), Tone::Warn)
+        } else if exec.is_dry_run() {
+            (format!("would send {}", signal.name), Tone::Info)
+        } else {
+            match signal.send(node.pid) {
+                Ok(()) => (format!("sent {}", signal.name), Tone::Good),
+                Err(e) => {
+                    failed += 1;
+                    (e.to_string(), Tone::Bad)
                 }
             }
-        },
-        Err(e) => println!("{} {}", "Lsblk failed:".bright_red(), e),
+        };
+        out.rows.push(Row::new(vec![node.pid.to_string(), format!("{}{}", node.prefix, name), result.clone()], tone));
+        out.records.push(Record {
+            fields: vec![
+                ("pid".to_string(), Field::Int(node.pid.into())),
+                ("depth".to_string(), Field::Int(node.depth as i64)),
+                ("name".to_string(), Field::Text(name.to_string())),
+                ("signal".to_string(), Field::Text(signal.name.to_string())),
+                ("result".to_string(), Field::Text(result)),
+            ],
+        });
     }
+    out.status = Some(if failed == 0 { 0 } else { 1 });
+    if exec.is_dry_run() {
+        return Ok(out);
+    }
+    if failed == 0 {
+        out.note(Tone::Good, format!("✅ Sent {} to {} process(es)", signal.name, pids.len()));
+    } else {
+        out.note(Tone::Bad, format!("❌ {} of {} process(es) could not be signalled", failed, pids.len()));
+    }
+    Ok(out)
 }
 
-pub fn mount() {
-    println!("{}", "Showing mounted filesystems...".bright_cyan());
-    let output = Command::new("mount")
-        .output();
+pub fn htop(exec: &dyn Executor) -> CommandResult {
+    let mut out = attach(exec, "htop", &[])?;
+    out.note(Tone::Good, "Htop closed.");
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => {
-            let mounts = String::from_utf8_lossy(&out.stdout);
-            if mounts.is_empty() {
-                println!("{}", "No mounted filesystems found.".bright_red());
-            } else {
-                println!("{}", "Mounted Filesystems:".bright_cyan());
-                let lines: Vec<&str> = mounts.lines().collect();
-                for line in lines {
-                    let fields: Vec<&str> = line.split_whitespace().collect();
-                    if fields.len() >= 3 {
-                        let mountpoint = fields[2];
-                        let color = if mountpoint == "/" { "yellow" } 
-                                 else if mountpoint.starts_with("/") { "green" } 
-                                 else { "blue" };
-                        println!("{}", line.color(color));
-                    } else {
-                        println!("{}", line.bright_green());
-                    }
-                }
-            }
-        },
-        Err(e) => println!("{} {}", "Mount failed:".bright_red(), e),
+/// Processes picked by `target` and `by` (see `Selector::parse`), never gremlin itself. Only an
+/// explicit PID can pick init or a kernel thread; a name, user or pattern never does.
+fn kill_targets(proc: &ProcFs, target: &str, by: &str) -> Result<Vec<Process>, CommandError> {
+    let target = require(target, "PID, name, user or pattern")?;
+    let selector = Selector::parse(target, by).map_err(CommandError::InvalidArgument)?;
+    let explicit = matches!(selector, Selector::Pid(_));
+    let own_pid = std::process::id();
+    let targets: Vec<Process> = process::list(proc)
+        .into_iter()
+        .filter(|process| process.pid != own_pid && selector.matches(process))
+        .filter(|process| explicit || (process.pid != 1 && !process.kernel_thread))
+        .collect();
+    if targets.is_empty() {
+        return Err(CommandError::NoMatch(target.to_string()));
     }
+    Ok(targets)
 }
 
-pub fn umount(device: &str) {
-    if device.is_empty() {
-        println!("{}", "Please provide a device or mount point to unmount".bright_red());
-        return;
+/// `kill`: send `signal` (TERM by default) to the processes picked by `target` and `by`, and
+/// report per PID what was sent and whether it exited. With `escalate` seconds, processes still
+/// running after that long get KILL.
+pub fn kill(exec: &dyn Executor, proc: &ProcFs, target: &str, by: &str, signal: &str, escalate: &str) -> CommandResult {
+    let targets = kill_targets(proc, target, by)?;
+    let signal = match signal.trim() {
+        "" => signals::TERM,
+        name => Signal::from_name(name).ok_or_else(|| CommandError::InvalidArgument(format!("signal '{}': unknown signal", name)))?,
+    };
+    let escalate = escalate.trim().parse().ok().map(Duration::from_secs);
+    let pids: Vec<u32> = targets.iter().map(|process| process.pid).collect();
+
+    let mut out = CommandOutput {
+        title: format!("Sending {} to {} process(es):", signal.name, targets.len()),
+        argv: ["kill", "-s", signal.name].iter().map(|arg| arg.to_string()).chain(pids.iter().map(u32::to_string)).collect(),
+        dry_run: exec.is_dry_run(),
+        headers: ["PID", "USER", "NAME", "SIGNALS", "RESULT"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    if exec.is_dry_run() {
+        let plan = match escalate {
+            Some(after) if signal != signals::KILL => format!("{}, then KILL after {}s", signal.name, after.as_secs()),
+            _ => signal.name.to_string(),
+        };
+        for process in &targets {
+            let fields = vec![process.pid.to_string(), process.user.clone(), process.name.clone(), plan.clone(), "would send".to_string()];
+            out.rows.push(Row::new(fields, Tone::Info));
+            out.records.push(kill_record(process, signal.name, "would send"));
+        }
+        return Ok(out);
     }
 
-    println!("{}", format!("Unmounting {}...", device).bright_cyan());
-    let output = Command::new("umount")
-        .arg(device)
-        .output();
+    let started = Instant::now();
+    let outcomes = signals::deliver(proc, &pids, signal, escalate);
+    out.duration = started.elapsed();
+    let count = |fate: fn(&Fate) -> bool| outcomes.iter().filter(|outcome| fate(&outcome.fate)).count();
+    let (exited, running, stopped, failed) = (
+        count(|fate| *fate == Fate::Exited),
+        count(|fate| *fate == Fate::Running),
+        count(|fate| *fate == Fate::Stopped),
+        count(|fate| matches!(fate, Fate::Failed(_))),
+    );
+    for (process, outcome) in targets.iter().zip(&outcomes) {
+        let sent: Vec<&str> = outcome.sent.iter().map(|signal| signal.name).collect();
+        let tone = match outcome.fate {
+            Fate::Exited => Tone::Good,
+            Fate::Failed(_) => Tone::Bad,
+            Fate::Running | Fate::Stopped if escalate.is_some() => Tone::Bad,
+            Fate::Running | Fate::Stopped => Tone::Normal,
+        };
+        out.rows.push(Row::new(
+            vec![process.pid.to_string(), process.user.clone(), process.name.clone(), sent.join(" → "), outcome.fate.describe()],
+            tone,
+        ));
+        out.records.push(kill_record(process, &sent.join(","), &outcome.fate.describe()));
+    }
 
-    match output {
-        Ok(out) => {
-            if out.status.success() {
-                println!("{}", format!("Successfully unmounted {}", device).bright_green());
-            } else {
-                println!("{}", format!("Failed to unmount {}: {}", 
-                    device, String::from_utf8_lossy(&out.stderr)).bright_red());
-            }
-        },
-        Err(e) => println!("{} {}", "Umount failed:".bright_red(), e),
+    let mut summary = format!("{} signalled: {} exited", outcomes.len() - failed, exited);
+    if running > 0 {
+        summary += &format!(", {} still running", running);
     }
+    if stopped > 0 {
+        summary += &format!(", {} stopped", stopped);
+    }
+    let survived = escalate.is_some() && running + stopped > 0;
+    out.note(if failed > 0 || survived { Tone::Bad } else { Tone::Good }, summary);
+    if failed > 0 {
+        out.note(Tone::Bad, format!("❌ {} could not be signalled", failed));
+    }
+    out.status = Some(if failed > 0 || survived { 1 } else { 0 });
+    Ok(out)
 }
 
-pub fn journalctl_system() {
-    println!("{}", "Checking system logs...".bright_cyan());
-    let output = Command::new("journalctl")
-        .arg("-xe")
-        .output();
+/// One `kill` target as a record; dry runs list the same fields with the signal that would be sent.
+fn kill_record(process: &Process, signals: &str, result: &str) -> Record {
+    Record {
+        fields: vec![
+            ("pid".to_string(), Field::Int(process.pid.into())),
+            ("user".to_string(), Field::Text(process.user.clone())),
+            ("name".to_string(), Field::Text(process.name.clone())),
+            ("command".to_string(), Field::Text(process.command.clone())),
+            ("signals".to_string(), Field::Text(signals.to_string())),
+            ("result".to_string(), Field::Text(result.to_string())),
+        ],
+    }
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Journalctl failed:".bright_red(), e),
+pub fn pgrep(exec: &dyn Executor, name: &str) -> CommandResult {
+    let name = require(name, "process name")?;
+    let mut out = capture(exec, "pgrep", &["-l", name])?.with_title("Found processes:");
+    if out.stdout.trim().is_empty() {
+        return Err(CommandError::NoMatch(name.to_string()));
     }
+    out.headers = vec!["PID".to_string(), "NAME".to_string()];
+    out.rows = out.stdout.lines().map(|line| Row::new(split_columns(line, 2), Tone::Normal)).collect();
+    out.note(Tone::Info, "Details of one with `gremlin sys inspect <pid>`.");
+    Ok(out)
 }
 
-pub fn journalctl_service(service: &str) {
-    println!("{}", format!("Checking logs for service {}...", service).bright_cyan());
-    let output = Command::new("journalctl")
-        .arg("-u")
-        .arg(service)
-        .output();
+/// What `inspect` can show, summary first.
+pub const INSPECT_SECTIONS: [&str; 10] = ["summary", "cmdline", "environ", "fds", "maps", "limits", "cgroup", "namespaces", "threads", "io"];
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Journalctl failed:".bright_red(), e),
+/// A section that couldn't be read. Most of `/proc/<pid>` is private to the process owner and root.
+fn section_error(proc: &ProcFs, pid: u32, file: &str, error: io::Error) -> CommandError {
+    let path = proc.path(format!("{}/{}", pid, file)).display().to_string();
+    match error.kind() {
+        io::ErrorKind::PermissionDenied => CommandError::PermissionDenied(format!("{} is only readable by the process owner or root", path)),
+        _ => CommandError::Unreadable(format!("{}: {}", path, error)),
     }
 }
 
-pub fn dmesg() {
-    println!("{}", "Checking kernel ring buffer...".bright_cyan());
-    let output = Command::new("dmesg")
-        .output();
+/// A summary value as shown to humans when it couldn't be read.
+fn missing(error: &io::Error) -> String {
+    match error.kind() {
+        io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        io::ErrorKind::NotFound => "-".to_string(),
+        _ => error.to_string(),
+    }
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Dmesg failed:".bright_red(), e),
+/// A two-column FIELD/VALUE table with the same values as a single typed record. Values that
+/// couldn't be read are `null` in the record.
+fn key_values(entries: Vec<(&str, &str, io::Result<Field>)>) -> CommandOutput {
+    let mut out = CommandOutput { headers: vec!["FIELD".to_string(), "VALUE".to_string()], ..Default::default() };
+    let mut record = Record::default();
+    for (label, key, value) in entries {
+        let (shown, field, tone) = match value {
+            Ok(field) => (field.to_string(), field, Tone::Normal),
+            Err(e) => (missing(&e), Field::Null, if e.kind() == io::ErrorKind::PermissionDenied { Tone::Warn } else { Tone::Normal }),
+        };
+        out.rows.push(Row::new(vec![label.to_string(), shown], tone));
+        record.fields.push((key.to_string(), field));
     }
+    out.records.push(record);
+    out
 }
 
-pub fn tail_syslog() {
-    println!("{}", "Watching system logs in real-time...".bright_cyan());
-    let status = Command::new("tail")
-        .arg("-f")
-        .arg("/var/log/syslog")
-        .status();
+/// `inspect`: one section of what `/proc/<pid>` knows about a process, each with typed records
+/// so it can be exported with `--format json`.
+pub fn inspect(proc: &ProcFs, pid: &str, section: &str) -> CommandResult {
+    let pid: u32 = pid.trim().parse().map_err(|_| CommandError::InvalidArgument(format!("PID '{}': expected a number", pid.trim())))?;
+    let process = process::read(proc, pid).ok_or_else(|| CommandError::NoMatch(pid.to_string()))?;
+    let section = match section.trim().to_lowercase() {
+        section if section.is_empty() => "summary".to_string(),
+        section => section,
+    };
+    let (title, out) = match section.as_str() {
+        "summary" => ("Process", inspect_summary(proc, &process)),
+        "cmdline" => ("Command line", inspect_cmdline(proc, &process)?),
+        "environ" | "env" => ("Environment", inspect_environ(proc, pid)?),
+        "fds" | "fd" => ("Open files", inspect_fds(proc, pid)?),
+        "maps" => ("Memory maps", inspect_maps(proc, pid)?),
+        "limits" => ("Resource limits", inspect_limits(proc, pid)?),
+        "cgroup" | "cgroups" => ("Control groups", inspect_cgroups(proc, pid)?),
+        "namespaces" | "ns" => ("Namespaces", inspect_namespaces(proc, pid)?),
+        "threads" | "tasks" => ("Threads", inspect_threads(proc, pid)?),
+        "io" => ("I/O counters", inspect_io(proc, pid)?),
+        _ => {
+            return Err(CommandError::InvalidArgument(format!("section '{}': expected one of {}", section, INSPECT_SECTIONS.join(", "))));
+        }
+    };
+    Ok(out.with_title(format!("{} of {} ({}):", title, pid, process.name)))
+}
 
-    match status {
-        Ok(_) => println!("{}", "Log watching ended.".bright_green()),
-        Err(e) => println!("{} {}", "Tail failed:".bright_red(), e),
+fn inspect_summary(proc: &ProcFs, process: &Process) -> CommandOutput {
+    let pid = process.pid;
+    let text = |value: String| Ok(Field::Text(value));
+    let (oom_score, oom_adjust) = match inspect::oom(proc, pid) {
+        Ok((score, adjust)) => (Ok(Field::Int(score)), Ok(Field::Int(adjust))),
+        Err(e) => (Err(io::Error::new(e.kind(), e.to_string())), Err(e)),
+    };
+    let (read_bytes, write_bytes) = match inspect::io_counters(proc, pid) {
+        Ok(io) => (Ok(Field::Bytes(io.read_bytes)), Ok(Field::Bytes(io.write_bytes))),
+        Err(e) => (Err(io::Error::new(e.kind(), e.to_string())), Err(e)),
+    };
+    let cgroup = inspect::cgroups(proc, pid).map(|cgroups| {
+        // The unified hierarchy if there is one, else the first v1 controller.
+        let cgroup = cgroups.iter().find(|cgroup| cgroup.hierarchy == 0).or(cgroups.first());
+        cgroup.map(|cgroup| Field::Text(cgroup.path.clone())).unwrap_or(Field::Null)
+    });
+    let mut out = key_values(vec![
+        ("PID", "pid", Ok(Field::Int(pid.into()))),
+        ("Parent", "ppid", Ok(Field::Int(process.ppid.into()))),
+        ("Name", "name", text(process.name.clone())),
+        ("State", "state", text(process.state.to_string())),
+        ("User", "user", text(format!("{} ({})", process.user, process.uid))),
+        ("Command", "command", text(process.command.clone())),
+        ("Executable", "exe", inspect::link(proc, pid, "exe").map(Field::Text)),
+        ("Working dir", "cwd", inspect::link(proc, pid, "cwd").map(Field::Text)),
+        ("Started", "start_time", Ok(process.start_time.map(|time| Field::Text(time.to_rfc3339())).unwrap_or(Field::Null))),
+        ("CPU time", "cpu_seconds", Ok(Field::Float((process.cpu_seconds * 100.0).round() / 100.0))),
+        ("CPU", "cpu_percent", Ok(Field::Percent(process.cpu_percent))),
+        ("Resident memory", "rss_bytes", Ok(Field::Bytes(process.rss_bytes))),
+        ("Threads", "threads", Ok(Field::Int(process.threads.into()))),
+        ("Open files", "fds", inspect::fds(proc, pid).map(|fds| Field::Int(fds.len() as i64))),
+        ("OOM score", "oom_score", oom_score),
+        ("OOM adjust", "oom_score_adj", oom_adjust),
+        ("Disk read", "read_bytes", read_bytes),
+        ("Disk written", "write_bytes", write_bytes),
+        ("Cgroup", "cgroup", cgroup),
+    ]);
+    out.rows[0].tone = state_tone(process.state);
+    if out.rows.iter().any(|row| row.tone == Tone::Warn) {
+        out.note(Tone::Warn, "Some details are only readable by the process owner or root.");
     }
+    out.note(Tone::Info, format!("More with `gremlin sys inspect {} <section>`: {}", pid, INSPECT_SECTIONS[1..].join(", ")));
+    out
 }
 
-pub fn less_auth_log() {
-    println!("{}", "Checking authentication logs...".bright_cyan());
-    let status = Command::new("less")
-        .arg("/var/log/auth.log")
-        .status();
+fn inspect_cmdline(proc: &ProcFs, process: &Process) -> CommandResult {
+    let args = inspect::cmdline(proc, process.pid).map_err(|e| section_error(proc, process.pid, "cmdline", e))?;
+    let mut out = CommandOutput { headers: vec!["#".to_string(), "ARGUMENT".to_string()], ..Default::default() };
+    for (index, arg) in args.into_iter().enumerate() {
+        out.push_record(
+            Record { fields: vec![("index".to_string(), Field::Int(index as i64)), ("arg".to_string(), Field::Text(arg))] },
+            Tone::Normal,
+        );
+    }
+    if out.rows.is_empty() {
+        out.note(Tone::Info, format!("{} is a kernel thread or a zombie and has no command line.", process.name));
+    }
+    Ok(out)
+}
 
-    match status {
-        Ok(_) => println!("{}", "Auth log viewing ended.".bright_green()),
-        Err(e) => println!("{} {}", "Less failed:".bright_red(), e),
+fn inspect_environ(proc: &ProcFs, pid: u32) -> CommandResult {
+    let vars = inspect::environ(proc, pid).map_err(|e| section_error(proc, pid, "environ", e))?;
+    let masked = vars.iter().filter(|var| var.masked).count();
+    let mut out = CommandOutput { headers: vec!["NAME".to_string(), "VALUE".to_string()], ..Default::default() };
+    for var in vars {
+        let tone = if var.masked { Tone::Warn } else { Tone::Normal };
+        out.push_record(Record { fields: vec![("name".to_string(), Field::Text(var.name)), ("value".to_string(), Field::Text(var.value))] }, tone);
+    }
+    if masked > 0 {
+        out.note(Tone::Info, format!("{} value(s) that look like passwords, tokens or keys are masked as ***.", masked));
     }
+    out.note(Tone::Info, "This is the environment the process started with; later changes it made aren't visible.");
+    Ok(out)
 }
 
-pub fn systemctl_start(service: &str) {
-    println!("{}", "🚀 Starting service...".bright_green());
-    let output = std::process::Command::new("systemctl")
-        .arg("start")
-        .arg(service)
-        .output()
-        .expect("Failed to start service");
-    
-    if output.status.success() {
-        println!("{}", "✅ Service started successfully!".bright_green());
+fn inspect_fds(proc: &ProcFs, pid: u32) -> CommandResult {
+    let fds = inspect::fds(proc, pid).map_err(|e| section_error(proc, pid, "fd", e))?;
+    // Endpoints of sockets, by inode; only read when the process has any.
+    let sockets: HashMap<u64, Socket> = if fds.iter().any(|fd| fd.kind == FdKind::Socket) {
+        net::sockets(proc, &Protocol::ALL).into_iter().map(|socket| (socket.inode, socket)).collect()
     } else {
-        println!("{}", "❌ Failed to start service:".bright_red());
-        println!("{}", String::from_utf8_lossy(&output.stderr));
+        HashMap::new()
+    };
+    let mut out = CommandOutput { headers: ["FD", "TYPE", "TARGET"].map(String::from).to_vec(), ..Default::default() };
+    let mut counts: Vec<(FdKind, usize)> = Vec::new();
+    for fd in fds {
+        let socket = fd.inode.and_then(|inode| sockets.get(&inode)).filter(|_| fd.kind == FdKind::Socket).map(Socket::describe);
+        match counts.iter_mut().find(|(kind, _)| *kind == fd.kind) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((fd.kind, 1)),
+        }
+        let shown = match &socket {
+            Some(socket) => format!("{} {}", fd.target, socket),
+            None => fd.target.clone(),
+        };
+        out.rows.push(Row::new(vec![fd.fd.to_string(), fd.kind.name().to_string(), shown], Tone::Normal));
+        out.records.push(Record {
+            fields: vec![
+                ("fd".to_string(), Field::Int(fd.fd.into())),
+                ("type".to_string(), Field::Text(fd.kind.name().to_string())),
+                ("target".to_string(), Field::Text(fd.target)),
+                ("inode".to_string(), fd.inode.map(|inode| Field::Int(inode as i64)).unwrap_or(Field::Null)),
+                ("socket".to_string(), socket.map(Field::Text).unwrap_or(Field::Null)),
+            ],
+        });
+    }
+    counts.sort();
+    if !counts.is_empty() {
+        let counts: Vec<String> = counts.iter().map(|(kind, count)| format!("{} {}", count, kind.name())).collect();
+        out.note(Tone::Info, format!("{} open: {}", out.rows.len(), counts.join(", ")));
     }
+    Ok(out)
 }
 
-pub fn systemctl_stop(service: &str) {
-    println!("{}", "🛑 Stopping service...".bright_yellow());
-    let output = std::process::Command::new("systemctl")
-        .arg("stop")
-        .arg(service)
-        .output()
-        .expect("Failed to stop service");
-    
-    if output.status.success() {
-        println!("{}", "✅ Service stopped successfully!".bright_green());
-    } else {
-        println!("{}", "❌ Failed to stop service:".bright_red());
-        println!("{}", String::from_utf8_lossy(&output.stderr));
+fn inspect_maps(proc: &ProcFs, pid: u32) -> CommandResult {
+    let mappings = inspect::maps(proc, pid).map_err(|e| section_error(proc, pid, "maps", e))?;
+    let mut out = CommandOutput { headers: ["MAPPED", "REGIONS", "SIZE", "EXEC"].map(String::from).to_vec(), ..Default::default() };
+    for group in inspect::summarize_maps(&mappings) {
+        out.rows.push(Row::new(
+            vec![group.name.clone(), group.mappings.to_string(), human_bytes(group.size), if group.executable { "x" } else { "" }.to_string()],
+            if group.executable { Tone::Info } else { Tone::Normal },
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("name".to_string(), Field::Text(group.name)),
+                ("regions".to_string(), Field::Int(group.mappings as i64)),
+                ("size_bytes".to_string(), Field::Bytes(group.size)),
+                ("executable".to_string(), Field::Text(if group.executable { "yes" } else { "no" }.to_string())),
+            ],
+        });
     }
+    let total: u64 = mappings.iter().map(|mapping| mapping.size()).sum();
+    let mut summary = format!("{} regions, {} of address space", mappings.len(), human_bytes(total));
+    // smaps_rollup is missing on older kernels; the summary just leaves it out.
+    if let Ok(rollup) = inspect::rollup(proc, pid) {
+        for key in ["Rss", "Pss", "Swap"] {
+            if let Some((_, bytes)) = rollup.iter().find(|(name, _)| name == key) {
+                summary += &format!(", {} {}", human_bytes(*bytes), key);
+            }
+        }
+    }
+    out.note(Tone::Info, summary);
+    Ok(out)
 }
 
-pub fn systemctl_restart(service: &str) {
-    println!("{}", "🔄 Restarting service...".bright_cyan());
-    let output = std::process::Command::new("systemctl")
-        .arg("restart")
-        .arg(service)
-        .output()
-        .expect("Failed to restart service");
-    
-    if output.status.success() {
-        println!("{}", "✅ Service restarted successfully!".bright_green());
-    } else {
-        println!("{}", "❌ Failed to restart service:".bright_red());
-        println!("{}", String::from_utf8_lossy(&output.stderr));
+fn inspect_limits(proc: &ProcFs, pid: u32) -> CommandResult {
+    let limits = inspect::limits(proc, pid).map_err(|e| section_error(proc, pid, "limits", e))?;
+    let mut out = CommandOutput { headers: ["LIMIT", "SOFT", "HARD", "UNITS"].map(String::from).to_vec(), ..Default::default() };
+    let shown = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_else(|| "unlimited".to_string());
+    let field = |value: Option<u64>| value.map(|value| Field::Int(value.min(i64::MAX as u64) as i64)).unwrap_or(Field::Null);
+    for limit in limits {
+        out.rows.push(Row::new(vec![limit.name.clone(), shown(limit.soft), shown(limit.hard), limit.unit.clone()], Tone::Normal));
+        out.records.push(Record {
+            fields: vec![
+                ("name".to_string(), Field::Text(limit.name)),
+                ("soft".to_string(), field(limit.soft)),
+                ("hard".to_string(), field(limit.hard)),
+                ("unit".to_string(), Field::Text(limit.unit)),
+            ],
+        });
     }
+    out.note(Tone::Info, "Unlimited values are null in JSON.");
+    Ok(out)
 }
 
-pub fn systemctl_enable(service: &str) {
-    println!("{}", "🔌 Enabling service...".bright_blue());
-    let output = std::process::Command::new("systemctl")
-        .arg("enable")
-        .arg(service)
-        .output()
-        .expect("Failed to enable service");
-    
-    if output.status.success() {
-        println!("{}", "✅ Service enabled successfully!".bright_green());
-    } else {
-        println!("{}", "❌ Failed to enable service:".bright_red());
-        println!("{}", String::from_utf8_lossy(&output.stderr));
+fn inspect_cgroups(proc: &ProcFs, pid: u32) -> CommandResult {
+    let cgroups = inspect::cgroups(proc, pid).map_err(|e| section_error(proc, pid, "cgroup", e))?;
+    let mut out = CommandOutput { headers: ["HIERARCHY", "CONTROLLERS", "PATH"].map(String::from).to_vec(), ..Default::default() };
+    for cgroup in cgroups {
+        let controllers = if cgroup.hierarchy == 0 && cgroup.controllers.is_empty() { "(unified)".to_string() } else { cgroup.controllers };
+        out.push_record(
+            Record {
+                fields: vec![
+                    ("hierarchy".to_string(), Field::Int(cgroup.hierarchy.into())),
+                    ("controllers".to_string(), Field::Text(controllers)),
+                    ("path".to_string(), Field::Text(cgroup.path)),
+                ],
+            },
+            Tone::Normal,
+        );
     }
+    Ok(out)
 }
 
-pub fn systemctl_disable(service: &str) {
-    println!("{}", "🔌 Disabling service...".bright_yellow());
-    let output = std::process::Command::new("systemctl")
-        .arg("disable")
-        .arg(service)
-        .output()
-        .expect("Failed to disable service");
-    
-    if output.status.success() {
-        println!("{}", "✅ Service disabled successfully!".bright_green());
-    } else {
-        println!("{}", "❌ Failed to disable service:".bright_red());
-        println!("{}", String::from_utf8_lossy(&output.stderr));
+fn inspect_namespaces(proc: &ProcFs, pid: u32) -> CommandResult {
+    let namespaces = inspect::namespaces(proc, pid).map_err(|e| section_error(proc, pid, "ns", e))?;
+    // Namespaces that differ from init's are what put a process in a container.
+    let init = inspect::namespaces(proc, 1).ok().filter(|init| !init.is_empty());
+    let mut out = CommandOutput { headers: ["TYPE", "INODE", "VS INIT"].map(String::from).to_vec(), ..Default::default() };
+    for namespace in namespaces {
+        let shared = init.as_ref().map(|init| init.contains(&namespace));
+        let (compared, tone) = match shared {
+            Some(true) => ("shared", Tone::Normal),
+            Some(false) => ("own", Tone::Info),
+            None => ("unknown", Tone::Normal),
+        };
+        out.push_record(
+            Record {
+                fields: vec![
+                    ("type".to_string(), Field::Text(namespace.kind)),
+                    ("inode".to_string(), Field::Int(namespace.inode as i64)),
+                    ("vs_init".to_string(), Field::Text(compared.to_string())),
+                ],
+            },
+            tone,
+        );
+    }
+    if init.is_none() {
+        out.note(Tone::Warn, "Can't read init's namespaces to compare with.");
     }
+    Ok(out)
 }
 
-pub fn systemctl_status() {
-    println!("{}", "📊 Checking system status...".bright_cyan());
-    let output = std::process::Command::new("systemctl")
-        .arg("status")
-        .output()
-        .expect("Failed to get system status");
-    
-    println!("{}", String::from_utf8_lossy(&output.stdout));
+fn inspect_threads(proc: &ProcFs, pid: u32) -> CommandResult {
+    let threads = inspect::threads(proc, pid).map_err(|e| section_error(proc, pid, "task", e))?;
+    let mut out = CommandOutput { headers: ["TID", "NAME", "S", "CPU TIME"].map(String::from).to_vec(), ..Default::default() };
+    for thread in threads {
+        out.rows.push(Row::new(
+            vec![thread.tid.to_string(), thread.name.clone(), thread.state.to_string(), format!("{:.2}s", thread.cpu_seconds)],
+            state_tone(thread.state),
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("tid".to_string(), Field::Int(thread.tid.into())),
+                ("name".to_string(), Field::Text(thread.name)),
+                ("state".to_string(), Field::Text(thread.state.to_string())),
+                ("cpu_seconds".to_string(), Field::Float(thread.cpu_seconds)),
+            ],
+        });
+    }
+    Ok(out)
 }
 
-pub fn systemctl_list() {
-    println!("{}", "📋 Listing all services...".bright_cyan());
-    let output = std::process::Command::new("systemctl")
-        .arg("list-units")
-        .arg("--type=service")
-        .arg("--all")
-        .output()
-        .expect("Failed to list services");
-    
-    println!("{}", String::from_utf8_lossy(&output.stdout));
+fn inspect_io(proc: &ProcFs, pid: u32) -> CommandResult {
+    let io = inspect::io_counters(proc, pid).map_err(|e| section_error(proc, pid, "io", e))?;
+    let count = |value: u64| Ok(Field::Int(value as i64));
+    let mut out = key_values(vec![
+        ("Read (all)", "rchar", Ok(Field::Bytes(io.rchar))),
+        ("Written (all)", "wchar", Ok(Field::Bytes(io.wchar))),
+        ("Read calls", "syscr", count(io.syscr)),
+        ("Write calls", "syscw", count(io.syscw)),
+        ("Read from disk", "read_bytes", Ok(Field::Bytes(io.read_bytes))),
+        ("Written to disk", "write_bytes", Ok(Field::Bytes(io.write_bytes))),
+        ("Cancelled writes", "cancelled_write_bytes", Ok(Field::Bytes(io.cancelled_write_bytes))),
+    ]);
+    out.note(Tone::Info, "\"All\" includes page cache hits, pipes and sockets; \"disk\" is what reached storage.");
+    Ok(out)
 }
 
-pub fn service_status(service: &str) {
-    println!("{}", format!("Checking status of service {}...", service).bright_cyan());
-    let output = Command::new("service")
-        .arg(service)
-        .arg("status")
-        .output();
+// Resource Monitoring
+
+/// `interval` seconds between readings and how many to take, from the `SAMPLING` arguments.
+fn sampling(interval: &str, count: &str) -> (Duration, usize) {
+    (Duration::from_secs(interval.parse().unwrap_or(1)), count.parse().unwrap_or(5))
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Service status check failed:".bright_red(), e),
+/// Take a reading, then `count` more `interval` apart, calling `each` with every consecutive
+/// pair, the seconds between them and the time of the later one. False if `/proc` couldn't be read.
+fn sample<T>(interval: Duration, count: usize, read: impl Fn() -> Option<T>, mut each: impl FnMut(&T, &T, f64, &str)) -> bool {
+    let Some(mut before) = read() else { return false };
+    let mut taken = Instant::now();
+    for _ in 0..count {
+        thread::sleep(interval);
+        let Some(after) = read() else { return false };
+        let seconds = taken.elapsed().as_secs_f64();
+        taken = Instant::now();
+        each(&before, &after, seconds, &Local::now().format("%H:%M:%S").to_string());
+        before = after;
     }
+    true
 }
 
-pub fn chkrootkit() {
-    println!("{}", "Scanning for rootkits with chkrootkit...".bright_cyan());
-    let output = Command::new("chkrootkit")
-        .output();
+fn unreadable(proc: &ProcFs, file: &str) -> CommandError {
+    CommandError::Unreadable(proc.path(file).display().to_string())
+}
+
+fn percent_field(value: f64) -> Field {
+    Field::Percent((value * 10.0).round() / 10.0)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Chkrootkit scan failed:".bright_red(), e),
+pub fn free(proc: &ProcFs) -> CommandResult {
+    let memory = memory::read(proc).ok_or_else(|| unreadable(proc, "meminfo"))?;
+    let mut out = CommandOutput {
+        title: "Memory Usage:".to_string(),
+        headers: ["", "total", "used", "free", "shared", "buff/cache", "available"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    let mem = [memory.total, memory.used(), memory.free, memory.shared, memory.buffers + memory.cached, memory.available].map(Some);
+    // Shared, buff/cache and available only mean something for memory.
+    let swap = [Some(memory.swap_total), Some(memory.swap_used()), Some(memory.swap_free), None, None, None];
+    let rows = [("Mem", mem, memory.used_percent()), ("Swap", swap, memory.swap_percent())];
+    for (kind, values, used) in rows {
+        let keys = ["total_bytes", "used_bytes", "free_bytes", "shared_bytes", "buff_cache_bytes", "available_bytes"];
+        let fields = std::iter::once(("kind".to_string(), Field::Text(kind.to_string())))
+            .chain(keys.iter().zip(values).map(|(key, value)| (key.to_string(), value.map(Field::Bytes).unwrap_or(Field::Null))))
+            .collect();
+        out.push_record(Record { fields }, usage_tone(used as f32, 70.0, 90.0));
     }
+    Ok(out)
 }
 
-pub fn rkhunter() {
-    println!("{}", "Scanning for rootkits with rkhunter...".bright_cyan());
-    let output = Command::new("rkhunter")
-        .arg("--check")
-        .output();
+/// Processes, memory, paging and CPU per interval, like `vmstat`.
+pub fn vmstat(proc: &ProcFs, interval: &str, count: &str) -> CommandResult {
+    let (every, samples) = sampling(interval, count);
+    let mut out = CommandOutput {
+        title: format!("System Statistics (interval: {}s, count: {}):", interval, count),
+        headers: ["r", "b", "swpd", "free", "buff", "cache", "si", "so", "bi", "bo", "in", "cs", "us", "sy", "id", "wa", "st"]
+            .map(String::from)
+            .to_vec(),
+        ..Default::default()
+    };
+    let read = || Some((cpu::read(proc)?, memory::read_paging(proc)?));
+    let ok = sample(every, samples, read, |(cpu_before, paging_before), (cpu_after, paging_after), seconds, _| {
+        let memory = memory::read(proc).unwrap_or_default();
+        let usage = CpuUsage::between(&cpu_before.all, &cpu_after.all);
+        let rate = |before: u64, after: u64| (after.saturating_sub(before) as f64 / seconds).round() as i64;
+        let swap_kib = |pages: i64| pages * (memory::PAGE_SIZE / 1024) as i64;
+        let fields = vec![
+            ("r".to_string(), Field::Int(cpu_after.procs_running as i64)),
+            ("b".to_string(), Field::Int(cpu_after.procs_blocked as i64)),
+            ("swpd_bytes".to_string(), Field::Bytes(memory.swap_used())),
+            ("free_bytes".to_string(), Field::Bytes(memory.free)),
+            ("buff_bytes".to_string(), Field::Bytes(memory.buffers)),
+            ("cache_bytes".to_string(), Field::Bytes(memory.cached)),
+            ("si".to_string(), Field::Int(swap_kib(rate(paging_before.swapped_in, paging_after.swapped_in)))),
+            ("so".to_string(), Field::Int(swap_kib(rate(paging_before.swapped_out, paging_after.swapped_out)))),
+            ("bi".to_string(), Field::Int(rate(paging_before.paged_in, paging_after.paged_in))),
+            ("bo".to_string(), Field::Int(rate(paging_before.paged_out, paging_after.paged_out))),
+            ("in".to_string(), Field::Int(rate(cpu_before.interrupts, cpu_after.interrupts))),
+            ("cs".to_string(), Field::Int(rate(cpu_before.context_switches, cpu_after.context_switches))),
+            ("us".to_string(), Field::Int((usage.user + usage.nice).round() as i64)),
+            ("sy".to_string(), Field::Int((usage.system + usage.irq + usage.softirq).round() as i64)),
+            ("id".to_string(), Field::Int(usage.idle.round() as i64)),
+            ("wa".to_string(), Field::Int(usage.iowait.round() as i64)),
+            ("st".to_string(), Field::Int(usage.steal.round() as i64)),
+        ];
+        out.push_record(Record { fields }, usage_tone(usage.busy() as f32, 50.0, 80.0));
+    });
+    if !ok {
+        return Err(unreadable(proc, "stat"));
+    }
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Rkhunter scan failed:".bright_red(), e),
+/// Per-device throughput, latency and utilisation per interval, like `iostat -x`.
+pub fn iostat(proc: &ProcFs, interval: &str, count: &str) -> CommandResult {
+    let (every, samples) = sampling(interval, count);
+    let mut out = CommandOutput {
+        title: format!("I/O Statistics (interval: {}s, count: {}):", interval, count),
+        headers: ["Time", "Device", "r/s", "w/s", "read/s", "write/s", "r_await", "w_await", "%util"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    let ok = sample(every, samples, || disk::read(proc), |before, after, seconds, time| {
+        for rates in disk::rates(before, after, seconds) {
+            let fields = vec![
+                ("time".to_string(), Field::Text(time.to_string())),
+                ("device".to_string(), Field::Text(rates.name)),
+                ("reads_per_sec".to_string(), Field::Float((rates.reads_per_sec * 100.0).round() / 100.0)),
+                ("writes_per_sec".to_string(), Field::Float((rates.writes_per_sec * 100.0).round() / 100.0)),
+                ("read_bytes_per_sec".to_string(), Field::Bytes(rates.read_bytes_per_sec.round() as u64)),
+                ("write_bytes_per_sec".to_string(), Field::Bytes(rates.write_bytes_per_sec.round() as u64)),
+                ("read_await_ms".to_string(), Field::Float((rates.read_await_ms * 100.0).round() / 100.0)),
+                ("write_await_ms".to_string(), Field::Float((rates.write_await_ms * 100.0).round() / 100.0)),
+                ("util_percent".to_string(), percent_field(rates.util_percent)),
+            ];
+            out.push_record(Record { fields }, usage_tone(rates.util_percent as f32, 50.0, 80.0));
+        }
+    });
+    if !ok {
+        return Err(unreadable(proc, "diskstats"));
     }
+    if out.rows.is_empty() {
+        out.note(Tone::Warn, "No disks with any I/O yet.");
+    }
+    Ok(out)
 }
 
-pub fn lynis() {
-    println!("{}", "Running system audit with Lynis...".bright_cyan());
-    let output = Command::new("lynis")
-        .arg("audit")
-        .arg("system")
-        .output();
+/// Usage split by mode for every core and all of them together, like `mpstat -P ALL`.
+pub fn mpstat(proc: &ProcFs, interval: &str, count: &str) -> CommandResult {
+    cpu_table(proc, interval, count, true)
+        .map(|out| out.with_title(format!("CPU Statistics (interval: {}s, count: {}):", interval, count)))
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Lynis audit failed:".bright_red(), e),
+/// Usage of all cores together per interval, like `sar -u`.
+pub fn sar(proc: &ProcFs, interval: &str, count: &str) -> CommandResult {
+    cpu_table(proc, interval, count, false)
+        .map(|out| out.with_title(format!("System Activity Report (interval: {}s, count: {}):", interval, count)))
+}
+
+fn cpu_table(proc: &ProcFs, interval: &str, count: &str, per_core: bool) -> CommandResult {
+    let (every, samples) = sampling(interval, count);
+    let mut out = CommandOutput {
+        headers: ["Time", "CPU", "%usr", "%nice", "%sys", "%iowait", "%irq", "%soft", "%steal", "%idle"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    let ok = sample(every, samples, || cpu::read(proc), |before, after, _, time| {
+        let all = std::iter::once(("all".to_string(), &before.all, &after.all));
+        let cores = before.cores.iter().zip(&after.cores).enumerate().map(|(i, (b, a))| (i.to_string(), b, a));
+        let rows: Vec<_> = if per_core { all.chain(cores).collect() } else { all.collect() };
+        for (name, before, after) in rows {
+            let usage = CpuUsage::between(before, after);
+            let fields = vec![
+                ("time".to_string(), Field::Text(time.to_string())),
+                ("cpu".to_string(), Field::Text(name)),
+                ("user_percent".to_string(), percent_field(usage.user)),
+                ("nice_percent".to_string(), percent_field(usage.nice)),
+                ("system_percent".to_string(), percent_field(usage.system)),
+                ("iowait_percent".to_string(), percent_field(usage.iowait)),
+                ("irq_percent".to_string(), percent_field(usage.irq)),
+                ("softirq_percent".to_string(), percent_field(usage.softirq)),
+                ("steal_percent".to_string(), percent_field(usage.steal)),
+                ("idle_percent".to_string(), percent_field(usage.idle)),
+            ];
+            out.push_record(Record { fields }, usage_tone(usage.busy() as f32, 50.0, 80.0));
+        }
+    });
+    if !ok {
+        return Err(unreadable(proc, "stat"));
     }
+    Ok(out)
 }
 
-pub fn clamscan(path: &str) {
-    println!("{}", format!("Scanning {} for viruses...", path).bright_cyan());
-    let output = Command::new("clamscan")
-        .arg("-r")
-        .arg(path)
-        .output();
+/// How long tasks stalled on CPU, memory and I/O over the last 10s, 1m and 5m.
+pub fn pressure(proc: &ProcFs) -> CommandResult {
+    let mut out = CommandOutput {
+        title: "Pressure Stall Information:".to_string(),
+        headers: ["Resource", "Scope", "avg10", "avg60", "avg300", "Total stall"].map(String::from).to_vec(),
+        ..Default::default()
+    };
+    for pressure in pressure::read(proc) {
+        let tone = usage_tone(pressure.avg10 as f32, 10.0, 40.0);
+        let total = Duration::from_micros(pressure.total_us);
+        out.rows.push(Row::new(
+            vec![
+                pressure.resource.clone(),
+                pressure.scope.clone(),
+                format!("{:.2}%", pressure.avg10),
+                format!("{:.2}%", pressure.avg60),
+                format!("{:.2}%", pressure.avg300),
+                format!("{:.1}s", total.as_secs_f64()),
+            ],
+            tone,
+        ));
+        out.records.push(Record {
+            fields: vec![
+                ("resource".to_string(), Field::Text(pressure.resource)),
+                ("scope".to_string(), Field::Text(pressure.scope)),
+                ("avg10_percent".to_string(), Field::Percent(pressure.avg10)),
+                ("avg60_percent".to_string(), Field::Percent(pressure.avg60)),
+                ("avg300_percent".to_string(), Field::Percent(pressure.avg300)),
+                ("total_us".to_string(), Field::Int(pressure.total_us as i64)),
+            ],
+        });
+    }
+    if out.rows.is_empty() {
+        out.note(Tone::Warn, format!("No pressure information in {} (the kernel needs CONFIG_PSI)", proc.path("pressure").display()));
+    }
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "ClamAV scan failed:".bright_red(), e),
+// Disk and Storage
+
+const DF_SCHEMA: [(&str, Kind); 6] = [
+    ("filesystem", Kind::Text),
+    ("size_bytes", Kind::Bytes),
+    ("used_bytes", Kind::Bytes),
+    ("available_bytes", Kind::Bytes),
+    ("use_percent", Kind::Percent),
+    ("mounted_on", Kind::Text),
+];
+
+pub fn df(exec: &dyn Executor) -> CommandResult {
+    let mut out = capture(exec, "df", &["-B1"])?.with_title("Disk Usage:");
+    out.headers = ["Filesystem", "Size", "Used", "Avail", "Use%", "Mounted on"].map(String::from).to_vec();
+    let stdout = out.stdout.clone();
+    for line in stdout.lines().skip(1) {
+        let record = Record::from_columns(&DF_SCHEMA, &split_columns(line, DF_SCHEMA.len()));
+        let usage = record.get("use_percent").and_then(|u| u.as_f64()).unwrap_or(0.0) as f32;
+        out.push_record(record, usage_tone(usage, 70.0, 90.0));
     }
+    Ok(out)
 }
 
-pub fn fail2ban() {
-    println!("{}", "🔒 Checking fail2ban status...".bright_cyan());
-    let output = std::process::Command::new("sudo")
-        .arg("fail2ban-client")
-        .arg("status")
-        .output()
-        .expect("Failed to execute fail2ban-client");
-    println!("{}", String::from_utf8_lossy(&output.stdout));
+pub fn du(exec: &dyn Executor, path: &str) -> CommandResult {
+    let mut out = capture(exec, "du", &["-h", "--max-depth=1", path])?.with_title(format!("Directory sizes in {}:", path));
+    out.headers = vec!["Size".to_string(), "Path".to_string()];
+    out.rows = out.stdout.lines().map(|line| Row::new(split_columns(line, 2), Tone::Normal)).collect();
+    Ok(out)
 }
 
-pub fn gpg_encrypt(file: &str, recipient: &str) {
-    println!("{}", format!("Encrypting {} for {}...", file, recipient).bright_cyan());
-    let output = Command::new("gpg")
-        .arg("--encrypt")
-        .arg("--recipient")
-        .arg(recipient)
-        .arg(file)
-        .output();
+pub fn ncdu(exec: &dyn Executor, path: &str) -> CommandResult {
+    let mut out = attach(exec, "ncdu", &[path])?;
+    out.note(Tone::Good, "Ncdu closed.");
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "GPG encryption failed:".bright_red(), e),
+const LSBLK_SCHEMA: [(&str, Kind); 5] = [
+    ("name", Kind::Text),
+    ("size_bytes", Kind::Bytes),
+    ("type", Kind::Text),
+    ("mountpoint", Kind::Text),
+    ("fstype", Kind::Text),
+];
+
+pub fn lsblk(exec: &dyn Executor) -> CommandResult {
+    let columns = ["NAME", "SIZE", "TYPE", "MOUNTPOINT", "FSTYPE"];
+    let mut out = capture(exec, "lsblk", &["-b", "-P", "-o", &columns.join(",")])?.with_title("Block Devices:");
+    out.headers = columns.map(String::from).to_vec();
+    let stdout = out.stdout.clone();
+    for line in stdout.lines() {
+        let pairs = parse_key_values(line);
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|c| pairs.iter().find(|(k, _)| k == c).map(|(_, v)| v.clone()).unwrap_or_default())
+            .collect();
+        let tone = mount_tone(&fields[3]);
+        out.push_record(Record::from_columns(&LSBLK_SCHEMA, &fields), tone);
     }
+    Ok(out)
 }
 
-pub fn gpg_decrypt(file: &str) {
-    println!("{}", format!("Decrypting {}...", file).bright_cyan());
-    let output = Command::new("gpg")
-        .arg("--decrypt")
-        .arg(file)
-        .output();
+/// Parse `KEY="value" KEY2="value 2"` pairs as printed by `lsblk -P`.
+fn parse_key_values(line: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim();
+    while let Some(eq) = rest.find("=\"") {
+        let key = rest[..eq].trim().to_string();
+        let value_start = eq + 2;
+        let value_end = rest[value_start..].find('"').map(|i| value_start + i).unwrap_or(rest.len());
+        pairs.push((key, rest[value_start..value_end].to_string()));
+        rest = rest.get(value_end + 1..).unwrap_or("");
+    }
+    pairs
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "GPG decryption failed:".bright_red(), e),
+fn mount_tone(mountpoint: &str) -> Tone {
+    if mountpoint == "/" {
+        Tone::Warn
+    } else if mountpoint.starts_with('/') {
+        Tone::Good
+    } else {
+        Tone::Info
     }
 }
 
-pub fn openssl_encrypt(file: &str, password: &str) {
-    println!("{}", format!("Encrypting {} with OpenSSL...", file).bright_cyan());
-    let output = Command::new("openssl")
-        .arg("enc")
-        .arg("-aes-256-cbc")
-        .arg("-salt")
-        .arg("-in")
-        .arg(file)
-        .arg("-out")
-        .arg(format!("{}.enc", file))
-        .arg("-pass")
-        .arg(format!("pass:{}", password))
-        .output();
+const MOUNT_SCHEMA: [(&str, Kind); 4] = [
+    ("device", Kind::Text),
+    ("mountpoint", Kind::Text),
+    ("fstype", Kind::Text),
+    ("options", Kind::Text),
+];
+
+pub fn mount(exec: &dyn Executor) -> CommandResult {
+    let mut out = capture(exec, "mount", &[])?.with_title("Mounted Filesystems:");
+    out.headers = ["Device", "Mount point", "Type", "Options"].map(String::from).to_vec();
+    let stdout = out.stdout.clone();
+    for line in stdout.lines() {
+        // "<device> on <mountpoint> type <fstype> (<options>)"
+        let Some((device, rest)) = line.split_once(" on ") else { continue };
+        let Some((mountpoint, rest)) = rest.split_once(" type ") else { continue };
+        let (fstype, options) = rest.split_once(' ').unwrap_or((rest, ""));
+        let options = options.trim_start_matches('(').trim_end_matches(')');
+        let fields = [device, mountpoint, fstype, options].map(String::from);
+        out.push_record(Record::from_columns(&MOUNT_SCHEMA, &fields), mount_tone(mountpoint));
+    }
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "OpenSSL encryption failed:".bright_red(), e),
+/// Resolve a device name to its mount point; paths are returned unchanged.
+fn resolve_mountpoint(exec: &dyn Executor, target: &str) -> String {
+    if target.starts_with('/') {
+        return target.to_string();
     }
+    probe(exec, "findmnt", &["-n", "-o", "TARGET", target])
+        .ok()
+        .map(|out| out.stdout.lines().next().unwrap_or("").trim().to_string())
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| target.to_string())
 }
 
-pub fn openssl_decrypt(file: &str, password: &str) {
-    println!("{}", format!("Decrypting {} with OpenSSL...", file).bright_cyan());
-    let output = Command::new("openssl")
-        .arg("enc")
-        .arg("-aes-256-cbc")
-        .arg("-d")
-        .arg("-salt")
-        .arg("-in")
-        .arg(file)
-        .arg("-out")
-        .arg(file.trim_end_matches(".enc"))
-        .arg("-pass")
-        .arg(format!("pass:{}", password))
-        .output();
+/// Unmount a mount point, resolving a device name to its mount point first.
+pub fn umount(exec: &dyn Executor, target: &str) -> CommandResult {
+    let target = require(target, "device or mount point")?;
+    let mountpoint = resolve_mountpoint(exec, target);
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "OpenSSL decryption failed:".bright_red(), e),
+    let mut out = capture(exec, "umount", &[&mountpoint])?;
+    if out.success() {
+        out.note(Tone::Good, format!("Successfully unmounted {}", mountpoint));
     }
+    Ok(out)
 }
 
-pub fn apt_install(package: &str) {
-    println!("{}", format!("Installing {} with apt...", package).bright_cyan());
-    let output = Command::new("sudo")
-        .arg("apt")
-        .arg("install")
-        .arg("-y")
-        .arg(package)
-        .output();
+// Log Diving
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Apt install failed:".bright_red(), e),
+pub fn journalctl_system(exec: &dyn Executor) -> CommandResult {
+    let mut out = capture(exec, "journalctl", &["-xe", "--no-pager", "--since", "1 hour ago"])?
+        .with_title("System journal (last hour):");
+    out.rows = log_rows(&out.stdout);
+    if out.rows.is_empty() {
+        out.note(Tone::Bad, "No recent system logs found.");
     }
+    Ok(out)
 }
 
-pub fn apt_remove(package: &str) {
-    println!("{}", format!("Removing {} with apt...", package).bright_cyan());
-    let output = Command::new("sudo")
-        .arg("apt")
-        .arg("remove")
-        .arg("-y")
-        .arg(package)
-        .output();
+/// Logs for one service, or the last entries of every running service when none is given.
+pub fn journalctl_service(exec: &dyn Executor, service: &str) -> CommandResult {
+    let service = service.trim();
+    if !service.is_empty() {
+        let mut out = capture(exec, "journalctl", &["-u", service, "-n", "50", "--no-pager"])?
+            .with_title(format!("Logs for {}:", service));
+        out.rows = log_rows(&out.stdout);
+        return Ok(out);
+    }
+
+    let mut out = capture(exec, "systemctl", &["list-units", "--type=service", "--state=running", "--no-legend", "--plain"])?
+        .with_title("Recent logs of running services:");
+    let services: Vec<String> = out
+        .stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().next().map(String::from))
+        .collect();
+
+    for service in services {
+        out.rows.push(Row::new(vec![format!("• {}", service)], Tone::Good));
+        if let Ok(logs) = capture(exec, "journalctl", &["-u", &service, "-n", "5", "--no-pager"]) {
+            out.duration += logs.duration;
+            out.rows.extend(logs.stdout.lines().map(|line| Row::new(vec![format!("  {}", line)], Tone::Info)));
+        }
+    }
+    if out.rows.is_empty() {
+        out.note(Tone::Bad, "No running services found.");
+    }
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Apt remove failed:".bright_red(), e),
+pub fn dmesg(exec: &dyn Executor) -> CommandResult {
+    let mut out = capture(exec, "dmesg", &["-T", "--level=err,warn"])?.with_title("Recent kernel messages:");
+    out.rows = log_rows(&out.stdout);
+    if out.rows.is_empty() && out.success() {
+        out.note(Tone::Bad, "No recent kernel messages found.");
     }
+    Ok(out)
+}
+
+pub fn tail_syslog(exec: &dyn Executor) -> CommandResult {
+    let mut out = capture(exec, "tail", &["-n", "50", "/var/log/syslog"])?.with_title("Recent system log entries:");
+    out.rows = log_rows(&out.stdout);
+    Ok(out)
+}
+
+pub fn auth_log(exec: &dyn Executor) -> CommandResult {
+    let mut out = capture(exec, "tail", &["-n", "50", "/var/log/auth.log"])?.with_title("Recent authentication logs:");
+    out.rows = log_rows(&out.stdout);
+    Ok(out)
 }
 
-pub fn apt_update() {
-    println!("{}", "Updating package lists with apt...".bright_cyan());
-    let output = Command::new("sudo")
-        .arg("apt")
-        .arg("update")
-        .output();
+// Service Management
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Apt update failed:".bright_red(), e),
+fn systemctl_action(exec: &dyn Executor, action: &str, service: &str, done: &str) -> CommandResult {
+    let service = require(service, "service name")?;
+    let mut out = capture(exec, "systemctl", &[action, service])?;
+    if out.success() {
+        out.note(Tone::Good, format!("✅ Service {} {}!", service, done));
     }
+    Ok(out)
 }
 
-pub fn pacman_install(package: &str) {
-    println!("{}", format!("Installing {} with pacman...", package).bright_cyan());
-    let output = Command::new("sudo")
-        .arg("pacman")
-        .arg("-S")
-        .arg("--noconfirm")
-        .arg(package)
-        .output();
+pub fn systemctl_start(exec: &dyn Executor, service: &str) -> CommandResult {
+    systemctl_action(exec, "start", service, "started")
+}
+
+pub fn systemctl_stop(exec: &dyn Executor, service: &str) -> CommandResult {
+    systemctl_action(exec, "stop", service, "stopped")
+}
+
+pub fn systemctl_restart(exec: &dyn Executor, service: &str) -> CommandResult {
+    systemctl_action(exec, "restart", service, "restarted")
+}
+
+pub fn systemctl_enable(exec: &dyn Executor, service: &str) -> CommandResult {
+    systemctl_action(exec, "enable", service, "enabled")
+}
+
+pub fn systemctl_disable(exec: &dyn Executor, service: &str) -> CommandResult {
+    systemctl_action(exec, "disable", service, "disabled")
+}
+
+pub fn systemctl_status(exec: &dyn Executor) -> CommandResult {
+    Ok(capture(exec, "systemctl", &["status", "--no-pager"])?.with_title("📊 System status:"))
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Pacman install failed:".bright_red(), e),
+const SYSTEMCTL_LIST_SCHEMA: [(&str, Kind); 5] = [
+    ("unit", Kind::Text),
+    ("load", Kind::Text),
+    ("active", Kind::Text),
+    ("sub", Kind::Text),
+    ("description", Kind::Text),
+];
+
+pub fn systemctl_list(exec: &dyn Executor) -> CommandResult {
+    let mut out = capture(exec, "systemctl", &["list-units", "--type=service", "--all", "--no-legend", "--plain"])?
+        .with_title("📋 All services:");
+    out.headers = ["Unit", "Load", "Active", "Sub", "Description"].map(String::from).to_vec();
+    let stdout = out.stdout.clone();
+    for line in stdout.lines() {
+        let fields = split_columns(line, SYSTEMCTL_LIST_SCHEMA.len());
+        let tone = match fields.get(2).map(String::as_str) {
+            Some("active") => Tone::Good,
+            Some("failed") => Tone::Bad,
+            _ => Tone::Info,
+        };
+        out.push_record(Record::from_columns(&SYSTEMCTL_LIST_SCHEMA, &fields), tone);
     }
+    Ok(out)
+}
+
+// Security
+
+pub fn chkrootkit(exec: &dyn Executor) -> CommandResult {
+    Ok(capture(exec, "chkrootkit", &[])?.with_title("Rootkit scan (chkrootkit):"))
+}
+
+pub fn rkhunter(exec: &dyn Executor) -> CommandResult {
+    Ok(capture(exec, "rkhunter", &["--check", "--skip-keypress"])?.with_title("Rootkit scan (rkhunter):"))
 }
 
-pub fn pacman_remove(package: &str) {
-    println!("{}", format!("Removing {} with pacman...", package).bright_cyan());
-    let output = Command::new("sudo")
-        .arg("pacman")
-        .arg("-R")
-        .arg("--noconfirm")
-        .arg(package)
-        .output();
+pub fn lynis(exec: &dyn Executor) -> CommandResult {
+    Ok(capture(exec, "lynis", &["audit", "system"])?.with_title("System audit (Lynis):"))
+}
+
+pub fn clamscan(exec: &dyn Executor, path: &str) -> CommandResult {
+    let path = require(path, "path")?;
+    Ok(capture(exec, "clamscan", &["-r", path])?.with_title(format!("Virus scan of {}:", path)))
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Pacman remove failed:".bright_red(), e),
+pub fn fail2ban(exec: &dyn Executor) -> CommandResult {
+    Ok(capture(exec, "fail2ban-client", &["status"])?.with_title("🔒 fail2ban status:"))
+}
+
+pub fn gpg_encrypt(exec: &dyn Executor, file: &str, recipient: &str) -> CommandResult {
+    let file = require(file, "file")?;
+    let recipient = require(recipient, "recipient")?;
+    let mut out = capture(exec, "gpg", &["--encrypt", "--recipient", recipient, file])?;
+    if out.success() {
+        out.note(Tone::Good, format!("✅ Encrypted {} for {}", file, recipient));
     }
+    Ok(out)
 }
 
-pub fn pacman_update() {
-    println!("{}", "Updating system with pacman...".bright_cyan());
-    let output = Command::new("sudo")
-        .arg("pacman")
-        .arg("-Syu")
-        .arg("--noconfirm")
-        .output();
+pub fn gpg_decrypt(exec: &dyn Executor, file: &str) -> CommandResult {
+    let file = require(file, "file")?;
+    Ok(capture(exec, "gpg", &["--decrypt", file])?.with_title(format!("Decrypted {}:", file)))
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Pacman update failed:".bright_red(), e),
+pub fn openssl_encrypt(exec: &dyn Executor, file: &str, password: &str) -> CommandResult {
+    let file = require(file, "file")?;
+    let password = require(password, "password")?;
+    let output = format!("{}.enc", file);
+    let pass = format!("pass:{}", password);
+    let mut out = capture(exec, "openssl", &["enc", "-aes-256-cbc", "-salt", "-in", file, "-out", &output, "-pass", &pass])?;
+    if out.success() {
+        out.note(Tone::Good, format!("✅ Encrypted {} to {}", file, output));
     }
+    Ok(out)
 }
 
-pub fn yay_install(package: &str) {
-    println!("{}", format!("Installing {} with yay...", package).bright_cyan());
-    let output = Command::new("yay")
-        .arg("-S")
-        .arg("--noconfirm")
-        .arg(package)
-        .output();
+pub fn openssl_decrypt(exec: &dyn Executor, file: &str, password: &str) -> CommandResult {
+    let file = require(file, "file")?;
+    let password = require(password, "password")?;
+    let output = decrypted_path(file)?;
+    let pass = format!("pass:{}", password);
+    let mut out = capture(exec, "openssl", &["enc", "-aes-256-cbc", "-d", "-salt", "-in", file, "-out", &output, "-pass", &pass])?;
+    if out.success() {
+        out.note(Tone::Good, format!("✅ Decrypted {} to {}", file, output));
+    }
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Yay install failed:".bright_red(), e),
+/// Where `openssl_decrypt` writes its plaintext: the input path without `.enc`.
+fn decrypted_path(file: &str) -> Result<String, CommandError> {
+    match file.strip_suffix(".enc") {
+        Some(output) if !output.is_empty() => Ok(output.to_string()),
+        _ => Err(CommandError::InvalidArgument(format!("{} does not end in .enc", file))),
     }
 }
 
-pub fn which(binary: &str) {
-    println!("{}", format!("Finding binary {}...", binary).bright_cyan());
-    let output = Command::new("which")
-        .arg(binary)
-        .output();
+// Package Management
+
+fn package_manager() -> Result<&'static dyn PackageManager, CommandError> {
+    packages::detect().ok_or_else(|| CommandError::NotInstalled("a supported package manager (apt, pacman, yay, paru, dnf, zypper, apk)".to_string()))
+}
+
+/// Space-separated package names, mapped to what this manager calls them.
+fn package_list(manager: &dyn PackageManager, packages: &str) -> Result<Vec<String>, CommandError> {
+    let packages = require(packages, "package")?;
+    Ok(packages.split_whitespace().map(|name| packages::package_name(manager, name)).collect())
+}
+
+pub fn pkg_install(exec: &dyn Executor, packages: &str) -> CommandResult {
+    let manager = package_manager()?;
+    let packages = package_list(manager, packages)?;
+    Ok(run(exec, manager.install(&packages))?.with_title(format!("📦 Installing {} with {}:", packages.join(" "), manager.name())))
+}
+
+pub fn pkg_remove(exec: &dyn Executor, packages: &str) -> CommandResult {
+    let manager = package_manager()?;
+    let packages = package_list(manager, packages)?;
+    Ok(run(exec, manager.remove(&packages))?.with_title(format!("📦 Removing {} with {}:", packages.join(" "), manager.name())))
+}
+
+/// Refresh the package index and upgrade everything, stopping at the first failing step.
+pub fn pkg_update(exec: &dyn Executor) -> CommandResult {
+    let manager = package_manager()?;
+    let mut out = CommandOutput::default().with_title(format!("📦 Updating with {}:", manager.name()));
+    for invocation in manager.update() {
+        let step = run(exec, invocation)?;
+        if !out.argv.is_empty() {
+            out.argv.push("&&".to_string());
+        }
+        out.argv.extend(step.argv);
+        out.stdout.push_str(&step.stdout);
+        out.stderr.push_str(&step.stderr);
+        out.status = step.status;
+        out.dry_run = step.dry_run;
+        out.duration += step.duration;
+        out.notes.extend(step.notes);
+        if !out.dry_run && !out.success() {
+            break;
+        }
+    }
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Which failed:".bright_red(), e),
+pub fn pkg_search(exec: &dyn Executor, query: &str) -> CommandResult {
+    let query = require(query, "query")?;
+    let manager = package_manager()?;
+    let mut out = run(exec, manager.search(query))?.with_title(format!("📦 Packages matching '{}' ({}):", query, manager.name()));
+    let found = manager.parse_search(&out.stdout);
+    package_table(&mut out, found);
+    if out.rows.is_empty() && !out.dry_run {
+        out.note(Tone::Warn, format!("No packages match '{}'", query));
     }
+    Ok(out)
+}
+
+pub fn pkg_info(exec: &dyn Executor, package: &str) -> CommandResult {
+    let manager = package_manager()?;
+    let package = packages::package_name(manager, require(package, "package")?);
+    Ok(run(exec, manager.info(&package))?.with_title(format!("📦 {} ({}):", package, manager.name())))
 }
 
-pub fn whereis(binary: &str) {
-    println!("{}", format!("Locating {}...", binary).bright_cyan());
-    let output = Command::new("whereis")
-        .arg(binary)
-        .output();
+pub fn pkg_list_installed(exec: &dyn Executor) -> CommandResult {
+    let manager = package_manager()?;
+    let mut out = run(exec, manager.list_installed())?.with_title(format!("Installed packages ({}):", manager.name()));
+    let installed = manager.parse_installed(&out.stdout);
+    package_table(&mut out, installed);
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Whereis failed:".bright_red(), e),
+pub fn pkg_list_upgradable(exec: &dyn Executor) -> CommandResult {
+    let manager = package_manager()?;
+    let mut out = run(exec, manager.list_upgradable())?.with_title(format!("Upgradable packages ({}):", manager.name()));
+    let upgrades = manager.parse_upgradable(&out.stdout);
+    out.headers = ["Name", "Installed", "Available"].map(String::from).to_vec();
+    for upgrade in &upgrades {
+        out.push_record(
+            Record {
+                fields: vec![
+                    ("name".to_string(), Field::Text(upgrade.name.clone())),
+                    ("installed".to_string(), Field::Text(upgrade.current.clone())),
+                    ("available".to_string(), Field::Text(upgrade.available.clone())),
+                ],
+            },
+            Tone::Warn,
+        );
+    }
+    if !out.dry_run {
+        match upgrades.len() {
+            0 => out.note(Tone::Good, "✅ Everything is up to date"),
+            n => out.note(Tone::Warn, format!("⬆️ {} package(s) can be upgraded", n)),
+        }
     }
+    Ok(out)
 }
 
-pub fn find_binary(name: &str) {
-    println!("{}", format!("Searching for {}...", name).bright_cyan());
-    let output = Command::new("find")
-        .arg("/usr/bin")
-        .arg("/usr/local/bin")
-        .arg("-name")
-        .arg(format!("*{}*", name))
-        .output();
+pub fn pkg_owns_file(exec: &dyn Executor, path: &str) -> CommandResult {
+    let path = require(path, "path")?;
+    let manager = package_manager()?;
+    let mut out = run(exec, manager.owns_file(path))?.with_title(format!("📦 Owner of {} ({}):", path, manager.name()));
+    match manager.parse_owner(&out.stdout).filter(|_| out.success()) {
+        Some(owner) => {
+            out.records.push(Record {
+                fields: vec![
+                    ("path".to_string(), Field::Text(path.to_string())),
+                    ("package".to_string(), Field::Text(owner.clone())),
+                ],
+            });
+            out.note(Tone::Good, format!("✅ {} belongs to {}", path, owner));
+        }
+        None if !out.dry_run => out.note(Tone::Warn, format!("⚠️ {} is not owned by any package", path)),
+        None => {}
+    }
+    Ok(out)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Find failed:".bright_red(), e),
+/// Fill in the Name/Version/Description table for a list of packages.
+fn package_table(out: &mut CommandOutput, packages: Vec<Package>) {
+    out.headers = ["Name", "Version", "Description"].map(String::from).to_vec();
+    for package in packages {
+        out.push_record(
+            Record {
+                fields: vec![
+                    ("name".to_string(), Field::Text(package.name)),
+                    ("version".to_string(), Field::Text(package.version)),
+                    ("description".to_string(), Field::Text(package.description)),
+                ],
+            },
+            Tone::Normal,
+        );
     }
 }
 
-pub fn locate_binary(name: &str) {
-    println!("{}", format!("Locating {}...", name).bright_cyan());
-    let output = Command::new("locate")
-        .arg(name)
-        .output();
+pub fn which(exec: &dyn Executor, binary: &str) -> CommandResult {
+    let binary = require(binary, "binary")?;
+    capture(exec, "which", &[binary])
+}
+
+pub fn whereis(exec: &dyn Executor, binary: &str) -> CommandResult {
+    let binary = require(binary, "binary")?;
+    capture(exec, "whereis", &[binary])
+}
+
+pub fn find_binary(exec: &dyn Executor, name: &str) -> CommandResult {
+    let name = require(name, "name")?;
+    let pattern = format!("*{}*", name);
+    capture(exec, "find", &["/usr/bin", "/usr/local/bin", "-name", &pattern])
+}
+
+pub fn locate_binary(exec: &dyn Executor, name: &str) -> CommandResult {
+    let name = require(name, "name")?;
+    capture(exec, "locate", &[name])
+}
+
+// Previews: what a destructive command is about to touch, shown before asking for confirmation.
+
+/// PIDs, owners and command lines `kill` would signal.
+pub fn preview_kill(proc: &ProcFs, target: &str, by: &str) -> Result<Vec<String>, CommandError> {
+    Ok(kill_targets(proc, target, by)?
+        .iter()
+        .map(|process| {
+            let command: String = process.command.replace(['\n', '\t'], " ").chars().take(100).collect();
+            format!("PID {} ({}): {}", process.pid, process.user, command)
+        })
+        .collect())
+}
+
+/// What `kill-tree` will signal: the process and its descendants, drawn as a tree.
+pub fn preview_tree(proc: &ProcFs, target: &str) -> Result<Vec<String>, CommandError> {
+    let processes = process::list(proc);
+    let tree = Tree::new(&processes);
+    let pid = tree_target(target)?;
+    if tree.get(pid).is_none() {
+        return Err(CommandError::NoMatch(format!("PID {}", pid)));
+    }
+    Ok(tree
+        .draw(&[pid], &HashSet::new(), None)
+        .iter()
+        .filter_map(|node| {
+            let process = tree.get(node.pid)?;
+            let command: String = process.command.replace(['\n', '\t'], " ").chars().take(80).collect();
+            let skipped = if node.pid == std::process::id() { "  (gremlin itself, skipped)" } else { "" };
+            Some(format!("PID {:<7} {}{}{}", node.pid, node.prefix, command, skipped))
+        })
+        .collect())
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Locate failed:".bright_red(), e),
+/// The mount point being removed and every process with a cwd, root, executable or open
+/// file under it.
+pub fn preview_mount_users(exec: &dyn Executor, target: &str) -> Result<Vec<String>, CommandError> {
+    let mountpoint = resolve_mountpoint(exec, target);
+    let mut lines = vec![format!("Mount point {}", mountpoint)];
+    // Every process lives under `/`; listing them all says nothing.
+    if mountpoint != "/" {
+        lines.extend(mount_users(&ProcFs::system(), &mountpoint));
     }
+    Ok(lines)
+}
+
+/// One line per process, other than gremlin, using a path under `mountpoint`.
+fn mount_users(proc: &ProcFs, mountpoint: &str) -> Vec<String> {
+    const MAX_CHARS: usize = 100;
+
+    let root = Path::new(mountpoint);
+    let own_pid = std::process::id();
+    process::list(proc)
+        .into_iter()
+        .filter(|process| process.pid != own_pid)
+        .filter(|process| {
+            let links = ["cwd", "root", "exe"].iter().filter_map(|name| inspect::link(proc, process.pid, name).ok());
+            let files = inspect::fds(proc, process.pid).unwrap_or_default().into_iter().map(|fd| fd.target);
+            links.chain(files).any(|target| Path::new(&target).starts_with(root))
+        })
+        .map(|process| {
+            let command = process.command.replace(['\n', '\t'], " ");
+            let command = if command.chars().count() > MAX_CHARS {
+                format!("{}…", command.chars().take(MAX_CHARS).collect::<String>())
+            } else {
+                command
+            };
+            format!("In use by PID {}: {}", process.pid, command)
+        })
+        .collect()
 }
 
-pub fn dpkg_list() {
-    println!("{}", "Listing installed packages...".bright_cyan());
-    let output = Command::new("dpkg")
-        .arg("-l")
-        .output();
+/// Service description, state and the units that depend on it.
+pub fn preview_service(exec: &dyn Executor, service: &str) -> Result<Vec<String>, CommandError> {
+    let show = probe(exec, "systemctl", &["show", service, "--property=Description,ActiveState,MainPID"])?;
+    let mut lines: Vec<String> = show.stdout.lines().map(|line| format!("{}: {}", service, line)).collect();
+
+    let dependents = probe(exec, "systemctl", &["list-dependencies", "--reverse", "--plain", "--no-pager", service])?;
+    lines.extend(
+        dependents
+            .stdout
+            .lines()
+            .skip(1)
+            .map(|line| format!("Needed by {}", line.trim())),
+    );
+    Ok(lines)
+}
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Dpkg list failed:".bright_red(), e),
+/// Packages the package manager would remove along with the requested ones.
+pub fn preview_pkg_remove(exec: &dyn Executor, packages: &str) -> Result<Vec<String>, CommandError> {
+    let manager = package_manager()?;
+    let packages = package_list(manager, packages)?;
+    let Some(simulation) = manager.simulate_remove(&packages) else {
+        return Ok(packages.iter().map(|package| format!("Remove {}", package)).collect());
+    };
+    let simulated = run(exec, simulation)?;
+    let lines: Vec<String> = manager
+        .parse_removals(&simulated.stdout)
+        .into_iter()
+        .map(|package| format!("Remove {} {}", package.name, package.version).trim_end().to_string())
+        .collect();
+    if lines.is_empty() {
+        return Err(CommandError::NoMatch(packages.join(" ")));
     }
+    Ok(lines)
+}
+
+/// The plaintext file `openssl_decrypt` will write, and whether it already exists.
+pub fn preview_decrypt_output(file: &str) -> Result<Vec<String>, CommandError> {
+    let output = decrypted_path(file)?;
+    Ok(vec![match fs::metadata(&output) {
+        Ok(meta) => format!("Overwrite {} ({} bytes)", output, meta.len()),
+        Err(_) => format!("Create {}", output),
+    }])
 }
 
-pub fn pacman_list() {
-    println!("{}", "Listing installed packages...".bright_cyan());
-    let output = Command::new("pacman")
-        .arg("-Q")
-        .output();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::{Completed, ScriptedExecutor};
+    use crate::procfs::fixture::{FakeProcess, Fixture, KTHREAD_FLAGS};
+
+    #[test]
+    fn df_parses_bytes_and_usage() {
+        let exec = ScriptedExecutor::new().on(
+            "df -B1",
+            Completed::ok(
+                "Filesystem     1B-blocks       Used  Available Use% Mounted on\n\
+                 /dev/sda1    1000000000  950000000   50000000  95% /\n\
+                 tmpfs          20000000          0   20000000   0% /run/user/1000\n\
+                 //nas/share  3000000000 1500000000 1500000000  50% /mnt/nas share\n",
+            ),
+        );
+        let out = df(&exec).unwrap();
+        assert_eq!(exec.calls()[0].argv(), ["df", "-B1"]);
+        assert_eq!(out.records.len(), 3);
+        let root = &out.records[0];
+        assert_eq!(root.get("filesystem"), Some(&Field::Text("/dev/sda1".to_string())));
+        assert_eq!(root.get("size_bytes"), Some(&Field::Bytes(1_000_000_000)));
+        assert_eq!(root.get("available_bytes"), Some(&Field::Bytes(50_000_000)));
+        assert_eq!(root.get("use_percent"), Some(&Field::Percent(95.0)));
+        assert_eq!(out.rows[0].tone, Tone::Bad);
+        assert_eq!(out.rows[1].tone, Tone::Good);
+        // The last column keeps its spaces.
+        assert_eq!(out.records[2].get("mounted_on"), Some(&Field::Text("/mnt/nas share".to_string())));
+    }
+
+    #[test]
+    fn df_reports_a_missing_binary() {
+        let exec = ScriptedExecutor::new();
+        assert!(matches!(df(&exec), Err(CommandError::NotInstalled(program)) if program == "df"));
+    }
+
+    #[test]
+    fn free_leaves_memory_only_columns_empty_for_swap() {
+        let fixture = Fixture::new();
+        assert!(matches!(free(&fixture.proc()), Err(CommandError::Unreadable(path)) if path.ends_with("meminfo")));
+        fixture.file("meminfo", "MemTotal: 1000 kB\nMemFree: 100 kB\nMemAvailable: 50 kB\nSwapTotal: 400 kB\nSwapFree: 300 kB\n");
+        let out = free(&fixture.proc()).unwrap();
+        let (mem, swap) = (&out.records[0], &out.records[1]);
+        assert_eq!(mem.get("available_bytes"), Some(&Field::Bytes(50 * 1024)));
+        assert_eq!(out.rows[0].tone, Tone::Bad);
+        assert_eq!(swap.get("used_bytes"), Some(&Field::Bytes(100 * 1024)));
+        for key in ["shared_bytes", "buff_cache_bytes", "available_bytes"] {
+            assert_eq!(swap.get(key), Some(&Field::Null), "{}", key);
+        }
+    }
+
+    #[test]
+    fn vmstat_and_iostat_need_their_proc_files() {
+        let fixture = Fixture::new();
+        assert!(matches!(vmstat(&fixture.proc(), "0", "1"), Err(CommandError::Unreadable(path)) if path.ends_with("stat")));
+        assert!(matches!(iostat(&fixture.proc(), "0", "1"), Err(CommandError::Unreadable(path)) if path.ends_with("diskstats")));
+
+        fixture
+            .file("stat", "cpu 10 0 10 80 0 0 0 0\nintr 5\nctxt 7\nprocs_running 2\nprocs_blocked 1\n")
+            .file("vmstat", "pgpgin 1\npgpgout 2\npswpin 0\npswpout 0\n")
+            .file("meminfo", "MemTotal: 1000 kB\nMemFree: 600 kB\n")
+            .file("diskstats", "   8       0 sda 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n");
+        let out = vmstat(&fixture.proc(), "0", "2").unwrap();
+        assert_eq!(out.records.len(), 2);
+        let sample = &out.records[0];
+        assert_eq!((sample.get("r"), sample.get("b")), (Some(&Field::Int(2)), Some(&Field::Int(1))));
+        assert_eq!(sample.get("free_bytes"), Some(&Field::Bytes(600 * 1024)));
+        // Nothing moved between the readings, so the CPU counts as idle.
+        assert_eq!(sample.get("id"), Some(&Field::Int(100)));
+
+        let out = iostat(&fixture.proc(), "0", "1").unwrap();
+        assert!(out.records.is_empty());
+        assert_eq!(out.notes[0].text, "No disks with any I/O yet.");
+    }
+
+    fn probe(url: &str, status: &str, expect: &str, repeat: &str) -> CommandResult {
+        curl(&ScriptedExecutor::new(), url, "GET", "", "", "5", status, expect, repeat, "5000", false)
+    }
+
+    fn texts(out: &CommandOutput) -> Vec<&str> {
+        out.notes.iter().map(|note| note.text.as_str()).collect()
+    }
+
+    #[test]
+    fn curl_checks_status_and_body() {
+        let (port, _) = http::tests::serve(|request, _| match request.target.as_str() {
+            "/missing" => b"HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\n\r\nnope".to_vec(),
+            _ => http::tests::ok("{\"healthy\": true}"),
+        });
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let out = probe(&format!("{}/health", url), "200,204", "healthy.*true", "1").unwrap();
+        assert_eq!(out.status, Some(0));
+        assert_eq!(out.exit_code(), 0);
+        assert!(out.records.iter().any(|record| record.get("header") == Some(&Field::Text("Content-Type".to_string()))));
+        assert!(texts(&out).contains(&"✅ All checks passed"));
+
+        let out = probe(&format!("{}/missing", url), "2xx", "healthy", "1").unwrap();
+        assert_eq!(out.exit_code(), 1);
+        assert!(texts(&out).contains(&"❌ status 404 is not 2xx"));
+        assert!(texts(&out).contains(&"❌ body does not match /healthy/"));
+
+        // Without checks a 404 is still a completed request.
+        assert_eq!(probe(&format!("{}/missing", url), "", "", "1").unwrap().exit_code(), 0);
+        assert!(matches!(probe(&url, "abc", "", "1"), Err(CommandError::InvalidArgument(_))));
+        assert!(matches!(probe(&url, "", "(", "1"), Err(CommandError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn curl_repeats_and_reports_percentiles() {
+        let (port, seen) = http::tests::serve(|_, _| http::tests::ok("ok"));
+        let out = probe(&format!("http://127.0.0.1:{}/", port), "200", "", "5").unwrap();
+        assert_eq!(seen.lock().unwrap().len(), 5);
+        assert_eq!(out.records.len(), 5);
+        assert_eq!(out.records[4].get("attempt"), Some(&Field::Int(5)));
+        assert_eq!(out.records[0].get("result"), Some(&Field::Text("ok".to_string())));
+        assert!(texts(&out).iter().any(|note| note.starts_with("Total: min ") && note.contains(", p99 ")));
+        assert!(texts(&out).contains(&"5 of 5 request(s) succeeded"));
+        assert_eq!(out.exit_code(), 0);
+    }
+
+    #[test]
+    fn curl_repeat_counts_unreachable_attempts_as_failures() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let url = format!("http://127.0.0.1:{}/", port);
+        assert!(matches!(probe(&url, "", "", "1"), Err(CommandError::Unreachable(_))));
+        let out = probe(&url, "", "", "3").unwrap();
+        assert_eq!(out.rows.len(), 3);
+        assert!(out.rows.iter().all(|row| row.tone == Tone::Bad));
+        assert_eq!(out.records.len(), 3);
+        assert!(out.records.iter().all(|record| record.get("status") == Some(&Field::Null)));
+        assert_eq!(out.exit_code(), 1);
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let samples: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 0.0), Duration::from_millis(1));
+        assert_eq!(percentile(&samples, 50.0), Duration::from_millis(5));
+        assert_eq!(percentile(&samples, 90.0), Duration::from_millis(9));
+        assert_eq!(percentile(&samples, 99.0), Duration::from_millis(10));
+        assert_eq!(percentile(&samples, 100.0), Duration::from_millis(10));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn status_patterns_accept_codes_and_classes() {
+        assert!(status_pattern("200") && status_pattern("5xx") && status_pattern("2XX"));
+        assert!(!status_pattern("600") && !status_pattern("20") && !status_pattern("abc"));
+        assert!(status_matches("2xx", 204) && status_matches("404", 404));
+        assert!(!status_matches("2xx", 301) && !status_matches("200", 201));
+    }
 
-    match output {
-        Ok(out) => println!("{}", String::from_utf8_lossy(&out.stdout).bright_green()),
-        Err(e) => println!("{} {}", "Pacman list failed:".bright_red(), e),
+    /// init, kthreadd with a worker, and a service running as UID 4242 with a `1` in its command line.
+    fn signal_fixture() -> Fixture {
+        let fixture = Fixture::new();
+        fixture
+            .process(FakeProcess { pid: 1, name: "init", cmdline: &["/sbin/init", "1"], ..Default::default() })
+            .process(FakeProcess { pid: 2, name: "kthreadd", flags: KTHREAD_FLAGS, ..Default::default() })
+            .process(FakeProcess { pid: 31, ppid: 2, name: "kworker/0:1", flags: KTHREAD_FLAGS, ..Default::default() })
+            .process(FakeProcess { pid: 500, ppid: 1, name: "worker", uid: 4242, cmdline: &["/usr/bin/worker", "--queue", "1"], ..Default::default() });
+        fixture
     }
-} 
\ No newline at end of file
+
+    fn target_pids(proc: &ProcFs, target: &str, by: &str) -> Result<Vec<u32>, CommandError> {
+        kill_targets(proc, target, by).map(|targets| targets.iter().map(|process| process.pid).collect())
+    }
+
+    #[test]
+    fn kill_by_uid_and_numeric_pattern_never_reach_init_or_kernel_threads() {
+        let fixture = signal_fixture();
+        let proc = fixture.proc();
+        assert_eq!(target_pids(&proc, "4242", "user").unwrap(), [500]);
+        assert_eq!(target_pids(&proc, "1", "pattern").unwrap(), [500]);
+        assert_eq!(target_pids(&proc, ".", "pattern").unwrap(), [500]);
+        assert_eq!(target_pids(&proc, "0", "user").map_err(|e| e.exit_code()), Err(1));
+        assert!(matches!(kill_targets(&proc, "kthreadd", "name"), Err(CommandError::NoMatch(_))));
+        // An explicit PID is taken at its word.
+        assert_eq!(target_pids(&proc, "1", "").unwrap(), [1]);
+        assert_eq!(target_pids(&proc, "31", "pid").unwrap(), [31]);
+        assert!(matches!(kill_targets(&proc, "/usr/bin/worker", ""), Err(CommandError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn kill_dry_run_lists_targets_without_signalling() {
+        let fixture = signal_fixture();
+        let exec = crate::exec::RecordingExecutor::new();
+        let out = kill(&exec, &fixture.proc(), "4242", "user", "HUP", "5").unwrap();
+        assert!(out.dry_run);
+        assert_eq!(out.argv, ["kill", "-s", "HUP", "500"]);
+        assert_eq!(out.rows[0].fields[3], "HUP, then KILL after 5s");
+        assert_eq!(out.records.len(), out.rows.len());
+        let record = &out.records[0].fields;
+        assert_eq!(record[0], ("pid".to_string(), Field::Int(500)));
+        assert_eq!(record[4], ("signals".to_string(), Field::Text("HUP".to_string())));
+        assert_eq!(record[5], ("result".to_string(), Field::Text("would send".to_string())));
+    }
+
+    #[test]
+    fn kill_tree_never_signals_gremlin_itself() {
+        let own_pid = std::process::id();
+        let fixture = Fixture::new();
+        fixture
+            .process(FakeProcess { pid: 1, name: "init", cmdline: &["/sbin/init"], ..Default::default() })
+            .process(FakeProcess { pid: own_pid + 1, ppid: 1, name: "bash", cmdline: &["-bash"], ..Default::default() })
+            .process(FakeProcess { pid: own_pid + 2, ppid: own_pid + 1, name: "sleep", cmdline: &["sleep", "60"], ..Default::default() })
+            .process(FakeProcess { pid: own_pid, ppid: own_pid + 1, name: "gremlin", cmdline: &["gremlin", "sys", "kill-tree"], ..Default::default() });
+        let proc = fixture.proc();
+        let shell = (own_pid + 1).to_string();
+
+        let out = kill_tree(&crate::exec::RecordingExecutor::new(), &proc, &shell, "TERM").unwrap();
+        assert_eq!(out.argv, ["kill".to_string(), "-s".to_string(), "TERM".to_string(), shell.clone(), (own_pid + 2).to_string()]);
+        let results: Vec<(&str, &str)> = out.rows.iter().map(|row| (row.fields[0].as_str(), row.fields[2].as_str())).collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(out.title, format!("Sending TERM to {} and 1 descendant(s):", shell));
+        assert!(results.contains(&(own_pid.to_string().as_str(), "skipped: gremlin itself")));
+        assert!(preview_tree(&proc, &shell).unwrap().iter().any(|line| line.ends_with("(gremlin itself, skipped)")));
+
+        assert!(matches!(kill_tree(&crate::exec::RecordingExecutor::new(), &proc, "999999", "TERM"), Err(CommandError::NoMatch(_))));
+        assert!(matches!(kill_tree(&crate::exec::RecordingExecutor::new(), &proc, "1", "TERM"), Err(CommandError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn mount_users_are_matched_by_path_components() {
+        use std::os::unix::fs::symlink;
+
+        let fixture = Fixture::new();
+        fixture
+            .process(FakeProcess { pid: 10, name: "bash", cmdline: &["bash", "-c", "echo hi\nsleep 1"], ..Default::default() })
+            .process(FakeProcess { pid: 11, name: "postgres", cmdline: &["postgres"], ..Default::default() })
+            .process(FakeProcess { pid: 12, name: "rsync", cmdline: &["rsync", "-a"], ..Default::default() });
+        let proc = fixture.proc();
+        for (link, target) in [("10/cwd", "/mnt/data/work"), ("11/fd/3", "/mnt/database/base"), ("12/root", "/"), ("12/fd/4", "/mnt/data")] {
+            std::fs::create_dir_all(proc.path(link).parent().unwrap()).unwrap();
+            symlink(target, proc.path(link)).unwrap();
+        }
+        assert_eq!(mount_users(&proc, "/mnt/data"), ["In use by PID 10: bash -c echo hi sleep 1", "In use by PID 12: rsync -a"]);
+        assert!(mount_users(&proc, "/srv").is_empty());
+    }
+
+    #[test]
+    fn dry_runs_show_what_would_run_as_root() {
+        let recording = crate::exec::RecordingExecutor::new();
+        let exec = privilege::AsRoot(&recording);
+        let umount = Invocation::new("umount").arg("/mnt/data");
+        assert!(exec.effective(&umount).elevated);
+        assert!(!exec.effective(&umount.clone().read_only()).elevated);
+
+        let out = run(&exec, umount.clone()).unwrap();
+        let expected = privilege::resolve(&umount.clone().elevated()).map_or_else(|_| umount.argv(), |resolved| resolved.argv());
+        assert_eq!(out.argv, expected);
+        assert!(out.argv.ends_with(&["umount".to_string(), "/mnt/data".to_string()]));
+        assert_eq!(out.notes[0].text, format!("🧪 dry run: {}", expected.join(" ")));
+        let json: serde_json::Value = serde_json::from_str(&crate::sys_admin::render::to_json(&out)).unwrap();
+        assert_eq!(json["dry_run"], true);
+        assert_eq!(json["command"], expected.join(" "));
+    }
+}
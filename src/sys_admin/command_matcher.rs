@@ -1,127 +1,154 @@
-use crate::sys_admin::command_names;
-use crate::sys_admin::command_categories;
-use crate::sys_admin::process;
-use crate::sys_admin::resource;
-use crate::sys_admin::disk;
-use crate::sys_admin::logs;
-use crate::sys_admin::networking;
-use crate::sys_admin::services;
-use crate::sys_admin::security;
-use crate::sys_admin::packages;
-use crate::sys_admin::command_types::{CommandName, CommandCategory, COMMAND_MAP};
-
 use std::io::{self, Write};
 use std::fs;
 use colored::*;
 
+use dialoguer::Password;
+
+use crate::audit;
+use crate::exec::{self, Executor};
+use crate::packages;
+use crate::privilege;
+use crate::sys_admin::args::{ArgKind, ArgSpec, Args};
+use crate::sys_admin::command_output::{CommandError, CommandResult, Tone};
+use crate::sys_admin::doctor;
+use crate::sys_admin::guard;
+use crate::sys_admin::playbook;
+use crate::sys_admin::plugins;
+use crate::sys_admin::registry::{self, CommandCategory, CommandSpec, Danger};
+use crate::sys_admin::render;
+use crate::sys_admin::repl;
+
 fn load_ascii(path: &str) -> String {
     fs::read_to_string(path).unwrap_or_else(|_| "✨ [ASCII missing] ✨".to_string())
 }
 
-pub fn run_command(cmd: &str, arg: &str) {
-    let command = match cmd {
-        // Process Management
-        "ps" => Some(CommandName::Ps),
-        "htop" => Some(CommandName::Htop),
-        "kill" => Some(CommandName::KillGraceful),
-        "kill9" => Some(CommandName::KillForce),
-        "pkill" => Some(CommandName::Pkill),
-        "pgrep" => Some(CommandName::Pgrep),
-
-        // Resource Monitoring
-        "free" => Some(CommandName::Free),
-        "vmstat" => Some(CommandName::Vmstat),
-        "iostat" => Some(CommandName::Iostat),
-        "mpstat" => Some(CommandName::Mpstat),
-        "sar" => Some(CommandName::Sar),
-
-        // Disk and Storage
-        "df" => Some(CommandName::Df),
-        "du" => Some(CommandName::Du),
-        "ncdu" => Some(CommandName::Ncdu),
-        "lsblk" => Some(CommandName::Lsblk),
-        "mount" => Some(CommandName::Mount),
-        "umount" => Some(CommandName::Umount),
-
-        // Log Diving
-        "journalctl" => Some(CommandName::JournalctlSystem),
-        "journalctl-service" => Some(CommandName::JournalctlService),
-        "dmesg" => Some(CommandName::Dmesg),
-        "tail-syslog" => Some(CommandName::TailSyslog),
-        "auth-log" => Some(CommandName::AuthLog),
-
-        // Networking
-        "ip" => Some(CommandName::Ip),
-        "ping" => Some(CommandName::Ping),
-        "traceroute" => Some(CommandName::Traceroute),
-        "netstat" => Some(CommandName::Netstat),
-        "nmap" => Some(CommandName::Nmap),
-        "curl" => Some(CommandName::Curl),
-        "dig" => Some(CommandName::Dig),
-
-        // Service Management
-        "systemctl-start" => Some(CommandName::SystemctlStart),
-        "systemctl-stop" => Some(CommandName::SystemctlStop),
-        "systemctl-restart" => Some(CommandName::SystemctlRestart),
-        "systemctl-enable" => Some(CommandName::SystemctlEnable),
-        "systemctl-disable" => Some(CommandName::SystemctlDisable),
-        "systemctl-status" => Some(CommandName::SystemctlStatus),
-        "systemctl-list" => Some(CommandName::SystemctlList),
-
-        // Security
-        "chkrootkit" => Some(CommandName::Chkrootkit),
-        "rkhunter" => Some(CommandName::Rkhunter),
-        "lynis" => Some(CommandName::Lynis),
-        "clamscan" => Some(CommandName::Clamscan),
-        "fail2ban" => Some(CommandName::Fail2ban),
-        "gpg-encrypt" => Some(CommandName::GpgEncrypt),
-        "gpg-decrypt" => Some(CommandName::GpgDecrypt),
-        "openssl-encrypt" => Some(CommandName::OpensslEncrypt),
-        "openssl-decrypt" => Some(CommandName::OpensslDecrypt),
-
-        // Package Management
-        "apt-install" => Some(CommandName::AptInstall),
-        "apt-remove" => Some(CommandName::AptRemove),
-        "apt-update" => Some(CommandName::AptUpdate),
-        "pacman-install" => Some(CommandName::PacmanInstall),
-        "pacman-remove" => Some(CommandName::PacmanRemove),
-        "pacman-update" => Some(CommandName::PacmanUpdate),
-        "yay-install" => Some(CommandName::YayInstall),
-        "which" => Some(CommandName::Which),
-        "whereis" => Some(CommandName::Whereis),
-        "find-binary" => Some(CommandName::FindBinary),
-        "locate-binary" => Some(CommandName::LocateBinary),
-        "dpkg-list" => Some(CommandName::DpkgList),
-        "pacman-list" => Some(CommandName::PacmanList),
-
-        _ => None,
-    };
+fn get_input(prompt: &str) -> String {
+    print!("{}", prompt.bright_blue());
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
+
+/// Run a command by name or alias and return what it produced, without printing anything.
+/// Arguments are validated against the command's specs, and destructive commands confirmed,
+/// before anything is spawned.
+/// Every run of a known command is recorded in the audit log.
+pub fn execute(cmd: &str, raw_args: &[String]) -> CommandResult {
+    let exec = exec::current();
+    let result = preflight(&*exec, cmd).and_then(|_| execute_with(&*exec, cmd, raw_args));
+    if let Some(spec) = registry::find(cmd) {
+        audit::record(&audit_entry(spec, raw_args, &result));
+    }
+    result
+}
 
-    match command {
-        Some(cmd) => {
-            if let Some(handler) = COMMAND_MAP.get(&cmd) {
-                handler.lock().unwrap()(arg);
-            } else {
-                println!("{} {}", "Error:".bright_red(), "Command handler not found".bright_red());
+/// Fail early with `NotInstalled` when a required binary is missing, instead of an OS error
+/// from the spawn. Dry runs only warn, see `execute_with`.
+fn preflight(exec: &dyn Executor, cmd: &str) -> Result<(), CommandError> {
+    let missing = registry::find(cmd).map(|spec| spec.missing_binaries()).unwrap_or_default();
+    if missing.is_empty() || exec.is_dry_run() {
+        Ok(())
+    } else {
+        Err(CommandError::NotInstalled(missing.join(", ")))
+    }
+}
+
+/// `execute` with an explicit executor, e.g. a scripted fake.
+pub fn execute_with(exec: &dyn Executor, cmd: &str, raw_args: &[String]) -> CommandResult {
+    let spec = registry::find(cmd).ok_or_else(|| CommandError::UnknownCommand(cmd.to_string()))?;
+    let args = Args::parse(spec.name, spec.args, raw_args)?;
+    let as_root = privilege::AsRoot(exec);
+    let exec: &dyn Executor = if spec.root { &as_root } else { exec };
+    let affected = guard::confirm(exec, spec, &args)?;
+
+    let mut output = (spec.run)(exec, &args)?;
+    if exec.is_dry_run() {
+        if spec.root && !privilege::is_root() {
+            match privilege::elevator() {
+                Some(elevator) => output.note(Tone::Info, format!("🔑 would run as root via {}", elevator.program())),
+                None => output.note(Tone::Warn, "🔑 needs root, but none of sudo, doas, run0 or pkexec is available"),
             }
         }
-        None => println!("{} {}", "Unknown command:".bright_red(), cmd),
+        for target in affected {
+            output.note(Tone::Warn, format!("would affect: {}", target));
+        }
+        let missing = spec.missing_binaries();
+        if !missing.is_empty() {
+            output.note(Tone::Warn, format!("not installed: {}", missing.join(", ")));
+        }
     }
+    Ok(output)
 }
 
-pub fn show_available_commands() {
-    let names = command_names::get_command_names();
-    let cats = command_categories::get_command_categories();
+/// Audit record for one run, with secret arguments masked.
+pub(crate) fn audit_entry(spec: &CommandSpec, raw_args: &[String], result: &CommandResult) -> audit::Entry {
+    let slots = Args::slots(spec.args, raw_args).unwrap_or_else(|_| (0..raw_args.len()).collect());
+    let args = raw_args
+        .iter()
+        .zip(slots)
+        .map(|(arg, slot)| match spec.args.get(slot) {
+            Some(arg_spec) if arg_spec.kind == ArgKind::Secret && Args::value(arg_spec, arg) != arg => format!("{}=***", arg_spec.name),
+            Some(arg_spec) if arg_spec.kind == ArgKind::Secret => "***".to_string(),
+            _ => arg.clone(),
+        })
+        .collect();
+    let entry = audit::Entry::new(audit::Kind::Sys, spec.name, args);
+    match result {
+        Ok(output) => audit::Entry {
+            argv: output.redacted_argv(),
+            exit_code: output.exit_code(),
+            dry_run: output.dry_run,
+            ..entry
+        }
+        .with_duration(output.duration),
+        Err(e) => audit::Entry { exit_code: e.exit_code(), error: Some(e.to_string()), ..entry },
+    }
+}
+
+/// Run a command by name and print its output; used by the menus.
+pub fn run_command(cmd: &str, raw_args: &[String]) {
+    match execute(cmd, raw_args) {
+        Ok(output) => render::print_output(&output),
+        Err(e) => render::print_error(&e),
+    }
+}
+
+/// Danger, root and plugin markers shown after a command's description.
+fn markers(spec: &CommandSpec) -> String {
+    let danger = match spec.danger {
+        Danger::Safe => "".normal(),
+        Danger::Caution => " ⚠️".yellow(),
+        Danger::Destructive => " ☠️".red(),
+    };
+    let root = if spec.root { " 🔑".bright_yellow() } else { "".normal() };
+    let plugin = if plugins::is_plugin(spec.name) { " 🔌".bright_blue() } else { "".normal() };
+    format!("{}{}{}", danger, root, plugin)
+}
 
+pub fn show_available_commands() {
     println!("\n{}", "Available Commands:".bright_cyan().bold());
-    for (cmd, desc) in &names {
-        let cat = cats.get(cmd).unwrap_or(&"Uncategorized");
-        println!("{} {} {} {} {}",
+    for category in CommandCategory::ALL {
+        show_commands_by_category(category);
+    }
+}
+
+pub fn show_commands_by_category(category: CommandCategory) {
+    println!("\n{}", format!("[ {} {} ]", category.emoji(), category.name()).bright_purple().bold());
+
+    for spec in registry::in_category(category) {
+        let aliases = if spec.aliases.is_empty() {
+            String::new()
+        } else {
+            format!(" (aka {})", spec.aliases.join(", "))
+        };
+        println!("{} {}{} {} {}{}",
             "•".bright_green(),
-            cmd.bright_magenta(),
-            format!("({})", cat).bright_yellow(),
+            spec.usage().bright_magenta(),
+            aliases.bright_yellow(),
             "–".bright_blue(),
-            desc.bright_cyan()
+            spec.description.bright_cyan(),
+            markers(spec)
         );
     }
 }
@@ -130,144 +157,174 @@ pub fn run() {
     let splash_art = load_ascii("ascii/SysGirl.txt");
     println!("{}", splash_art.bright_magenta());
     println!("\n{}", "🛠️ System Admin Dashboard 🛠️".bright_purple().bold().blink());
-    
+
+    let show_all = CommandCategory::ALL.len() + 1;
+    let palette = show_all + 1;
+    let playbooks = palette + 1;
+    let doctor = playbooks + 1;
     loop {
         println!("\n{}", "System Admin Menu:".bright_cyan());
-        println!("{} {}", "1.".bright_green(), "Process Management 🧵".bright_magenta());
-        println!("{} {}", "2.".bright_green(), "Resource Monitoring 📊".bright_cyan());
-        println!("{} {}", "3.".bright_green(), "Disk and Storage 💾".bright_purple());
-        println!("{} {}", "4.".bright_green(), "Log Diving 📜".bright_yellow());
-        println!("{} {}", "5.".bright_green(), "Networking Tools 🌐".bright_blue());
-        println!("{} {}", "6.".bright_green(), "Service Management ⚙️".bright_magenta());
-        println!("{} {}", "7.".bright_green(), "Security Tools 🔒".bright_cyan());
-        println!("{} {}", "8.".bright_green(), "Package Management 📦".bright_purple());
-        println!("{} {}", "9.".bright_green(), "Show All Commands 📋".bright_yellow());
+        for (i, category) in CommandCategory::ALL.iter().enumerate() {
+            println!("{} {}",
+                format!("{}.", i + 1).bright_green(),
+                format!("{} {}", category.name(), category.emoji()).bright_magenta()
+            );
+        }
+        println!("{} {}", format!("{}.", show_all).bright_green(), "Show All Commands 📋".bright_yellow());
+        println!("{} {}", format!("{}.", palette).bright_green(), "Command Palette (gremlin>) 🔎".bright_yellow());
+        println!("{} {}", format!("{}.", playbooks).bright_green(), "Playbooks 📒".bright_yellow());
+        println!("{} {}", format!("{}.", doctor).bright_green(), "Doctor: check required tools 🩺".bright_yellow());
         println!("{} {}", "0.".bright_green(), "Back to Main Menu 🔙".bright_blue());
 
-        print!("\n{}", "Enter your choice (0-9): ".bright_blue());
-        io::stdout().flush().unwrap();
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        
-        match input.trim() {
-            "1" => process::run(),
-            "2" => resource::run(),
-            "3" => disk::run(),
-            "4" => logs::run(),
-            "5" => networking::run(),
-            "6" => services::run(),
-            "7" => security::run(),
-            "8" => packages::run(),
-            "9" => show_available_commands(),
-            "0" => return,
+        let choice = get_input(&format!("\nEnter your choice (0-{}): ", doctor));
+        match choice.parse::<usize>() {
+            Ok(0) => return,
+            Ok(n) if n == show_all => show_available_commands(),
+            Ok(n) if n == palette => repl::run(),
+            Ok(n) if n == playbooks => playbook::menu(),
+            Ok(n) if n == doctor => doctor::menu(),
+            Ok(n) if n <= CommandCategory::ALL.len() => run_category(CommandCategory::ALL[n - 1]),
             _ => println!("{}", "⚠️ Invalid choice. Try again.".bright_red()),
         }
     }
 }
 
-pub fn show_commands_by_category(category: CommandCategory) {
-    println!("\n{}", format!("[ {} ]", category_name(category)).bright_purple().bold());
-    
-    for cmd in CommandName::iter() {
-        if cmd.category() == category {
-            println!("{} {} {} {}",
-                "•".bright_green(),
-                cmd_name(cmd).bright_magenta(),
+/// Menu for one category: pick a command by number or name, get prompted for its arguments.
+pub fn run_category(category: CommandCategory) {
+    let splash_art = load_ascii(category.ascii());
+    println!("{}", splash_art.bright_magenta());
+    println!("\n{}", format!("{} {} Dashboard {}", category.emoji(), category.name(), category.emoji()).bright_purple().bold().blink());
+
+    let commands: Vec<&CommandSpec> = registry::in_category(category).collect();
+    loop {
+        println!("\n{}", "Available Commands:".bright_cyan());
+        for (i, spec) in commands.iter().enumerate() {
+            println!("{} {} {} {}{}",
+                format!("{}.", i + 1).bright_green(),
+                spec.name.bright_magenta(),
                 "–".bright_blue(),
-                cmd.description().bright_cyan()
+                spec.description.bright_cyan(),
+                markers(spec)
             );
         }
+        println!("\n{} {}", "0.".bright_green(), "Back to System Admin Dashboard 🔙".bright_blue());
+
+        let choice = get_input(&format!("\nEnter command number or name (0-{}): ", commands.len()));
+        let spec = match choice.parse::<usize>() {
+            Ok(0) => return,
+            Ok(n) if n <= commands.len() => commands[n - 1],
+            _ => match registry::find(&choice) {
+                Some(spec) => spec,
+                None => {
+                    println!("{}", "⚠️ Invalid choice. Try again.".bright_red());
+                    continue;
+                }
+            },
+        };
+
+        let missing = spec.missing_binaries();
+        if !missing.is_empty() {
+            println!("{} {}", "Missing tools:".bright_yellow(), missing.join(", ").bright_red());
+            if !install_requirements(&missing) {
+                continue;
+            }
+        }
+
+        match spec.args.iter().map(prompt_arg).collect::<Option<Vec<String>>>() {
+            Some(args) => run_command(spec.name, &args),
+            None => render::print_error(&CommandError::Cancelled),
+        }
+    }
+}
+
+/// Ask for one argument until it passes validation. Secrets are read without echo.
+/// Leaving a required argument empty cancels the command.
+pub fn prompt_arg(spec: &ArgSpec) -> Option<String> {
+    loop {
+        let value = if spec.kind == ArgKind::Secret {
+            Password::new()
+                .with_prompt(spec.prompt.trim_end_matches([':', ' ']))
+                .allow_empty_password(!spec.required)
+                .interact()
+                .unwrap_or_default()
+        } else {
+            get_input(spec.prompt)
+        };
+
+        if spec.required && value.trim().is_empty() {
+            return None;
+        }
+        match spec.check(&value) {
+            Ok(value) => return Some(value),
+            Err(e) => render::print_error(&e),
+        }
     }
 }
 
-fn category_name(category: CommandCategory) -> &'static str {
-    match category {
-        CommandCategory::ProcessManagement => "Process Management",
-        CommandCategory::ResourceMonitoring => "Resource Monitoring",
-        CommandCategory::DiskAndStorage => "Disk and Storage",
-        CommandCategory::LogDiving => "Log Diving",
-        CommandCategory::Networking => "Networking",
-        CommandCategory::ServiceManagement => "Service Management",
-        CommandCategory::Security => "Security",
-        CommandCategory::PackageManagement => "Package Management",
+/// Offer to install the packages providing `missing` binaries.
+fn install_requirements(missing: &[&str]) -> bool {
+    let mut providers: Vec<String> = Vec::new();
+    for binary in missing {
+        match packages::provider(binary) {
+            Some(package) if !providers.contains(&package) => providers.push(package),
+            Some(_) => {}
+            None => {
+                println!("{}", format!("No known package provides {}; install it manually.", binary).bright_red());
+                return false;
+            }
+        }
+    }
+
+    let answer = get_input(&format!("Install {} now? (y/n): ", providers.join(" ")));
+    if !answer.eq_ignore_ascii_case("y") {
+        return false;
+    }
+
+    println!("\n{}", "Installing required packages...".bright_yellow());
+    println!("{}", "This may take a few minutes...".bright_yellow());
+    println!("{}", "Please enter your password when prompted".bright_yellow());
+    println!("{}", "----------------------------------------".bright_yellow());
+
+    let Some(manager) = packages::detect() else {
+        println!("{}", "Unsupported package manager".bright_red());
+        return false;
+    };
+    println!("{}", format!("Using {} package manager...", manager.name()).bright_cyan());
+    let providers: Vec<&str> = providers.iter().map(String::as_str).collect();
+    let status = packages::install_attached(&*exec::current(), manager, &providers);
+
+    println!("{}", "----------------------------------------".bright_yellow());
+    match status {
+        Ok(true) => {
+            println!("{}", "Installation complete!".bright_green());
+            true
+        },
+        _ => {
+            println!("{}", "Installation failed. Please try again.".bright_red());
+            false
+        }
     }
 }
 
-fn cmd_name(cmd: CommandName) -> &'static str {
-    match cmd {
-        // Process Management
-        CommandName::Ps => "ps",
-        CommandName::Htop => "htop",
-        CommandName::KillGraceful => "kill",
-        CommandName::KillForce => "kill9",
-        CommandName::Pkill => "pkill",
-        CommandName::Pgrep => "pgrep",
-
-        // Resource Monitoring
-        CommandName::Free => "free",
-        CommandName::Vmstat => "vmstat",
-        CommandName::Iostat => "iostat",
-        CommandName::Mpstat => "mpstat",
-        CommandName::Sar => "sar",
-
-        // Disk and Storage
-        CommandName::Df => "df",
-        CommandName::Du => "du",
-        CommandName::Ncdu => "ncdu",
-        CommandName::Lsblk => "lsblk",
-        CommandName::Mount => "mount",
-        CommandName::Umount => "umount",
-
-        // Log Diving
-        CommandName::JournalctlSystem => "journalctl",
-        CommandName::JournalctlService => "journalctl-service",
-        CommandName::Dmesg => "dmesg",
-        CommandName::TailSyslog => "tail-syslog",
-        CommandName::AuthLog => "auth-log",
-
-        // Networking
-        CommandName::Ip => "ip",
-        CommandName::Ping => "ping",
-        CommandName::Traceroute => "traceroute",
-        CommandName::Netstat => "netstat",
-        CommandName::Nmap => "nmap",
-        CommandName::Curl => "curl",
-        CommandName::Dig => "dig",
-
-        // Service Management
-        CommandName::SystemctlStart => "systemctl-start",
-        CommandName::SystemctlStop => "systemctl-stop",
-        CommandName::SystemctlRestart => "systemctl-restart",
-        CommandName::SystemctlEnable => "systemctl-enable",
-        CommandName::SystemctlDisable => "systemctl-disable",
-        CommandName::SystemctlStatus => "systemctl-status",
-        CommandName::SystemctlList => "systemctl-list",
-
-        // Security
-        CommandName::Chkrootkit => "chkrootkit",
-        CommandName::Rkhunter => "rkhunter",
-        CommandName::Lynis => "lynis",
-        CommandName::Clamscan => "clamscan",
-        CommandName::Fail2ban => "fail2ban",
-        CommandName::GpgEncrypt => "gpg-encrypt",
-        CommandName::GpgDecrypt => "gpg-decrypt",
-        CommandName::OpensslEncrypt => "openssl-encrypt",
-        CommandName::OpensslDecrypt => "openssl-decrypt",
-
-        // Package Management
-        CommandName::AptInstall => "apt-install",
-        CommandName::AptRemove => "apt-remove",
-        CommandName::AptUpdate => "apt-update",
-        CommandName::PacmanInstall => "pacman-install",
-        CommandName::PacmanRemove => "pacman-remove",
-        CommandName::PacmanUpdate => "pacman-update",
-        CommandName::YayInstall => "yay-install",
-        CommandName::Which => "which",
-        CommandName::Whereis => "whereis",
-        CommandName::FindBinary => "find-binary",
-        CommandName::LocateBinary => "locate-binary",
-        CommandName::DpkgList => "dpkg-list",
-        CommandName::PacmanList => "pacman-list",
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::{Completed, ScriptedExecutor};
+    use crate::sys_admin::command_logic;
+
+    #[test]
+    fn audit_entries_never_hold_the_openssl_password() {
+        let spec = registry::find("openssl-encrypt").unwrap();
+        let exec = ScriptedExecutor::new().on("openssl", Completed::ok(""));
+        let result = command_logic::openssl_encrypt(&exec, "notes.txt", "hunter2");
+
+        for (raw, logged) in [(["notes.txt", "hunter2"], ["notes.txt", "***"]), (["password=hunter2", "notes.txt"], ["password=***", "notes.txt"])] {
+            let raw: Vec<String> = raw.into_iter().map(String::from).collect();
+            let entry = audit_entry(spec, &raw, &result);
+            let line = serde_json::to_string(&entry).unwrap();
+            assert!(!line.contains("hunter2"), "{}", line);
+            assert_eq!(entry.args, logged);
+            assert!(entry.argv.contains(&"pass:***".to_string()));
+        }
     }
-} 
\ No newline at end of file
+}
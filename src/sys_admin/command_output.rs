@@ -0,0 +1,419 @@
+use std::fmt;
+use std::io;
+use std::time::Duration;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use thiserror::Error;
+
+/// How a row or note should be highlighted when rendered for humans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tone {
+    #[default]
+    Normal,
+    Good,
+    Warn,
+    Bad,
+    Info,
+    Plain,
+}
+
+/// One parsed line of command output, split into fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub fields: Vec<String>,
+    pub tone: Tone,
+}
+
+/// A typed value in a record. Sizes are always bytes and percentages plain numbers,
+/// so JSON/CSV consumers never have to parse `3.0G` or `18%`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Text(String),
+    Int(i64),
+    Float(f64),
+    Bytes(u64),
+    Percent(f64),
+    Null,
+}
+
+/// How a raw column should be interpreted when building a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Text,
+    Int,
+    Float,
+    /// A byte count, with or without a unit suffix (`512`, `3.0G`, `5.9Gi`).
+    Bytes,
+    /// A count of KiB, as printed by `ps` and `vmstat`.
+    Kibibytes,
+    Percent,
+}
+
+/// One typed row of output: ordered `(key, value)` pairs, serialized as a JSON object.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Record {
+    pub fields: Vec<(String, Field)>,
+}
+
+/// A one-line verdict shown after the output, e.g. "✅ Target is reachable".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub tone: Tone,
+    pub text: String,
+}
+
+/// Everything a command produced. Handlers build this without printing anything;
+/// turning it into text is the job of `render`.
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    pub title: String,
+    /// The program and arguments that were run.
+    pub argv: Vec<String>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Exit code, `None` if the process was killed by a signal or never started.
+    pub status: Option<i32>,
+    /// Nothing was run; `argv` is what would have been.
+    pub dry_run: bool,
+    pub duration: Duration,
+    pub headers: Vec<String>,
+    pub rows: Vec<Row>,
+    /// Typed version of the output, for `--format json|csv`. Empty for free-form commands.
+    pub records: Vec<Record>,
+    pub notes: Vec<Note>,
+}
+
+pub type CommandResult = Result<CommandOutput, CommandError>;
+
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("Unknown command: {0}")]
+    UnknownCommand(String),
+    #[error("Missing argument: {0}")]
+    MissingArgument(&'static str),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("{0} is not installed")]
+    NotInstalled(String),
+    #[error("Failed to run {program}: {source}")]
+    Spawn {
+        program: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("No process found matching '{0}'")]
+    NoMatch(String),
+    /// A kernel interface such as `/proc/stat` couldn't be read.
+    #[error("Can't read {0}")]
+    Unreadable(String),
+    /// A network server didn't answer or refused the connection.
+    #[error("Can't reach {0}")]
+    Unreachable(String),
+    #[error("Cancelled")]
+    Cancelled,
+}
+
+impl CommandError {
+    /// Wrap a spawn failure, reporting a missing binary as `NotInstalled` and a missing
+    /// elevation tool as `PermissionDenied`.
+    pub fn spawn(program: &str, source: io::Error) -> Self {
+        match source.kind() {
+            io::ErrorKind::NotFound => CommandError::NotInstalled(program.to_string()),
+            io::ErrorKind::PermissionDenied => CommandError::PermissionDenied(source.to_string()),
+            _ => CommandError::Spawn { program: program.to_string(), source },
+        }
+    }
+
+    /// Exit code used by the CLI for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandError::UnknownCommand(_)
+            | CommandError::MissingArgument(_)
+            | CommandError::InvalidArgument(_) => 2,
+            CommandError::NotInstalled(_) => 127,
+            CommandError::PermissionDenied(_) => 126,
+            CommandError::Cancelled => 130,
+            CommandError::Spawn { .. }
+            | CommandError::NoMatch(_)
+            | CommandError::Unreadable(_)
+            | CommandError::Unreachable(_) => 1,
+        }
+    }
+}
+
+impl CommandOutput {
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Add a record along with its human-readable row.
+    pub fn push_record(&mut self, record: Record, tone: Tone) {
+        self.rows.push(record.to_row(tone));
+        self.records.push(record);
+    }
+
+    pub fn note(&mut self, tone: Tone, text: impl Into<String>) {
+        self.notes.push(Note { tone, text: text.into() });
+    }
+
+    /// True when the process exited with status 0, or nothing was spawned and no status was set.
+    pub fn success(&self) -> bool {
+        self.status.map_or(self.argv.is_empty(), |status| status == 0)
+    }
+
+    /// Exit code used by the CLI for this output. Native commands that spawn nothing can still
+    /// fail (an assertion, say) by setting `status`.
+    pub fn exit_code(&self) -> i32 {
+        if self.dry_run {
+            0
+        } else if self.argv.is_empty() {
+            self.status.unwrap_or(0)
+        } else {
+            self.status.unwrap_or(1)
+        }
+    }
+
+    /// The command line as a single shell-like string.
+    pub fn command_line(&self) -> String {
+        self.redacted_argv().join(" ")
+    }
+
+    /// `argv` with inline secrets masked, safe to show or log.
+    pub fn redacted_argv(&self) -> Vec<String> {
+        self.argv.iter().map(|arg| redact(arg).to_string()).collect()
+    }
+}
+
+impl Row {
+    pub fn new(fields: Vec<String>, tone: Tone) -> Self {
+        Self { fields, tone }
+    }
+}
+
+impl Field {
+    /// Parse a raw column. Empty and `-` values become `Null`; values that don't parse
+    /// as the requested kind are kept as text.
+    pub fn parse(raw: &str, kind: Kind) -> Self {
+        let raw = raw.trim();
+        if raw.is_empty() || raw == "-" {
+            return Field::Null;
+        }
+        let parsed = match kind {
+            Kind::Text => None,
+            Kind::Int => raw.parse().ok().map(Field::Int),
+            Kind::Float => raw.parse().ok().map(Field::Float),
+            Kind::Bytes => parse_size(raw).map(Field::Bytes),
+            Kind::Kibibytes => raw.parse::<u64>().ok().map(|kib| Field::Bytes(kib * 1024)),
+            Kind::Percent => raw.trim_end_matches('%').parse().ok().map(Field::Percent),
+        };
+        parsed.unwrap_or_else(|| Field::Text(raw.to_string()))
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Field::Int(n) => Some(*n as f64),
+            Field::Float(n) | Field::Percent(n) => Some(*n),
+            Field::Bytes(n) => Some(*n as f64),
+            Field::Text(_) | Field::Null => None,
+        }
+    }
+
+    /// The value as a CSV cell: numbers unformatted, null empty.
+    pub fn raw(&self) -> String {
+        match self {
+            Field::Text(s) => s.clone(),
+            Field::Int(n) => n.to_string(),
+            Field::Float(n) | Field::Percent(n) => n.to_string(),
+            Field::Bytes(n) => n.to_string(),
+            Field::Null => String::new(),
+        }
+    }
+}
+
+/// Human-readable form, e.g. `3.0G` for bytes and `18%` for percentages.
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Bytes(n) => f.write_str(&human_bytes(*n)),
+            Field::Percent(n) => write!(f, "{}%", n),
+            other => f.write_str(&other.raw()),
+        }
+    }
+}
+
+impl Serialize for Field {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Field::Text(s) => serializer.serialize_str(s),
+            Field::Int(n) => serializer.serialize_i64(*n),
+            Field::Float(n) | Field::Percent(n) => serializer.serialize_f64(*n),
+            Field::Bytes(n) => serializer.serialize_u64(*n),
+            Field::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+impl Record {
+    /// Build a record from raw columns and a `(key, kind)` schema. Missing columns are `Null`.
+    pub fn from_columns(schema: &[(&str, Kind)], columns: &[String]) -> Self {
+        let fields = schema
+            .iter()
+            .enumerate()
+            .map(|(i, (key, kind))| {
+                let value = columns.get(i).map(|raw| Field::parse(raw, *kind)).unwrap_or(Field::Null);
+                (key.to_string(), value)
+            })
+            .collect();
+        Self { fields }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Field> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(k, _)| k.as_str())
+    }
+
+    pub fn to_row(&self, tone: Tone) -> Row {
+        Row::new(self.fields.iter().map(|(_, v)| v.to_string()).collect(), tone)
+    }
+}
+
+impl Serialize for Record {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for (key, value) in &self.fields {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Parse `512`, `0B`, `3.0G`, `553Mi` or `1.5KiB` into bytes (binary multiples, as coreutils uses).
+pub fn parse_size(raw: &str) -> Option<u64> {
+    let split = raw.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let unit = unit.strip_suffix('B').unwrap_or(unit);
+    let unit = match unit.strip_suffix('i') {
+        Some(prefix) if !prefix.is_empty() => prefix,
+        _ => unit,
+    };
+    let exponent = match unit.to_ascii_uppercase().as_str() {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        "P" => 5,
+        _ => return None,
+    };
+    Some((number * 1024f64.powi(exponent)).round() as u64)
+}
+
+/// Format bytes like `df -h`: one decimal below 10, whole numbers above.
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else if value < 10.0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+/// Hide inline secrets such as openssl's `pass:<password>` when showing a command line.
+fn redact(arg: &str) -> &str {
+    if arg.starts_with("pass:") {
+        "pass:***"
+    } else {
+        arg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_take_binary_suffixes() {
+        let cases = [
+            ("512", Some(512)),
+            ("0B", Some(0)),
+            ("4K", Some(4096)),
+            ("4k", Some(4096)),
+            ("4Ki", Some(4096)),
+            ("1.5KiB", Some(1536)),
+            ("553Mi", Some(553 * 1024 * 1024)),
+            ("3.0G", Some(3 * 1024 * 1024 * 1024)),
+            ("2T", Some(2 << 40)),
+            ("0.5M", Some(512 * 1024)),
+            ("", None),
+            ("K", None),
+            ("-1K", None),
+            ("1.2.3M", None),
+            ("12X", None),
+            ("5BB", None),
+            ("5iB", None),
+            ("3 G", None),
+        ];
+        for (raw, bytes) in cases {
+            assert_eq!(parse_size(raw), bytes, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn bytes_print_like_df() {
+        assert_eq!(human_bytes(0), "0B");
+        assert_eq!(human_bytes(1023), "1023B");
+        assert_eq!(human_bytes(1024), "1.0K");
+        assert_eq!(human_bytes(1536), "1.5K");
+        assert_eq!(human_bytes(10 * 1024), "10K");
+        assert_eq!(human_bytes(3 * 1024 * 1024 * 1024), "3.0G");
+        assert_eq!(human_bytes(u64::MAX), "16384P");
+    }
+
+    #[test]
+    fn fields_parse_by_kind_and_fall_back_to_text() {
+        assert_eq!(Field::parse(" 42 ", Kind::Int), Field::Int(42));
+        assert_eq!(Field::parse("4.5", Kind::Int), Field::Text("4.5".to_string()));
+        assert_eq!(Field::parse("0.25", Kind::Float), Field::Float(0.25));
+        assert_eq!(Field::parse("18%", Kind::Percent), Field::Percent(18.0));
+        assert_eq!(Field::parse("1.5K", Kind::Bytes), Field::Bytes(1536));
+        assert_eq!(Field::parse("2048", Kind::Kibibytes), Field::Bytes(2 * 1024 * 1024));
+        assert_eq!(Field::parse("lots", Kind::Bytes), Field::Text("lots".to_string()));
+        assert_eq!(Field::parse("12", Kind::Text), Field::Text("12".to_string()));
+        assert_eq!(Field::parse("-", Kind::Int), Field::Null);
+        assert_eq!(Field::parse("  ", Kind::Text), Field::Null);
+
+        assert_eq!((Field::Bytes(1536).to_string(), Field::Bytes(1536).raw()), ("1.5K".to_string(), "1536".to_string()));
+        assert_eq!((Field::Percent(18.5).to_string(), Field::Null.raw()), ("18.5%".to_string(), String::new()));
+    }
+
+    #[test]
+    fn records_fill_missing_columns_with_null() {
+        let schema = [("pid", Kind::Int), ("mem", Kind::Bytes), ("name", Kind::Text)];
+        let record = Record::from_columns(&schema, &["7".to_string(), "2M".to_string()]);
+        assert_eq!(record.keys().collect::<Vec<_>>(), ["pid", "mem", "name"]);
+        assert_eq!(record.get("pid"), Some(&Field::Int(7)));
+        assert_eq!(record.get("mem"), Some(&Field::Bytes(2 * 1024 * 1024)));
+        assert_eq!(record.get("name"), Some(&Field::Null));
+        assert_eq!(record.to_row(Tone::Normal).fields, ["7", "2.0M", ""]);
+    }
+
+    #[test]
+    fn argv_hides_openssl_passwords() {
+        let output = CommandOutput { argv: ["openssl", "enc", "-pass", "pass:hunter2"].map(String::from).to_vec(), ..Default::default() };
+        assert_eq!(output.redacted_argv(), ["openssl", "enc", "-pass", "pass:***"]);
+    }
+}
@@ -0,0 +1,785 @@
+//! `gremlin sys dashboard`: a full-screen view of CPU, memory, load, disk and network with
+//! sparklines, above a process table that can be sorted and signalled without leaving it.
+//! Everything is read from `/proc` on each refresh; rates are the difference between two
+//! refreshes.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+use colored::*;
+use console::{Key, Term};
+
+use crate::audit;
+use crate::config;
+use crate::exec::Executor;
+use crate::procfs::cpu::{self, CpuStat, CpuUsage, LoadAverage};
+use crate::procfs::disk::{self, DiskCounters};
+use crate::procfs::memory::{self, Memory};
+use crate::procfs::net::{self, InterfaceCounters};
+use crate::procfs::process::{self, Process, SortKey};
+use crate::procfs::tree::Tree;
+use crate::procfs::ProcFs;
+use crate::signals::{self, Signal};
+use crate::sys_admin::command_logic;
+use crate::sys_admin::command_output::{human_bytes, CommandError, CommandOutput, CommandResult, Tone};
+use crate::sys_admin::render;
+
+/// Samples kept per sparkline.
+const HISTORY: usize = 240;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const FASTEST: Duration = Duration::from_millis(100);
+const SLOWEST: Duration = Duration::from_secs(10);
+const HELP: &str = "↑↓ PgUp PgDn select  Tab/< > sort  c m p u n sort by cpu/mem/pid/user/name  r reverse  \
+                    t tree  ←→ fold  k signal  x signal subtree  T term  K kill  +/- rate  q quit";
+
+/// The last `HISTORY` values of one metric.
+#[derive(Default)]
+struct History(VecDeque<f64>);
+
+impl History {
+    fn push(&mut self, value: f64) {
+        if self.0.len() == HISTORY {
+            self.0.pop_front();
+        }
+        self.0.push_back(value);
+    }
+
+    /// The newest `width` values as bars scaled to `max`, or to the largest value when `None`.
+    fn sparkline(&self, width: usize, max: Option<f64>) -> String {
+        let max = max.unwrap_or_else(|| self.0.iter().copied().fold(0.0, f64::max));
+        self.0
+            .iter()
+            .skip(self.0.len().saturating_sub(width))
+            .map(|&value| {
+                let level = if max > 0.0 { (value / max * (SPARKS.len() - 1) as f64).round() as usize } else { 0 };
+                SPARKS[level.min(SPARKS.len() - 1)]
+            })
+            .collect()
+    }
+}
+
+/// Counters from one refresh.
+struct Reading {
+    at: Instant,
+    cpu: CpuStat,
+    disks: Vec<DiskCounters>,
+    interfaces: Vec<InterfaceCounters>,
+    cpu_seconds: HashMap<u32, f64>,
+}
+
+/// Processes a signal goes to: the selected one, or it and its descendants.
+struct Target {
+    /// Parents before their children; never gremlin itself.
+    pids: Vec<u32>,
+    /// How the status line names them, e.g. `1234 (cargo) and 12 descendants`.
+    label: String,
+    /// The subtree as drawn for the confirmation, one line per process; empty for one process.
+    tree: Vec<String>,
+}
+
+enum Prompt {
+    /// Typing the signal to send.
+    Signal { target: Target, input: String },
+    /// Waiting for y/n before sending.
+    Confirm { target: Target, signal: Signal },
+}
+
+struct Dashboard<'a> {
+    exec: &'a dyn Executor,
+    proc: ProcFs,
+    refresh: Duration,
+    sort: SortKey,
+    reverse: bool,
+    /// Every process from the last refresh.
+    all: Vec<Process>,
+    /// The rows shown: `all` sorted, or drawn as a tree without the folded subtrees.
+    processes: Vec<Process>,
+    /// Tree lines in front of each row's command, in tree view.
+    prefixes: Vec<String>,
+    tree: bool,
+    folded: HashSet<u32>,
+    /// Highlighted row, and its PID so the selection follows the process across re-sorts.
+    selected: usize,
+    selected_pid: Option<u32>,
+    scroll: usize,
+    /// Process rows that fit on screen at the last draw; PgUp/PgDn move this far.
+    rows: usize,
+    previous: Option<Reading>,
+    cpu: f64,
+    cores: Vec<f64>,
+    memory: Option<Memory>,
+    load: Option<LoadAverage>,
+    /// Bytes per second read and written, summed over the reported disks.
+    disk: (f64, f64),
+    /// Bytes per second received and sent, summed over every interface but loopback.
+    network: (f64, f64),
+    cpu_history: History,
+    memory_history: History,
+    disk_history: History,
+    network_history: History,
+    prompt: Option<Prompt>,
+    status: Option<(Tone, String)>,
+    /// Signals sent, reported once the dashboard closes.
+    sent: Vec<String>,
+}
+
+impl<'a> Dashboard<'a> {
+    fn new(exec: &'a dyn Executor, proc: ProcFs, refresh: Duration, sort: SortKey, reverse: bool) -> Self {
+        Self {
+            exec,
+            proc,
+            refresh,
+            sort,
+            reverse,
+            all: Vec::new(),
+            processes: Vec::new(),
+            prefixes: Vec::new(),
+            tree: false,
+            folded: HashSet::new(),
+            selected: 0,
+            selected_pid: None,
+            scroll: 0,
+            rows: 1,
+            previous: None,
+            cpu: 0.0,
+            cores: Vec::new(),
+            memory: None,
+            load: None,
+            disk: (0.0, 0.0),
+            network: (0.0, 0.0),
+            cpu_history: History::default(),
+            memory_history: History::default(),
+            disk_history: History::default(),
+            network_history: History::default(),
+            prompt: None,
+            status: None,
+            sent: Vec::new(),
+        }
+    }
+
+    /// Read `/proc` again and work out the rates since the last refresh.
+    fn refresh(&mut self) {
+        let processes = process::list(&self.proc);
+        let reading = Reading {
+            at: Instant::now(),
+            cpu: cpu::read(&self.proc).unwrap_or_default(),
+            disks: disk::read(&self.proc).unwrap_or_default(),
+            interfaces: net::read(&self.proc),
+            cpu_seconds: processes.iter().map(|process| (process.pid, process.cpu_seconds)).collect(),
+        };
+        self.all = processes;
+        self.memory = memory::read(&self.proc);
+        self.load = cpu::load_average(&self.proc);
+
+        if let Some(previous) = &self.previous {
+            let seconds = reading.at.duration_since(previous.at).as_secs_f64().max(0.001);
+            self.cpu = CpuUsage::between(&previous.cpu.all, &reading.cpu.all).busy();
+            self.cores = previous
+                .cpu
+                .cores
+                .iter()
+                .zip(&reading.cpu.cores)
+                .map(|(before, after)| CpuUsage::between(before, after).busy())
+                .collect();
+            let disks = disk::rates(&previous.disks, &reading.disks, seconds);
+            self.disk = (
+                disks.iter().map(|rates| rates.read_bytes_per_sec).sum(),
+                disks.iter().map(|rates| rates.write_bytes_per_sec).sum(),
+            );
+            self.network = network_rates(&previous.interfaces, &reading.interfaces, seconds);
+            // CPU over the last interval, as top shows it, rather than the lifetime average.
+            for process in &mut self.all {
+                let before = previous.cpu_seconds.get(&process.pid).copied().unwrap_or_default();
+                process.cpu_percent = ((process.cpu_seconds - before).max(0.0) / seconds * 1000.0).round() / 10.0;
+            }
+
+            self.cpu_history.push(self.cpu);
+            self.memory_history.push(self.memory.as_ref().map(Memory::used_percent).unwrap_or_default());
+            self.disk_history.push(self.disk.0 + self.disk.1);
+            self.network_history.push(self.network.0 + self.network.1);
+        }
+        self.previous = Some(reading);
+        self.resort();
+    }
+
+    /// Rebuild the rows, keeping the selected process highlighted if it is still there.
+    fn resort(&mut self) {
+        if self.tree {
+            let tree = Tree::new(&self.all);
+            let nodes = tree.draw(tree.roots(), &self.folded, None);
+            self.processes = nodes.iter().filter_map(|node| tree.get(node.pid).cloned()).collect();
+            self.prefixes = nodes
+                .iter()
+                .map(|node| match node.hidden {
+                    0 => node.prefix.clone(),
+                    hidden => format!("{}[+{}] ", node.prefix, hidden),
+                })
+                .collect();
+        } else {
+            self.processes = self.all.clone();
+            self.prefixes.clear();
+            process::sort(&mut self.processes, self.sort, self.reverse);
+        }
+        let index = self
+            .selected_pid
+            .and_then(|pid| self.processes.iter().position(|process| process.pid == pid))
+            .unwrap_or(self.selected);
+        self.select(index);
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.processes.len().saturating_sub(1));
+        self.selected_pid = self.processes.get(self.selected).map(|process| process.pid);
+    }
+
+    fn sort_by(&mut self, key: SortKey) {
+        if self.tree {
+            self.status = Some((Tone::Warn, "The tree view is in PID order; press t for the sorted list".to_string()));
+            return;
+        }
+        if self.sort == key {
+            self.reverse = !self.reverse;
+        } else {
+            self.sort = key;
+            self.reverse = false;
+        }
+        self.resort();
+    }
+
+    /// Move the sort column `step` places through `SortKey::ALL`.
+    fn cycle_sort(&mut self, step: isize) {
+        if self.tree {
+            return self.sort_by(self.sort);
+        }
+        let count = SortKey::ALL.len() as isize;
+        let current = SortKey::ALL.iter().position(|key| *key == self.sort).unwrap_or_default() as isize;
+        self.sort = SortKey::ALL[(current + step).rem_euclid(count) as usize];
+        self.reverse = false;
+        self.resort();
+    }
+
+    fn set_refresh(&mut self, refresh: Duration) {
+        self.refresh = refresh.clamp(FASTEST, SLOWEST);
+        self.status = Some((Tone::Info, format!("Refreshing every {:.1}s", self.refresh.as_secs_f64())));
+    }
+
+    /// Handle one key; `false` closes the dashboard.
+    fn handle(&mut self, key: Key) -> bool {
+        if let Some(prompt) = self.prompt.take() {
+            self.answer(prompt, key);
+            return true;
+        }
+        self.status = None;
+        match key {
+            Key::Char('q') | Key::Escape | Key::CtrlC => return false,
+            Key::ArrowUp => self.select(self.selected.saturating_sub(1)),
+            Key::ArrowDown => self.select(self.selected + 1),
+            Key::PageUp => self.select(self.selected.saturating_sub(self.rows)),
+            Key::PageDown => self.select(self.selected + self.rows),
+            Key::Home => self.select(0),
+            Key::End => self.select(usize::MAX),
+            Key::Tab | Key::Char('>') => self.cycle_sort(1),
+            Key::BackTab | Key::Char('<') => self.cycle_sort(-1),
+            Key::Char('c') => self.sort_by(SortKey::Cpu),
+            Key::Char('m') => self.sort_by(SortKey::Mem),
+            Key::Char('p') => self.sort_by(SortKey::Pid),
+            Key::Char('u') => self.sort_by(SortKey::User),
+            Key::Char('n') => self.sort_by(SortKey::Command),
+            Key::Char('r') if !self.tree => {
+                self.reverse = !self.reverse;
+                self.resort();
+            }
+            Key::Char('t') => {
+                self.tree = !self.tree;
+                self.resort();
+            }
+            Key::ArrowLeft if self.tree => self.fold(true),
+            Key::ArrowRight if self.tree => self.fold(false),
+            Key::Char('+') => self.set_refresh(self.refresh / 2),
+            Key::Char('-') => self.set_refresh(self.refresh * 2),
+            Key::Char('k') => {
+                if let Some(target) = self.target(false) {
+                    self.prompt = Some(Prompt::Signal { target, input: String::new() });
+                }
+            }
+            Key::Char('x') => {
+                if let Some(target) = self.target(true) {
+                    self.prompt = Some(Prompt::Signal { target, input: String::new() });
+                }
+            }
+            Key::Char('T') => self.confirm(signals::TERM),
+            Key::Char('K') => self.confirm(signals::KILL),
+            _ => {}
+        }
+        true
+    }
+
+    /// Fold or unfold the selected process's subtree; folding a leaf folds its parent instead.
+    fn fold(&mut self, fold: bool) {
+        let Some(process) = self.processes.get(self.selected) else { return };
+        let (pid, ppid) = (process.pid, process.ppid);
+        if !fold {
+            self.folded.remove(&pid);
+        } else if Tree::new(&self.all).children(pid).is_empty() || self.folded.contains(&pid) {
+            if self.processes.iter().any(|process| process.pid == ppid) {
+                self.folded.insert(ppid);
+                self.selected_pid = Some(ppid);
+            }
+        } else {
+            self.folded.insert(pid);
+        }
+        self.resort();
+    }
+
+    /// The selected process, with its descendants when `subtree` is set. PID 1's subtree is
+    /// every process, so it can only be signalled on its own; gremlin never signals itself.
+    fn target(&mut self, subtree: bool) -> Option<Target> {
+        let process = self.processes.get(self.selected)?;
+        let own_pid = std::process::id();
+        if process.pid == own_pid {
+            self.status = Some((Tone::Bad, "❌ That's this dashboard; quit with q instead".to_string()));
+            return None;
+        }
+        let label = format!("{} ({})", process.pid, process.name);
+        if !subtree {
+            return Some(Target { pids: vec![process.pid], label, tree: Vec::new() });
+        }
+        if process.pid <= 1 {
+            self.status = Some((Tone::Bad, "❌ PID 1's subtree is every process; signal it on its own with k".to_string()));
+            return None;
+        }
+        let tree = Tree::new(&self.all);
+        let nodes = tree.draw(&[process.pid], &HashSet::new(), None);
+        let pids: Vec<u32> = nodes.iter().map(|node| node.pid).filter(|&pid| pid != own_pid).collect();
+        let lines = nodes
+            .iter()
+            .map(|node| {
+                let name = tree.get(node.pid).map(|process| process.name.as_str()).unwrap_or_default();
+                let skipped = if node.pid == own_pid { "  (gremlin, skipped)" } else { "" };
+                format!("{:>7} {}{}{}", node.pid, node.prefix, name, skipped)
+            })
+            .collect();
+        let label = format!("{} and {} descendant(s)", label, pids.len().saturating_sub(1));
+        Some(Target { pids, label, tree: lines })
+    }
+
+    fn confirm(&mut self, signal: Signal) {
+        if let Some(target) = self.target(false) {
+            self.prompt = Some(Prompt::Confirm { target, signal });
+        }
+    }
+
+    fn answer(&mut self, prompt: Prompt, key: Key) {
+        match (prompt, key) {
+            (Prompt::Signal { .. }, Key::Escape | Key::CtrlC) => self.status = Some((Tone::Warn, "Cancelled".to_string())),
+            (Prompt::Signal { target, input }, Key::Enter) => {
+                let input = if input.is_empty() { "TERM".to_string() } else { input };
+                match Signal::from_name(&input) {
+                    Some(signal) => self.prompt = Some(Prompt::Confirm { target, signal }),
+                    None => self.status = Some((Tone::Bad, format!("❌ Unknown signal '{}'", input))),
+                }
+            }
+            (Prompt::Signal { target, mut input }, Key::Backspace) => {
+                input.pop();
+                self.prompt = Some(Prompt::Signal { target, input });
+            }
+            (Prompt::Signal { target, mut input }, Key::Char(c)) if c.is_ascii_alphanumeric() => {
+                input.push(c);
+                self.prompt = Some(Prompt::Signal { target, input });
+            }
+            (prompt @ Prompt::Signal { .. }, _) => self.prompt = Some(prompt),
+            (Prompt::Confirm { target, signal }, Key::Char('y' | 'Y')) => self.send(&target, signal),
+            (Prompt::Confirm { .. }, _) => self.status = Some((Tone::Warn, "Cancelled".to_string())),
+        }
+    }
+
+    /// Send the signal directly, parents first, audited like the commands are; dry runs only
+    /// say what they'd do.
+    fn send(&mut self, target: &Target, signal: Signal) {
+        let what = format!("{} to {}", signal.name, target.label);
+        if self.exec.is_dry_run() {
+            self.status = Some((Tone::Info, format!("🧪 dry run: would send {}", what)));
+            self.sent.push(format!("🧪 Would have sent {}", what));
+            return;
+        }
+        let own_pid = std::process::id();
+        let pids: Vec<u32> = target.pids.iter().copied().filter(|&pid| pid != own_pid).collect();
+        let errors: Vec<String> = pids.iter().filter_map(|&pid| signal.send(pid).err().map(|e| format!("{}: {}", pid, e))).collect();
+        let mut argv = vec!["kill".to_string(), "-s".to_string(), signal.name.to_string()];
+        argv.extend(pids.iter().map(u32::to_string));
+        let entry = audit::Entry {
+            argv,
+            ..audit::Entry::new(audit::Kind::Sys, "dashboard", Vec::new())
+        };
+        if errors.is_empty() {
+            audit::record(&entry);
+            self.status = Some((Tone::Good, format!("✅ Sent {}", what)));
+            self.sent.push(format!("✅ Sent {}", what));
+        } else {
+            audit::record(&audit::Entry { exit_code: 1, error: Some(errors.join("; ")), ..entry });
+            self.status = Some((Tone::Bad, format!("❌ Could not send {}: {}", what, errors.join("; "))));
+        }
+    }
+
+    /// The whole screen, one string per line, each at most `width` columns.
+    fn frame(&mut self, width: usize, height: usize) -> Vec<String> {
+        let mut lines = vec![self.header(width), String::new()];
+
+        let columns = core_columns(width);
+        let cell = width / columns;
+        let core_bar = cell.saturating_sub(16).max(4);
+        for chunk in self.cores.iter().enumerate().collect::<Vec<_>>().chunks(columns) {
+            let cells: Vec<String> = chunk
+                .iter()
+                .map(|(i, usage)| {
+                    let label = format!("cpu{:<3}", i);
+                    let value = format!("{:>6.1}%", usage);
+                    let pad = " ".repeat(cell.saturating_sub(label.len() + core_bar + 2 + value.len()));
+                    format!("{}{}{}{}", label, bar(**usage, core_bar), value, pad)
+                })
+                .collect();
+            lines.push(cells.concat());
+        }
+
+        let gauge = (width / 3).clamp(10, 40);
+        let left = 5 + gauge + 2 + 24;
+        let spark = width.saturating_sub(left + 1);
+        let memory = self.memory.clone().unwrap_or_default();
+        lines.push(format!(
+            "{}{}{:<24} {}",
+            "CPU  ".bold(),
+            bar(self.cpu, gauge),
+            format!(" {:.1}%", self.cpu),
+            self.cpu_history.sparkline(spark, Some(100.0)).cyan()
+        ));
+        lines.push(format!(
+            "{}{}{:<24} {}",
+            "Mem  ".bold(),
+            bar(memory.used_percent(), gauge),
+            format!(" {}/{} {:.0}%", human_bytes(memory.used()), human_bytes(memory.total), memory.used_percent()),
+            self.memory_history.sparkline(spark, Some(100.0)).cyan()
+        ));
+        lines.push(format!(
+            "{}{}{:<24}",
+            "Swap ".bold(),
+            bar(memory.swap_percent(), gauge),
+            format!(" {}/{}", human_bytes(memory.swap_used()), human_bytes(memory.swap_total))
+        ));
+        let rates = |label: &str, first: &str, (a, b): (f64, f64), history: &History| {
+            let text = format!("{} {}/s  {} {}/s", first, human_bytes(a as u64), label, human_bytes(b as u64));
+            format!("{:<width$} {}", text, history.sparkline(spark, None).cyan(), width = left - 5)
+        };
+        lines.push(format!("{}{}", "Disk ".bold(), rates("write", "read", self.disk, &self.disk_history)));
+        lines.push(format!("{}{}", "Net  ".bold(), rates("tx", "rx", self.network, &self.network_history)));
+        lines.push(String::new());
+
+        let table_header = format!("{:>7} {:<9} S {:>6} {:>6} {:>4} COMMAND", "PID", "USER", "%CPU", "RSS", "THR");
+        lines.push(format!("{:<width$}", table_header, width = width).reversed().to_string());
+
+        let rows = height.saturating_sub(lines.len() + 2).max(1);
+        self.rows = rows;
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + rows {
+            self.scroll = self.selected + 1 - rows;
+        }
+        // Confirming a subtree: list exactly what will be signalled instead of the table.
+        let confirming = match &self.prompt {
+            Some(Prompt::Confirm { target, signal }) if !target.tree.is_empty() => Some((target, signal)),
+            _ => None,
+        };
+        if let Some((target, signal)) = confirming {
+            let shown = if target.tree.len() >= rows { rows.saturating_sub(2) } else { target.tree.len() };
+            lines.push(render::paint(&format!("{} goes to these {} process(es), parents first:", signal.name, target.pids.len()), Tone::Warn).to_string());
+            for line in target.tree.iter().take(shown) {
+                lines.push(console::truncate_str(line, width, "").to_string());
+            }
+            if target.tree.len() > shown {
+                lines.push(format!("… and {} more", target.tree.len() - shown));
+            }
+        }
+        for (i, process) in self.processes.iter().enumerate().skip(self.scroll).take(if confirming.is_some() { 0 } else { rows }) {
+            let row = format!(
+                "{:>7} {:<9} {} {:>6.1} {:>6} {:>4} {}{}",
+                process.pid,
+                console::truncate_str(&process.user, 9, ""),
+                process.state,
+                process.cpu_percent,
+                human_bytes(process.rss_bytes),
+                process.threads,
+                self.prefixes.get(i).map(String::as_str).unwrap_or_default(),
+                process.command.replace('\n', " ")
+            );
+            let row = console::truncate_str(&row, width, "").to_string();
+            lines.push(if i == self.selected {
+                format!("{:<width$}", row, width = width).reversed().to_string()
+            } else {
+                match process.state {
+                    'R' => render::paint(&row, Tone::Good).to_string(),
+                    'D' => render::paint(&row, Tone::Warn).to_string(),
+                    'Z' | 'X' => render::paint(&row, Tone::Bad).to_string(),
+                    _ => row,
+                }
+            });
+        }
+        while lines.len() < height.saturating_sub(2) {
+            lines.push(String::new());
+        }
+
+        lines.push(console::truncate_str(HELP, width, "…").dimmed().to_string());
+        lines.push(self.status_line(width));
+        lines
+    }
+
+    fn header(&self, width: usize) -> String {
+        let host = self.proc.read("sys/kernel/hostname").unwrap_or_default();
+        let mut parts = vec![format!("gremlin dashboard — {}", host.trim())];
+        if let Some(uptime) = self.proc.uptime() {
+            parts.push(format!("up {}", uptime_text(uptime)));
+        }
+        if let Some(load) = self.load {
+            parts.push(format!("load {:.2} {:.2} {:.2}", load.one, load.five, load.fifteen));
+            parts.push(format!("{} tasks, {} running", load.total, load.running));
+        }
+        let arrow = if self.sort.descending() != self.reverse { "▼" } else { "▲" };
+        parts.push(format!("sort {} {}", self.sort.name(), arrow));
+        console::truncate_str(&parts.join("  ·  "), width, "…").bold().to_string()
+    }
+
+    fn status_line(&self, width: usize) -> String {
+        let (tone, text) = match (&self.prompt, &self.status) {
+            (Some(Prompt::Signal { target, input }), _) => {
+                (Tone::Info, format!("Signal for {}, e.g. TERM, HUP, INT, USR1, KILL [TERM]: {}▏", target.label, input))
+            }
+            (Some(Prompt::Confirm { target, signal }), _) => {
+                (Tone::Warn, format!("Send {} (signal {}) to {}? (y/n)", signal.name, signal.number, target.label))
+            }
+            (None, Some((tone, text))) => (*tone, text.clone()),
+            (None, None) if self.tree => {
+                let pid = self.processes.get(self.selected).map(|process| process.pid).unwrap_or_default();
+                let totals = Tree::new(&self.all).totals(pid);
+                (
+                    Tone::Plain,
+                    format!(
+                        "Subtree of {}: {} processes, {:.1}% CPU, {} · refresh every {:.1}s",
+                        pid,
+                        totals.processes,
+                        totals.cpu_percent,
+                        human_bytes(totals.rss_bytes),
+                        self.refresh.as_secs_f64()
+                    ),
+                )
+            }
+            (None, None) => (
+                Tone::Plain,
+                format!("{} processes · refresh every {:.1}s", self.processes.len(), self.refresh.as_secs_f64()),
+            ),
+        };
+        render::paint(&console::truncate_str(&text, width, "…"), tone).to_string()
+    }
+}
+
+/// Bytes per second received and sent over every interface except loopback.
+fn network_rates(before: &[InterfaceCounters], after: &[InterfaceCounters], seconds: f64) -> (f64, f64) {
+    after
+        .iter()
+        .filter(|interface| !interface.is_loopback())
+        .filter_map(|interface| {
+            let earlier = before.iter().find(|earlier| earlier.name == interface.name)?;
+            Some((
+                interface.rx_bytes.saturating_sub(earlier.rx_bytes) as f64 / seconds,
+                interface.tx_bytes.saturating_sub(earlier.tx_bytes) as f64 / seconds,
+            ))
+        })
+        .fold((0.0, 0.0), |(rx, tx), (r, t)| (rx + r, tx + t))
+}
+
+/// Per-core bars side by side, as many as fit at a readable width.
+fn core_columns(width: usize) -> usize {
+    (width / 36).clamp(1, 4)
+}
+
+/// `[|||||     ]`, coloured by how full it is.
+fn bar(percent: f64, width: usize) -> String {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f64).round() as usize;
+    let tone = if percent > 85.0 {
+        Tone::Bad
+    } else if percent > 60.0 {
+        Tone::Warn
+    } else {
+        Tone::Good
+    };
+    format!("[{}{}]", render::paint(&"|".repeat(filled), tone), " ".repeat(width - filled))
+}
+
+fn uptime_text(seconds: f64) -> String {
+    let minutes = seconds as u64 / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {:02}:{:02}", days, hours, minutes)
+    } else {
+        format!("{:02}:{:02}", hours, minutes)
+    }
+}
+
+/// The terminal in full-screen, unbuffered, unechoed mode; put back as it was on drop.
+struct Screen {
+    original: libc::termios,
+}
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        // SAFETY: termios is plain data; tcgetattr fills it in or fails.
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        // Ctrl-C arrives as a key instead of a signal, so the terminal is always restored.
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        // SAFETY: `raw` is a fully initialised termios copied from the one tcgetattr returned.
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Alternate screen, cursor hidden.
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush()?;
+        Ok(Screen { original })
+    }
+
+    fn draw(&self, lines: &[String]) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        write!(stdout, "\x1b[H{}\x1b[K\x1b[J", lines.join("\x1b[K\r\n"))?;
+        stdout.flush()
+    }
+
+    /// Keys typed within `timeout`; none if it runs out.
+    fn keys(&self, timeout: Duration) -> io::Result<Vec<Key>> {
+        let mut stdin = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        let millis = timeout.as_millis().min(i32::MAX as u128) as i32;
+        // SAFETY: one valid pollfd that outlives the call, and a count of 1 to match.
+        match unsafe { libc::poll(&mut stdin, 1, millis) } {
+            0 => return Ok(Vec::new()),
+            n if n < 0 => {
+                let e = io::Error::last_os_error();
+                return if e.kind() == io::ErrorKind::Interrupted { Ok(Vec::new()) } else { Err(e) };
+            }
+            _ => {}
+        }
+        let mut buffer = [0u8; 64];
+        // SAFETY: read writes at most `buffer.len()` bytes into the stack buffer it points at.
+        let read = unsafe { libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len()) };
+        match read {
+            0 => Err(io::ErrorKind::UnexpectedEof.into()),
+            n if n < 0 => Err(io::Error::last_os_error()),
+            n => Ok(decode(&buffer[..n as usize])),
+        }
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        // SAFETY: `original` is the termios tcgetattr filled in when the screen was entered.
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+/// Split raw terminal input into keys; arrows and paging keys arrive as escape sequences.
+fn decode(input: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(input);
+    let mut chars = text.chars().peekable();
+    let mut keys = Vec::new();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if matches!(chars.peek(), Some('[' | 'O')) => {
+                chars.next();
+                let mut sequence = String::new();
+                for c in chars.by_ref() {
+                    sequence.push(c);
+                    if c.is_ascii_alphabetic() || c == '~' {
+                        break;
+                    }
+                }
+                match sequence.as_str() {
+                    "A" => Key::ArrowUp,
+                    "B" => Key::ArrowDown,
+                    "C" => Key::ArrowRight,
+                    "D" => Key::ArrowLeft,
+                    "H" | "1~" | "7~" => Key::Home,
+                    "F" | "4~" | "8~" => Key::End,
+                    "5~" => Key::PageUp,
+                    "6~" => Key::PageDown,
+                    "Z" => Key::BackTab,
+                    _ => Key::Unknown,
+                }
+            }
+            '\x1b' => Key::Escape,
+            '\r' | '\n' => Key::Enter,
+            '\t' => Key::Tab,
+            '\x7f' | '\x08' => Key::Backspace,
+            '\x03' => Key::CtrlC,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// `gremlin sys dashboard [refresh-ms] [sort]`. Empty arguments fall back to the
+/// `[dashboard]` config section.
+pub fn run(exec: &dyn Executor, proc: &ProcFs, refresh: &str, sort: &str) -> CommandResult {
+    let settings = &config::get().dashboard;
+    let refresh_ms = match refresh.trim() {
+        "" => settings.refresh_ms,
+        ms => ms.parse().map_err(|_| CommandError::InvalidArgument(format!("refresh '{}': expected milliseconds", ms)))?,
+    };
+    let refresh = Duration::from_millis(refresh_ms).clamp(FASTEST, SLOWEST);
+    let (sort, reverse) = command_logic::sort_key(if sort.trim().is_empty() { &settings.sort } else { sort })?;
+
+    // SAFETY: isatty only inspects the descriptor.
+    if !Term::stdout().is_term() || unsafe { libc::isatty(libc::STDIN_FILENO) } == 0 {
+        return Err(CommandError::InvalidArgument("the dashboard needs an interactive terminal".to_string()));
+    }
+
+    let started = Instant::now();
+    let mut dashboard = Dashboard::new(exec, proc.clone(), refresh, sort, reverse);
+    // Rates need two readings; take the first one just before the screen appears.
+    dashboard.refresh();
+    thread::sleep(FASTEST * 2);
+    dashboard.refresh();
+
+    let screen = Screen::enter()
+        .map_err(|e| CommandError::InvalidArgument(format!("the dashboard needs an interactive terminal: {}", e)))?;
+    let mut next = Instant::now() + dashboard.refresh;
+    loop {
+        let (height, width) = Term::stdout().size();
+        let (height, width) = (height as usize, width as usize);
+        if screen.draw(&dashboard.frame(width, height)).is_err() {
+            break;
+        }
+        let keys = match screen.keys(next.saturating_duration_since(Instant::now())) {
+            Ok(keys) => keys,
+            Err(_) => break,
+        };
+        if !keys.into_iter().all(|key| dashboard.handle(key)) {
+            break;
+        }
+        if Instant::now() >= next {
+            dashboard.refresh();
+            next = Instant::now() + dashboard.refresh;
+        }
+    }
+    drop(screen);
+
+    let mut out = CommandOutput { duration: started.elapsed(), ..Default::default() };
+    for sent in &dashboard.sent {
+        out.note(Tone::Info, sent.clone());
+    }
+    out.note(Tone::Good, "Dashboard closed.");
+    Ok(out)
+}
@@ -0,0 +1,331 @@
+//! `gremlin doctor`: which binaries the commands need, which package provides each one on this
+//! machine, and whether it is available, missing or has an upgrade pending. Missing packages can
+//! be installed through the detected package manager.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use colored::*;
+
+use crate::exec::{self, Executor};
+use crate::packages::{self, PackageManager, Upgrade};
+use crate::privilege;
+use crate::sys_admin::command_matcher;
+use crate::sys_admin::command_output::{CommandError, CommandOutput, Field, Record, Tone};
+use crate::sys_admin::registry::{self, CommandCategory, CommandSpec};
+use crate::sys_admin::render::{self, Format};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Available,
+    /// Installed, but the package manager has a newer version.
+    Outdated,
+    Missing,
+}
+
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Available => "available",
+            Status::Outdated => "outdated",
+            Status::Missing => "missing",
+        }
+    }
+
+    fn tone(&self) -> Tone {
+        match self {
+            Status::Available => Tone::Normal,
+            Status::Outdated => Tone::Warn,
+            Status::Missing => Tone::Bad,
+        }
+    }
+}
+
+/// One required binary and what depends on it.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub binary: &'static str,
+    /// Canonical name of the providing package, `None` when gremlin doesn't know it.
+    pub package: Option<String>,
+    pub status: Status,
+    /// The pending upgrade, for outdated packages.
+    pub upgrade: Option<Upgrade>,
+    pub commands: Vec<&'static str>,
+    pub categories: Vec<CommandCategory>,
+}
+
+/// Check every binary the given commands need. Inputs are passed in so the result can be
+/// worked out for any machine.
+pub fn diagnose<'a>(
+    specs: impl Iterator<Item = &'a CommandSpec>,
+    on_path: impl Fn(&str) -> bool,
+    provider: impl Fn(&str) -> Option<String>,
+    manager: Option<&dyn PackageManager>,
+    upgrades: &[Upgrade],
+) -> Vec<Check> {
+    let mut checks: BTreeMap<&'static str, Check> = BTreeMap::new();
+    for spec in specs {
+        for &binary in spec.binaries {
+            let check = checks.entry(binary).or_insert_with(|| Check {
+                binary,
+                package: provider(binary),
+                status: Status::Missing,
+                upgrade: None,
+                commands: Vec::new(),
+                categories: Vec::new(),
+            });
+            check.commands.push(spec.name);
+            if !check.categories.contains(&spec.category) {
+                check.categories.push(spec.category);
+            }
+        }
+    }
+
+    for check in checks.values_mut() {
+        if !on_path(check.binary) {
+            continue;
+        }
+        let installed_as = match (manager, &check.package) {
+            (Some(manager), Some(package)) => Some(packages::package_name(manager, package)),
+            _ => None,
+        };
+        check.upgrade = upgrades.iter().find(|upgrade| Some(&upgrade.name) == installed_as.as_ref()).cloned();
+        check.status = if check.upgrade.is_some() { Status::Outdated } else { Status::Available };
+    }
+    checks.into_values().collect()
+}
+
+/// `diagnose` for this machine: commands in `category` (or all), `PATH`, and the detected
+/// package manager's pending upgrades.
+pub fn check(exec: &dyn Executor, category: Option<CommandCategory>) -> Vec<Check> {
+    let manager = packages::detect();
+    let upgrades = manager
+        .and_then(|manager| {
+            let listed = exec.run(&manager.list_upgradable()).ok()?;
+            Some(manager.parse_upgradable(&listed.stdout))
+        })
+        .unwrap_or_default();
+    let specs = registry::all().filter(|spec| category.is_none_or(|category| spec.category == category));
+    diagnose(specs, exec::on_path, packages::provider, manager, &upgrades)
+}
+
+/// Canonical packages providing the missing binaries, each once.
+pub fn missing_packages(checks: &[Check]) -> Vec<String> {
+    let mut missing: Vec<String> = Vec::new();
+    for check in checks.iter().filter(|check| check.status == Status::Missing) {
+        if let Some(package) = check.package.as_ref().filter(|package| !missing.contains(package)) {
+            missing.push(package.clone());
+        }
+    }
+    missing
+}
+
+pub fn to_output(checks: &[Check], manager: Option<&dyn PackageManager>) -> CommandOutput {
+    let mut out = CommandOutput::default().with_title("🩺 Gremlin doctor:");
+    out.headers = ["Binary", "Package", "Status", "Version", "Categories", "Used by"].map(String::from).to_vec();
+    for check in checks {
+        let package = match (manager, &check.package) {
+            (Some(manager), Some(package)) => Field::Text(packages::package_name(manager, package)),
+            (None, Some(package)) => Field::Text(package.clone()),
+            (_, None) => Field::Null,
+        };
+        let version = match &check.upgrade {
+            Some(upgrade) if upgrade.current.is_empty() => Field::Text(format!("→ {}", upgrade.available)),
+            Some(upgrade) => Field::Text(format!("{} → {}", upgrade.current, upgrade.available)),
+            None => Field::Null,
+        };
+        let categories: Vec<&str> = check.categories.iter().map(|category| category.name()).collect();
+        out.push_record(
+            Record {
+                fields: vec![
+                    ("binary".to_string(), Field::Text(check.binary.to_string())),
+                    ("package".to_string(), package),
+                    ("status".to_string(), Field::Text(check.status.name().to_string())),
+                    ("version".to_string(), version),
+                    ("categories".to_string(), Field::Text(categories.join(", "))),
+                    ("commands".to_string(), Field::Text(check.commands.join(", "))),
+                ],
+            },
+            check.status.tone(),
+        );
+    }
+
+    let count = |status: Status| checks.iter().filter(|check| check.status == status).count();
+    let (available, outdated, missing) = (count(Status::Available), count(Status::Outdated), count(Status::Missing));
+    let tone = if missing > 0 { Tone::Bad } else if outdated > 0 { Tone::Warn } else { Tone::Good };
+    out.note(tone, format!("{} available, {} outdated, {} missing", available, outdated, missing));
+
+    match manager {
+        Some(manager) => out.note(Tone::Info, format!("📦 Package manager: {}", manager.name())),
+        None => out.note(Tone::Warn, "📦 No supported package manager found; outdated packages can't be checked"),
+    }
+    if privilege::is_root() {
+        out.note(Tone::Info, "🔑 Running as root");
+    } else {
+        match privilege::elevator() {
+            Some(elevator) => out.note(Tone::Info, format!("🔑 Root commands run via {}", elevator.program())),
+            None => out.note(Tone::Warn, "🔑 None of sudo, doas, run0 or pkexec is available; root commands will fail"),
+        }
+    }
+
+    let unknown: Vec<&str> = checks
+        .iter()
+        .filter(|check| check.status == Status::Missing && check.package.is_none())
+        .map(|check| check.binary)
+        .collect();
+    if !unknown.is_empty() {
+        out.note(Tone::Warn, format!("No known package for {}; add it under [packages.binaries] in the config", unknown.join(", ")));
+    }
+    let installable = missing_packages(checks);
+    if !installable.is_empty() {
+        out.note(Tone::Warn, format!("Run `gremlin doctor --install` to install: {}", installable.join(" ")));
+    }
+    out
+}
+
+/// Install the packages behind missing binaries with the `install` command, so it is
+/// audited and honours `--dry-run`. `Ok(None)` when nothing is missing.
+pub fn install_missing(checks: &[Check]) -> Result<Option<CommandOutput>, CommandError> {
+    let missing = missing_packages(checks);
+    if missing.is_empty() {
+        return Ok(None);
+    }
+    command_matcher::execute("install", &[missing.join(" ")]).map(Some)
+}
+
+/// `gremlin doctor [--category <name>] [--install]`; returns the exit code, 1 when anything is
+/// still missing.
+pub fn run(category: Option<&str>, install: bool, format: Format) -> i32 {
+    let category = match category.map(|name| CommandCategory::from_name(name).ok_or(name)).transpose() {
+        Ok(category) => category,
+        Err(name) => {
+            let names: Vec<&str> = CommandCategory::ALL.iter().map(|category| category.name()).collect();
+            let e = CommandError::InvalidArgument(format!("unknown category '{}', expected one of: {}", name, names.join(", ")));
+            render::print_error_as(&e, format);
+            return e.exit_code();
+        }
+    };
+
+    let exec = exec::current();
+    let mut checks = check(&*exec, category);
+    if install {
+        match install_missing(&checks) {
+            Ok(Some(output)) => {
+                if format == Format::Table {
+                    render::print_output(&output);
+                    println!();
+                }
+                checks = check(&*exec, category);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                render::print_error_as(&e, format);
+                return e.exit_code();
+            }
+        }
+    }
+
+    render::print_output_as(&to_output(&checks, packages::detect()), format);
+    if checks.iter().any(|check| check.status == Status::Missing) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Menu entry: show the report and offer to install what's missing.
+pub fn menu() {
+    let checks = check(&*exec::current(), None);
+    render::print_output(&to_output(&checks, packages::detect()));
+    if missing_packages(&checks).is_empty() {
+        return;
+    }
+
+    print!("{}", "\nInstall the missing packages now? (y/n): ".bright_blue());
+    io::stdout().flush().unwrap();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).unwrap();
+    if answer.trim().eq_ignore_ascii_case("y") {
+        match install_missing(&checks) {
+            Ok(Some(output)) => render::print_output(&output),
+            Ok(None) => {}
+            Err(e) => render::print_error(&e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys_admin::registry::Danger;
+
+    const fn spec(name: &'static str, category: CommandCategory, binaries: &'static [&'static str]) -> CommandSpec {
+        CommandSpec {
+            name,
+            aliases: &[],
+            category,
+            description: "",
+            args: &[],
+            binaries,
+            danger: Danger::Safe,
+            root: false,
+            affects: None,
+            run: |_, _| Ok(CommandOutput::default()),
+        }
+    }
+
+    const SPECS: &[CommandSpec] = &[
+        spec("htop", CommandCategory::ResourceMonitoring, &["htop"]),
+        spec("nmap", CommandCategory::Networking, &["nmap"]),
+        spec("services", CommandCategory::ServiceManagement, &["systemctl"]),
+        spec("logs", CommandCategory::LogDiving, &["journalctl", "systemctl"]),
+        spec("kubectl-pods", CommandCategory::ProcessManagement, &["kubectl"]),
+        spec("native", CommandCategory::Networking, &[]),
+    ];
+
+    fn statuses(checks: &[Check]) -> Vec<(&str, Option<&str>, &str)> {
+        checks.iter().map(|check| (check.binary, check.package.as_deref(), check.status.name())).collect()
+    }
+
+    #[test]
+    fn binaries_are_available_or_missing_by_path() {
+        let checks = diagnose(SPECS.iter(), |binary| ["htop", "systemctl"].contains(&binary), |binary| packages::provider_in(&BTreeMap::new(), binary), None, &[]);
+        assert_eq!(
+            statuses(&checks),
+            [
+                ("htop", Some("htop"), "available"),
+                ("journalctl", Some("systemd"), "missing"),
+                ("kubectl", None, "missing"),
+                ("nmap", Some("nmap"), "missing"),
+                ("systemctl", Some("systemd"), "available"),
+            ]
+        );
+        let systemctl = &checks[4];
+        assert_eq!(systemctl.commands, ["services", "logs"]);
+        assert_eq!(systemctl.categories, [CommandCategory::ServiceManagement, CommandCategory::LogDiving]);
+        // journalctl comes from systemd like systemctl; it is only listed once.
+        assert_eq!(missing_packages(&checks), ["systemd", "nmap"]);
+    }
+
+    #[test]
+    fn configured_providers_override_the_built_in_table() {
+        let overrides: BTreeMap<String, String> =
+            [("kubectl", "kubernetes-client"), ("nmap", "nmap-custom")].map(|(binary, package)| (binary.to_string(), package.to_string())).into();
+        let checks = diagnose(SPECS.iter(), |_| false, |binary| packages::provider_in(&overrides, binary), None, &[]);
+        let packages: Vec<(&str, Option<&str>)> = checks.iter().map(|check| (check.binary, check.package.as_deref())).collect();
+        assert_eq!(packages[2..4], [("kubectl", Some("kubernetes-client")), ("nmap", Some("nmap-custom"))]);
+        assert_eq!(missing_packages(&checks), ["htop", "systemd", "kubernetes-client", "nmap-custom"]);
+    }
+
+    #[test]
+    fn pending_upgrades_mark_installed_binaries_outdated() {
+        let upgrades = [Upgrade { name: "htop".to_string(), current: "3.2.1".to_string(), available: "3.3.0".to_string() }];
+        let apt = packages::by_name("apt").unwrap();
+        let checks = diagnose(SPECS[..1].iter(), |_| true, |binary| packages::provider_in(&BTreeMap::new(), binary), Some(apt.as_ref()), &upgrades);
+        assert_eq!(statuses(&checks), [("htop", Some("htop"), "outdated")]);
+        assert_eq!(checks[0].upgrade.as_ref().map(|upgrade| upgrade.available.as_str()), Some("3.3.0"));
+
+        // Without a package manager the upgrade can't be matched to the binary.
+        let checks = diagnose(SPECS[..1].iter(), |_| true, |binary| packages::provider_in(&BTreeMap::new(), binary), None, &upgrades);
+        assert_eq!(statuses(&checks), [("htop", Some("htop"), "available")]);
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use colored::*;
+use dialoguer::Confirm;
+
+use crate::exec::Executor;
+use crate::sys_admin::args::Args;
+use crate::sys_admin::command_output::CommandError;
+use crate::sys_admin::registry::{CommandSpec, Danger};
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Skip confirmation prompts (`--yes`), for scripts and cron jobs.
+pub fn set_assume_yes(enabled: bool) {
+    ASSUME_YES.store(enabled, Ordering::Relaxed);
+}
+
+/// Show what a destructive command is about to touch and ask before letting it run.
+/// Returns the affected targets; safe and caution-level commands pass straight through.
+/// Prompts go to stderr so `--format json` output stays clean.
+pub fn confirm(exec: &dyn Executor, spec: &CommandSpec, args: &Args) -> Result<Vec<String>, CommandError> {
+    if spec.danger < Danger::Destructive {
+        return Ok(Vec::new());
+    }
+
+    let targets = match spec.affects {
+        Some(affects) => affects(exec, args)?,
+        None => Vec::new(),
+    };
+    if exec.is_dry_run() || ASSUME_YES.load(Ordering::Relaxed) {
+        return Ok(targets);
+    }
+
+    eprintln!("{}", format!("☠️ {} is destructive. This will affect:", spec.name).bright_red().bold());
+    for target in &targets {
+        eprintln!("  {} {}", "•".bright_red(), target.bright_yellow());
+    }
+
+    // A non-interactive stdin can't answer, so it counts as "no"; use --yes instead.
+    let proceed = Confirm::new()
+        .with_prompt("Proceed?")
+        .default(false)
+        .interact()
+        .unwrap_or(false);
+    if proceed {
+        Ok(targets)
+    } else {
+        Err(CommandError::Cancelled)
+    }
+}
@@ -1,17 +1,16 @@
+pub mod args;
 pub mod command_logic;
 pub mod command_matcher;
-pub mod command_types;
-pub mod command_names;
-pub mod command_categories;
-pub mod networking;
-pub mod process;
-pub mod resource;
-pub mod disk;
-pub mod logs;
-pub mod services;
-pub mod security;
-pub mod packages;
+pub mod command_output;
+pub mod dashboard;
+pub mod doctor;
+pub mod guard;
+pub mod playbook;
+pub mod plugins;
+pub mod registry;
+pub mod render;
+pub mod repl;
 
 pub fn run() {
     command_matcher::run();
-} 
\ No newline at end of file
+}
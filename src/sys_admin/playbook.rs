@@ -0,0 +1,697 @@
+//! Named sequences of sys admin commands, stored as TOML or YAML files in
+//! `<config dir>/playbooks/`. The file name (without extension) is the playbook name.
+//!
+//! ```toml
+//! description = "Update, check disk space, then restart a failed service"
+//!
+//! [[steps]]
+//! command = "update"
+//! continue_on_failure = true
+//!
+//! [[steps]]
+//! command = "df"
+//!
+//! [[steps]]
+//! command = "systemctl-list"
+//! capture = "unit"
+//! capture_pattern = "(\\S+\\.service)\\s+loaded\\s+failed"
+//!
+//! [[steps]]
+//! command = "systemctl-restart"
+//! args = ["{{unit}}"]
+//! only_if = "loaded\\s+failed"
+//! ```
+//!
+//! Captured values can be used in later arguments as `{{name}}`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use colored::*;
+use regex::Regex;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::config;
+use crate::sys_admin::command_matcher;
+use crate::sys_admin::command_output::{CommandError, CommandOutput, CommandResult, Field, Record, Row, Tone};
+use crate::sys_admin::registry;
+use crate::sys_admin::render::{self, Format};
+
+const EXTENSIONS: [&str; 3] = ["toml", "yaml", "yml"];
+
+#[derive(Error, Debug)]
+pub enum PlaybookError {
+    #[error("No playbook named '{0}' (looked in {1})")]
+    NotFound(String, String),
+    #[error("Could not read {path}: {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Invalid playbook {path}: {message}")]
+    Parse { path: String, message: String },
+    #[error("Playbook '{playbook}', step {step}: {message}")]
+    InvalidStep { playbook: String, step: usize, message: String },
+}
+
+impl PlaybookError {
+    /// Exit code used by the CLI for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PlaybookError::NotFound(..) => 127,
+            PlaybookError::Read { .. } => 1,
+            PlaybookError::Parse { .. } | PlaybookError::InvalidStep { .. } => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Playbook {
+    /// File name without its extension.
+    #[serde(skip)]
+    pub name: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(default)]
+    pub description: String,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// Registry command name or alias.
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Keep going with the next step if this one fails.
+    #[serde(default)]
+    pub continue_on_failure: bool,
+    /// Only run if the output of the previous step that ran matches this regex.
+    pub only_if: Option<String>,
+    /// Store this step's output as `{{capture}}` for later arguments.
+    pub capture: Option<String>,
+    /// Store the first group (or the whole match) of this regex instead of the full output.
+    pub capture_pattern: Option<String>,
+}
+
+impl Step {
+    /// How the step is shown in progress lines and the summary.
+    pub fn label(&self) -> String {
+        std::iter::once(self.command.as_str()).chain(self.args.iter().map(String::as_str)).collect::<Vec<_>>().join(" ")
+    }
+}
+
+pub fn playbooks_dir() -> PathBuf {
+    config::config_dir().join("playbooks")
+}
+
+impl Playbook {
+    /// Parse a playbook file; the format is picked from the extension.
+    pub fn from_path(path: &Path) -> Result<Self, PlaybookError> {
+        let display = path.display().to_string();
+        let content = fs::read_to_string(path).map_err(|source| PlaybookError::Read { path: display.clone(), source })?;
+        let parsed: Result<Playbook, String> = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(&content).map_err(|e| e.to_string()),
+            _ => toml::from_str(&content).map_err(|e| e.to_string()),
+        };
+        let mut playbook = parsed.map_err(|message| PlaybookError::Parse { path: display, message })?;
+        playbook.name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        playbook.path = path.to_path_buf();
+        playbook.validate()?;
+        Ok(playbook)
+    }
+
+    /// Catch mistakes before the first step runs: unknown commands, bad regexes and
+    /// `{{variables}}` that no earlier step captures.
+    fn validate(&self) -> Result<(), PlaybookError> {
+        let invalid = |step: usize, message: String| PlaybookError::InvalidStep { playbook: self.name.clone(), step, message };
+        if self.steps.is_empty() {
+            return Err(invalid(0, "no steps".to_string()));
+        }
+
+        let mut captured: Vec<&str> = Vec::new();
+        for (i, step) in self.steps.iter().enumerate() {
+            let n = i + 1;
+            let spec = registry::find(&step.command).ok_or_else(|| {
+                let hint = registry::suggest(&step.command).map(|s| format!(", did you mean `{}`?", s)).unwrap_or_default();
+                invalid(n, format!("unknown command `{}`{}", step.command, hint))
+            })?;
+            if step.args.len() > spec.args.len() {
+                return Err(invalid(n, format!("`{}` takes at most {} argument(s)", spec.name, spec.args.len())));
+            }
+            for pattern in step.only_if.iter().chain(&step.capture_pattern) {
+                Regex::new(pattern).map_err(|e| invalid(n, format!("bad pattern `{}`: {}", pattern, e)))?;
+            }
+            if step.capture_pattern.is_some() && step.capture.is_none() {
+                return Err(invalid(n, "capture_pattern needs a capture name".to_string()));
+            }
+            for variable in step.args.iter().flat_map(|arg| variables(arg)) {
+                if !captured.contains(&variable) {
+                    return Err(invalid(n, format!("`{{{{{}}}}}` is not captured by an earlier step", variable)));
+                }
+            }
+            if let Some(name) = &step.capture {
+                captured.push(name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Names of the `{{variables}}` used in an argument.
+fn variables(arg: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = arg;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else { break };
+        names.push(&rest[start + 2..start + 2 + len]);
+        rest = &rest[start + 2 + len + 2..];
+    }
+    names
+}
+
+/// Replace `{{variables}}` with captured values in one pass from the left, so captured text is
+/// never scanned for placeholders again; fails on one that wasn't captured at run time.
+fn substitute(arg: &str, vars: &HashMap<String, String>) -> Result<String, CommandError> {
+    let mut value = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else { break };
+        let name = &rest[start + 2..start + 2 + len];
+        let captured = vars
+            .get(name)
+            .ok_or_else(|| CommandError::InvalidArgument(format!("`{{{{{}}}}}` was not captured", name)))?;
+        value.push_str(&rest[..start]);
+        value.push_str(captured);
+        rest = &rest[start + 2 + len + 2..];
+    }
+    value.push_str(rest);
+    Ok(value)
+}
+
+/// Every playbook in the playbooks dir, sorted by name. Broken files are returned as errors
+/// so listings can point at them.
+pub fn load_all() -> Vec<Result<Playbook, PlaybookError>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(playbooks_dir())
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    paths.retain(|path| path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| EXTENSIONS.contains(&ext)));
+    paths.sort();
+    paths.iter().map(|path| Playbook::from_path(path)).collect()
+}
+
+/// A playbook by name, or by path to a playbook file.
+pub fn load(name: &str) -> Result<Playbook, PlaybookError> {
+    let path = Path::new(name);
+    if path.is_file() {
+        return Playbook::from_path(path);
+    }
+    let dir = playbooks_dir();
+    EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| PlaybookError::NotFound(name.to_string(), dir.display().to_string()))
+        .and_then(|path| Playbook::from_path(&path))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    /// Failed, but the step allows the playbook to go on.
+    Tolerated,
+    Skipped,
+    DryRun,
+    /// An earlier step failed and stopped the playbook.
+    NotRun,
+}
+
+impl StepStatus {
+    fn name(&self) -> &'static str {
+        match self {
+            StepStatus::Passed => "passed",
+            StepStatus::Failed => "failed",
+            StepStatus::Tolerated => "failed (continued)",
+            StepStatus::Skipped => "skipped",
+            StepStatus::DryRun => "dry run",
+            StepStatus::NotRun => "not run",
+        }
+    }
+
+    fn tone(&self) -> Tone {
+        match self {
+            StepStatus::Passed => Tone::Good,
+            StepStatus::Failed => Tone::Bad,
+            StepStatus::Tolerated => Tone::Warn,
+            StepStatus::Skipped | StepStatus::NotRun => Tone::Plain,
+            StepStatus::DryRun => Tone::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StepOutcome {
+    pub label: String,
+    pub status: StepStatus,
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+    /// Why a step failed or was skipped.
+    pub detail: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub playbook: String,
+    pub steps: Vec<StepOutcome>,
+}
+
+impl Report {
+    fn count(&self, status: StepStatus) -> usize {
+        self.steps.iter().filter(|step| step.status == status).count()
+    }
+
+    /// 1 if a failing step stopped the playbook, 0 otherwise.
+    pub fn exit_code(&self) -> i32 {
+        if self.count(StepStatus::Failed) > 0 { 1 } else { 0 }
+    }
+
+    /// The summary as a table / JSON / CSV output.
+    pub fn to_output(&self) -> CommandOutput {
+        let mut out = CommandOutput::default().with_title(format!("📒 Playbook '{}' summary:", self.playbook));
+        out.headers = ["#", "Step", "Status", "Exit", "Duration", "Detail"].map(String::from).to_vec();
+        for (i, step) in self.steps.iter().enumerate() {
+            let exit = step.exit_code.map(|code| code.to_string()).unwrap_or_else(|| "-".to_string());
+            out.rows.push(Row::new(
+                vec![
+                    (i + 1).to_string(),
+                    step.label.clone(),
+                    step.status.name().to_string(),
+                    exit,
+                    format!("{}ms", step.duration.as_millis()),
+                    step.detail.clone(),
+                ],
+                step.status.tone(),
+            ));
+            out.records.push(Record {
+                fields: vec![
+                    ("step".to_string(), Field::Int(i as i64 + 1)),
+                    ("command".to_string(), Field::Text(step.label.clone())),
+                    ("status".to_string(), Field::Text(step.status.name().to_string())),
+                    ("exit_code".to_string(), step.exit_code.map(|code| Field::Int(code as i64)).unwrap_or(Field::Null)),
+                    ("duration_ms".to_string(), Field::Int(step.duration.as_millis() as i64)),
+                    ("detail".to_string(), Field::Text(step.detail.clone())),
+                ],
+            });
+        }
+
+        let mut counts = vec![format!("{} passed", self.count(StepStatus::Passed))];
+        for status in [StepStatus::Failed, StepStatus::Tolerated, StepStatus::Skipped, StepStatus::DryRun, StepStatus::NotRun] {
+            let n = self.count(status);
+            if n > 0 {
+                counts.push(format!("{} {}", n, status.name()));
+            }
+        }
+        let tone = if self.exit_code() == 0 { Tone::Good } else { Tone::Bad };
+        let icon = if self.exit_code() == 0 { "✅" } else { "❌" };
+        out.note(tone, format!("{} {}", icon, counts.join(", ")));
+        out.status = Some(self.exit_code());
+        out.duration = self.steps.iter().map(|step| step.duration).sum();
+        out
+    }
+}
+
+/// Run a playbook through `command_matcher::execute`, so every step is validated, confirmed
+/// and audited like a command typed by hand. Progress and step output are printed in table
+/// format only, keeping JSON/CSV output clean.
+pub fn run(playbook: &Playbook, format: Format) -> Report {
+    run_with(playbook, format, command_matcher::execute)
+}
+
+/// `run` with another way to execute steps, e.g. `command_matcher::execute_with` and a fake executor.
+pub fn run_with(playbook: &Playbook, format: Format, mut execute: impl FnMut(&str, &[String]) -> CommandResult) -> Report {
+    let loud = format == Format::Table;
+    let total = playbook.steps.len();
+    if loud {
+        println!("\n{}", format!("📒 Running playbook '{}' ({} steps)", playbook.name, total).bright_purple().bold());
+        if !playbook.description.is_empty() {
+            println!("{}", playbook.description.bright_cyan());
+        }
+    }
+
+    let mut vars: HashMap<String, String> = HashMap::new();
+    let mut previous_output = String::new();
+    let mut outcomes = Vec::with_capacity(total);
+    let mut stopped = false;
+
+    for (i, step) in playbook.steps.iter().enumerate() {
+        let mut outcome = StepOutcome {
+            label: step.label(),
+            status: StepStatus::NotRun,
+            exit_code: None,
+            duration: Duration::ZERO,
+            detail: String::new(),
+        };
+        if stopped {
+            outcomes.push(outcome);
+            continue;
+        }
+        if loud {
+            println!("\n{} {}", format!("[{}/{}]", i + 1, total).bright_blue(), format!("▶ {}", outcome.label).bright_magenta().bold());
+        }
+
+        if let Some(pattern) = &step.only_if {
+            // Patterns were checked when the playbook was loaded.
+            let matched = Regex::new(pattern).map(|re| re.is_match(&previous_output)).unwrap_or(false);
+            if !matched {
+                outcome.status = StepStatus::Skipped;
+                outcome.detail = format!("previous output doesn't match /{}/", pattern);
+                if loud {
+                    println!("{}", format!("⏭️ Skipped: {}", outcome.detail).bright_yellow());
+                }
+                outcomes.push(outcome);
+                continue;
+            }
+        }
+
+        let result = step
+            .args
+            .iter()
+            .map(|arg| substitute(arg, &vars))
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|args| {
+                outcome.label = std::iter::once(step.command.clone()).chain(args.iter().cloned()).collect::<Vec<_>>().join(" ");
+                execute(&step.command, &args)
+            });
+
+        let failed = match &result {
+            Ok(output) => {
+                if loud {
+                    render::print_output(output);
+                }
+                outcome.exit_code = output.status;
+                outcome.duration = output.duration;
+                previous_output = output_text(output);
+                if output.dry_run {
+                    outcome.status = StepStatus::DryRun;
+                    false
+                } else if output.success() {
+                    outcome.status = StepStatus::Passed;
+                    false
+                } else {
+                    outcome.detail = output.stderr.lines().next().unwrap_or_default().trim().to_string();
+                    true
+                }
+            }
+            Err(e) => {
+                if loud {
+                    render::print_error(e);
+                }
+                outcome.exit_code = Some(e.exit_code());
+                outcome.detail = e.to_string();
+                previous_output = e.to_string();
+                true
+            }
+        };
+
+        if failed {
+            outcome.status = if step.continue_on_failure { StepStatus::Tolerated } else { StepStatus::Failed };
+            stopped = !step.continue_on_failure;
+            if loud {
+                let message = if stopped { "🛑 Step failed, stopping the playbook" } else { "⚠️ Step failed, continuing" };
+                println!("{}", message.bright_red());
+            }
+        } else if let Some(name) = &step.capture {
+            match capture(step.capture_pattern.as_deref(), &previous_output) {
+                Some(value) => {
+                    if loud {
+                        println!("{}", format!("📌 {} = {}", name, value).bright_cyan());
+                    }
+                    vars.insert(name.clone(), value);
+                }
+                None if loud => println!("{}", format!("⚠️ Nothing captured for `{}`", name).bright_yellow()),
+                None => {}
+            }
+        }
+        outcomes.push(outcome);
+    }
+
+    Report { playbook: playbook.name.clone(), steps: outcomes }
+}
+
+/// Text a step produced, for `only_if` and `capture`: stdout, or the parsed rows one per line.
+fn output_text(output: &CommandOutput) -> String {
+    if !output.stdout.trim().is_empty() {
+        return output.stdout.clone();
+    }
+    output.rows.iter().map(|row| row.fields.join("\t")).collect::<Vec<_>>().join("\n")
+}
+
+fn capture(pattern: Option<&str>, text: &str) -> Option<String> {
+    let Some(pattern) = pattern else {
+        return Some(text.trim().to_string());
+    };
+    let captures = Regex::new(pattern).ok()?.captures(text)?;
+    captures.get(1).or_else(|| captures.get(0)).map(|m| m.as_str().trim().to_string())
+}
+
+/// Playbooks as a table / JSON / CSV output; broken files are listed with their error.
+pub fn list_output() -> CommandOutput {
+    let mut out = CommandOutput::default().with_title(format!("Playbooks in {}:", playbooks_dir().display()));
+    out.headers = ["Name", "Steps", "Description"].map(String::from).to_vec();
+    for playbook in load_all() {
+        let (name, steps, description, tone) = match playbook {
+            Ok(playbook) => (playbook.name, playbook.steps.len().to_string(), playbook.description, Tone::Normal),
+            Err(e) => ("-".to_string(), "-".to_string(), e.to_string(), Tone::Bad),
+        };
+        out.records.push(Record {
+            fields: vec![
+                ("name".to_string(), Field::Text(name.clone())),
+                ("steps".to_string(), steps.parse().map(Field::Int).unwrap_or(Field::Null)),
+                ("description".to_string(), Field::Text(description.clone())),
+            ],
+        });
+        out.rows.push(Row::new(vec![name, steps, description], tone));
+    }
+    if out.rows.is_empty() {
+        out.note(Tone::Info, "No playbooks yet. Add a .toml or .yaml file there to create one.");
+    }
+    out
+}
+
+/// Menu entry: list playbooks and run one by number or name.
+pub fn menu() {
+    loop {
+        let playbooks: Vec<Playbook> = load_all()
+            .into_iter()
+            .filter_map(|playbook| match playbook {
+                Ok(playbook) => Some(playbook),
+                Err(e) => {
+                    println!("{} {}", "Error:".bright_red(), e.to_string().bright_red());
+                    None
+                }
+            })
+            .collect();
+
+        println!("\n{}", format!("📒 Playbooks ({}):", playbooks_dir().display()).bright_cyan());
+        if playbooks.is_empty() {
+            println!("{}", "No playbooks yet. Add a .toml or .yaml file there to create one.".bright_yellow());
+            return;
+        }
+        for (i, playbook) in playbooks.iter().enumerate() {
+            println!("{} {} {} {}",
+                format!("{}.", i + 1).bright_green(),
+                playbook.name.bright_magenta(),
+                "–".bright_blue(),
+                format!("{} ({} steps)", playbook.description, playbook.steps.len()).bright_cyan()
+            );
+        }
+        println!("\n{} {}", "0.".bright_green(), "Back to System Admin Dashboard 🔙".bright_blue());
+
+        print!("{}", format!("\nEnter playbook number or name (0-{}): ", playbooks.len()).bright_blue());
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            return;
+        }
+        let choice = input.trim();
+        let playbook = match choice.parse::<usize>() {
+            Ok(0) => return,
+            Ok(n) if n <= playbooks.len() => &playbooks[n - 1],
+            _ => match playbooks.iter().find(|playbook| playbook.name == choice) {
+                Some(playbook) => playbook,
+                None => {
+                    println!("{}", "⚠️ Invalid choice. Try again.".bright_red());
+                    continue;
+                }
+            },
+        };
+        let report = run(playbook, Format::Table);
+        println!();
+        render::print_output(&report.to_output());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn parse(name: &str, content: &str) -> Result<Playbook, PlaybookError> {
+        let dir = TempDir::new();
+        dir.file(name, content);
+        Playbook::from_path(&dir.root().join(name))
+    }
+
+    fn step_error(content: &str) -> (usize, String) {
+        match parse("book.toml", content) {
+            Err(PlaybookError::InvalidStep { step, message, .. }) => (step, message),
+            other => panic!("expected an invalid step, got {:?}", other),
+        }
+    }
+
+    fn output(stdout: &str, status: i32) -> CommandResult {
+        Ok(CommandOutput { stdout: stdout.to_string(), status: Some(status), ..Default::default() })
+    }
+
+    /// What a command returns in `run_canned`.
+    type Canned<'a> = (&'a str, fn() -> CommandResult);
+
+    /// Run quietly with canned results per command, recording what was executed.
+    fn run_canned(playbook: &Playbook, results: &[Canned]) -> (Report, Vec<String>) {
+        let mut calls = Vec::new();
+        let report = run_with(playbook, Format::Json, |command, args| {
+            calls.push(std::iter::once(command.to_string()).chain(args.iter().cloned()).collect::<Vec<_>>().join(" "));
+            results.iter().find(|(name, _)| *name == command).map(|(_, result)| result()).unwrap_or_else(|| output("", 0))
+        });
+        (report, calls)
+    }
+
+    fn statuses(report: &Report) -> Vec<StepStatus> {
+        report.steps.iter().map(|step| step.status).collect()
+    }
+
+    #[test]
+    fn toml_and_yaml_playbooks_parse_alike() {
+        let toml = parse(
+            "nightly.toml",
+            "description = \"Nightly\"\n[[steps]]\ncommand = \"df\"\n[[steps]]\ncommand = \"journalctl-service\"\nargs = [\"nginx\"]\ncapture = \"state\"\ncapture_pattern = 'Active: (\\w+)'\n",
+        )
+        .unwrap();
+        let yaml = parse(
+            "nightly.yml",
+            "description: Nightly\nsteps:\n  - command: df\n  - command: journalctl-service\n    args: [nginx]\n    capture: state\n    capture_pattern: 'Active: (\\w+)'\n",
+        )
+        .unwrap();
+        for playbook in [&toml, &yaml] {
+            assert_eq!((playbook.name.as_str(), playbook.description.as_str(), playbook.steps.len()), ("nightly", "Nightly", 2));
+            assert_eq!(playbook.steps[1].label(), "journalctl-service nginx");
+            assert_eq!(playbook.steps[1].capture_pattern.as_deref(), Some(r"Active: (\w+)"));
+            assert!(!playbook.steps[0].continue_on_failure);
+        }
+        assert!(matches!(parse("bad.yaml", "steps:\n  - command: df\n    retries: 3\n"), Err(PlaybookError::Parse { .. })));
+        assert!(matches!(parse("bad.toml", "steps = 3"), Err(PlaybookError::Parse { .. })));
+    }
+
+    #[test]
+    fn validation_points_at_the_broken_step() {
+        assert_eq!(step_error("steps = []").0, 0);
+        let (step, message) = step_error("[[steps]]\ncommand = \"df\"\n[[steps]]\ncommand = \"no-such-command\"\n");
+        assert_eq!((step, message.starts_with("unknown command `no-such-command`")), (2, true));
+        assert_eq!(step_error("[[steps]]\ncommand = \"df\"\nargs = [\"/\"]\n"), (1, "`df` takes at most 0 argument(s)".to_string()));
+        assert!(step_error("[[steps]]\ncommand = \"df\"\nonly_if = \"(\"\n").1.starts_with("bad pattern `(`"));
+        assert!(step_error("[[steps]]\ncommand = \"df\"\ncapture = \"x\"\ncapture_pattern = \"[\"\n").1.starts_with("bad pattern `[`"));
+        assert_eq!(step_error("[[steps]]\ncommand = \"df\"\ncapture_pattern = \"x\"\n").1, "capture_pattern needs a capture name");
+        let uncaptured = "[[steps]]\ncommand = \"systemctl-restart\"\nargs = [\"{{unit}}\"]\n[[steps]]\ncommand = \"df\"\ncapture = \"unit\"\n";
+        assert_eq!(step_error(uncaptured), (1, "`{{unit}}` is not captured by an earlier step".to_string()));
+    }
+
+    #[test]
+    fn variables_are_found_and_substituted() {
+        assert_eq!(variables("{{a}}-{{b}} {{a}}"), ["a", "b", "a"]);
+        assert_eq!(variables("{{open"), Vec::<&str>::new());
+        assert_eq!(variables("plain"), Vec::<&str>::new());
+
+        let vars: HashMap<String, String> = [("unit".to_string(), "nginx".to_string()), ("n".to_string(), "{{unit}}".to_string())].into();
+        assert_eq!(substitute("{{unit}}.service", &vars).unwrap(), "nginx.service");
+        assert_eq!(substitute("{{n}}", &vars).unwrap(), "{{unit}}");
+        assert!(matches!(substitute("{{missing}}", &vars), Err(CommandError::InvalidArgument(_))));
+        assert_eq!(substitute("{{unit}} {{open", &vars).unwrap(), "nginx {{open");
+    }
+
+    #[test]
+    fn captured_text_is_never_expanded_again() {
+        let vars: HashMap<String, String> =
+            [("banner".to_string(), "hello {{x}} {{banner}}".to_string()), ("x".to_string(), "--force".to_string())].into();
+        assert_eq!(substitute("{{banner}} {{x}}", &vars).unwrap(), "hello {{x}} {{banner}} --force");
+        assert_eq!(substitute("{{x}}{{banner}}", &vars).unwrap(), "--forcehello {{x}} {{banner}}");
+    }
+
+    #[test]
+    fn only_if_skips_on_the_previous_output() {
+        let playbook = parse(
+            "restart.toml",
+            "[[steps]]\ncommand = \"journalctl-service\"\nargs = [\"nginx\"]\n\
+             [[steps]]\ncommand = \"systemctl-restart\"\nargs = [\"nginx\"]\nonly_if = \"Active: failed\"\n\
+             [[steps]]\ncommand = \"df\"\nonly_if = \"running\"\n",
+        )
+        .unwrap();
+        // The skipped restart leaves the status output as the one `df` is checked against.
+        let (report, calls) = run_canned(&playbook, &[("journalctl-service", || output("Active: active (running)", 0))]);
+        assert_eq!(statuses(&report), [StepStatus::Passed, StepStatus::Skipped, StepStatus::Passed]);
+        assert_eq!(calls, ["journalctl-service nginx", "df"]);
+
+        let (report, _) = run_canned(&playbook, &[("journalctl-service", || output("Active: failed", 3))]);
+        assert_eq!(statuses(&report), [StepStatus::Failed, StepStatus::NotRun, StepStatus::NotRun]);
+    }
+
+    #[test]
+    fn failures_stop_the_playbook_unless_tolerated() {
+        let playbook = parse(
+            "update.toml",
+            "[[steps]]\ncommand = \"update\"\ncontinue_on_failure = true\n[[steps]]\ncommand = \"df\"\n[[steps]]\ncommand = \"free\"\n[[steps]]\ncommand = \"uptime\"\n",
+        );
+        // `uptime` isn't a command, so validation catches it before anything runs.
+        assert!(matches!(playbook, Err(PlaybookError::InvalidStep { step: 4, .. })));
+
+        let playbook = parse(
+            "update.toml",
+            "[[steps]]\ncommand = \"update\"\ncontinue_on_failure = true\n[[steps]]\ncommand = \"df\"\n[[steps]]\ncommand = \"free\"\n",
+        )
+        .unwrap();
+        let (report, calls) = run_canned(&playbook, &[("update", || output("", 100)), ("df", || Err(CommandError::NotInstalled("df".to_string())))]);
+        assert_eq!(statuses(&report), [StepStatus::Tolerated, StepStatus::Failed, StepStatus::NotRun]);
+        assert_eq!(calls, ["update", "df"]);
+        assert_eq!(report.steps[1].detail, "df is not installed");
+        assert_eq!(report.exit_code(), 1);
+
+        let (report, _) = run_canned(&playbook, &[("update", || output("", 100))]);
+        assert_eq!(statuses(&report), [StepStatus::Tolerated, StepStatus::Passed, StepStatus::Passed]);
+        assert_eq!(report.exit_code(), 0);
+    }
+
+    #[test]
+    fn captures_feed_later_arguments() {
+        let playbook = parse(
+            "capture.toml",
+            "[[steps]]\ncommand = \"journalctl-service\"\nargs = [\"nginx\"]\ncapture = \"state\"\ncapture_pattern = 'Active: (\\w+)'\n\
+             [[steps]]\ncommand = \"journalctl-service\"\nargs = [\"nginx\"]\ncapture = \"whole\"\ncapture_pattern = 'Main PID: \\d+'\n\
+             [[steps]]\ncommand = \"which\"\ncapture = \"raw\"\n\
+             [[steps]]\ncommand = \"journalctl-service\"\nargs = [\"{{state}}|{{whole}}|{{raw}}\"]\n",
+        )
+        .unwrap();
+        let (report, calls) = run_canned(
+            &playbook,
+            &[("journalctl-service", || output("Active: inactive (dead)\nMain PID: 42 (nginx)\n", 0)), ("which", || output("  /usr/bin/nginx \n", 0))],
+        );
+        assert_eq!(calls[3], "journalctl-service inactive|Main PID: 42|/usr/bin/nginx");
+        assert_eq!(report.steps[3].label, calls[3]);
+        assert_eq!(capture(Some("nothing here"), "text"), None);
+    }
+}
@@ -0,0 +1,394 @@
+//! Team-specific commands loaded from `<config dir>/plugins/*.toml`. Each manifest becomes a
+//! `CommandSpec` next to the built-ins, so plugins show up in listings, menus, the palette
+//! and playbooks, and run through the same validation, confirmation and audit path.
+//!
+//! ```toml
+//! name = "restart-web"
+//! aliases = ["rw"]
+//! category = "service-management"
+//! description = "Restart a web service and check its health endpoint"
+//! danger = "caution"
+//! root = true
+//! binaries = ["systemctl", "curl"]
+//! # A shell template; `{arg}` is replaced by the shell-quoted value...
+//! template = "systemctl restart {service} && curl -fsS {url}"
+//! # ...or an executable, relative to the plugins dir, given the values as positional
+//! # arguments. Without either, the executable next to the manifest with the same name runs.
+//! # exec = "restart-web.sh"
+//!
+//! [[args]]
+//! name = "service"
+//! kind = "service"
+//!
+//! [[args]]
+//! name = "url"
+//! kind = "url"
+//! required = false
+//! default = "http://localhost/health"
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use colored::*;
+use serde::Deserialize;
+
+use crate::config;
+use crate::exec::{Executor, Invocation};
+use crate::sys_admin::args::{ArgKind, ArgSpec, Args};
+use crate::sys_admin::command_logic;
+use crate::sys_admin::command_output::{CommandError, CommandResult};
+use crate::sys_admin::registry::{CommandCategory, CommandSpec, Danger, COMMANDS};
+
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    category: String,
+    description: String,
+    #[serde(default = "default_danger")]
+    danger: Danger,
+    #[serde(default)]
+    binaries: Vec<String>,
+    /// Run as root through sudo/doas/run0/pkexec.
+    #[serde(default)]
+    root: bool,
+    template: Option<String>,
+    exec: Option<PathBuf>,
+    /// Give the program the terminal (pagers, TUIs, prompts) instead of capturing its output.
+    #[serde(default)]
+    interactive: bool,
+    #[serde(default)]
+    args: Vec<ManifestArg>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestArg {
+    name: String,
+    #[serde(default = "default_kind")]
+    kind: ArgKind,
+    prompt: Option<String>,
+    #[serde(default = "default_required")]
+    required: bool,
+    default: Option<String>,
+}
+
+fn default_danger() -> Danger {
+    Danger::Caution
+}
+
+fn default_kind() -> ArgKind {
+    ArgKind::Text
+}
+
+fn default_required() -> bool {
+    true
+}
+
+enum Action {
+    Template(String),
+    Exec(PathBuf),
+}
+
+struct Plugin {
+    spec: CommandSpec,
+    action: Action,
+    interactive: bool,
+}
+
+pub fn plugins_dir() -> PathBuf {
+    config::config_dir().join("plugins")
+}
+
+/// Specs of every plugin that loaded; broken manifests are reported once and skipped.
+pub fn specs() -> impl Iterator<Item = &'static CommandSpec> {
+    loaded().iter().map(|plugin| &plugin.spec)
+}
+
+pub fn is_plugin(name: &str) -> bool {
+    loaded().iter().any(|plugin| plugin.spec.name == name)
+}
+
+fn loaded() -> &'static [Plugin] {
+    PLUGINS.get_or_init(|| load_dir(&plugins_dir()))
+}
+
+fn load_dir(dir: &Path) -> Vec<Plugin> {
+    let mut manifests: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    manifests.retain(|path| path.extension().is_some_and(|ext| ext == "toml"));
+    manifests.sort();
+
+    let mut plugins: Vec<Plugin> = Vec::new();
+    for path in manifests {
+        let taken = |name: &str| {
+            COMMANDS.iter().chain(plugins.iter().map(|plugin| &plugin.spec))
+                .any(|spec| spec.name == name || spec.aliases.contains(&name))
+        };
+        match load(&path).and_then(|manifest| {
+            match std::iter::once(&manifest.name).chain(&manifest.aliases).find(|name| taken(name)) {
+                Some(name) => Err(format!("`{}` is already a command", name)),
+                None => build(dir, &path, manifest),
+            }
+        }) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => eprintln!("{} {}: {}", "⚠️ Skipping plugin".bright_yellow(), path.display(), e),
+        }
+    }
+    plugins
+}
+
+fn load(path: &Path) -> Result<Manifest, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    toml::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn build(dir: &Path, path: &Path, manifest: Manifest) -> Result<Plugin, String> {
+    let category = CommandCategory::from_name(&manifest.category).ok_or_else(|| {
+        let names: Vec<&str> = CommandCategory::ALL.iter().map(|category| category.name()).collect();
+        format!("unknown category '{}', expected one of: {}", manifest.category, names.join(", "))
+    })?;
+
+    let action = match (manifest.template, manifest.exec) {
+        (Some(_), Some(_)) => return Err("set either `template` or `exec`, not both".to_string()),
+        (Some(template), None) => Action::Template(template),
+        (None, exec) => {
+            let program = match exec {
+                Some(exec) => dir.join(exec),
+                None => path.with_extension(""),
+            };
+            if !program.is_file() {
+                return Err(format!("no executable at {}", program.display()));
+            }
+            Action::Exec(program)
+        }
+    };
+
+    let args: Vec<ArgSpec> = manifest
+        .args
+        .into_iter()
+        .map(|arg| {
+            let prompt = arg.prompt.unwrap_or_else(|| format!("Enter {}: ", arg.name));
+            ArgSpec {
+                name: leak(arg.name),
+                kind: arg.kind,
+                prompt: leak(prompt),
+                required: arg.required,
+                default: arg.default.map(leak),
+            }
+        })
+        .collect();
+
+    let spec = CommandSpec {
+        name: leak(manifest.name),
+        aliases: Box::leak(manifest.aliases.into_iter().map(leak).collect()),
+        category,
+        description: leak(manifest.description),
+        args: Box::leak(args.into_boxed_slice()),
+        binaries: Box::leak(manifest.binaries.into_iter().map(leak).collect()),
+        danger: manifest.danger,
+        root: manifest.root,
+        affects: Some(preview),
+        run,
+    };
+    Ok(Plugin { spec, action, interactive: manifest.interactive })
+}
+
+/// Plugins live for the whole process, like the built-in `COMMANDS`.
+fn leak(value: String) -> &'static str {
+    Box::leak(value.into_boxed_str())
+}
+
+fn find(name: &str) -> Result<&'static Plugin, CommandError> {
+    loaded()
+        .iter()
+        .find(|plugin| plugin.spec.name == name)
+        .ok_or_else(|| CommandError::UnknownCommand(name.to_string()))
+}
+
+impl Plugin {
+    fn invocation(&self, args: &Args) -> Invocation {
+        self.build(|arg| args.get(arg.name).to_string())
+    }
+
+    /// The same command line with every secret argument as `***`: what previews, notes and
+    /// the audit log get to see.
+    fn shown(&self, args: &Args) -> Invocation {
+        self.build(|arg| match arg.kind {
+            ArgKind::Secret => "***".to_string(),
+            _ => args.get(arg.name).to_string(),
+        })
+    }
+
+    fn build(&self, value: impl Fn(&ArgSpec) -> String) -> Invocation {
+        let invocation = match &self.action {
+            Action::Template(template) => {
+                let script = substitute(template, |name| {
+                    self.spec.args.iter().find(|arg| arg.name == name).map(|arg| shell_quote(&value(arg)))
+                });
+                Invocation::new("sh").arg("-c").arg(script)
+            }
+            Action::Exec(program) => Invocation::new(program.display().to_string()).args(self.spec.args.iter().map(value)),
+        };
+        if self.interactive {
+            invocation.attached()
+        } else {
+            invocation
+        }
+    }
+
+    fn run(&self, exec: &dyn Executor, args: &Args) -> CommandResult {
+        command_logic::run_shown(exec, &self.invocation(args), &self.shown(args))
+    }
+
+    fn preview(&self, args: &Args) -> Vec<String> {
+        vec![format!("runs: {}", self.shown(args).command_line())]
+    }
+}
+
+/// Replace each `{name}` that `value` knows with its value, in one pass from the left, so
+/// text that has already been substituted is never scanned for placeholders again.
+fn substitute(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut script = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        script.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}').and_then(|close| Some((close, value(&after[..close])?))) {
+            Some((close, replacement)) => {
+                script.push_str(&replacement);
+                rest = &after[close + 1..];
+            }
+            None => {
+                script.push('{');
+                rest = after;
+            }
+        }
+    }
+    script.push_str(rest);
+    script
+}
+
+/// Single-quote a value so a template can't be broken out of.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn run(exec: &dyn Executor, args: &Args) -> CommandResult {
+    find(args.command())?.run(exec, args)
+}
+
+/// The resolved command line, shown before a destructive plugin runs.
+fn preview(_exec: &dyn Executor, args: &Args) -> Result<Vec<String>, CommandError> {
+    Ok(find(args.command())?.preview(args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use crate::sys_admin::command_matcher;
+
+    fn names(plugins: &[Plugin]) -> Vec<&str> {
+        plugins.iter().map(|plugin| plugin.spec.name).collect()
+    }
+
+    fn script(plugin: &Plugin, raw: &[&str]) -> String {
+        let raw: Vec<String> = raw.iter().map(|value| value.to_string()).collect();
+        let args = Args::parse(plugin.spec.name, plugin.spec.args, &raw).unwrap();
+        plugin.invocation(&args).args[1].clone()
+    }
+
+    #[test]
+    fn shell_quote_keeps_single_quotes_inside_the_value() {
+        assert_eq!(shell_quote("plain"), "'plain'");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$(id); `id`"), "'$(id); `id`'");
+    }
+
+    #[test]
+    fn substitution_never_rescans_substituted_values() {
+        let value = |name: &str| match name {
+            "a" => Some(shell_quote("{b}")),
+            "b" => Some(shell_quote("; id #")),
+            _ => None,
+        };
+        assert_eq!(substitute("echo {a} {b}", value), "echo '{b}' '; id #'");
+        assert_eq!(substitute("awk '{print}' {a} {", value), "awk '{print}' '{b}' {");
+        assert_eq!(substitute("{b}{a}", value), "'; id #''{b}'");
+    }
+
+    #[test]
+    fn templates_quote_each_argument_once() {
+        let dir = TempDir::new();
+        dir.file("say.toml", "name = \"plugin-say\"\ncategory = \"log-diving\"\ndescription = \"Say\"\ntemplate = \"echo {a} {b}\"\n[[args]]\nname = \"a\"\n[[args]]\nname = \"b\"\n");
+        let plugins = load_dir(dir.root());
+        assert_eq!(names(&plugins), ["plugin-say"]);
+        assert_eq!(script(&plugins[0], &["{b}", "; id #"]), "echo '{b}' '; id #'");
+    }
+
+    #[test]
+    fn load_dir_skips_broken_and_clashing_manifests() {
+        let dir = TempDir::new();
+        let manifest = |name: &str, extra: &str| format!("name = \"{}\"\ncategory = \"log-diving\"\ndescription = \"Test\"\n{}", name, extra);
+        dir
+            .file("a-first.toml", &manifest("plugin-first", "aliases = [\"pf\"]\ntemplate = \"true\"\n"))
+            .file("b-builtin.toml", &manifest("kill", "template = \"true\"\n"))
+            .file("c-alias.toml", &manifest("plugin-other", "aliases = [\"pf\"]\ntemplate = \"true\"\n"))
+            .file("d-same.toml", &manifest("plugin-first", "template = \"true\"\n"))
+            .file("e-both.toml", &manifest("plugin-both", "template = \"true\"\nexec = \"run.sh\"\n"))
+            .file("f-category.toml", &manifest("plugin-category", "template = \"true\"\n").replace("log-diving", "gardening"))
+            .file("g-missing.toml", &manifest("plugin-missing", "exec = \"nowhere.sh\"\n"))
+            .file("h-exec.toml", &manifest("plugin-exec", "exec = \"run.sh\"\n[[args]]\nname = \"a\"\n"))
+            .file("i-sibling.toml", &manifest("plugin-sibling", ""))
+            .file("i-sibling", "#!/bin/sh\n")
+            .file("run.sh", "#!/bin/sh\n")
+            .file("notes.txt", "not a manifest");
+        let plugins = load_dir(dir.root());
+        assert_eq!(names(&plugins), ["plugin-first", "plugin-exec", "plugin-sibling"]);
+
+        let raw = vec!["{x} y".to_string()];
+        let args = Args::parse("plugin-exec", plugins[1].spec.args, &raw).unwrap();
+        assert_eq!(plugins[1].invocation(&args).args, ["{x} y"]);
+        assert_eq!(plugins[2].invocation(&Args::default()).program, dir.root().join("i-sibling").display().to_string());
+    }
+
+    #[test]
+    fn secret_arguments_are_masked_everywhere_they_are_shown() {
+        let dir = TempDir::new();
+        let manifest = |name: &str, action: &str| {
+            format!("name = \"{}\"\ncategory = \"security\"\ndescription = \"Unlock\"\n{}\n[[args]]\nname = \"vault\"\n[[args]]\nname = \"token\"\nkind = \"secret\"\n", name, action)
+        };
+        dir
+            .file("a-template.toml", &manifest("plugin-unlock", "template = \"unlock {vault} --token {token}\""))
+            .file("b-exec.toml", &manifest("plugin-unlock-exec", "exec = \"unlock.sh\""))
+            .file("unlock.sh", "#!/bin/sh\n");
+        let plugins = load_dir(dir.root());
+        assert_eq!(names(&plugins), ["plugin-unlock", "plugin-unlock-exec"]);
+
+        let raw = vec!["prod".to_string(), "s3cr3t-t0ken".to_string()];
+        for plugin in &plugins {
+            let args = Args::parse(plugin.spec.name, plugin.spec.args, &raw).unwrap();
+            assert!(plugin.invocation(&args).command_line().contains("s3cr3t-t0ken"));
+
+            let exec = crate::exec::RecordingExecutor::new();
+            let result = plugin.run(&exec, &args);
+            assert!(exec.recorded()[0].command_line().contains("s3cr3t-t0ken"));
+            let output = result.as_ref().unwrap();
+            assert!(output.redacted_argv().iter().any(|arg| arg.contains("***")));
+            let notes: Vec<&str> = output.notes.iter().map(|note| note.text.as_str()).collect();
+            let preview = plugin.preview(&args);
+            let audit = serde_json::to_string(&command_matcher::audit_entry(&plugin.spec, &raw, &result)).unwrap();
+            for shown in [output.redacted_argv().join(" "), notes.join("\n"), preview.join("\n"), audit] {
+                assert!(!shown.contains("s3cr3t-t0ken"), "{}", shown);
+            }
+        }
+    }
+}
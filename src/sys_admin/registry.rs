@@ -0,0 +1,1083 @@
+use crate::exec::{self, Executor};
+use crate::procfs::ProcFs;
+use crate::sys_admin::args::{ArgKind, ArgSpec, Args};
+use crate::sys_admin::command_logic;
+use crate::sys_admin::command_output::{CommandError, CommandResult};
+use crate::sys_admin::dashboard;
+use crate::sys_admin::plugins;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandCategory {
+    ProcessManagement,
+    ResourceMonitoring,
+    DiskAndStorage,
+    LogDiving,
+    Networking,
+    ServiceManagement,
+    Security,
+    PackageManagement,
+}
+
+/// How much damage a command can do; used to decide whether to ask before running it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Danger {
+    /// Read-only.
+    Safe,
+    /// Changes system state in a way that is easy to undo (start a service, install a package).
+    Caution,
+    /// Kills processes, removes packages, unmounts or overwrites files.
+    Destructive,
+}
+
+/// Runs a command with the given executor.
+pub type Handler = fn(&dyn Executor, &Args) -> CommandResult;
+
+/// Resolves a command's targets with read-only lookups; see `CommandSpec::affects`.
+pub type Preview = fn(&dyn Executor, &Args) -> Result<Vec<String>, CommandError>;
+
+/// Everything gremlin knows about a command. Help, menus and dispatch are all generated
+/// from `COMMANDS` and the loaded plugins, so adding a command means adding one entry there.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub category: CommandCategory,
+    pub description: &'static str,
+    pub args: &'static [ArgSpec],
+    /// Programs that must be on `PATH` for the command to work.
+    pub binaries: &'static [&'static str],
+    pub danger: Danger,
+    /// Needs root: everything it runs, except read-only lookups, is elevated.
+    pub root: bool,
+    /// Lists what a destructive command is about to touch (PIDs, mount users, packages...).
+    pub affects: Option<Preview>,
+    pub run: Handler,
+}
+
+impl CommandCategory {
+    pub const ALL: [CommandCategory; 8] = [
+        CommandCategory::ProcessManagement,
+        CommandCategory::ResourceMonitoring,
+        CommandCategory::DiskAndStorage,
+        CommandCategory::LogDiving,
+        CommandCategory::Networking,
+        CommandCategory::ServiceManagement,
+        CommandCategory::Security,
+        CommandCategory::PackageManagement,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommandCategory::ProcessManagement => "Process Management",
+            CommandCategory::ResourceMonitoring => "Resource Monitoring",
+            CommandCategory::DiskAndStorage => "Disk and Storage",
+            CommandCategory::LogDiving => "Log Diving",
+            CommandCategory::Networking => "Networking",
+            CommandCategory::ServiceManagement => "Service Management",
+            CommandCategory::Security => "Security",
+            CommandCategory::PackageManagement => "Package Management",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            CommandCategory::ProcessManagement => "🧵",
+            CommandCategory::ResourceMonitoring => "📊",
+            CommandCategory::DiskAndStorage => "💾",
+            CommandCategory::LogDiving => "📜",
+            CommandCategory::Networking => "🌐",
+            CommandCategory::ServiceManagement => "⚙️",
+            CommandCategory::Security => "🔒",
+            CommandCategory::PackageManagement => "📦",
+        }
+    }
+
+    /// Category by display name or kebab-case name, e.g. `Service Management` or `service-management`.
+    pub fn from_name(name: &str) -> Option<CommandCategory> {
+        let wanted = name.trim().to_lowercase().replace(['-', '_'], " ");
+        CommandCategory::ALL.into_iter().find(|category| category.name().to_lowercase() == wanted)
+    }
+
+    pub fn ascii(&self) -> &'static str {
+        match self {
+            CommandCategory::ProcessManagement => "ascii/processGirl.txt",
+            CommandCategory::ResourceMonitoring => "ascii/resourceGirl.txt",
+            CommandCategory::DiskAndStorage => "ascii/diskGirl.txt",
+            CommandCategory::LogDiving => "ascii/logsGirl.txt",
+            CommandCategory::Networking => "ascii/networkingGirl.txt",
+            CommandCategory::ServiceManagement => "ascii/servicesGirl.txt",
+            CommandCategory::Security => "ascii/securityGirl.txt",
+            CommandCategory::PackageManagement => "ascii/packagesGirl.txt",
+        }
+    }
+}
+
+/// Built-in commands followed by plugin commands.
+pub fn all() -> impl Iterator<Item = &'static CommandSpec> {
+    COMMANDS.iter().chain(plugins::specs())
+}
+
+/// Look a command up by name or alias.
+pub fn find(name: &str) -> Option<&'static CommandSpec> {
+    all().find(|spec| spec.name == name || spec.aliases.contains(&name))
+}
+
+/// Closest command name to a mistyped one, for "did you mean" hints.
+pub fn suggest(name: &str) -> Option<&'static str> {
+    let max_distance = (name.chars().count() / 3).max(2);
+    all()
+        .flat_map(|spec| std::iter::once(spec.name).chain(spec.aliases.iter().copied()).map(move |n| (spec.name, n)))
+        .map(|(canonical, candidate)| (canonical, strsim::damerau_levenshtein(name, candidate)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(canonical, _)| canonical)
+}
+
+pub fn in_category(category: CommandCategory) -> impl Iterator<Item = &'static CommandSpec> {
+    all().filter(move |spec| spec.category == category)
+}
+
+impl CommandSpec {
+    /// Required binaries that are not on `PATH`.
+    pub fn missing_binaries(&self) -> Vec<&'static str> {
+        self.binaries.iter().copied().filter(|bin| !exec::on_path(bin)).collect()
+    }
+
+    /// Usage line such as `gpg-encrypt <file> <recipient>`.
+    pub fn usage(&self) -> String {
+        std::iter::once(self.name.to_string())
+            .chain(self.args.iter().map(|arg| {
+                if arg.required {
+                    format!("<{}>", arg.name)
+                } else {
+                    format!("[{}]", arg.name)
+                }
+            }))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+const PS_QUERY: &[ArgSpec] = &[
+    ArgSpec::optional("sort", ArgKind::Text, "Sort by pid, ppid, user, state, cpu, mem, threads, start or command (- to flip) [cpu]: ", Some("cpu")),
+    ArgSpec::optional("top", ArgKind::PositiveInt, "Show the top N (empty for all): ", None),
+    ArgSpec::optional("user", ArgKind::Text, "Only processes of user (empty for all): ", None),
+    ArgSpec::optional("name", ArgKind::Text, "Name or command regex (empty for all): ", None),
+    ArgSpec::optional("state", ArgKind::Text, "States, e.g. R or DZ (empty for all): ", None),
+    ArgSpec::optional("min-mem", ArgKind::Size, "Minimum memory, e.g. 100M (empty for any): ", None),
+];
+const DASHBOARD: &[ArgSpec] = &[
+    ArgSpec::optional("refresh", ArgKind::PositiveInt, "Refresh every N milliseconds (empty for the configured rate): ", None),
+    ArgSpec::optional("sort", ArgKind::Text, "Sort processes by cpu, mem, pid, user... (empty for the configured order): ", None),
+];
+const TREE_QUERY: &[ArgSpec] = &[
+    ArgSpec::optional("root", ArgKind::Text, "Start from PID or name regex (empty for every process): ", None),
+    ArgSpec::optional("depth", ArgKind::PositiveInt, "Fold below depth (empty for no limit): ", None),
+    ArgSpec::optional("fold", ArgKind::Text, "Fold these PIDs or names, comma-separated (default kthreadd): ", Some("kthreadd")),
+];
+const TREE_SIGNAL: &[ArgSpec] = &[
+    ArgSpec::required("target", ArgKind::Pid, "Enter PID at the top of the tree: "),
+    ArgSpec::optional("signal", ArgKind::Signal, "Signal (default TERM): ", Some("TERM")),
+];
+const KILL: &[ArgSpec] = &[
+    ArgSpec::required("target", ArgKind::Text, "Enter PID, process name, user or pattern: "),
+    ArgSpec::optional("signal", ArgKind::Signal, "Signal, e.g. TERM, HUP, INT, USR1, STOP, CONT (default TERM): ", Some("TERM")),
+    ArgSpec::optional("by", ArgKind::Text, "Match by pid, name, user or pattern (default: PID if numeric, else name): ", None),
+    ArgSpec::optional("escalate", ArgKind::PositiveInt, "Seconds to wait before sending KILL (empty to not escalate): ", None),
+];
+const KILL_FORCE: &[ArgSpec] = &[
+    ArgSpec::required("target", ArgKind::Text, "Enter PID, process name, user or pattern: "),
+    ArgSpec::optional("by", ArgKind::Text, "Match by pid, name, user or pattern (default: PID if numeric, else name): ", None),
+];
+const INSPECT: &[ArgSpec] = &[
+    ArgSpec::required("pid", ArgKind::Pid, "Enter PID: "),
+    ArgSpec::optional("section", ArgKind::Text, "Section: summary, cmdline, environ, fds, maps, limits, cgroup, namespaces, threads or io (default summary): ", Some("summary")),
+];
+const PROCESS_NAME: &[ArgSpec] = &[ArgSpec::required("name", ArgKind::ProcessName, "Enter process name: ")];
+const SAMPLING: &[ArgSpec] = &[
+    ArgSpec::optional("interval", ArgKind::PositiveInt, "Interval in seconds (default 1): ", Some("1")),
+    ArgSpec::optional("count", ArgKind::PositiveInt, "Number of samples (default 5): ", Some("5")),
+];
+const PATH: &[ArgSpec] = &[ArgSpec::optional("path", ArgKind::ExistingPath, "Enter path (default .): ", Some("."))];
+const SOCKETS: &[ArgSpec] = &[
+    ArgSpec::optional("proto", ArgKind::Text, "Protocols: tcp, udp, unix, tcp6... or all (default tcp,udp): ", Some("tcp,udp")),
+    ArgSpec::optional("state", ArgKind::Text, "State: listening, connected, all or e.g. TIME_WAIT (default listening): ", Some("listening")),
+    ArgSpec::optional("port", ArgKind::PositiveInt, "Only this local or remote port (empty for any): ", None),
+    ArgSpec::optional("process", ArgKind::Text, "Only sockets of this PID or process name regex (empty for any): ", None),
+];
+const PORT: &[ArgSpec] = &[ArgSpec::required("port", ArgKind::PositiveInt, "Enter port number: ")];
+const SCAN: &[ArgSpec] = &[
+    ArgSpec::required("target", ArgKind::Text, "Enter IPs, hostnames or CIDR blocks, comma-separated (e.g. 192.168.1.0/24): "),
+    ArgSpec::optional("ports", ArgKind::Text, "Ports, ranges or presets top, web, db, mail, remote, files, all (default top): ", Some("top")),
+    ArgSpec::optional("concurrency", ArgKind::PositiveInt, "Connections at once (default 200): ", Some("200")),
+    ArgSpec::optional("timeout", ArgKind::PositiveInt, "Connect timeout in milliseconds (default 1000): ", Some("1000")),
+    ArgSpec::optional("allow-public", ArgKind::YesNo, "Allow scanning public addresses you are authorised to scan? (default no): ", Some("no")),
+];
+const DNS_QUERY: &[ArgSpec] = &[
+    ArgSpec::required("name", ArgKind::Text, "Enter domain, or IP address for a reverse lookup: "),
+    ArgSpec::optional("type", ArgKind::Text, "Record type: A, AAAA, MX, TXT, NS, CNAME, SOA, SRV, PTR or CAA (default A, PTR for IPs): ", None),
+    ArgSpec::optional("server", ArgKind::Text, "DNS server (empty for the system resolver, all to compare every one): ", None),
+    ArgSpec::optional("port", ArgKind::PositiveInt, "Server port (default 53): ", Some("53")),
+    ArgSpec::optional("tcp", ArgKind::YesNo, "Query over TCP? (default no): ", Some("no")),
+    ArgSpec::optional("timeout", ArgKind::PositiveInt, "Timeout in milliseconds (default 2000): ", Some("2000")),
+];
+const HTTP_REQUEST: &[ArgSpec] = &[
+    ArgSpec::required("url", ArgKind::Url, "Enter URL: "),
+    ArgSpec::optional("method", ArgKind::Text, "Method (default GET): ", Some("GET")),
+    ArgSpec::optional("headers", ArgKind::Text, "Headers as Name: value, separated by | (e.g. Accept: application/json | X-Debug: 1): ", None),
+    ArgSpec::optional("body", ArgKind::Text, "Request body, or @path to send a file: ", None),
+    ArgSpec::optional("redirects", ArgKind::Text, "Redirects to follow, 0 for none (default 5): ", Some("5")),
+    ArgSpec::optional("status", ArgKind::Text, "Expected status, e.g. 200, 2xx or 200,204 (empty for no check): ", None),
+    ArgSpec::optional("expect", ArgKind::Text, "Regex the body must match (empty for no check): ", None),
+    ArgSpec::optional("repeat", ArgKind::PositiveInt, "Send it how many times (default 1): ", Some("1")),
+    ArgSpec::optional("timeout", ArgKind::PositiveInt, "Timeout in milliseconds (default 5000): ", Some("5000")),
+    ArgSpec::optional("insecure", ArgKind::YesNo, "Accept any TLS certificate, e.g. self-signed? (default no): ", Some("no")),
+];
+const TARGET: &[ArgSpec] = &[ArgSpec::required("target", ArgKind::Hostname, "Enter target (IP or hostname): ")];
+const SERVICE: &[ArgSpec] = &[ArgSpec::required("service", ArgKind::Service, "Enter service name: ")];
+const PACKAGE: &[ArgSpec] = &[ArgSpec::required("package", ArgKind::Package, "Enter package name: ")];
+const PACKAGES: &[ArgSpec] = &[ArgSpec::required("packages", ArgKind::Packages, "Enter package name(s): ")];
+const QUERY: &[ArgSpec] = &[ArgSpec::required("query", ArgKind::Text, "Search for: ")];
+const OWNED_PATH: &[ArgSpec] = &[ArgSpec::required("path", ArgKind::ExistingPath, "Enter file path: ")];
+const BINARY: &[ArgSpec] = &[ArgSpec::required("binary", ArgKind::Text, "Enter binary name: ")];
+const FILE_AND_PASSWORD: &[ArgSpec] = &[
+    ArgSpec::required("file", ArgKind::ExistingFile, "Enter file: "),
+    ArgSpec::required("password", ArgKind::Secret, "Enter password: "),
+];
+
+pub static COMMANDS: &[CommandSpec] = &[
+    // Process Management
+    CommandSpec {
+        name: "ps",
+        aliases: &[],
+        category: CommandCategory::ProcessManagement,
+        description: "Show running processes, sorted and filtered",
+        args: PS_QUERY,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| {
+            command_logic::ps(
+                &ProcFs::system(),
+                args.get("sort"),
+                args.get("top"),
+                args.get("user"),
+                args.get("name"),
+                args.get("state"),
+                args.get("min-mem"),
+            )
+        },
+    },
+    CommandSpec {
+        name: "htop",
+        aliases: &["top"],
+        category: CommandCategory::ProcessManagement,
+        description: "Interactive process viewer",
+        args: &[],
+        binaries: &["htop"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::htop(exec),
+    },
+    CommandSpec {
+        name: "pstree",
+        aliases: &["tree"],
+        category: CommandCategory::ProcessManagement,
+        description: "Process tree with subtree CPU and memory totals",
+        args: TREE_QUERY,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| command_logic::pstree(&ProcFs::system(), args.get("root"), args.get("depth"), args.get("fold")),
+    },
+    CommandSpec {
+        name: "kill-tree",
+        aliases: &["killtree"],
+        category: CommandCategory::ProcessManagement,
+        description: "Signal a process and all of its descendants",
+        args: TREE_SIGNAL,
+        binaries: &[],
+        danger: Danger::Destructive,
+        root: false,
+        affects: Some(|_, args| command_logic::preview_tree(&ProcFs::system(), args.get("target"))),
+        run: |exec, args| command_logic::kill_tree(exec, &ProcFs::system(), args.get("target"), args.get("signal")),
+    },
+    CommandSpec {
+        name: "kill",
+        aliases: &["term", "signal"],
+        category: CommandCategory::ProcessManagement,
+        description: "Signal processes by PID, name, user or pattern, optionally escalating to KILL",
+        args: KILL,
+        binaries: &[],
+        danger: Danger::Destructive,
+        root: false,
+        affects: Some(|_, args| command_logic::preview_kill(&ProcFs::system(), args.get("target"), args.get("by"))),
+        run: |exec, args| {
+            command_logic::kill(
+                exec,
+                &ProcFs::system(),
+                args.get("target"),
+                args.get("by"),
+                args.get("signal"),
+                args.get("escalate"),
+            )
+        },
+    },
+    CommandSpec {
+        name: "kill9",
+        aliases: &["kill-force"],
+        category: CommandCategory::ProcessManagement,
+        description: "Forcefully terminate processes with KILL",
+        args: KILL_FORCE,
+        binaries: &[],
+        danger: Danger::Destructive,
+        root: false,
+        affects: Some(|_, args| command_logic::preview_kill(&ProcFs::system(), args.get("target"), args.get("by"))),
+        run: |exec, args| command_logic::kill(exec, &ProcFs::system(), args.get("target"), args.get("by"), "KILL", ""),
+    },
+    CommandSpec {
+        name: "pkill",
+        aliases: &[],
+        category: CommandCategory::ProcessManagement,
+        description: "Terminate processes whose name or command line matches a pattern",
+        args: PROCESS_NAME,
+        binaries: &[],
+        danger: Danger::Destructive,
+        root: false,
+        affects: Some(|_, args| command_logic::preview_kill(&ProcFs::system(), args.get("name"), "pattern")),
+        run: |exec, args| command_logic::kill(exec, &ProcFs::system(), args.get("name"), "pattern", "TERM", ""),
+    },
+    CommandSpec {
+        name: "pgrep",
+        aliases: &[],
+        category: CommandCategory::ProcessManagement,
+        description: "Find processes by name",
+        args: PROCESS_NAME,
+        binaries: &["pgrep"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::pgrep(exec, args.get("name")),
+    },
+    CommandSpec {
+        name: "inspect",
+        aliases: &["pinfo", "procinfo"],
+        category: CommandCategory::ProcessManagement,
+        description: "Everything /proc knows about one process: files, memory, limits, cgroup, threads, I/O",
+        args: INSPECT,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| command_logic::inspect(&ProcFs::system(), args.get("pid"), args.get("section")),
+    },
+    // Resource Monitoring
+    CommandSpec {
+        name: "free",
+        aliases: &["mem"],
+        category: CommandCategory::ResourceMonitoring,
+        description: "Memory usage",
+        args: &[],
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, _| command_logic::free(&ProcFs::system()),
+    },
+    CommandSpec {
+        name: "vmstat",
+        aliases: &[],
+        category: CommandCategory::ResourceMonitoring,
+        description: "Memory + process + CPU stats",
+        args: SAMPLING,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| command_logic::vmstat(&ProcFs::system(), args.get("interval"), args.get("count")),
+    },
+    CommandSpec {
+        name: "iostat",
+        aliases: &[],
+        category: CommandCategory::ResourceMonitoring,
+        description: "CPU and disk I/O stats",
+        args: SAMPLING,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| command_logic::iostat(&ProcFs::system(), args.get("interval"), args.get("count")),
+    },
+    CommandSpec {
+        name: "mpstat",
+        aliases: &[],
+        category: CommandCategory::ResourceMonitoring,
+        description: "Per-core CPU stats",
+        args: SAMPLING,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| command_logic::mpstat(&ProcFs::system(), args.get("interval"), args.get("count")),
+    },
+    CommandSpec {
+        name: "sar",
+        aliases: &[],
+        category: CommandCategory::ResourceMonitoring,
+        description: "System activity report",
+        args: SAMPLING,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| command_logic::sar(&ProcFs::system(), args.get("interval"), args.get("count")),
+    },
+    CommandSpec {
+        name: "pressure",
+        aliases: &["psi"],
+        category: CommandCategory::ResourceMonitoring,
+        description: "CPU, memory and I/O stall pressure",
+        args: &[],
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, _| command_logic::pressure(&ProcFs::system()),
+    },
+    CommandSpec {
+        name: "dashboard",
+        aliases: &["dash", "monitor"],
+        category: CommandCategory::ResourceMonitoring,
+        description: "Live full-screen CPU, memory, disk, network and process view",
+        args: DASHBOARD,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| dashboard::run(exec, &ProcFs::system(), args.get("refresh"), args.get("sort")),
+    },
+    // Disk and Storage
+    CommandSpec {
+        name: "df",
+        aliases: &[],
+        category: CommandCategory::DiskAndStorage,
+        description: "Disk space usage",
+        args: &[],
+        binaries: &["df"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::df(exec),
+    },
+    CommandSpec {
+        name: "du",
+        aliases: &[],
+        category: CommandCategory::DiskAndStorage,
+        description: "Folder sizes",
+        args: PATH,
+        binaries: &["du"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::du(exec, args.get("path")),
+    },
+    CommandSpec {
+        name: "ncdu",
+        aliases: &[],
+        category: CommandCategory::DiskAndStorage,
+        description: "Interactive disk usage explorer",
+        args: PATH,
+        binaries: &["ncdu"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::ncdu(exec, args.get("path")),
+    },
+    CommandSpec {
+        name: "lsblk",
+        aliases: &[],
+        category: CommandCategory::DiskAndStorage,
+        description: "Block devices",
+        args: &[],
+        binaries: &["lsblk"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::lsblk(exec),
+    },
+    CommandSpec {
+        name: "mount",
+        aliases: &["mounts"],
+        category: CommandCategory::DiskAndStorage,
+        description: "Show mounted filesystems",
+        args: &[],
+        binaries: &["mount"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::mount(exec),
+    },
+    CommandSpec {
+        name: "umount",
+        aliases: &["unmount"],
+        category: CommandCategory::DiskAndStorage,
+        description: "Unmount a device",
+        args: &[ArgSpec::required("target", ArgKind::MountPoint, "Enter device or mount point: ")],
+        binaries: &["umount", "findmnt"],
+        danger: Danger::Destructive,
+        root: true,
+        affects: Some(|exec, args| command_logic::preview_mount_users(exec, args.get("target"))),
+        run: |exec, args| command_logic::umount(exec, args.get("target")),
+    },
+    // Log Diving
+    CommandSpec {
+        name: "journalctl",
+        aliases: &["journal"],
+        category: CommandCategory::LogDiving,
+        description: "System journal (last hour)",
+        args: &[],
+        binaries: &["journalctl"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::journalctl_system(exec),
+    },
+    CommandSpec {
+        name: "journalctl-service",
+        aliases: &["service-logs"],
+        category: CommandCategory::LogDiving,
+        description: "Service logs (all running services when none is given)",
+        args: &[ArgSpec::optional("service", ArgKind::Service, "Enter service name (empty for all running): ", None)],
+        binaries: &["journalctl", "systemctl"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::journalctl_service(exec, args.get("service")),
+    },
+    CommandSpec {
+        name: "dmesg",
+        aliases: &[],
+        category: CommandCategory::LogDiving,
+        description: "Kernel errors and warnings",
+        args: &[],
+        binaries: &["dmesg"],
+        danger: Danger::Safe,
+        root: true,
+        affects: None,
+        run: |exec, _| command_logic::dmesg(exec),
+    },
+    CommandSpec {
+        name: "tail-syslog",
+        aliases: &["syslog"],
+        category: CommandCategory::LogDiving,
+        description: "Last 50 syslog entries",
+        args: &[],
+        binaries: &["tail"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::tail_syslog(exec),
+    },
+    CommandSpec {
+        name: "auth-log",
+        aliases: &[],
+        category: CommandCategory::LogDiving,
+        description: "Last 50 authentication log entries",
+        args: &[],
+        binaries: &["tail"],
+        danger: Danger::Safe,
+        root: true,
+        affects: None,
+        run: |exec, _| command_logic::auth_log(exec),
+    },
+    // Networking
+    CommandSpec {
+        name: "ip",
+        aliases: &["ip-a", "interfaces"],
+        category: CommandCategory::Networking,
+        description: "Show network interfaces",
+        args: &[],
+        binaries: &["ip"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::ip_a(exec),
+    },
+    CommandSpec {
+        name: "ping",
+        aliases: &[],
+        category: CommandCategory::Networking,
+        description: "Test network connectivity",
+        args: TARGET,
+        binaries: &["ping"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::ping(exec, args.get("target")),
+    },
+    CommandSpec {
+        name: "traceroute",
+        aliases: &[],
+        category: CommandCategory::Networking,
+        description: "Trace network path",
+        args: TARGET,
+        binaries: &["traceroute"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::traceroute(exec, args.get("target")),
+    },
+    CommandSpec {
+        name: "netstat",
+        aliases: &["sockets", "ss"],
+        category: CommandCategory::Networking,
+        description: "Sockets with their owning processes, listening ones by default",
+        args: SOCKETS,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| {
+            command_logic::netstat(&ProcFs::system(), args.get("proto"), args.get("state"), args.get("port"), args.get("process"))
+        },
+    },
+    CommandSpec {
+        name: "port",
+        aliases: &["whoport", "who-uses"],
+        category: CommandCategory::Networking,
+        description: "Which processes are listening on or connected to a port",
+        args: PORT,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |_, args| command_logic::port_users(&ProcFs::system(), args.get("port")),
+    },
+    CommandSpec {
+        name: "nmap",
+        aliases: &[],
+        category: CommandCategory::Networking,
+        description: "Network scanner",
+        args: TARGET,
+        binaries: &["nmap"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::nmap(exec, args.get("target")),
+    },
+    CommandSpec {
+        name: "portscan",
+        aliases: &["scan"],
+        category: CommandCategory::Networking,
+        description: "Built-in TCP port scan of hosts or subnets, with service banners",
+        args: SCAN,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| {
+            command_logic::port_scan(
+                exec,
+                args.get("target"),
+                args.get("ports"),
+                args.get("concurrency"),
+                args.get("timeout"),
+                args.flag("allow-public"),
+            )
+        },
+    },
+    CommandSpec {
+        name: "curl",
+        aliases: &["http", "probe"],
+        category: CommandCategory::Networking,
+        description: "Inspect an HTTP request: redirects, headers, timing, checks and latency percentiles",
+        args: HTTP_REQUEST,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| {
+            command_logic::curl(
+                exec,
+                args.get("url"),
+                args.get("method"),
+                args.get("headers"),
+                args.get("body"),
+                args.get("redirects"),
+                args.get("status"),
+                args.get("expect"),
+                args.get("repeat"),
+                args.get("timeout"),
+                args.flag("insecure"),
+            )
+        },
+    },
+    CommandSpec {
+        name: "dig",
+        aliases: &["dns", "lookup"],
+        category: CommandCategory::Networking,
+        description: "DNS lookup of any record type, reverse lookups and resolver comparison",
+        args: DNS_QUERY,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| {
+            command_logic::dig(
+                exec,
+                args.get("name"),
+                args.get("type"),
+                args.get("server"),
+                args.get("port"),
+                args.flag("tcp"),
+                args.get("timeout"),
+            )
+        },
+    },
+    // Service Management
+    CommandSpec {
+        name: "systemctl-start",
+        aliases: &["start"],
+        category: CommandCategory::ServiceManagement,
+        description: "Start a service",
+        args: SERVICE,
+        binaries: &["systemctl"],
+        danger: Danger::Caution,
+        root: true,
+        affects: None,
+        run: |exec, args| command_logic::systemctl_start(exec, args.get("service")),
+    },
+    CommandSpec {
+        name: "systemctl-stop",
+        aliases: &["stop"],
+        category: CommandCategory::ServiceManagement,
+        description: "Stop a service",
+        args: SERVICE,
+        binaries: &["systemctl"],
+        danger: Danger::Destructive,
+        root: true,
+        affects: Some(|exec, args| command_logic::preview_service(exec, args.get("service"))),
+        run: |exec, args| command_logic::systemctl_stop(exec, args.get("service")),
+    },
+    CommandSpec {
+        name: "systemctl-restart",
+        aliases: &["restart"],
+        category: CommandCategory::ServiceManagement,
+        description: "Restart a service",
+        args: SERVICE,
+        binaries: &["systemctl"],
+        danger: Danger::Caution,
+        root: true,
+        affects: None,
+        run: |exec, args| command_logic::systemctl_restart(exec, args.get("service")),
+    },
+    CommandSpec {
+        name: "systemctl-enable",
+        aliases: &["enable"],
+        category: CommandCategory::ServiceManagement,
+        description: "Enable a service at boot",
+        args: SERVICE,
+        binaries: &["systemctl"],
+        danger: Danger::Caution,
+        root: true,
+        affects: None,
+        run: |exec, args| command_logic::systemctl_enable(exec, args.get("service")),
+    },
+    CommandSpec {
+        name: "systemctl-disable",
+        aliases: &["disable"],
+        category: CommandCategory::ServiceManagement,
+        description: "Disable a service at boot",
+        args: SERVICE,
+        binaries: &["systemctl"],
+        danger: Danger::Destructive,
+        root: true,
+        affects: Some(|exec, args| command_logic::preview_service(exec, args.get("service"))),
+        run: |exec, args| command_logic::systemctl_disable(exec, args.get("service")),
+    },
+    CommandSpec {
+        name: "systemctl-status",
+        aliases: &["status"],
+        category: CommandCategory::ServiceManagement,
+        description: "System status",
+        args: &[],
+        binaries: &["systemctl"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::systemctl_status(exec),
+    },
+    CommandSpec {
+        name: "systemctl-list",
+        aliases: &["services"],
+        category: CommandCategory::ServiceManagement,
+        description: "List all services",
+        args: &[],
+        binaries: &["systemctl"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::systemctl_list(exec),
+    },
+    // Security
+    CommandSpec {
+        name: "chkrootkit",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "Check for rootkits",
+        args: &[],
+        binaries: &["chkrootkit"],
+        danger: Danger::Safe,
+        root: true,
+        affects: None,
+        run: |exec, _| command_logic::chkrootkit(exec),
+    },
+    CommandSpec {
+        name: "rkhunter",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "Rootkit hunter",
+        args: &[],
+        binaries: &["rkhunter"],
+        danger: Danger::Safe,
+        root: true,
+        affects: None,
+        run: |exec, _| command_logic::rkhunter(exec),
+    },
+    CommandSpec {
+        name: "lynis",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "Security audit",
+        args: &[],
+        binaries: &["lynis"],
+        danger: Danger::Safe,
+        root: true,
+        affects: None,
+        run: |exec, _| command_logic::lynis(exec),
+    },
+    CommandSpec {
+        name: "clamscan",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "Virus scan a path",
+        args: &[ArgSpec::required("path", ArgKind::ExistingPath, "Enter path to scan: ")],
+        binaries: &["clamscan"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::clamscan(exec, args.get("path")),
+    },
+    CommandSpec {
+        name: "fail2ban",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "fail2ban status",
+        args: &[],
+        binaries: &["fail2ban-client"],
+        danger: Danger::Safe,
+        root: true,
+        affects: None,
+        run: |exec, _| command_logic::fail2ban(exec),
+    },
+    CommandSpec {
+        name: "gpg-encrypt",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "Encrypt a file with GPG",
+        args: &[
+            ArgSpec::required("file", ArgKind::ExistingFile, "Enter file: "),
+            ArgSpec::required("recipient", ArgKind::Text, "Enter recipient: "),
+        ],
+        binaries: &["gpg"],
+        danger: Danger::Caution,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::gpg_encrypt(exec, args.get("file"), args.get("recipient")),
+    },
+    CommandSpec {
+        name: "gpg-decrypt",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "Decrypt a file with GPG",
+        args: &[ArgSpec::required("file", ArgKind::ExistingFile, "Enter file: ")],
+        binaries: &["gpg"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::gpg_decrypt(exec, args.get("file")),
+    },
+    CommandSpec {
+        name: "openssl-encrypt",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "Encrypt a file with OpenSSL (writes <file>.enc)",
+        args: FILE_AND_PASSWORD,
+        binaries: &["openssl"],
+        danger: Danger::Caution,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::openssl_encrypt(exec, args.get("file"), args.get("password")),
+    },
+    CommandSpec {
+        name: "openssl-decrypt",
+        aliases: &[],
+        category: CommandCategory::Security,
+        description: "Decrypt a .enc file with OpenSSL (overwrites the plaintext)",
+        args: FILE_AND_PASSWORD,
+        binaries: &["openssl"],
+        danger: Danger::Destructive,
+        root: false,
+        affects: Some(|_, args| command_logic::preview_decrypt_output(args.get("file"))),
+        run: |exec, args| command_logic::openssl_decrypt(exec, args.get("file"), args.get("password")),
+    },
+    // Package Management: backed by the detected package manager, which elevates the
+    // changing operations itself (AUR helpers must not run as root).
+    CommandSpec {
+        name: "install",
+        aliases: &["add", "apt-install", "pacman-install", "yay-install"],
+        category: CommandCategory::PackageManagement,
+        description: "Install packages (space-separated)",
+        args: PACKAGES,
+        binaries: &[],
+        danger: Danger::Caution,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::pkg_install(exec, args.get("packages")),
+    },
+    CommandSpec {
+        name: "remove",
+        aliases: &["uninstall", "apt-remove", "pacman-remove"],
+        category: CommandCategory::PackageManagement,
+        description: "Remove packages (space-separated)",
+        args: PACKAGES,
+        binaries: &[],
+        danger: Danger::Destructive,
+        root: false,
+        affects: Some(|exec, args| command_logic::preview_pkg_remove(exec, args.get("packages"))),
+        run: |exec, args| command_logic::pkg_remove(exec, args.get("packages")),
+    },
+    CommandSpec {
+        name: "update",
+        aliases: &["upgrade", "apt-update", "pacman-update"],
+        category: CommandCategory::PackageManagement,
+        description: "Refresh package lists and upgrade everything",
+        args: &[],
+        binaries: &[],
+        danger: Danger::Caution,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::pkg_update(exec),
+    },
+    CommandSpec {
+        name: "search",
+        aliases: &["pkg-search"],
+        category: CommandCategory::PackageManagement,
+        description: "Search available packages",
+        args: QUERY,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::pkg_search(exec, args.get("query")),
+    },
+    CommandSpec {
+        name: "info",
+        aliases: &["pkg-info"],
+        category: CommandCategory::PackageManagement,
+        description: "Show package details",
+        args: PACKAGE,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::pkg_info(exec, args.get("package")),
+    },
+    CommandSpec {
+        name: "list-installed",
+        aliases: &["packages", "dpkg-list", "pacman-list"],
+        category: CommandCategory::PackageManagement,
+        description: "List installed packages",
+        args: &[],
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::pkg_list_installed(exec),
+    },
+    CommandSpec {
+        name: "list-upgradable",
+        aliases: &["outdated"],
+        category: CommandCategory::PackageManagement,
+        description: "List packages with updates available",
+        args: &[],
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, _| command_logic::pkg_list_upgradable(exec),
+    },
+    CommandSpec {
+        name: "owns-file",
+        aliases: &["owner"],
+        category: CommandCategory::PackageManagement,
+        description: "Find the package a file belongs to",
+        args: OWNED_PATH,
+        binaries: &[],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::pkg_owns_file(exec, args.get("path")),
+    },
+    CommandSpec {
+        name: "which",
+        aliases: &[],
+        category: CommandCategory::PackageManagement,
+        description: "Find binary location",
+        args: BINARY,
+        binaries: &["which"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::which(exec, args.get("binary")),
+    },
+    CommandSpec {
+        name: "whereis",
+        aliases: &[],
+        category: CommandCategory::PackageManagement,
+        description: "Locate binary and docs",
+        args: BINARY,
+        binaries: &["whereis"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::whereis(exec, args.get("binary")),
+    },
+    CommandSpec {
+        name: "find-binary",
+        aliases: &[],
+        category: CommandCategory::PackageManagement,
+        description: "Search /usr/bin and /usr/local/bin",
+        args: BINARY,
+        binaries: &["find"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::find_binary(exec, args.get("binary")),
+    },
+    CommandSpec {
+        name: "locate-binary",
+        aliases: &[],
+        category: CommandCategory::PackageManagement,
+        description: "Find binary in the locate database",
+        args: BINARY,
+        binaries: &["locate"],
+        danger: Danger::Safe,
+        root: false,
+        affects: None,
+        run: |exec, args| command_logic::locate_binary(exec, args.get("binary")),
+    },
+];
@@ -0,0 +1,210 @@
+use clap::ValueEnum;
+use colored::*;
+use serde::Serialize;
+
+use crate::sys_admin::command_output::{CommandError, CommandOutput, Record, Row, Tone};
+
+/// How `gremlin sys` prints a command's result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Format {
+    /// Coloured, aligned output for humans
+    #[default]
+    Table,
+    /// One JSON object with typed records, for jq and dashboards
+    Json,
+    /// Typed records as CSV with a header row, for spreadsheets
+    Csv,
+}
+
+/// JSON shape of a command result. `stdout` is only included for free-form commands.
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    command: String,
+    status: Option<i32>,
+    /// Nothing was changed; `command` is what would have run.
+    dry_run: bool,
+    duration_ms: u128,
+    records: &'a [Record],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stdout: Option<&'a str>,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    stderr: &'a str,
+    notes: Vec<&'a str>,
+}
+
+/// Apply the colour for a tone.
+pub fn paint(text: &str, tone: Tone) -> ColoredString {
+    match tone {
+        Tone::Normal => text.bright_green(),
+        Tone::Good => text.green(),
+        Tone::Warn => text.yellow(),
+        Tone::Bad => text.red(),
+        Tone::Info => text.blue(),
+        Tone::Plain => text.white(),
+    }
+}
+
+/// Print a command's output the way the menus show it.
+pub fn print_output(output: &CommandOutput) {
+    if !output.title.is_empty() {
+        println!("{}", output.title.bright_cyan());
+    }
+
+    if !output.rows.is_empty() {
+        print_table(&output.headers, &output.rows);
+    } else if !output.stdout.trim().is_empty() {
+        println!("{}", output.stdout.trim_end().bright_green());
+    }
+
+    for note in &output.notes {
+        println!("{}", paint(&note.text, note.tone));
+    }
+
+    if !output.success() && !output.dry_run && !output.argv.is_empty() {
+        let stderr = output.stderr.trim();
+        let status = output.status.map(|s| s.to_string()).unwrap_or_else(|| "signal".to_string());
+        println!("{} {}", format!("❌ `{}` failed ({}):", output.command_line(), status).bright_red(), stderr.bright_red());
+    }
+}
+
+pub fn print_error(err: &CommandError) {
+    println!("{} {}", "Error:".bright_red(), err.to_string().bright_red());
+}
+
+/// Print a command's output in the requested format.
+pub fn print_output_as(output: &CommandOutput, format: Format) {
+    match format {
+        Format::Table => print_output(output),
+        Format::Json => println!("{}", to_json(output)),
+        Format::Csv => print!("{}", to_csv(output)),
+    }
+}
+
+/// Print an error in the requested format; JSON errors go to stdout so pipelines see them.
+pub fn print_error_as(err: &CommandError, format: Format) {
+    match format {
+        Format::Json => println!("{}", serde_json::json!({ "error": err.to_string(), "exit_code": err.exit_code() })),
+        Format::Table | Format::Csv => eprintln!("{} {}", "Error:".bright_red(), err.to_string().bright_red()),
+    }
+}
+
+pub fn to_json(output: &CommandOutput) -> String {
+    let json = JsonOutput {
+        command: output.command_line(),
+        status: output.status,
+        dry_run: output.dry_run,
+        duration_ms: output.duration.as_millis(),
+        records: &output.records,
+        stdout: Some(output.stdout.as_str()).filter(|_| output.records.is_empty()),
+        stderr: output.stderr.trim(),
+        notes: output.notes.iter().map(|n| n.text.as_str()).collect(),
+    };
+    serde_json::to_string_pretty(&json).unwrap_or_default()
+}
+
+/// Records as CSV. Free-form commands become a single `line` column.
+pub fn to_csv(output: &CommandOutput) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    if let Some(first) = output.records.first() {
+        let _ = writer.write_record(first.keys());
+        for record in &output.records {
+            let _ = writer.write_record(record.fields.iter().map(|(_, v)| v.raw()));
+        }
+    } else {
+        let _ = writer.write_record(["line"]);
+        for line in output.stdout.lines() {
+            let _ = writer.write_record([line]);
+        }
+    }
+    String::from_utf8(writer.into_inner().unwrap_or_default()).unwrap_or_default()
+}
+
+/// Print rows as aligned columns, each row coloured by its tone.
+pub fn print_table(headers: &[String], rows: &[Row]) {
+    let columns = rows.iter().map(|r| r.fields.len()).chain([headers.len()]).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for fields in rows.iter().map(|r| r.fields.as_slice()).chain([headers]) {
+        for (i, field) in fields.iter().enumerate() {
+            widths[i] = widths[i].max(field.chars().count());
+        }
+    }
+
+    if !headers.is_empty() {
+        println!("{}", format_line(headers, &widths).bright_green().bold());
+    }
+    for row in rows {
+        println!("{}", paint(&format_line(&row.fields, &widths), row.tone));
+    }
+}
+
+fn format_line(fields: &[String], widths: &[usize]) -> String {
+    let last = fields.len().saturating_sub(1);
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            if i == last {
+                field.clone()
+            } else {
+                format!("{:<width$}", field, width = widths[i])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys_admin::command_output::Field;
+
+    fn typed() -> CommandOutput {
+        let mut output = CommandOutput { argv: vec!["ps".to_string()], status: Some(0), ..Default::default() };
+        for (name, cpu, rss, note) in [("bash", Field::Float(1.5), Field::Bytes(2048), Field::Null), ("tmux: a, \"b\"\nc", Field::Float(0.0), Field::Int(7), Field::Text("x".to_string()))] {
+            output.push_record(
+                Record {
+                    fields: vec![
+                        ("pid".to_string(), Field::Int(42)),
+                        ("name".to_string(), Field::Text(name.to_string())),
+                        ("cpu".to_string(), cpu),
+                        ("rss".to_string(), rss),
+                        ("note".to_string(), note),
+                    ],
+                },
+                Tone::Normal,
+            );
+        }
+        output.note(Tone::Info, "2 processes");
+        output
+    }
+
+    #[test]
+    fn json_keeps_types_and_nulls() {
+        let json: serde_json::Value = serde_json::from_str(&to_json(&typed())).unwrap();
+        assert_eq!(json["command"], "ps");
+        assert_eq!(json["status"], 0);
+        assert_eq!(json["dry_run"], false);
+        assert_eq!(json["records"][0], serde_json::json!({ "pid": 42, "name": "bash", "cpu": 1.5, "rss": 2048, "note": null }));
+        assert_eq!(json["records"][1]["name"], "tmux: a, \"b\"\nc");
+        assert!(json["records"][1]["cpu"].is_f64() && json["records"][0]["pid"].is_i64());
+        assert_eq!(json["notes"], serde_json::json!(["2 processes"]));
+        assert!(json.get("stdout").is_none() && json.get("stderr").is_none());
+    }
+
+    #[test]
+    fn json_falls_back_to_stdout_for_free_form_commands() {
+        let output = CommandOutput { argv: vec!["uptime".to_string()], stdout: "up 3 days\n".to_string(), stderr: " warn \n".to_string(), status: None, ..Default::default() };
+        let json: serde_json::Value = serde_json::from_str(&to_json(&output)).unwrap();
+        assert_eq!((json["stdout"].as_str(), json["stderr"].as_str()), (Some("up 3 days\n"), Some("warn")));
+        assert!(json["status"].is_null());
+        assert_eq!(json["records"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn csv_quotes_commas_quotes_and_newlines() {
+        assert_eq!(to_csv(&typed()), "pid,name,cpu,rss,note\n42,bash,1.5,2048,\n42,\"tmux: a, \"\"b\"\"\nc\",0,7,x\n");
+
+        let output = CommandOutput { stdout: "plain\nwith, comma\n".to_string(), ..Default::default() };
+        assert_eq!(to_csv(&output), "line\nplain\n\"with, comma\"\n");
+    }
+}
@@ -0,0 +1,381 @@
+use std::cell::OnceCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use colored::*;
+use dialoguer::theme::Theme;
+use dialoguer::{Completion, FuzzySelect, History, Input};
+
+use crate::config;
+use crate::exec::Invocation;
+use crate::procfs::ProcFs;
+use crate::signals;
+use crate::sys_admin::args::{ArgKind, Args};
+use crate::sys_admin::command_matcher;
+use crate::sys_admin::command_output::CommandError;
+use crate::sys_admin::registry::{self, CommandSpec};
+use crate::sys_admin::render;
+
+/// Words the REPL handles itself instead of looking them up in the registry.
+const BUILTINS: [&str; 5] = ["help", "search", "commands", "exit", "quit"];
+
+/// Entries kept in the history file.
+const HISTORY_LIMIT: usize = 1000;
+
+/// `gremlin>` prompt: type a command with its arguments, Tab to complete, ↑/↓ for history.
+/// An empty line opens a fuzzy search over every command.
+pub fn run() {
+    println!("\n{}", "🔎 gremlin command palette 🔎".bright_purple().bold());
+    println!("{}", "Type a command, Tab to complete, ↑/↓ for history, Enter on an empty line to search, `help` for more.".bright_cyan());
+
+    let mut history = FileHistory::load(config::config_dir().join("history"));
+    let completer = Completer::new(ProcFs::system());
+    loop {
+        let line: String = match Input::with_theme(&PromptTheme)
+            .with_prompt("gremlin")
+            .allow_empty(true)
+            .history_with(&mut history)
+            .completion_with(&completer)
+            .interact_text()
+        {
+            Ok(line) => line,
+            // No terminal, or stdin closed.
+            Err(_) => return,
+        };
+
+        let words = split_line(&line);
+        let Some((cmd, args)) = words.split_first() else {
+            search("");
+            continue;
+        };
+        match cmd.as_str() {
+            "exit" | "quit" => return,
+            "help" => help(),
+            "commands" => command_matcher::show_available_commands(),
+            "search" => search(&args.join(" ")),
+            _ => run_line(cmd, args),
+        }
+    }
+}
+
+fn help() {
+    println!("\n{}", "Built-ins:".bright_cyan().bold());
+    for (word, description) in [
+        ("<command> [args...]", "run a command; missing arguments are asked for"),
+        ("search [text]", "fuzzy search commands by name and description (same as an empty line)"),
+        ("commands", "list every command with its usage"),
+        ("help", "show this help"),
+        ("exit", "leave the palette"),
+    ] {
+        println!("{} {} {} {}", "•".bright_green(), word.bright_magenta(), "–".bright_blue(), description.bright_cyan());
+    }
+}
+
+/// Run a typed command, prompting for any required arguments that were left off.
+fn run_line(cmd: &str, args: &[String]) {
+    let Some(spec) = registry::find(cmd) else {
+        render::print_error(&CommandError::UnknownCommand(cmd.to_string()));
+        if let Some(suggestion) = registry::suggest(cmd) {
+            println!("{}", format!("Did you mean `{}`?", suggestion).bright_yellow());
+        }
+        return;
+    };
+
+    let mut args = args.to_vec();
+    for arg in spec.args.iter().skip(args.len()).filter(|arg| arg.required) {
+        match command_matcher::prompt_arg(arg) {
+            Some(value) => args.push(value),
+            None => return render::print_error(&CommandError::Cancelled),
+        }
+    }
+    command_matcher::run_command(spec.name, &args);
+}
+
+/// Fuzzy-pick a command by name or description, then run it.
+fn search(query: &str) {
+    let commands: Vec<&CommandSpec> = registry::all().collect();
+    let items: Vec<String> = commands
+        .iter()
+        .map(|spec| format!("{} – {}", spec.usage(), spec.description))
+        .collect();
+    let picked = FuzzySelect::new()
+        .with_prompt("Search commands")
+        .with_initial_text(query)
+        .items(&items)
+        .max_length(15)
+        .interact_opt();
+    if let Ok(Some(index)) = picked {
+        run_line(commands[index].name, &[]);
+    }
+}
+
+/// Split a line on whitespace, keeping single- or double-quoted text together.
+fn split_line(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+struct PromptTheme;
+
+impl Theme for PromptTheme {
+    fn format_input_prompt(&self, f: &mut dyn fmt::Write, prompt: &str, _default: Option<&str>) -> fmt::Result {
+        write!(f, "{} ", format!("{}>", prompt).bright_magenta().bold())
+    }
+
+    fn format_input_prompt_selection(&self, f: &mut dyn fmt::Write, prompt: &str, sel: &str) -> fmt::Result {
+        write!(f, "{} {}", format!("{}>", prompt).bright_magenta().bold(), sel)
+    }
+}
+
+/// Up-arrow history backed by a file in the config dir, newest entry first.
+/// Secret arguments are dropped before a line is stored.
+struct FileHistory {
+    path: PathBuf,
+    entries: VecDeque<String>,
+}
+
+impl FileHistory {
+    fn load(path: PathBuf) -> Self {
+        let mut entries: VecDeque<String> = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .rev()
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect();
+        if entries.len() > HISTORY_LIMIT {
+            entries.truncate(HISTORY_LIMIT);
+            let kept: Vec<&str> = entries.iter().rev().map(String::as_str).collect();
+            let _ = fs::write(&path, kept.join("\n") + "\n");
+        }
+        Self { path, entries }
+    }
+}
+
+impl History<String> for FileHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.entries.get(pos).cloned()
+    }
+
+    fn write(&mut self, line: &String) {
+        let line = without_secrets(line);
+        if line.is_empty() || self.entries.front() == Some(&line) {
+            return;
+        }
+        self.entries.push_front(line.clone());
+        self.entries.truncate(HISTORY_LIMIT);
+
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&self.path) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+/// The line cut off before the first argument that is a secret, e.g. an openssl password.
+fn without_secrets(line: &str) -> String {
+    let words = split_line(line);
+    let Some(spec) = words.first().and_then(|cmd| registry::find(cmd)) else {
+        return line.trim().to_string();
+    };
+    let slots = Args::slots(spec.args, &words[1..]).unwrap_or_else(|_| (0..words.len() - 1).collect());
+    match slots.iter().position(|&slot| spec.args.get(slot).is_some_and(|arg| arg.kind == ArgKind::Secret)) {
+        Some(secret) => words[..=secret].join(" "),
+        None => line.trim().to_string(),
+    }
+}
+
+/// Tab completion for command names and, after a command, for the value of the argument
+/// being typed: PIDs, process names, services, mount points and paths.
+struct Completer {
+    proc: ProcFs,
+    services: OnceCell<Vec<String>>,
+}
+
+impl Completion for Completer {
+    fn get(&self, input: &str) -> Option<String> {
+        let (head, word) = match input.rfind(' ') {
+            Some(i) => input.split_at(i + 1),
+            None => ("", input),
+        };
+        let previous = split_line(head);
+
+        let candidates = match previous.split_first() {
+            None => BUILTINS
+                .iter()
+                .copied()
+                .chain(registry::all().flat_map(|spec| std::iter::once(spec.name).chain(spec.aliases.iter().copied())))
+                .map(String::from)
+                .collect(),
+            Some((cmd, args)) => match registry::find(cmd).and_then(|spec| spec.args.get(args.len())) {
+                Some(arg) => self.values(arg.kind, word),
+                None => Vec::new(),
+            },
+        };
+
+        let matching: Vec<&String> = candidates.iter().filter(|c| c.starts_with(word)).collect();
+        match matching.as_slice() {
+            [] => None,
+            [only] if only.ends_with('/') => Some(format!("{}{}", head, only)),
+            [only] => Some(format!("{}{} ", head, only)),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.chars().count(), |len, c| {
+                    first.chars().zip(c.chars()).take(len).take_while(|(a, b)| a == b).count()
+                });
+                (common > word.chars().count()).then(|| format!("{}{}", head, first.chars().take(common).collect::<String>()))
+            }
+        }
+    }
+}
+
+impl Completer {
+    fn new(proc: ProcFs) -> Self {
+        Self { proc, services: OnceCell::new() }
+    }
+
+    fn values(&self, kind: ArgKind, word: &str) -> Vec<String> {
+        match kind {
+            ArgKind::Pid => pids(&self.proc),
+            ArgKind::ProcessName => process_names(&self.proc),
+            ArgKind::Service => self.services.get_or_init(services).clone(),
+            ArgKind::MountPoint => mount_points(&self.proc),
+            ArgKind::ExistingFile | ArgKind::ExistingPath => paths(word),
+            ArgKind::Signal => signals::SIGNALS.iter().map(|signal| signal.name.to_string()).collect(),
+            ArgKind::YesNo => vec!["yes".to_string(), "no".to_string()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn pids(proc: &ProcFs) -> Vec<String> {
+    proc.pids().iter().map(u32::to_string).collect()
+}
+
+fn process_names(proc: &ProcFs) -> Vec<String> {
+    let mut names: Vec<String> = proc
+        .pids()
+        .iter()
+        .filter_map(|pid| proc.read(format!("{}/comm", pid)).ok())
+        .map(|comm| comm.trim().to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn services() -> Vec<String> {
+    Invocation::new("systemctl")
+        .args(["list-units", "--type=service", "--all", "--plain", "--no-legend"])
+        .read_only()
+        .run()
+        .map(|out| out.stdout.lines().filter_map(|line| line.split_whitespace().next().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+/// Mount points and their devices from `/proc/mounts`.
+fn mount_points(proc: &ProcFs) -> Vec<String> {
+    let mounts = proc.read("mounts").unwrap_or_default();
+    let mut values: Vec<String> = mounts
+        .lines()
+        .flat_map(|line| line.split_whitespace().take(2).map(String::from).collect::<Vec<_>>())
+        .filter(|value| value.starts_with('/'))
+        .collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+/// Entries of the directory `word` points into; directories end in `/`.
+fn paths(word: &str) -> Vec<String> {
+    let (dir, prefix) = match word.rfind('/') {
+        Some(i) => (&word[..=i], &word[..=i]),
+        None => (".", ""),
+    };
+    fs::read_dir(Path::new(dir))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_str()?.to_string();
+                    let slash = if entry.path().is_dir() { "/" } else { "" };
+                    Some(format!("{}{}{}", prefix, name, slash))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::fixture::{FakeProcess, Fixture};
+
+    #[test]
+    fn lines_split_on_whitespace_outside_quotes() {
+        assert_eq!(split_line("  kill  1234   TERM "), ["kill", "1234", "TERM"]);
+        assert_eq!(split_line(r#"grep "two words" 'it"s' x"#), ["grep", "two words", "it\"s", "x"]);
+        assert_eq!(split_line("a'b c'd e"), ["ab cd", "e"]);
+        assert_eq!(split_line("find ''"), ["find", ""]);
+        assert_eq!(split_line("echo 'unterminated quote"), ["echo", "unterminated quote"]);
+        assert!(split_line("   ").is_empty());
+    }
+
+    #[test]
+    fn history_drops_everything_from_the_first_secret() {
+        assert_eq!(without_secrets("openssl-encrypt notes.txt hunter2"), "openssl-encrypt notes.txt");
+        assert_eq!(without_secrets("openssl-decrypt notes.txt.enc 'pass phrase' extra"), "openssl-decrypt notes.txt.enc");
+        assert_eq!(without_secrets("openssl-encrypt notes.txt"), "openssl-encrypt notes.txt");
+        assert_eq!(without_secrets("  kill 1234 TERM  "), "kill 1234 TERM");
+        assert_eq!(without_secrets("not-a-command secret"), "not-a-command secret");
+    }
+
+    #[test]
+    fn completions_read_the_configured_proc_tree() {
+        let fixture = Fixture::new();
+        fixture
+            .process(FakeProcess { pid: 1, name: "init", ..Default::default() })
+            .process(FakeProcess { pid: 42, name: "nginx", ..Default::default() })
+            .process(FakeProcess { pid: 43, name: "nginx", ..Default::default() })
+            .file("1/comm", "systemd\n")
+            .file("42/comm", "nginx\n")
+            .file("43/comm", "nginx\n")
+            .file("mounts", "/dev/sda1 / ext4 rw 0 0\nproc /proc proc rw 0 0\n/dev/sdb1 /mnt/data xfs rw 0 0\n");
+        let completer = Completer::new(fixture.proc());
+
+        assert_eq!(completer.values(ArgKind::Pid, ""), ["1", "42", "43"]);
+        assert_eq!(completer.values(ArgKind::ProcessName, ""), ["nginx", "systemd"]);
+        assert_eq!(completer.values(ArgKind::MountPoint, ""), ["/", "/dev/sda1", "/dev/sdb1", "/mnt/data", "/proc"]);
+        assert_eq!(completer.get("inspect 4"), None);
+        assert_eq!(completer.get("inspect 42"), Some("inspect 42 ".to_string()));
+    }
+}